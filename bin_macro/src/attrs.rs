@@ -0,0 +1,96 @@
+use syn::{Attribute, Error, Lit, Meta, NestedMeta, Result};
+
+/// Collects every `key` or `key = "value"` entry from `#[binary(...)]` attributes.
+pub fn binary_options(attrs: &[Attribute]) -> Result<Vec<(String, Option<Lit>, Meta)>> {
+    let mut options = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path.is_ident("binary")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) => {
+                            let key = path_name(&path)?;
+                            options.push((key, None, Meta::Path(path)));
+                        }
+                        NestedMeta::Meta(Meta::NameValue(nv)) => {
+                            let key = path_name(&nv.path)?;
+                            let lit = nv.lit.clone();
+                            options.push((key, Some(lit), Meta::NameValue(nv)));
+                        }
+                        NestedMeta::Meta(meta @ Meta::List(_)) => {
+                            let key = path_name(meta.path())?;
+                            options.push((key, None, meta));
+                        }
+                        other => {
+                            return Err(Error::new_spanned(other, "Expected a binary option"));
+                        }
+                    }
+                }
+            }
+            other => {
+                return Err(Error::new_spanned(
+                    other,
+                    "Expected options, e.g. #[binary(construct = \"Self::new\")]",
+                ))
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Finds a `key = "value"` option and returns its string value.
+pub fn string_option(attrs: &[Attribute], key: &str) -> Result<Option<syn::LitStr>> {
+    for (name, lit, meta) in binary_options(attrs)? {
+        if name == key {
+            return match lit {
+                Some(Lit::Str(s)) => Ok(Some(s)),
+                _ => Err(Error::new_spanned(
+                    meta,
+                    format!("Expected #[binary({} = \"...\")]", key),
+                )),
+            };
+        }
+    }
+    Ok(None)
+}
+
+/// Finds a `key = N` option and returns its integer value.
+pub fn int_option(attrs: &[Attribute], key: &str) -> Result<Option<syn::LitInt>> {
+    for (name, lit, meta) in binary_options(attrs)? {
+        if name == key {
+            return match lit {
+                Some(Lit::Int(i)) => Ok(Some(i)),
+                _ => Err(Error::new_spanned(
+                    meta,
+                    format!("Expected #[binary({} = N)]", key),
+                )),
+            };
+        }
+    }
+    Ok(None)
+}
+
+/// Finds a `key("value")` option and returns its string value.
+pub fn list_string_option(attrs: &[Attribute], key: &str) -> Result<Option<syn::LitStr>> {
+    for (name, _, meta) in binary_options(attrs)? {
+        if name != key {
+            continue;
+        }
+        if let Meta::List(list) = &meta {
+            if let [NestedMeta::Lit(Lit::Str(s))] = list.nested.iter().collect::<Vec<_>>()[..] {
+                return Ok(Some(s.clone()));
+            }
+        }
+        return Err(Error::new_spanned(
+            meta,
+            format!("Expected #[binary({}(\"...\"))]", key),
+        ));
+    }
+    Ok(None)
+}
+
+fn path_name(path: &syn::Path) -> Result<String> {
+    path.get_ident()
+        .map(|i| i.to_string())
+        .ok_or_else(|| Error::new_spanned(path, "Expected a binary option"))
+}
@@ -0,0 +1,92 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, LitInt, Result};
+
+use crate::stream::{impl_migrate_struct, impl_msgpack_struct, impl_named_fields};
+
+pub fn bits_parse(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = match input.data {
+        Data::Struct(v) => impl_named_fields(v.fields)?,
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "BitIo can only be derived for structs with named fields.",
+            ))
+        }
+    };
+
+    // fields without #[bits(N)] take their full width.
+    let mut widths = Vec::new();
+    for field in &fields {
+        let ty = &field.ty;
+        let mut attrs = field.attrs.iter().filter(|a| a.path.is_ident("bits"));
+        let width = match (attrs.next(), attrs.next()) {
+            (None, _) => quote!(<#ty as ::binary_utils::bits::BitField>::BITS),
+            (Some(attr), None) => {
+                let width = attr.parse_args::<LitInt>()?;
+                if width.base10_parse::<u32>()? == 0 {
+                    return Err(Error::new_spanned(
+                        width,
+                        "A bit field needs at least 1 bit",
+                    ));
+                }
+                quote!(#width)
+            }
+            (Some(_), Some(attr)) => {
+                return Err(Error::new_spanned(attr, "Duplicate #[bits] attribute"))
+            }
+        };
+        widths.push(width);
+    }
+
+    let names = fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let locals = fields.iter().map(|f| f.local()).collect::<Vec<_>>();
+    let build = quote!(Self { #(#names: #locals),* });
+    let msgpack = impl_msgpack_struct(name, &fields, &build);
+    let migrate = impl_migrate_struct(name, &fields, &build);
+
+    Ok(quote! {
+        #(
+            const _: () = assert!(
+                #widths <= <#types as ::binary_utils::bits::BitField>::BITS,
+                concat!("#[bits] of ", stringify!(#name), "::", stringify!(#names), " is wider than its type")
+            );
+        )*
+
+        #[automatically_derived]
+        impl Streamable for #name {
+            fn parse(&self) -> Result<Vec<u8>, ::binary_utils::error::BinaryError> {
+                let mut writer = ::binary_utils::bits::BitWriter::new();
+                #(writer.write(&self.#names, #widths)?;)*
+                Ok(writer.into_bytes())
+            }
+
+            fn compose(source: &[u8], position: &mut usize) -> Result<Self, ::binary_utils::error::BinaryError> {
+                let mut reader = ::binary_utils::bits::BitReader::new(source.get(*position..).unwrap_or(&[]));
+                #(let #locals = reader.read::<#types>(#widths)?;)*
+                *position += reader.bytes_read();
+                Ok(#build)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::binary_utils::schema::Schema for #name {
+            fn describe(out: &mut String) {
+                let widths: &[u32] = &[#(#widths),*];
+                let widths = widths.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+                out.push_str(&format!("bits<{}>", widths.join(",")));
+            }
+        }
+
+        #[automatically_derived]
+        impl ::binary_utils::layout::FixedSize for #name {
+            const WIRE_SIZE: usize = (0u32 #(+ #widths)*).div_ceil(8) as usize;
+        }
+
+        #msgpack
+
+        #migrate
+    })
+}
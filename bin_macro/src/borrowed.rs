@@ -0,0 +1,56 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Result};
+
+use crate::stream::impl_named_fields;
+
+pub fn borrowed_parse(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = match input.data {
+        Data::Struct(v) => impl_named_fields(v.fields)?,
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "BinaryStreamRef can only be derived for structs with named fields.",
+            ))
+        }
+    };
+
+    // the fields borrow from the source for the struct's only lifetime.
+    let mut lifetimes = input.generics.lifetimes();
+    let lifetime = match (lifetimes.next(), lifetimes.next()) {
+        (Some(def), None) => &def.lifetime,
+        (None, _) => {
+            return Err(Error::new(
+                name.span(),
+                "BinaryStreamRef needs a lifetime to borrow the source for, derive BinaryStream for owned structs.",
+            ))
+        }
+        (Some(_), Some(def)) => {
+            return Err(Error::new_spanned(
+                def,
+                "BinaryStreamRef only supports a single lifetime.",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let names = fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let locals = fields.iter().map(|f| f.local()).collect::<Vec<_>>();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::binary_utils::borrowed::StreamableRef<#lifetime> for #name #ty_generics #where_clause {
+            fn parse_ref(&self, out: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                #(::binary_utils::borrowed::StreamableRef::<#lifetime>::parse_ref(&self.#names, out)?;)*
+                Ok(())
+            }
+
+            fn compose_ref(source: &#lifetime [u8], position: &mut usize) -> Result<Self, ::binary_utils::error::BinaryError> {
+                #(let #locals = <#types as ::binary_utils::borrowed::StreamableRef<#lifetime>>::compose_ref(source, position)?;)*
+                Ok(Self { #(#names: #locals),* })
+            }
+        }
+    })
+}
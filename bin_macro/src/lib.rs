@@ -1,10 +1,27 @@
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
+mod attrs;
+mod bits;
+mod borrowed;
 mod stream;
 
-#[proc_macro_derive(BinaryStream)]
+#[proc_macro_derive(BinaryStream, attributes(binary, le, be, skip, default))]
 pub fn derive_stream(input: TokenStream) -> TokenStream {
     stream::stream_parse(parse_macro_input!(input as DeriveInput))
-        .unwrap()
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(BinaryStreamRef)]
+pub fn derive_stream_ref(input: TokenStream) -> TokenStream {
+    borrowed::borrowed_parse(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(BitIo, attributes(bits))]
+pub fn derive_bits(input: TokenStream) -> TokenStream {
+    bits::bits_parse(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
@@ -1,49 +1,146 @@
-use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
-use syn::{Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields, Lit, LitInt, Result, Type};
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{ParseStream, Parser};
+use syn::{
+    Attribute, Data, DataEnum, DeriveInput, Error, Expr, ExprLit, ExprPath, Fields, Lit, LitInt,
+    Meta, Pat, Result, Token, Type, Visibility,
+};
+
+use crate::attrs::{binary_options, int_option, list_string_option, string_option};
 
 pub fn stream_parse(input: DeriveInput) -> Result<TokenStream> {
+    let roundtrip = impl_roundtrip(&input.ident, &input.attrs)?;
+    let wire_eq = impl_wire_eq(&input.ident, &input.attrs)?;
+    let stream = derive_stream(input)?;
+    Ok(quote! {
+        #stream
+
+        #roundtrip
+
+        #wire_eq
+    })
+}
+
+fn derive_stream(input: DeriveInput) -> Result<TokenStream> {
     let name = &input.ident;
     let attrs = input.attrs;
     match input.data {
-        Data::Struct(v) => {
-            // iterate through struct fields
-            let (w, r) = impl_named_fields(v.fields);
-            let writes = quote!(#(#w)*);
-            let reads = quote!(#(#r),*);
-            // get the visibility etc on each field
-            // return a quote for block impl
-            Ok(quote! {
-                 #[automatically_derived]
-                 impl Streamable for #name {
-                      fn parse(&self) -> Result<Vec<u8>, ::binary_utils::error::BinaryError> {
-                           use ::std::io::Write;
-                           use binary_utils::varint::{VarInt, VarIntWriter};
-                           use binary_utils::{u24, u24Writer};
-                           let mut writer = Vec::new();
-                           #writes
-                           Ok(writer)
-                      }
-
-                      fn compose(source: &[u8], position: &mut usize) -> Result<Self, ::binary_utils::error::BinaryError> {
-                           use ::std::io::Read;
-                           use binary_utils::varint::{VarInt, VarIntReader};
-                           use binary_utils::{u24, u24Reader};
-
-                           Ok(Self {
-                                #reads
-                           })
-                      }
-                 }
-            })
+        Data::Struct(v) => derive_struct(name, &input.vis, &attrs, impl_named_fields(v.fields)?),
+        Data::Enum(data) => derive_enum(name, attrs, data),
+        Data::Union(_) => Err(syn::Error::new(
+            name.span(),
+            "BinaryStream does not support Type Unions. Use Enums instead.",
+        )),
+    }
+}
+
+/// A variant of a derived enum, its discriminant and its fields.
+struct EnumVariant {
+    ident: Ident,
+    discrim: Expr,
+    fields: Fields,
+    /// The id written instead of the discriminant, from `#[binary(id_expr = "...")]`.
+    id_expr: Option<Expr>,
+    /// The ids read as this variant, from `#[binary(id_match = "...")]`.
+    id_match: Option<Pat>,
+    /// For each field, how it is rebuilt from the id with `#[binary(from_id = "...")]`.
+    from_id: Vec<Option<Expr>>,
+}
+
+impl EnumVariant {
+    /// The id written for the variant, with its fields bound to their locals.
+    fn write_id(&self) -> TokenStream {
+        match &self.id_expr {
+            Some(expr) => quote!(#expr),
+            None => self.discrim.to_token_stream(),
         }
-        Data::Enum(data) => {
-            let representation =
-                find_one_attr("repr", attrs).expect("Enums must have a #[repr] attribute");
+    }
+
+    /// The pattern of ids read as the variant.
+    fn match_id(&self) -> TokenStream {
+        match &self.id_match {
+            Some(pattern) => quote!(#pattern),
+            None => self.discrim.to_token_stream(),
+        }
+    }
+
+    /// The locals and types of the fields written after the id.
+    fn body(&self) -> (Vec<Ident>, Vec<&Type>) {
+        self.locals()
+            .into_iter()
+            .zip(self.types())
+            .zip(&self.from_id)
+            .filter(|(_, from_id)| from_id.is_none())
+            .map(|(field, _)| field)
+            .unzip()
+    }
+
+    /// Builds the fields carried in the id from `id`, the id that was read.
+    fn id_fields(&self) -> TokenStream {
+        let fields = self
+            .locals()
+            .into_iter()
+            .zip(&self.from_id)
+            .filter_map(|(local, from_id)| from_id.as_ref().map(|expr| (local, expr)))
+            .collect::<Vec<_>>();
+        if fields.is_empty() {
+            return quote!();
+        }
+        let (locals, exprs): (Vec<_>, Vec<_>) = fields.into_iter().unzip();
+        quote!(#(let #locals = #exprs;)*)
+    }
+
+    /// The locals each field is bound to, in order.
+    fn locals(&self) -> Vec<Ident> {
+        match &self.fields {
+            Fields::Named(named) => named
+                .named
+                .iter()
+                .map(|f| format_ident!("__binary_{}", f.ident.as_ref().unwrap()))
+                .collect(),
+            Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len())
+                .map(|i| format_ident!("__binary_{}", i))
+                .collect(),
+            Fields::Unit => Vec::new(),
+        }
+    }
+
+    fn types(&self) -> Vec<&Type> {
+        self.fields.iter().map(|f| &f.ty).collect()
+    }
+
+    /// `Self::Variant` with every field bound to (or built from) its local.
+    fn pattern(&self) -> TokenStream {
+        let ident = &self.ident;
+        let locals = self.locals();
+        match &self.fields {
+            Fields::Named(named) => {
+                let names = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+                quote!(Self::#ident { #(#names: #locals),* })
+            }
+            Fields::Unnamed(_) => quote!(Self::#ident(#(#locals),*)),
+            Fields::Unit => quote!(Self::#ident),
+        }
+    }
+}
+
+/// Derives `Streamable` for an enum, written as its discriminant followed
+/// by the fields of the variant.
+///
+/// The discriminant is written as the `#[repr]` type, or a `u8` when the
+/// enum has no `#[repr]`.
+///
+/// A variant with `#[binary(id_expr = "...")]` writes that expression as its
+/// id instead, with `self.field` naming its fields, and is read for every id
+/// matching `#[binary(id_match = "...")]`. Fields marked
+/// `#[binary(from_id = "...")]` are carried in the id rather than written,
+/// and rebuilt from the `id` that was read.
+fn derive_enum(name: &Ident, attrs: Vec<Attribute>, data: DataEnum) -> Result<TokenStream> {
+    let enum_ty = match find_one_attr("repr", attrs) {
+        Some(representation) => {
             let enum_ty = representation
                 .parse_args::<Ident>()
                 .expect("Enums can only have types as attributes");
-
             if !enum_ty
                 .to_string()
                 .starts_with(|v| v == 'u' || v == 'i' || v == 'f')
@@ -53,159 +150,1199 @@ pub fn stream_parse(input: DeriveInput) -> Result<TokenStream> {
                     "Representation must be a primitive number",
                 ));
             }
+            enum_ty
+        }
+        None => format_ident!("u8"),
+    };
 
-            let (mut writers, mut readers) = (Vec::<TokenStream>::new(), Vec::<TokenStream>::new());
+    let mut variants = Vec::<EnumVariant>::new();
+    let mut last_field: Option<Expr> = None;
 
-            if !data.variants.iter().all(|v| match v.fields.clone() {
-                Fields::Unit => true,
-                Fields::Unnamed(_) => true,
-                _ => false,
-            }) {
+    for variant in &data.variants {
+        let id_expr = string_option(&variant.attrs, "id_expr")?
+            .map(|expr| {
+                let tokens = bind_self_fields(expr.parse::<TokenStream>()?);
+                syn::parse2::<Expr>(tokens)
+            })
+            .transpose()?;
+        let id_match = string_option(&variant.attrs, "id_match")?
+            .map(|pattern| pattern.parse::<Pat>())
+            .transpose()?;
+        if id_expr.is_some() != id_match.is_some() {
+            return Err(Error::new_spanned(
+                variant,
+                "#[binary(id_expr = \"...\")] and #[binary(id_match = \"...\")] must be used together",
+            ));
+        }
+        let from_id = variant
+            .fields
+            .iter()
+            .map(|f| {
+                let expr = string_option(&f.attrs, "from_id")?;
+                if expr.is_some() && id_expr.is_none() {
+                    return Err(Error::new_spanned(
+                        f,
+                        "#[binary(from_id = \"...\")] needs an #[binary(id_expr = \"...\")] on the variant",
+                    ));
+                }
+                expr.map(|expr| expr.parse::<Expr>()).transpose()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let field_attr = variant.fields.iter().flat_map(|f| &f.attrs).find(|a| {
+            ["le", "be", "skip", "default"]
+                .iter()
+                .any(|name| a.path.is_ident(name))
+        });
+        if let Some(attr) = field_attr {
+            return Err(Error::new_spanned(
+                attr,
+                "#[le], #[be], #[skip] and #[default] are only supported on struct fields",
+            ));
+        }
+        let discrim = match (&variant.discriminant, last_field) {
+            (Some((_, discrim)), _) => discrim.clone(),
+            // The variant has no discriminant but a previous one did,
+            // so we add a literal "one" to the last discriminant.
+            (
+                None,
+                Some(Expr::Lit(ExprLit {
+                    lit: Lit::Int(literal_value),
+                    ..
+                })),
+            ) => {
+                let next = literal_value.base10_parse::<u64>()? + 1;
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(LitInt::new(&format!("{}", next), Span::call_site())),
+                    attrs: Vec::new(),
+                })
+            }
+            (None, Some(_)) => {
                 return Err(Error::new_spanned(
-                    data.variants,
-                    "Enum Fields must be Uninitialized or Named",
+                    variant,
+                    "Enum discriminant must be a literal but the previous field was not a literal",
                 ));
             }
+            // If there is no last field, then this is the first field.
+            // In this case, we will just write the discriminant as 0.
+            (None, None) => Expr::Lit(ExprLit {
+                lit: Lit::Int(LitInt::new("0", Span::call_site())),
+                attrs: Vec::new(),
+            }),
+        };
+        last_field = Some(discrim.clone());
+        variants.push(EnumVariant {
+            ident: variant.ident.clone(),
+            discrim,
+            fields: variant.fields.clone(),
+            id_expr,
+            id_match,
+            from_id,
+        });
+    }
 
-            let mut last_field: Option<Expr> = None;
-
-            for variant in &data.variants {
-                // for each field...
-                // get the value of the last field.
-                match &variant.fields {
-                    Fields::Unit => {
-                        if let Some(da) = variant.discriminant.as_ref() {
-                            let discrim = da.1.clone();
-                            let var_name = variant.ident.clone();
-                            // writers
-                            writers.push(
-                                quote!(Self::#var_name => Ok((#discrim as #enum_ty).parse()?),),
-                            );
-                            // readers
-                            readers.push(quote!(#discrim => Ok(Self::#var_name),));
-                            last_field = Some(discrim.clone());
-                        } else {
-                            if last_field.is_some() {
-                                // The discriminant exists, but the variant is unit.
-                                // However there was a previous discriminant.
-                                // We need to add a literal "one" to the discriminant.
-                                // This is a bit tricky so bare with the hacks here.
-                                match last_field.unwrap() {
-                                    Expr::Lit(v) => {
-                                        // get the literal value of the last field.
-                                        let lit = v.lit.clone();
-                                        match lit {
-                                            Lit::Int(literal_value) => {
-                                                let next = literal_value.base10_parse::<u64>().unwrap() + 1;
-                                                // If last field is none, then this is the first field.
-                                                // In this case, we will just write the discriminant as 0.
-                                                last_field = Some(Expr::Lit(ExprLit {
-                                                    lit: Lit::Int(LitInt::new(
-                                                        &format!("{}", next),
-                                                        Span::call_site(),
-                                                    )),
-                                                    attrs: Vec::new(),
-                                                }));
-
-                                                let discrim = last_field.clone().unwrap();
-
-                                                let var_name = variant.ident.clone();
-                                                // writers
-                                                writers.push(quote!(Self::#var_name => Ok((#discrim as #enum_ty).parse()?),));
-                                                // readers
-                                                readers
-                                                    .push(quote!(#discrim => Ok(Self::#var_name),));
-                                            }
-                                            _ => {
-                                                return Err(Error::new_spanned(variant, "Enum discriminant must be a literal but the previous field was not a literal"));
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        return Err(Error::new_spanned(variant, "Enum discriminant must be a literal but the previous field was not a literal"));
-                                    }
-                                }
-                            } else {
-                                // If last field is none, then this is the first field.
-                                // In this case, we will just write the discriminant as 0.
-                                last_field = Some(Expr::Lit(ExprLit {
-                                    lit: Lit::Int(LitInt::new(&"0", Span::call_site())),
-                                    attrs: Vec::new(),
-                                }));
-
-                                let discrim = last_field.clone().unwrap();
-
-                                let var_name = variant.ident.clone();
-                                // writers
-                                writers.push(
-                                    quote!(Self::#var_name => Ok((#discrim as #enum_ty).parse()?),),
-                                );
-                                // readers
-                                readers.push(quote!(#discrim => Ok(Self::#var_name),));
-                            }
-                        }
-                    }
-                    Fields::Unnamed(_fields) => {
-                        return Err(Error::new_spanned(
-                            variant,
-                            "Variant fields are not explicitly supported yet.",
-                        ));
-                        // for field in fields.unnamed.iter() {
-                        //     dbg!("I am here 2\n\n\\nn\n\n");
-                        // }
-                    }
-                    _ => return Err(Error::new_spanned(variant.clone(), "Variant invalid")),
+    let patterns = variants.iter().map(|v| v.pattern()).collect::<Vec<_>>();
+    let write_ids = variants.iter().map(|v| v.write_id()).collect::<Vec<_>>();
+    let match_ids = variants.iter().map(|v| v.match_id()).collect::<Vec<_>>();
+    let id_fields = variants.iter().map(|v| v.id_fields()).collect::<Vec<_>>();
+    let (locals, types): (Vec<_>, Vec<_>) = variants.iter().map(|v| v.body()).unzip();
+
+    let unit = variants.iter().all(|v| v.fields.is_empty());
+    // only unit enums have the same size for every value.
+    let fixed_size = if unit {
+        quote! {
+            #[automatically_derived]
+            impl ::binary_utils::layout::FixedSize for #name {
+                const WIRE_SIZE: usize = ::std::mem::size_of::<#enum_ty>();
+            }
+        }
+    } else {
+        quote!()
+    };
+    let msgpack = impl_msgpack_enum(name, &enum_ty, &variants);
+    let migrate = impl_migrate_enum(name, &variants);
+    let schema = impl_schema_enum(name, &enum_ty, &variants);
+
+    Ok(quote! {
+        #msgpack
+
+        #migrate
+
+        #fixed_size
+
+        #schema
+
+        #[automatically_derived]
+        impl Streamable for #name {
+            fn parse(&self) -> Result<Vec<u8>, ::binary_utils::error::BinaryError> {
+                let mut out = ::binary_utils::alloc::allocate(<Self as Streamable>::size_hint(self).unwrap_or(0));
+                <Self as Streamable>::parse_into(self, &mut out)?;
+                Ok(out)
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                #[allow(unused_variables)]
+                match self {
+                    #(#patterns => {
+                        (#write_ids as #enum_ty).parse_into(out)?;
+                        #(Streamable::parse_into(#locals, out)?;)*
+                    })*
                 }
+                Ok(())
             }
 
-            Ok(quote! {
-                #[automatically_derived]
-                impl Streamable for #name {
-                    fn parse(&self) -> Result<Vec<u8>, ::binary_utils::error::BinaryError> {
-                        match self {
-                            #(#writers)*
-                        }
-                    }
+            fn compose(source: &[u8], offset: &mut usize) -> Result<Self, ::binary_utils::error::BinaryError> {
+                // get the repr type and read it
+                let v = ::binary_utils::error::compose_at::<#enum_ty>(source, offset)?;
+
+                match v {
+                    #(#match_ids => {
+                        #[allow(unused_variables)]
+                        let id = v;
+                        #id_fields
+                        #(let #locals = ::binary_utils::error::compose_at::<#types>(source, offset)?;)*
+                        Ok(#patterns)
+                    })*
+                    _ => Err(::binary_utils::error::BinaryError::RecoverableKnown(
+                        format!("{} is not a variant of {}", v, stringify!(#name))
+                    ))
+                }
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                #[allow(unused_variables)]
+                match self {
+                    #(#patterns => Some(::std::mem::size_of::<#enum_ty>() #(+ Streamable::size_hint(#locals)?)*),)*
+                }
+            }
 
-                    fn compose(source: &[u8], offset: &mut usize) -> Result<Self, ::binary_utils::error::BinaryError> {
-                        // get the repr type and read it
-                        let v = <#enum_ty>::compose(source, offset)?;
+            fn validate(source: &[u8], offset: &mut usize) -> Result<(), ::binary_utils::error::BinaryError> {
+                let v = <#enum_ty>::compose(source, offset)?;
 
-                        match v {
-                            #(#readers)*
-                            _ => panic!("Will not fit in enum!")
-                        }
+                match v {
+                    #(#match_ids => {
+                        #(<#types>::validate(source, offset)?;)*
+                        Ok(())
+                    })*
+                    _ => Err(::binary_utils::error::BinaryError::RecoverableKnown(
+                        format!("{} is not a variant of {}", v, stringify!(#name))
+                    ))
+                }
+            }
+        }
+    })
+}
+
+/// Rewrites `self.field` in an `id_expr` to the local the field is bound
+/// to, `self.0` to the first field of a tuple variant.
+fn bind_self_fields(tokens: TokenStream) -> TokenStream {
+    let mut out = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Ident(ident) if ident == "self" => {
+                let mut rest = tokens.clone();
+                match (rest.next(), rest.next()) {
+                    (Some(TokenTree::Punct(dot)), Some(field)) if dot.as_char() == '.' => {
+                        tokens = rest;
+                        let local = format_ident!("__binary_{}", field.to_string());
+                        out.extend(quote!((*#local)));
                     }
+                    _ => out.push(TokenTree::Ident(ident)),
                 }
+            }
+            TokenTree::Group(group) => {
+                let mut inner =
+                    proc_macro2::Group::new(group.delimiter(), bind_self_fields(group.stream()));
+                inner.set_span(group.span());
+                out.push(TokenTree::Group(inner));
+            }
+            other => out.push(other),
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// Emits a round trip test for types marked with `#[binary(test_roundtrip)]`.
+///
+/// The value comes from `Default`, or from the function given with
+/// `#[binary(test_roundtrip = "path::to::sample")]`, and is round tripped under
+/// both byte orders so codecs that only honour one of them are caught.
+fn impl_roundtrip(name: &Ident, attrs: &[Attribute]) -> Result<TokenStream> {
+    let mut sample = None;
+    let mut enabled = false;
+    for (key, lit, meta) in binary_options(attrs)? {
+        if key != "test_roundtrip" {
+            continue;
+        }
+        enabled = true;
+        sample =
+            match lit {
+                Some(Lit::Str(path)) => Some(path.parse::<ExprPath>()?),
+                None => None,
+                Some(_) => return Err(Error::new_spanned(
+                    meta,
+                    "Expected #[binary(test_roundtrip)] or #[binary(test_roundtrip = \"sample\")]",
+                )),
+            };
+    }
+
+    if !enabled {
+        return Ok(quote!());
+    }
+
+    let value = match sample {
+        Some(path) => quote!(#path()),
+        None => quote!(<#name as ::std::default::Default>::default()),
+    };
+    let test = format_ident!("__binary_roundtrip_{}", name.to_string().to_lowercase());
+
+    Ok(quote! {
+        #[cfg(test)]
+        #[test]
+        #[allow(non_snake_case)]
+        fn #test() {
+            use ::binary_utils::profile::{Endian, WireProfile};
+            let value: #name = #value;
+            for endian in [Endian::Big, Endian::Little] {
+                let profile = WireProfile::current().with_endian(endian);
+                let bytes = profile.encode(&value).unwrap();
+                let mut position = 0;
+                let decoded: #name = profile.decode(&bytes, &mut position).unwrap();
+                assert_eq!(decoded, value, "{} did not round trip ({:?})", stringify!(#name), endian);
+                assert_eq!(position, bytes.len(), "{} left bytes unread ({:?})", stringify!(#name), endian);
+            }
+        }
+    })
+}
+
+/// Implements `PartialEq`, `Eq` and `Hash` over the canonical encoding for `#[binary(wire_eq)]`.
+///
+/// Two values are equal when they encode to the same bytes under
+/// `WireProfile::CANONICAL`, whatever their fields hold in memory.
+fn impl_wire_eq(name: &Ident, attrs: &[Attribute]) -> Result<TokenStream> {
+    if !binary_options(attrs)?
+        .iter()
+        .any(|(key, _, _)| key == "wire_eq")
+    {
+        return Ok(quote!());
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::std::cmp::PartialEq for #name {
+            fn eq(&self, other: &Self) -> bool {
+                let profile = ::binary_utils::profile::WireProfile::CANONICAL;
+                profile.encode(self) == profile.encode(other)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::std::cmp::Eq for #name {}
+
+        #[automatically_derived]
+        impl ::std::hash::Hash for #name {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                let bytes = ::binary_utils::profile::WireProfile::CANONICAL.encode(self).ok();
+                ::std::hash::Hash::hash(&bytes, state);
+            }
+        }
+    })
+}
+
+/// A named struct field and the derive options it was annotated with.
+#[derive(Clone)]
+pub struct StructField {
+    pub name: Ident,
+    pub ty: Type,
+    pub attrs: Vec<Attribute>,
+    /// The value from `#[default]` or `#[default = expr]`, used when the
+    /// source ends before the field.
+    pub default: Option<TokenStream>,
+    /// The byte order from `#[le]` or `#[be]`, or the struct's
+    /// `#[binary(little_endian)]` or `#[binary(big_endian)]`.
+    pub endian: Option<FieldEndian>,
+    /// The predicate from `#[binary(cfg(...))]`, the field is only on the
+    /// wire when it holds.
+    pub cfg: Option<TokenStream>,
+    /// Whether the field is left out because its `cfg` doesn't hold.
+    pub excluded: bool,
+}
+
+/// A byte order a field is always written in, whatever the profile's is.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FieldEndian {
+    Little,
+    Big,
+}
+
+impl StructField {
+    /// The local the field is composed into, prefixed so it can't shadow `source` or `position`.
+    pub fn local(&self) -> Ident {
+        format_ident!("__binary_{}", self.name)
+    }
+
+    /// Whether the field is left out of the wire with `#[skip]`, or because
+    /// its `cfg` doesn't hold.
+    pub fn skipped(&self) -> bool {
+        self.excluded || self.attrs.iter().any(|a| a.path.is_ident("skip"))
+    }
+
+    /// Wraps `expr` so it runs with the field's byte order, if it has one.
+    pub fn scoped(&self, expr: TokenStream) -> TokenStream {
+        let endian = match self.endian {
+            Some(FieldEndian::Little) => quote!(::binary_utils::profile::Endian::Little),
+            Some(FieldEndian::Big) => quote!(::binary_utils::profile::Endian::Big),
+            None => return expr,
+        };
+        quote! {
+            ::binary_utils::profile::WireProfile::current()
+                .with_endian(#endian)
+                .scope(|| #expr)
+        }
+    }
+
+    /// The type the field is described as by `Schema`, `LE<T>` for little endian fields.
+    pub fn schema_ty(&self) -> TokenStream {
+        let ty = &self.ty;
+        match self.endian {
+            Some(FieldEndian::Little) => quote!(::binary_utils::LE<#ty>),
+            Some(FieldEndian::Big) => quote!(::binary_utils::BE<#ty>),
+            None => quote!(#ty),
+        }
+    }
+
+    /// Wraps the error of `read` with the field's path, e.g. `Login.name`.
+    pub fn with_path(&self, owner: &Ident, read: TokenStream) -> TokenStream {
+        let (owner, name) = (owner.to_string(), self.name.to_string());
+        quote! {
+            #read.map_err(|error| error.with_field(#owner, #name))
+        }
+    }
+
+    pub fn has_option(&self, key: &str) -> Result<bool> {
+        Ok(binary_options(&self.attrs)?
+            .iter()
+            .any(|(name, _, _)| name == key))
+    }
+}
+
+pub fn impl_named_fields(fields: Fields) -> Result<Vec<StructField>> {
+    match fields {
+        Fields::Named(v) => v
+            .named
+            .into_iter()
+            .map(|field| {
+                let endians = field
+                    .attrs
+                    .iter()
+                    .filter(|a| a.path.is_ident("le") || a.path.is_ident("be"));
+                if let Some(extra) = endians.skip(1).next() {
+                    return Err(Error::new_spanned(
+                        extra,
+                        "A field can only have one of #[le] and #[be]",
+                    ));
+                }
+                let default = match find_one_attr("default", field.attrs.clone()) {
+                    Some(attr) if attr.tokens.is_empty() => {
+                        Some(quote!(::std::default::Default::default()))
+                    }
+                    Some(attr) => {
+                        let value = |input: ParseStream| {
+                            input.parse::<Token![=]>()?;
+                            input.parse::<Expr>()
+                        };
+                        Some(value.parse2(attr.tokens)?.into_token_stream())
+                    }
+                    None => None,
+                };
+                let endian = if field.attrs.iter().any(|a| a.path.is_ident("le")) {
+                    Some(FieldEndian::Little)
+                } else if field.attrs.iter().any(|a| a.path.is_ident("be")) {
+                    Some(FieldEndian::Big)
+                } else {
+                    None
+                };
+                let cfg = field_cfg(&field.attrs)?;
+                Ok(StructField {
+                    name: field.ident.unwrap(),
+                    ty: field.ty,
+                    attrs: field.attrs,
+                    default,
+                    endian,
+                    cfg,
+                    excluded: false,
+                })
             })
+            .collect(),
+        Fields::Unnamed(v) => Err(Error::new_spanned(
+            v,
+            "Can not parse un-named fields at this current point in time.",
+        )),
+        Fields::Unit => Err(Error::new(
+            Span::call_site(),
+            "Can not use uninitalized data values.",
+        )),
+    }
+}
+
+/// Finds the predicate of a `#[binary(cfg(...))]` field option.
+fn field_cfg(attrs: &[Attribute]) -> Result<Option<TokenStream>> {
+    for (key, _, meta) in binary_options(attrs)? {
+        if key != "cfg" {
+            continue;
         }
-        Data::Union(_) => Err(syn::Error::new(
+        return match meta {
+            Meta::List(list) if list.nested.len() == 1 => Ok(Some(list.nested.to_token_stream())),
+            other => Err(Error::new_spanned(
+                other,
+                "Expected a single predicate, e.g. #[binary(cfg(feature = \"extended\"))]",
+            )),
+        };
+    }
+    Ok(None)
+}
+
+/// The most distinct `#[binary(cfg(...))]` predicates a struct can have, as
+/// every combination of them gets its own impls.
+const MAX_CFGS: usize = 4;
+
+/// Derives a struct once for every combination of its field `cfg`s, each
+/// under a `#[cfg]` matching that combination, so only the layout of the
+/// current build is compiled. Fields whose predicate doesn't hold are left
+/// out like `#[skip]` fields.
+fn derive_struct(
+    name: &Ident,
+    vis: &Visibility,
+    attrs: &[Attribute],
+    fields: Vec<StructField>,
+) -> Result<TokenStream> {
+    let mut cfgs = Vec::<TokenStream>::new();
+    for field in &fields {
+        if let Some(cfg) = &field.cfg {
+            if !cfgs.iter().any(|c| c.to_string() == cfg.to_string()) {
+                cfgs.push(cfg.clone());
+            }
+        }
+    }
+    if cfgs.is_empty() {
+        return derive_struct_layout(name, vis, attrs, fields);
+    }
+    if cfgs.len() > MAX_CFGS {
+        return Err(Error::new(
+            Span::call_site(),
+            format!(
+                "A struct can have at most {} distinct #[binary(cfg(...))] predicates",
+                MAX_CFGS
+            ),
+        ));
+    }
+
+    let mut out = TokenStream::new();
+    for enabled in 0..1usize << cfgs.len() {
+        let holds = |cfg: &TokenStream| {
+            let index = cfgs.iter().position(|c| c.to_string() == cfg.to_string());
+            index.is_some_and(|i| enabled & (1 << i) != 0)
+        };
+        let predicates = cfgs.iter().enumerate().map(|(i, cfg)| {
+            if enabled & (1 << i) != 0 {
+                quote!(#cfg)
+            } else {
+                quote!(not(#cfg))
+            }
+        });
+        let predicate = quote!(all(#(#predicates),*));
+
+        let mut fields = fields.clone();
+        for field in &mut fields {
+            field.excluded = field.cfg.as_ref().is_some_and(|cfg| !holds(cfg));
+        }
+        let items = syn::parse2::<syn::File>(derive_struct_layout(name, vis, attrs, fields)?)?;
+        for item in items.items {
+            out.extend(quote! {
+                #[cfg(#predicate)]
+                #item
+            });
+        }
+    }
+    Ok(out)
+}
+
+fn derive_struct_layout(
+    name: &Ident,
+    vis: &Visibility,
+    attrs: &[Attribute],
+    mut fields: Vec<StructField>,
+) -> Result<TokenStream> {
+    // fields without #[le] or #[be] follow the struct's byte order, if it has one.
+    let options = binary_options(attrs)?;
+    let endian =
+        match (
+            options.iter().any(|(key, _, _)| key == "little_endian"),
+            options.iter().any(|(key, _, _)| key == "big_endian"),
+        ) {
+            (true, true) => return Err(Error::new(
+                Span::call_site(),
+                "A struct can only have one of #[binary(little_endian)] and #[binary(big_endian)]",
+            )),
+            (true, false) => Some(FieldEndian::Little),
+            (false, true) => Some(FieldEndian::Big),
+            (false, false) => None,
+        };
+    for field in &mut fields {
+        field.endian = field.endian.or(endian);
+    }
+
+    // a user provided constructor, for private fields or #[non_exhaustive] structs.
+    let construct = match string_option(attrs, "construct")? {
+        Some(path) => Some(path.parse::<ExprPath>()?),
+        None => None,
+    };
+    let names = fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let values = fields.iter().map(|f| {
+        if f.skipped() {
+            quote!(::std::default::Default::default())
+        } else {
+            f.local().into_token_stream()
+        }
+    });
+    let build = match &construct {
+        Some(path) => quote!(#path(#(#values),*)),
+        None => quote!(Self { #(#names: #values),* }),
+    };
+    let summary = impl_summary(name, attrs, &fields)?;
+    let compose_into = impl_compose_into(name, &fields, construct.is_some());
+
+    // skipped fields aren't written or read, so everything else only sees the rest.
+    let fields = fields
+        .into_iter()
+        .filter(|f| !f.skipped())
+        .collect::<Vec<_>>();
+    if let Some(pair) = fields
+        .windows(2)
+        .find(|pair| pair[0].default.is_some() && pair[1].default.is_none())
+    {
+        return Err(Error::new_spanned(
+            &pair[1].name,
+            "Fields after a #[default] field must have a default as well",
+        ));
+    }
+    let writes = fields
+        .iter()
+        .map(|f| impl_streamable_lazy(name, f).0)
+        .collect::<Vec<_>>();
+    let reads = fields
+        .iter()
+        .map(|f| impl_streamable_lazy(name, f).1)
+        .collect::<Vec<_>>();
+    let validates = fields.iter().map(|f| {
+        let ty = &f.ty;
+        let validate = f.scoped(quote!(<#ty>::validate(source, position)));
+        match f.default {
+            Some(_) => quote!(if *position < source.len() { #validate?; }),
+            None => quote!(#validate?;),
+        }
+    });
+    let hints = fields.iter().map(|f| {
+        let name = &f.name;
+        f.scoped(quote!(Streamable::size_hint(&self.#name)))
+    });
+    let header = impl_header(name, vis, &fields, &build)?;
+    let spans = impl_field_spans(name, &fields, &build);
+    let fixed_size = impl_fixed_size(name, &fields);
+    let schema = impl_schema_struct(name, &fields);
+    let tlv = match int_option(attrs, "tlv_id")? {
+        Some(id) => {
+            let id = id.base10_parse::<u64>()?;
+            quote! {
+                #[automatically_derived]
+                impl ::binary_utils::tlv::TlvRecord for #name {
+                    const ID: u64 = #id;
+                }
+            }
+        }
+        None => quote!(),
+    };
+    let msgpack = impl_msgpack_struct(name, &fields, &build);
+    let proto = impl_proto(name, &fields, &build)?;
+    let migrate = impl_migrate_struct(name, &fields, &build);
+
+    Ok(quote! {
+         #[automatically_derived]
+         impl Streamable for #name {
+              fn parse(&self) -> Result<Vec<u8>, ::binary_utils::error::BinaryError> {
+                   let mut writer = ::binary_utils::alloc::allocate(<Self as Streamable>::size_hint(self).unwrap_or(0));
+                   <Self as Streamable>::parse_into(self, &mut writer)?;
+                   Ok(writer)
+              }
+
+              fn parse_into(&self, writer: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                   #(#writes)*
+                   Ok(())
+              }
+
+              fn compose(source: &[u8], position: &mut usize) -> Result<Self, ::binary_utils::error::BinaryError> {
+                   use ::std::io::Read;
+                   use binary_utils::varint::{VarInt, VarIntReader};
+                   use binary_utils::{u24, u24Reader};
+
+                   #(#reads)*
+                   Ok(#build)
+              }
+
+              #compose_into
+
+              fn validate(source: &[u8], position: &mut usize) -> Result<(), ::binary_utils::error::BinaryError> {
+                   #(#validates)*
+                   Ok(())
+              }
+
+              fn size_hint(&self) -> Option<usize> {
+                   Some(0 #(+ #hints?)*)
+              }
+         }
+
+         #header
+
+         #spans
+
+         #summary
+
+         #fixed_size
+
+         #schema
+
+         #tlv
+
+         #msgpack
+
+         #proto
+
+         #migrate
+    })
+}
+
+/// Overrides `compose_into` to read every field into its existing value, so
+/// the buffers of `String` and `Vec` fields are reused.
+///
+/// Structs with a `construct` function are built through it instead, as it
+/// may do more than assign the fields. Skipped fields are reset to their
+/// default, like `compose` leaves them.
+fn impl_compose_into(name: &Ident, fields: &[StructField], construct: bool) -> TokenStream {
+    let owner = name;
+    if construct {
+        return quote!();
+    }
+    let reads = fields.iter().map(|f| {
+        let name = &f.name;
+        if f.skipped() {
+            return quote!(self.#name = ::std::default::Default::default(););
+        }
+        let read = f.scoped(quote!(::binary_utils::error::compose_into_at(
+            &mut self.#name,
+            source,
+            position
+        )));
+        let read = f.with_path(owner, read);
+        match &f.default {
+            Some(default) => quote! {
+                if *position >= source.len() { self.#name = #default; } else { #read?; }
+            },
+            None => quote!(#read?;),
+        }
+    });
+
+    quote! {
+        fn compose_into(&mut self, source: &[u8], position: &mut usize) -> Result<(), ::binary_utils::error::BinaryError> {
+            #(#reads)*
+            Ok(())
+        }
+    }
+}
+
+/// Implements `Display` from `#[binary(summary("..."))]`.
+///
+/// The summary is a format string whose `{field}` or `{field:spec}`
+/// placeholders name fields of the struct, so large payload fields can be
+/// left out of log lines.
+fn impl_summary(name: &Ident, attrs: &[Attribute], fields: &[StructField]) -> Result<TokenStream> {
+    let template = match list_string_option(attrs, "summary")? {
+        Some(template) => template,
+        None => return Ok(quote!()),
+    };
+
+    let mut used = Vec::<Ident>::new();
+    let text = template.value();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' => {
+                let placeholder = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                let key = placeholder.split(':').next().unwrap_or("").trim();
+                let field = fields.iter().find(|f| f.name == key).ok_or_else(|| {
+                    Error::new_spanned(
+                        &template,
+                        format!("{} has no field `{}` to summarize", name, key),
+                    )
+                })?;
+                if !used.contains(&field.name) {
+                    used.push(field.name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::std::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, #template, #(#used = &self.#used),*)
+            }
+        }
+    })
+}
+
+/// Implements `FixedSize` as the sum of the field sizes.
+///
+/// The bounds are higher ranked so they aren't trivially false for structs
+/// with variable sized fields: those simply don't implement `FixedSize`.
+fn impl_fixed_size(name: &Ident, fields: &[StructField]) -> TokenStream {
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::layout::FixedSize for #name
+        where
+            #(for<'__binary> #types: ::binary_utils::layout::FixedSize,)*
+        {
+            const WIRE_SIZE: usize = 0 #(+ <#types as ::binary_utils::layout::FixedSize>::WIRE_SIZE)*;
+        }
+    }
+}
+
+/// Implements `Schema` as the layouts of the fields in order.
+fn impl_schema_struct(name: &Ident, fields: &[StructField]) -> TokenStream {
+    let types = fields.iter().map(|f| f.schema_ty()).collect::<Vec<_>>();
+    let separators = (0..fields.len()).map(|i| {
+        if i == 0 {
+            quote!()
+        } else {
+            quote!(out.push(',');)
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::schema::Schema for #name
+        where
+            #(for<'__binary> #types: ::binary_utils::schema::Schema,)*
+        {
+            fn describe(out: &mut String) {
+                out.push('{');
+                #(
+                    #separators
+                    <#types as ::binary_utils::schema::Schema>::describe(out);
+                )*
+                out.push('}');
+            }
+        }
+    }
+}
+
+/// Implements `Schema` as the `#[repr]` and every discriminant with the
+/// layouts of the fields of its variant.
+fn impl_schema_enum(name: &Ident, repr: &Ident, variants: &[EnumVariant]) -> TokenStream {
+    let types = variants.iter().flat_map(|v| v.types()).collect::<Vec<_>>();
+    let describe = variants.iter().enumerate().map(|(i, v)| {
+        let discrim = v.match_id().to_string();
+        let head = format!("{}{}:{{", if i == 0 { "" } else { "," }, discrim);
+        let (_, types) = v.body();
+        let separators = (0..types.len()).map(|i| {
+            if i == 0 {
+                quote!()
+            } else {
+                quote!(out.push(',');)
+            }
+        });
+        quote! {
+            out.push_str(#head);
+            #(
+                #separators
+                <#types as ::binary_utils::schema::Schema>::describe(out);
+            )*
+            out.push('}');
+        }
+    });
+    let head = format!("enum<{}>{{", repr);
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::schema::Schema for #name
+        where
+            #(for<'__binary> #types: ::binary_utils::schema::Schema,)*
+        {
+            fn describe(out: &mut String) {
+                out.push_str(#head);
+                #(#describe)*
+                out.push('}');
+            }
+        }
+    }
+}
+
+/// Implements `FieldSpans`, recording where each field was read from.
+fn impl_field_spans(name: &Ident, fields: &[StructField], build: &TokenStream) -> TokenStream {
+    let keys = fields.iter().map(|f| f.name.to_string());
+    let indices = 0..fields.len();
+    let reads = fields.iter().map(|f| impl_streamable_lazy(name, f).1);
+    let parses = fields.iter().map(|f| {
+        let name = &f.name;
+        f.scoped(quote!(Streamable::parse(&self.#name)))
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::tracked::FieldSpans for #name {
+            const FIELDS: &'static [&'static str] = &[#(#keys),*];
+
+            fn compose_spanned(
+                source: &[u8],
+                position: &mut usize,
+                spans: &mut Vec<::std::ops::Range<usize>>,
+            ) -> Result<Self, ::binary_utils::error::BinaryError> {
+                #(
+                    let start = *position;
+                    #reads
+                    spans.push(start..*position);
+                )*
+                Ok(#build)
+            }
+
+            fn parse_field(&self, index: usize) -> Result<Vec<u8>, ::binary_utils::error::BinaryError> {
+                match index {
+                    #(#indices => #parses,)*
+                    _ => Err(::binary_utils::error::BinaryError::OutOfBounds(
+                        index,
+                        Self::FIELDS.len(),
+                        "No field at this index.",
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Implements `Migrate` for a struct, visiting fields in declaration order.
+#[cfg(feature = "migrate")]
+pub fn impl_migrate_struct(
+    name: &Ident,
+    fields: &[StructField],
+    build: &TokenStream,
+) -> TokenStream {
+    let names = fields.iter().map(|f| &f.name);
+    let types = fields.iter().map(|f| &f.ty);
+    let locals = fields.iter().map(|f| f.local());
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::migrate::Migrate for #name {
+            fn write_format(&self, format: ::binary_utils::migrate::Format, out: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                #(::binary_utils::migrate::Migrate::write_format(&self.#names, format, out)?;)*
+                Ok(())
+            }
+
+            fn read_format(format: ::binary_utils::migrate::Format, source: &mut &[u8]) -> Result<Self, ::binary_utils::error::BinaryError> {
+                #(let #locals = <#types as ::binary_utils::migrate::Migrate>::read_format(format, source)?;)*
+                Ok(#build)
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "migrate"))]
+pub fn impl_migrate_struct(_: &Ident, _: &[StructField], _: &TokenStream) -> TokenStream {
+    quote!()
+}
+
+/// Implements `Migrate` for an enum as its variant index followed by its fields, as serde does.
+#[cfg(feature = "migrate")]
+fn impl_migrate_enum(name: &Ident, variants: &[EnumVariant]) -> TokenStream {
+    let patterns = variants.iter().map(|v| v.pattern()).collect::<Vec<_>>();
+    let locals = variants.iter().map(|v| v.locals()).collect::<Vec<_>>();
+    let types = variants.iter().map(|v| v.types()).collect::<Vec<_>>();
+    let indices = (0..variants.len() as u32).collect::<Vec<_>>();
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::migrate::Migrate for #name {
+            fn write_format(&self, format: ::binary_utils::migrate::Format, out: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                match self {
+                    #(#patterns => {
+                        ::binary_utils::migrate::write_variant(#indices, format, out);
+                        #(::binary_utils::migrate::Migrate::write_format(#locals, format, out)?;)*
+                    })*
+                }
+                Ok(())
+            }
+
+            fn read_format(format: ::binary_utils::migrate::Format, source: &mut &[u8]) -> Result<Self, ::binary_utils::error::BinaryError> {
+                match ::binary_utils::migrate::read_variant(format, source)? {
+                    #(#indices => {
+                        #(let #locals = <#types as ::binary_utils::migrate::Migrate>::read_format(format, source)?;)*
+                        Ok(#patterns)
+                    })*
+                    other => Err(::binary_utils::migrate::unknown_variant(other)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "migrate"))]
+fn impl_migrate_enum(_: &Ident, _: &[EnumVariant]) -> TokenStream {
+    quote!()
+}
+
+/// Implements `ProtoMessage` when fields are tagged with `#[binary(proto_tag = N)]`.
+fn impl_proto(name: &Ident, fields: &[StructField], build: &TokenStream) -> Result<TokenStream> {
+    let mut tags = Vec::new();
+    for field in fields {
+        if let Some(tag) = int_option(&field.attrs, "proto_tag")? {
+            let value = tag.base10_parse::<u32>()?;
+            if value == 0 || value >= 1 << 29 {
+                return Err(Error::new_spanned(
+                    tag,
+                    "Protobuf field numbers must be between 1 and 536870911",
+                ));
+            }
+            if tags.contains(&value) {
+                return Err(Error::new_spanned(tag, "Duplicate proto_tag"));
+            }
+            tags.push(value);
+        }
+    }
+
+    if tags.is_empty() {
+        return Ok(quote!());
+    }
+    if tags.len() != fields.len() {
+        return Err(Error::new(
             name.span(),
-            "BinaryStream does not support Type Unions. Use Enums instead.",
-        )),
+            "Every field needs a #[binary(proto_tag = N)] when one field has one",
+        ));
     }
+
+    let names = fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let types = fields.iter().map(|f| &f.ty);
+    let locals = fields.iter().map(|f| f.local());
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::binary_utils::proto::ProtoMessage for #name {
+            fn empty() -> Self {
+                #(let #locals = <#types as ::binary_utils::proto::ProtoField>::proto_default();)*
+                #build
+            }
+
+            fn write_fields(&self, out: &mut Vec<u8>) {
+                #(::binary_utils::proto::ProtoField::write_field(&self.#names, #tags, out);)*
+            }
+
+            fn merge_tag(
+                &mut self,
+                tag: u32,
+                wire_type: ::binary_utils::proto::WireType,
+                source: &mut &[u8],
+            ) -> Result<(), ::binary_utils::error::BinaryError> {
+                match tag {
+                    #(#tags => ::binary_utils::proto::ProtoField::merge_field(&mut self.#names, wire_type, source),)*
+                    _ => ::binary_utils::proto::skip_field(wire_type, source),
+                }
+            }
+        }
+    })
 }
 
-pub fn impl_named_fields(fields: Fields) -> (Vec<TokenStream>, Vec<TokenStream>) {
-    let mut writers = Vec::<TokenStream>::new();
-    let mut readers = Vec::<TokenStream>::new();
-    match fields {
-        Fields::Named(v) => {
-            for field in &v.named {
-                let field_id = field.ident.as_ref().unwrap();
-                let (writer, reader) = impl_streamable_lazy(field_id, &field.ty);
-                writers.push(writer);
-                readers.push(reader);
+/// Implements `MsgPack` for a struct as a map keyed by field name.
+#[cfg(feature = "msgpack")]
+pub fn impl_msgpack_struct(
+    name: &Ident,
+    fields: &[StructField],
+    build: &TokenStream,
+) -> TokenStream {
+    let count = fields.len() as u32;
+    let keys = fields
+        .iter()
+        .map(|f| f.name.to_string())
+        .collect::<Vec<_>>();
+    let names = fields.iter().map(|f| &f.name);
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let locals = fields.iter().map(|f| f.local()).collect::<Vec<_>>();
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::msgpack::MsgPack for #name {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                ::binary_utils::msgpack::write_map_len(out, #count)?;
+                #(
+                    ::binary_utils::msgpack::write_key(out, #keys)?;
+                    ::binary_utils::msgpack::MsgPack::to_msgpack(&self.#names, out)?;
+                )*
+                Ok(())
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, ::binary_utils::error::BinaryError> {
+                #(let mut #locals: Option<#types> = None;)*
+                for _ in 0..::binary_utils::msgpack::read_map_len(source)? {
+                    let key = ::binary_utils::msgpack::read_key(source)?;
+                    match key.as_str() {
+                        #(#keys => #locals = Some(<#types as ::binary_utils::msgpack::MsgPack>::from_msgpack(source)?),)*
+                        _ => return Err(::binary_utils::msgpack::unknown_field(&key)),
+                    }
+                }
+                #(let #locals = #locals.ok_or_else(|| ::binary_utils::msgpack::missing_field(#keys))?;)*
+                Ok(#build)
             }
         }
-        Fields::Unnamed(_v) => {
-            panic!("Can not parse un-named fields at this current point in time.")
+    }
+}
+
+#[cfg(not(feature = "msgpack"))]
+pub fn impl_msgpack_struct(_: &Ident, _: &[StructField], _: &TokenStream) -> TokenStream {
+    quote!()
+}
+
+/// Implements `MsgPack` for a unit enum as its `#[repr]` integer.
+///
+/// Enums with fields are written as an array of the discriminant followed
+/// by the fields of the variant.
+#[cfg(feature = "msgpack")]
+fn impl_msgpack_enum(name: &Ident, repr: &Ident, variants: &[EnumVariant]) -> TokenStream {
+    let patterns = variants.iter().map(|v| v.pattern()).collect::<Vec<_>>();
+    let write_ids = variants.iter().map(|v| v.write_id()).collect::<Vec<_>>();
+    let match_ids = variants.iter().map(|v| v.match_id()).collect::<Vec<_>>();
+
+    if variants.iter().all(|v| v.fields.is_empty()) {
+        return quote! {
+            #[automatically_derived]
+            impl ::binary_utils::msgpack::MsgPack for #name {
+                fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                    let value: #repr = match self {
+                        #(#patterns => #write_ids as #repr,)*
+                    };
+                    ::binary_utils::msgpack::MsgPack::to_msgpack(&value, out)
+                }
+
+                fn from_msgpack(source: &mut &[u8]) -> Result<Self, ::binary_utils::error::BinaryError> {
+                    match <#repr as ::binary_utils::msgpack::MsgPack>::from_msgpack(source)? {
+                        #(#match_ids => Ok(#patterns),)*
+                        other => Err(::binary_utils::msgpack::unknown_variant(other)),
+                    }
+                }
+            }
+        };
+    }
+
+    let locals = variants.iter().map(|v| v.locals()).collect::<Vec<_>>();
+    let types = variants.iter().map(|v| v.types()).collect::<Vec<_>>();
+    let lengths = variants
+        .iter()
+        .map(|v| v.fields.len() as u32 + 1)
+        .collect::<Vec<_>>();
+
+    quote! {
+        #[automatically_derived]
+        impl ::binary_utils::msgpack::MsgPack for #name {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), ::binary_utils::error::BinaryError> {
+                match self {
+                    #(#patterns => {
+                        ::binary_utils::msgpack::write_array_len(out, #lengths)?;
+                        ::binary_utils::msgpack::MsgPack::to_msgpack(&(#write_ids as #repr), out)?;
+                        #(::binary_utils::msgpack::MsgPack::to_msgpack(#locals, out)?;)*
+                    })*
+                }
+                Ok(())
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, ::binary_utils::error::BinaryError> {
+                let length = ::binary_utils::msgpack::read_array_len(source)?;
+                match <#repr as ::binary_utils::msgpack::MsgPack>::from_msgpack(source)? {
+                    #(#match_ids if length == #lengths => {
+                        #(let #locals = <#types as ::binary_utils::msgpack::MsgPack>::from_msgpack(source)?;)*
+                        Ok(#patterns)
+                    })*
+                    other => Err(::binary_utils::msgpack::unknown_variant(other)),
+                }
+            }
         }
-        Fields::Unit => {
-            panic!("Can not use uninitalized data values.")
+    }
+}
+
+#[cfg(not(feature = "msgpack"))]
+fn impl_msgpack_enum(_: &Ident, _: &Ident, _: &[EnumVariant]) -> TokenStream {
+    quote!()
+}
+
+/// Generates `{Name}Header` and the `PartialDecode` impl when leading
+/// fields are marked with `#[binary(header)]`.
+fn impl_header(
+    name: &Ident,
+    vis: &Visibility,
+    fields: &[StructField],
+    build: &TokenStream,
+) -> Result<TokenStream> {
+    let mut split = 0;
+    for (i, field) in fields.iter().enumerate() {
+        if field.has_option("header")? {
+            if i != split {
+                return Err(Error::new_spanned(
+                    &field.name,
+                    "Header fields must be the leading fields of the struct",
+                ));
+            }
+            split += 1;
         }
     }
-    (writers, readers)
+
+    if split == 0 {
+        return Ok(quote!());
+    }
+
+    let (head, rest) = fields.split_at(split);
+    let header_name = format_ident!("{}Header", name);
+    let doc = format!("The leading fields of [`{}`], decoded on their own.", name);
+    let head_names = head.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let head_types = head.iter().map(|f| &f.ty);
+    let head_locals = head.iter().map(|f| f.local()).collect::<Vec<_>>();
+    let head_reads = head.iter().map(|f| impl_streamable_lazy(name, f).1);
+    let rest_reads = rest.iter().map(|f| impl_streamable_lazy(name, f).1);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #header_name {
+            #(pub #head_names: #head_types),*
+        }
+
+        #[automatically_derived]
+        impl ::binary_utils::partial::PartialDecode for #name {
+            type Header = #header_name;
+
+            fn decode_header(source: &[u8], position: &mut usize) -> Result<Self::Header, ::binary_utils::error::BinaryError> {
+                #(#head_reads)*
+                Ok(#header_name { #(#head_names: #head_locals),* })
+            }
+
+            fn decode_rest(header: Self::Header, source: &[u8], position: &mut usize) -> Result<Self, ::binary_utils::error::BinaryError> {
+                let #header_name { #(#head_names: #head_locals),* } = header;
+                #(#rest_reads)*
+                Ok(#build)
+            }
+        }
+    })
 }
 
 // pub fn impl_unnamed_fields(_fields: FieldsUnnamed) -> (TokenStream, TokenStream) {
@@ -213,11 +1350,19 @@ pub fn impl_named_fields(fields: Fields) -> (Vec<TokenStream>, Vec<TokenStream>)
 //     todo!()
 // }
 
-pub fn impl_streamable_lazy(name: &Ident, ty: &Type) -> (TokenStream, TokenStream) {
-    (
-        quote! { writer.write(&self.#name.parse()?[..])?; },
-        quote!(#name: <#ty>::compose(&source, position)?),
-    )
+pub fn impl_streamable_lazy(owner: &Ident, field: &StructField) -> (TokenStream, TokenStream) {
+    // fields are read into prefixed locals so they can't shadow `source` or `position`.
+    let (name, ty, local) = (&field.name, &field.ty, field.local());
+    let write = field.scoped(quote!(Streamable::parse_into(&self.#name, writer)));
+    let read = field.scoped(quote!(::binary_utils::error::compose_at::<#ty>(&source, position)));
+    let read = field.with_path(owner, read);
+    let read = match &field.default {
+        Some(default) => quote! {
+            let #local = if *position >= source.len() { #default } else { #read? };
+        },
+        None => quote! { let #local = #read?; },
+    };
+    (quote! { #write?; }, read)
 }
 
 fn find_one_attr(name: &str, attrs: Vec<Attribute>) -> Option<Attribute> {
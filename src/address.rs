@@ -0,0 +1,201 @@
+//! Socket address layouts other than the default `SocketAddr` one.
+//!
+//! A bare `SocketAddr` is written as a version byte, the address octets and
+//! a Big Endian port. `RakNetAddr` and `PlainAddr` cover the consumers that
+//! need something else:
+//! ```rust
+//! use std::net::SocketAddr;
+//! use binary_utils::profile::{Endian, WireProfile};
+//! use binary_utils::{PlainAddr, RakNetAddr, Streamable};
+//!
+//! let address: SocketAddr = "127.0.0.1:19132".parse().unwrap();
+//! assert_eq!(RakNetAddr(address).parse().unwrap(), [4, 128, 255, 255, 254, 74, 188]);
+//!
+//! let le = WireProfile::DEFAULT.with_endian(Endian::Little);
+//! assert_eq!(le.encode(&PlainAddr(address)).unwrap(), [4, 127, 0, 0, 1, 188, 74]);
+//! ```
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+use crate::error::BinaryError;
+use crate::{bytes_at, Streamable};
+
+/// The IPv6 address family RakNet writes, `AF_INET6` on Windows.
+const RAKNET_AF_INET6: u16 = 23;
+
+/// A socket address in RakNet's layout.
+///
+/// IPv4 octets are ones-complemented, as offline messages require, and the
+/// port is Big Endian whatever the profile is. IPv6 addresses carry a
+/// Little Endian address family ahead of the port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RakNetAddr(pub SocketAddr);
+
+impl RakNetAddr {
+    /// Grabs the `inner` address, similar to `unwrap`.
+    pub fn inner(self) -> SocketAddr {
+        self.0
+    }
+}
+
+impl From<SocketAddr> for RakNetAddr {
+    fn from(address: SocketAddr) -> Self {
+        Self(address)
+    }
+}
+
+impl From<RakNetAddr> for SocketAddr {
+    fn from(address: RakNetAddr) -> Self {
+        address.0
+    }
+}
+
+impl Streamable for RakNetAddr {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut stream = crate::alloc::allocate(self.size_hint().unwrap_or(0));
+        self.parse_into(&mut stream)?;
+        Ok(stream)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        match self.0 {
+            SocketAddr::V4(addr) => {
+                out.push(4);
+                out.extend(addr.ip().octets().iter().map(|octet| !octet));
+                out.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            SocketAddr::V6(addr) => {
+                out.push(6);
+                out.extend_from_slice(&RAKNET_AF_INET6.to_le_bytes());
+                out.extend_from_slice(&addr.port().to_be_bytes());
+                out.extend_from_slice(&addr.flowinfo().to_be_bytes());
+                out.extend_from_slice(&addr.ip().octets());
+                out.extend_from_slice(&addr.scope_id().to_be_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let mut start = *position;
+        let mut take = |length: usize| {
+            let bytes = bytes_at(source, start, length)?;
+            start += length;
+            Ok::<_, BinaryError>(bytes)
+        };
+        let address = match take(1)?[0] {
+            4 => {
+                let mut parts: [u8; 4] = take(4)?.try_into().unwrap();
+                parts.iter_mut().for_each(|octet| *octet = !*octet);
+                let port = u16::from_be_bytes(take(2)?.try_into().unwrap());
+                SocketAddr::new(IpAddr::from(parts), port)
+            }
+            6 => {
+                let _family = take(2)?;
+                let port = u16::from_be_bytes(take(2)?.try_into().unwrap());
+                let flow = u32::from_be_bytes(take(4)?.try_into().unwrap());
+                let parts: [u8; 16] = take(16)?.try_into().unwrap();
+                let scope = u32::from_be_bytes(take(4)?.try_into().unwrap());
+                SocketAddr::from(SocketAddrV6::new(Ipv6Addr::from(parts), port, flow, scope))
+            }
+            version => return Err(unknown_version(version)),
+        };
+        *position = start;
+        Ok(Self(address))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(address_size(&self.0))
+    }
+}
+
+/// A socket address with its octets as they are and its port, flow and
+/// scope in the current profile's byte order.
+///
+/// Otherwise it's laid out like a bare `SocketAddr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlainAddr(pub SocketAddr);
+
+impl PlainAddr {
+    /// Grabs the `inner` address, similar to `unwrap`.
+    pub fn inner(self) -> SocketAddr {
+        self.0
+    }
+}
+
+impl From<SocketAddr> for PlainAddr {
+    fn from(address: SocketAddr) -> Self {
+        Self(address)
+    }
+}
+
+impl From<PlainAddr> for SocketAddr {
+    fn from(address: PlainAddr) -> Self {
+        address.0
+    }
+}
+
+impl Streamable for PlainAddr {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut stream = crate::alloc::allocate(self.size_hint().unwrap_or(0));
+        self.parse_into(&mut stream)?;
+        Ok(stream)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        match self.0 {
+            SocketAddr::V4(addr) => {
+                out.push(4);
+                out.extend_from_slice(&addr.ip().octets());
+                addr.port().parse_into(out)
+            }
+            SocketAddr::V6(addr) => {
+                out.push(6);
+                0u16.parse_into(out)?;
+                addr.port().parse_into(out)?;
+                addr.flowinfo().parse_into(out)?;
+                out.extend_from_slice(&addr.ip().octets());
+                addr.scope_id().parse_into(out)
+            }
+        }
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let mut start = *position;
+        let address = match bytes_at(source, start, 1)?[0] {
+            4 => {
+                let parts: [u8; 4] = bytes_at(source, start + 1, 4)?.try_into().unwrap();
+                start += 5;
+                let port = u16::compose(source, &mut start)?;
+                SocketAddr::new(IpAddr::from(parts), port)
+            }
+            6 => {
+                start += 1;
+                let _family = u16::compose(source, &mut start)?;
+                let port = u16::compose(source, &mut start)?;
+                let flow = u32::compose(source, &mut start)?;
+                let parts: [u8; 16] = bytes_at(source, start, 16)?.try_into().unwrap();
+                start += 16;
+                let scope = u32::compose(source, &mut start)?;
+                SocketAddr::from(SocketAddrV6::new(Ipv6Addr::from(parts), port, flow, scope))
+            }
+            version => return Err(unknown_version(version)),
+        };
+        *position = start;
+        Ok(Self(address))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(address_size(&self.0))
+    }
+}
+
+fn address_size(address: &SocketAddr) -> usize {
+    match address {
+        SocketAddr::V4(_) => 7,
+        SocketAddr::V6(_) => 29,
+    }
+}
+
+fn unknown_version(version: u8) -> BinaryError {
+    BinaryError::RecoverableKnown(format!("Unknown address version: {}", version))
+}
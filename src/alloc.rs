@@ -0,0 +1,95 @@
+//! A hook for pooling the byte buffers the codec builds.
+//!
+//! The buffers `parse` returns for the crate's own and derived types, and the
+//! ones it keeps internally (scratch buffers, encoded frames, socket batches
+//! and read windows), are taken from the process wide `BufferAlloc`. Buffers
+//! the crate is done with are handed to `BufferAlloc::release`, so a server
+//! can keep them and hand the capacity out again instead of going back to the
+//! global allocator. Buffers given to the caller are theirs to release.
+//!
+//! Decoded values, such as a `Vec<u8>` field, still use the global allocator.
+//! Without the `std` feature there is no lock to swap the hook behind, so
+//! buffers always come from `Global`.
+#![cfg_attr(feature = "std", doc = "```rust")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use binary_utils::alloc::{self, BufferAlloc};
+//!
+//! struct Counting(AtomicUsize);
+//!
+//! impl BufferAlloc for Counting {
+//!     fn allocate(&self, capacity: usize) -> Vec<u8> {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!         Vec::with_capacity(capacity)
+//!     }
+//! }
+//!
+//! static COUNTING: Counting = Counting(AtomicUsize::new(0));
+//! alloc::set_buffer_alloc(&COUNTING);
+//! let buffer = alloc::allocate(16);
+//! assert!(buffer.capacity() >= 16);
+//! assert_eq!(COUNTING.0.load(Ordering::Relaxed), 1);
+//! ```
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::prelude::*;
+
+/// A source of byte buffers.
+pub trait BufferAlloc: Send + Sync {
+    /// Returns an empty buffer with room for at least `capacity` bytes.
+    fn allocate(&self, capacity: usize) -> Vec<u8>;
+
+    /// Takes back a buffer the codec no longer needs, dropping it by default.
+    fn release(&self, buffer: Vec<u8>) {
+        drop(buffer);
+    }
+}
+
+/// Allocates from the global allocator, the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+impl BufferAlloc for Global {
+    fn allocate(&self, capacity: usize) -> Vec<u8> {
+        Vec::with_capacity(capacity)
+    }
+}
+
+#[cfg(feature = "std")]
+static ALLOC: RwLock<&'static dyn BufferAlloc> = RwLock::new(&Global);
+
+/// Takes the codec's buffers from `alloc` from now on.
+#[cfg(feature = "std")]
+pub fn set_buffer_alloc(alloc: &'static dyn BufferAlloc) {
+    *ALLOC.write().unwrap_or_else(|e| e.into_inner()) = alloc;
+}
+
+/// The allocator currently in use.
+#[cfg(feature = "std")]
+pub fn buffer_alloc() -> &'static dyn BufferAlloc {
+    *ALLOC.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The allocator currently in use.
+#[cfg(not(feature = "std"))]
+pub fn buffer_alloc() -> &'static dyn BufferAlloc {
+    &Global
+}
+
+/// Allocates a buffer from the current `BufferAlloc`.
+pub fn allocate(capacity: usize) -> Vec<u8> {
+    buffer_alloc().allocate(capacity)
+}
+
+/// Returns a buffer to the current `BufferAlloc`.
+pub fn release(buffer: Vec<u8>) {
+    buffer_alloc().release(buffer)
+}
+
+/// Copies `bytes` into a buffer from the current `BufferAlloc`.
+pub fn to_vec(bytes: &[u8]) -> Vec<u8> {
+    let mut buffer = allocate(bytes.len());
+    buffer.extend_from_slice(bytes);
+    buffer
+}
@@ -0,0 +1,244 @@
+//! Decoding whole captures for offline analysis.
+//!
+//! An `Analyzer` decodes every frame of a capture with a `Registry`, carrying
+//! on past frames that fail instead of stopping at the first error. Its
+//! `Report` counts the frames each packet type decoded and failed, and where
+//! decoding most often failed, which is usually enough to spot which packets
+//! changed after a protocol update.
+//! ```rust
+//! use binary_utils::analysis::Analyzer;
+//! use binary_utils::framing::LengthPrefix;
+//! use binary_utils::registry::{Framing, PacketId, Registry};
+//!
+//! let mut registry = Registry::<u64>::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::U16));
+//! registry.register::<u64>(0x01);
+//!
+//! let mut capture = Vec::new();
+//! registry.encode_into(&7u64, &mut capture).unwrap();
+//! // a ping that lost half of its body.
+//! capture.extend_from_slice(&[0, 5, 0x01, 0, 0, 0, 0]);
+//!
+//! let mut analyzer = Analyzer::new(&registry);
+//! analyzer.stream(&capture);
+//! let report = analyzer.report();
+//!
+//! assert_eq!(report.frames, 2);
+//! assert_eq!((report.packets[0].decoded, report.packets[0].failed), (1, 1));
+//! assert_eq!(report.packets[0].failures[0].offset, 1);
+//! println!("{}", report);
+//! ```
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::registry::{Framing, Registry};
+
+/// Failures shown per packet type by the `Display` of a `Report`.
+const SHOWN_FAILURES: usize = 3;
+
+/// Decodes frames with a registry, recording the result of every attempt.
+pub struct Analyzer<'a, M> {
+    registry: &'a Registry<M>,
+    frames: usize,
+    skipped: usize,
+    unreadable: usize,
+    packets: BTreeMap<u32, Counts>,
+}
+
+#[derive(Default)]
+struct Counts {
+    decoded: usize,
+    failed: usize,
+    failures: BTreeMap<(usize, String), usize>,
+}
+
+impl<'a, M> Analyzer<'a, M> {
+    /// Creates an analyzer decoding with `registry`.
+    pub fn new(registry: &'a Registry<M>) -> Self {
+        Self {
+            registry,
+            frames: 0,
+            skipped: 0,
+            unreadable: 0,
+            packets: BTreeMap::new(),
+        }
+    }
+
+    /// Decodes a single datagram, or a single frame body without its length.
+    ///
+    /// A frame that decodes without reading all of its bytes counts as a
+    /// failure, since that usually means fields were added to the packet.
+    pub fn datagram(&mut self, frame: &[u8]) {
+        self.frames += 1;
+        let (id, offset, result) = self.registry.decode_traced(frame);
+        let id = match id {
+            Some(id) => id,
+            None => {
+                self.unreadable += 1;
+                return;
+            }
+        };
+
+        let counts = self.packets.entry(id).or_default();
+        let error = match result {
+            Ok(_) if offset == frame.len() => {
+                counts.decoded += 1;
+                return;
+            }
+            Ok(_) => format!("{} trailing bytes", frame.len() - offset),
+            Err(e) => e.get_message(),
+        };
+        counts.failed += 1;
+        *counts.failures.entry((offset, error)).or_default() += 1;
+    }
+
+    /// Decodes every frame of a capture of a byte stream.
+    ///
+    /// When a length prefix is broken or runs past the end of the capture, the
+    /// bytes up to the next frame the registry can `resync` to are skipped.
+    /// For datagram framing the whole capture is a single datagram.
+    pub fn stream(&mut self, capture: &[u8]) {
+        let prefix = match self.registry.framing() {
+            Framing::LengthPrefixed(prefix) => prefix,
+            Framing::Datagram => return self.datagram(capture),
+        };
+
+        let mut position = 0;
+        while position < capture.len() {
+            let frame =
+                prefix
+                    .read(&capture[position..])
+                    .ok()
+                    .flatten()
+                    .and_then(|(length, header)| {
+                        capture
+                            .get(position + header..position + header + length)
+                            .map(|body| (body, header + length))
+                    });
+            match frame {
+                Some((body, size)) => {
+                    self.datagram(body);
+                    position += size;
+                }
+                None => {
+                    let next = self
+                        .registry
+                        .resync(capture, position + 1)
+                        .map_or(capture.len(), |found| found.offset);
+                    self.skipped += next - position;
+                    position = next;
+                }
+            }
+        }
+    }
+
+    /// A summary of every frame decoded so far.
+    pub fn report(&self) -> Report {
+        let packets = self
+            .packets
+            .iter()
+            .map(|(id, counts)| {
+                let mut failures: Vec<Failure> = counts
+                    .failures
+                    .iter()
+                    .map(|((offset, error), count)| Failure {
+                        offset: *offset,
+                        error: error.clone(),
+                        count: *count,
+                    })
+                    .collect();
+                failures.sort_by_key(|failure| std::cmp::Reverse(failure.count));
+                PacketReport {
+                    id: *id,
+                    name: self.registry.name_of_id(*id),
+                    decoded: counts.decoded,
+                    failed: counts.failed,
+                    failures,
+                }
+            })
+            .collect();
+
+        Report {
+            frames: self.frames,
+            skipped_bytes: self.skipped,
+            unreadable: self.unreadable,
+            packets,
+        }
+    }
+}
+
+/// The results of decoding a capture, from `Analyzer::report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// The number of frames decoded, successfully or not.
+    pub frames: usize,
+    /// Bytes skipped over to find the next frame after broken framing.
+    pub skipped_bytes: usize,
+    /// Frames whose packet id couldn't be read.
+    pub unreadable: usize,
+    /// The results per packet id, ordered by id.
+    pub packets: Vec<PacketReport>,
+}
+
+/// The results for a single packet id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketReport {
+    pub id: u32,
+    /// The registered type, `None` for ids that aren't registered.
+    pub name: Option<&'static str>,
+    pub decoded: usize,
+    pub failed: usize,
+    /// Where decoding failed, most frequent first.
+    pub failures: Vec<Failure>,
+}
+
+impl PacketReport {
+    /// The fraction of frames that decoded, from `0.0` to `1.0`.
+    pub fn success_rate(&self) -> f64 {
+        self.decoded as f64 / (self.decoded + self.failed) as f64
+    }
+}
+
+/// A failure seen one or more times at the same offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// The offset into the frame, counting from the packet id.
+    pub offset: usize,
+    pub error: String,
+    pub count: usize,
+}
+
+impl Report {
+    /// The report for packets with `id`.
+    pub fn packet(&self, id: u32) -> Option<&PacketReport> {
+        self.packets.iter().find(|packet| packet.id == id)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} frames, {} unreadable, {} bytes skipped",
+            self.frames, self.unreadable, self.skipped_bytes
+        )?;
+        for packet in &self.packets {
+            writeln!(
+                f,
+                "  {:#04x} {}: {}/{} decoded ({:.1}%)",
+                packet.id,
+                packet.name.unwrap_or("<unknown>"),
+                packet.decoded,
+                packet.decoded + packet.failed,
+                packet.success_rate() * 100.0
+            )?;
+            for failure in packet.failures.iter().take(SHOWN_FAILURES) {
+                writeln!(
+                    f,
+                    "    {}x at offset {}: {}",
+                    failure.count, failure.offset, failure.error
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
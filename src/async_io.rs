@@ -0,0 +1,437 @@
+//! Async adapters over tokio's `AsyncRead` and `AsyncWrite`.
+//!
+//! These are only available with the `tokio` feature.
+use std::collections::VecDeque;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{Sink, Stream};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+use crate::framing::{encode_frame, LengthPrefix};
+use crate::registry::Priority;
+use crate::Streamable;
+
+/// A `Sink` that encodes, frames and batches packets onto an `AsyncWrite`.
+///
+/// Encoded frames are held in a bounded queue. Once the queue is full,
+/// `poll_ready` applies backpressure until the writer has caught up. When
+/// writing, consecutive frames are coalesced into a single buffer (up to the
+/// batch size) so many small packets don't cost one write each.
+///
+/// With `with_priority`, queued frames are written highest `Priority` first,
+/// so when the writer falls behind urgent packets overtake bulk data.
+/// ```rust ignore
+/// use futures::SinkExt;
+/// use binary_utils::{async_io::PacketSink, framing::LengthPrefix};
+///
+/// let mut sink = PacketSink::<_, MyPacket>::new(socket, LengthPrefix::VarInt);
+/// sink.send(packet).await?;
+/// ```
+pub struct PacketSink<W, T> {
+    writer: W,
+    prefix: LengthPrefix,
+    /// One queue per `Priority`, lowest first.
+    queues: [VecDeque<Vec<u8>>; 3],
+    prioritize: Option<Prioritize<T>>,
+    capacity: usize,
+    batch: Vec<u8>,
+    written: usize,
+    max_batch: usize,
+    _marker: PhantomData<fn(T)>,
+}
+
+type Prioritize<T> = Box<dyn Fn(&T) -> Priority + Send + Sync>;
+
+impl<W, T> PacketSink<W, T>
+where
+    W: AsyncWrite + Unpin,
+    T: Streamable,
+{
+    /// The default amount of frames queued before applying backpressure.
+    pub const DEFAULT_CAPACITY: usize = 64;
+    /// The default size of a coalesced write, 16 KiB.
+    pub const DEFAULT_BATCH: usize = 16 * 1024;
+
+    /// Creates a sink with the default queue and batch sizes.
+    pub fn new(writer: W, prefix: LengthPrefix) -> Self {
+        Self::with_limits(writer, prefix, Self::DEFAULT_CAPACITY, Self::DEFAULT_BATCH)
+    }
+
+    /// Creates a sink queueing at most `capacity` frames, coalescing writes
+    /// of up to `max_batch` bytes.
+    pub fn with_limits(writer: W, prefix: LengthPrefix, capacity: usize, max_batch: usize) -> Self {
+        Self {
+            writer,
+            prefix,
+            queues: Default::default(),
+            prioritize: None,
+            capacity: capacity.max(1),
+            batch: crate::alloc::allocate(max_batch),
+            written: 0,
+            max_batch,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sends queued packets in the order of the priority `prioritize` gives them,
+    /// packets of the same priority keep the order they were sent in.
+    /// ```rust ignore
+    /// let sink = PacketSink::new(socket, LengthPrefix::VarInt)
+    ///     .with_priority(move |packet: &Outbound| registry.priority_of_id(packet.id()));
+    /// ```
+    pub fn with_priority(
+        mut self,
+        prioritize: impl Fn(&T) -> Priority + Send + Sync + 'static,
+    ) -> Self {
+        self.prioritize = Some(Box::new(prioritize));
+        self
+    }
+
+    /// The amount of frames waiting to be written.
+    pub fn queued(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    /// The queue holding the most urgent frames that are waiting.
+    fn next_queue(&mut self) -> Option<&mut VecDeque<Vec<u8>>> {
+        self.queues.iter_mut().rev().find(|queue| !queue.is_empty())
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Unwraps this sink, returning the underlying writer.
+    ///
+    /// Any frames that have not been flushed are lost.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes out queued frames, coalescing as many as fit in one batch.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            if self.written == self.batch.len() {
+                self.batch.clear();
+                self.written = 0;
+
+                let max_batch = self.max_batch;
+                while let Some(queue) = self.next_queue() {
+                    let length = queue.front().map_or(0, Vec::len);
+                    if !self.batch.is_empty() && self.batch.len() + length > max_batch {
+                        break;
+                    }
+                    let frame = self.next_queue().unwrap().pop_front().unwrap();
+                    self.batch.extend_from_slice(&frame[..]);
+                    crate::alloc::release(frame);
+                }
+
+                if self.batch.is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+            }
+
+            match Pin::new(&mut self.writer).poll_write(cx, &self.batch[self.written..]) {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "Could not write frame to the underlying writer.",
+                    )))
+                }
+                Poll::Ready(Ok(amount)) => self.written += amount,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<W, T> Sink<T> for PacketSink<W, T>
+where
+    W: AsyncWrite + Unpin,
+    T: Streamable,
+{
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.queued() < this.capacity {
+            return Poll::Ready(Ok(()));
+        }
+
+        match this.poll_drain(cx) {
+            Poll::Ready(result) => Poll::Ready(result),
+            // draining may have moved frames out of the queue into the batch.
+            Poll::Pending if this.queued() < this.capacity => Poll::Ready(Ok(())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> io::Result<()> {
+        let this = self.get_mut();
+        let mut frame = crate::alloc::allocate(0);
+        encode_frame(&item, this.prefix, &mut frame)?;
+        let priority = this
+            .prioritize
+            .as_ref()
+            .map_or(Priority::Normal, |f| f(&item));
+        this.queues[priority as usize].push_back(frame);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_drain(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.writer).poll_flush(cx),
+            other => other,
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.get_mut().writer).poll_shutdown(cx),
+            other => other,
+        }
+    }
+}
+
+/// A `Stream` that reads length-prefixed packets from an `AsyncRead`.
+///
+/// Partial reads are buffered internally until a whole frame is available.
+/// Frames larger than the configured maximum are rejected with
+/// `InvalidData` before their body is buffered. That, a malformed length
+/// prefix or a stream ending mid-frame loses track of where the next frame
+/// starts, so the stream ends after yielding the error. A frame whose body
+/// fails to decode is yielded as an error and reading carries on with the
+/// next one.
+/// ```rust ignore
+/// use futures::StreamExt;
+/// use binary_utils::{async_io::PacketStream, framing::LengthPrefix};
+///
+/// let mut stream = PacketStream::<_, MyPacket>::new(socket, LengthPrefix::VarInt);
+/// while let Some(packet) = stream.next().await {
+///     handle(packet?);
+/// }
+/// ```
+pub struct PacketStream<R, T> {
+    reader: R,
+    prefix: LengthPrefix,
+    buffer: Vec<u8>,
+    start: usize,
+    max_frame: usize,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R, T> PacketStream<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: Streamable,
+{
+    /// The default maximum frame body size, 8 MiB.
+    pub const DEFAULT_MAX_FRAME: usize = 8 * 1024 * 1024;
+    /// The amount of bytes requested from the reader per read.
+    const READ_CHUNK: usize = 4096;
+
+    /// Creates a stream with the default maximum frame size.
+    pub fn new(reader: R, prefix: LengthPrefix) -> Self {
+        Self::with_max_frame(reader, prefix, Self::DEFAULT_MAX_FRAME)
+    }
+
+    /// Creates a stream rejecting frame bodies larger than `max_frame` bytes.
+    pub fn with_max_frame(reader: R, prefix: LengthPrefix, max_frame: usize) -> Self {
+        Self {
+            reader,
+            prefix,
+            buffer: crate::alloc::allocate(Self::READ_CHUNK),
+            start: 0,
+            max_frame,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Unwraps this stream, returning the underlying reader.
+    ///
+    /// Any buffered bytes are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Attempts to take one whole frame out of the buffer.
+    ///
+    /// Errors in the length prefix end the stream, as the next frame can't
+    /// be found anymore.
+    fn next_frame(&mut self) -> Option<io::Result<T>> {
+        let available = &self.buffer[self.start..];
+        let (length, header) = match self.prefix.read(available) {
+            Ok(Some(v)) => v,
+            Ok(None) => return None,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e.into()));
+            }
+        };
+
+        if length > self.max_frame {
+            self.done = true;
+            return Some(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Frame of {} bytes exceeds the maximum of {} bytes.",
+                    length, self.max_frame
+                ),
+            )));
+        }
+
+        if available.len() < header + length {
+            return None;
+        }
+
+        // the prefix delimits the frame, so a body that fails to decode
+        // doesn't affect the frames after it.
+        let mut position: usize = 0;
+        let value = T::compose(&available[header..header + length], &mut position);
+        self.start += header + length;
+        Some(value.map_err(io::Error::from))
+    }
+}
+
+impl<R, T> Stream for PacketStream<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: Streamable,
+{
+    type Item = io::Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            if let Some(result) = this.next_frame() {
+                return Poll::Ready(Some(result));
+            }
+
+            if this.start > 0 {
+                this.buffer.drain(..this.start);
+                this.start = 0;
+            }
+
+            let filled = this.buffer.len();
+            this.buffer.resize(filled + Self::READ_CHUNK, 0);
+            let mut read_buf = ReadBuf::new(&mut this.buffer[filled..]);
+            let polled = Pin::new(&mut this.reader).poll_read(cx, &mut read_buf);
+            let read = read_buf.filled().len();
+            this.buffer.truncate(filled + read);
+
+            match polled {
+                Poll::Ready(Ok(())) if read == 0 => {
+                    this.done = true;
+                    if !this.buffer.is_empty() {
+                        return Poll::Ready(Some(Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "Stream ended in the middle of a frame.",
+                        ))));
+                    }
+                }
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Composes a single `T` from `reader`, reading only as much as it needs.
+///
+/// Values aren't framed, so bytes are taken from the reader's buffer until
+/// `T::validate` stops failing with an incomplete error, and the value is
+/// composed once it is whole; whatever follows the value is left in the
+/// buffer for the next read. Wrap a plain `AsyncRead` in a
+/// `tokio::io::BufReader` to use it here.
+///
+/// A value taking more than `max_len` bytes fails with `InvalidData`, so a
+/// bad length prefix can't make the task buffer without limit.
+/// ```rust ignore
+/// use binary_utils::async_io::{read_streamable, write_streamable};
+///
+/// let mut reader = tokio::io::BufReader::new(socket);
+/// let protocol: u32 = read_streamable(&mut reader, 4).await?;
+/// let name: String = read_streamable(&mut reader, 1024).await?;
+/// ```
+pub async fn read_streamable<R, T>(reader: &mut R, max_len: usize) -> io::Result<T>
+where
+    R: AsyncBufRead + Unpin,
+    T: Streamable,
+{
+    let too_long = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Value is longer than {} bytes.", max_len),
+        )
+    };
+
+    // bytes of the value that were consumed by earlier attempts
+    let mut pending = Vec::new();
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Reader ended in the middle of a value.",
+            ));
+        }
+
+        let start = pending.len();
+        let source = if start == 0 {
+            available
+        } else {
+            pending.extend_from_slice(available);
+            &pending[..]
+        };
+
+        let mut end = 0;
+        match T::validate(source, &mut end) {
+            Ok(()) if end > max_len => return Err(too_long()),
+            Ok(()) => {
+                let mut position = 0;
+                let value = T::compose(source, &mut position)?;
+                reader.consume(position - start);
+                return Ok(value);
+            }
+            Err(e) if e.is_incomplete() => {
+                if start == 0 {
+                    pending.extend_from_slice(available);
+                }
+                // the value needs more than the bytes already pending
+                if pending.len() >= max_len {
+                    return Err(too_long());
+                }
+                let read = pending.len() - start;
+                reader.consume(read);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Parses `value` and writes all of it to `writer`.
+pub async fn write_streamable<W, T>(writer: &mut W, value: &T) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Streamable,
+{
+    writer.write_all(&value.parse()?).await
+}
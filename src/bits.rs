@@ -0,0 +1,192 @@
+//! Reading and writing values narrower than a byte.
+//!
+//! `BitWriter` and `BitReader` pack values most significant bit first. Structs
+//! deriving `BitIo` are encoded with them, each field taking the amount of
+//! bits given by `#[bits(N)]` (or its full width without the attribute), and
+//! the last byte padded with zeros.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::{BitIo, Streamable};
+//!
+//! #[derive(Debug, PartialEq, BitIo)]
+//! pub struct FrameFlags {
+//!     #[bits(3)]
+//!     pub reliability: u8,
+//!     #[bits(1)]
+//!     pub split: bool,
+//! }
+//!
+//! let flags = FrameFlags { reliability: 3, split: true };
+//! assert_eq!(flags.parse().unwrap(), vec![0b0111_0000]);
+//! assert_eq!(FrameFlags::compose(&[0b0111_0000], &mut 0).unwrap(), flags);
+//! ```
+//! A field declared wider than its type fails the build:
+//! ```compile_fail
+//! use binary_utils::{BitIo, Streamable};
+//!
+//! #[derive(BitIo)]
+//! pub struct Flags {
+//!     #[bits(9)]
+//!     pub value: u8,
+//! }
+//! ```
+use crate::error::BinaryError;
+use crate::prelude::*;
+
+/// A value that can be stored in a bit field.
+pub trait BitField: Sized {
+    /// The most bits the value can take.
+    const BITS: u32;
+
+    fn to_bits(&self) -> u64;
+
+    fn from_bits(bits: u64) -> Self;
+}
+
+macro_rules! impl_bit_field {
+    ($($ty: ty),*) => {
+        $(
+            impl BitField for $ty {
+                const BITS: u32 = <$ty>::BITS;
+
+                fn to_bits(&self) -> u64 {
+                    *self as u64
+                }
+
+                fn from_bits(bits: u64) -> Self {
+                    bits as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_bit_field!(u8, u16, u32, u64);
+
+impl BitField for bool {
+    const BITS: u32 = 1;
+
+    fn to_bits(&self) -> u64 {
+        *self as u64
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        bits != 0
+    }
+}
+
+/// Packs values into bytes, most significant bit first.
+#[derive(Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    /// The amount of bits used in the last byte, 0 when it is full.
+    used: u32,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the low `count` bits of `value`.
+    ///
+    /// Fails if `value` needs more than `count` bits.
+    pub fn write_bits(&mut self, value: u64, count: u32) -> Result<(), BinaryError> {
+        if count > 64 || (count < 64 && value >> count != 0) {
+            return Err(BinaryError::OutOfBounds(
+                value as usize,
+                count as usize,
+                "Value does not fit in the bit field.",
+            ));
+        }
+
+        for bit in (0..count).rev() {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= (((value >> bit) & 1) as u8) << (7 - self.used);
+            self.used = (self.used + 1) % 8;
+        }
+        Ok(())
+    }
+
+    /// Writes a bit field value in `count` bits.
+    pub fn write<T: BitField>(&mut self, value: &T, count: u32) -> Result<(), BinaryError> {
+        self.write_bits(value.to_bits(), count)
+    }
+
+    /// The amount of bits written so far.
+    pub fn len(&self) -> usize {
+        match self.used {
+            0 => self.bytes.len() * 8,
+            used => (self.bytes.len() - 1) * 8 + used as usize,
+        }
+    }
+
+    /// Whether nothing was written yet.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The written bytes, with the last byte padded with zeros.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads values packed by a `BitWriter` from a byte slice.
+#[derive(Debug)]
+pub struct BitReader<'a> {
+    source: &'a [u8],
+    /// The position in bits.
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Reads from the start of `source`.
+    pub fn new(source: &'a [u8]) -> Self {
+        Self {
+            source,
+            position: 0,
+        }
+    }
+
+    /// Reads `count` bits as the low bits of a `u64`.
+    pub fn read_bits(&mut self, count: u32) -> Result<u64, BinaryError> {
+        if count > 64 {
+            return Err(BinaryError::OutOfBounds(
+                count as usize,
+                64,
+                "Can not read more than 64 bits at once.",
+            ));
+        }
+        if self.position + count as usize > self.source.len() * 8 {
+            return Err(BinaryError::EOF(self.source.len()));
+        }
+
+        let mut value = 0u64;
+        for _ in 0..count {
+            let byte = self.source[self.position / 8];
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | bit as u64;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    /// Reads a bit field value from `count` bits.
+    pub fn read<T: BitField>(&mut self, count: u32) -> Result<T, BinaryError> {
+        Ok(T::from_bits(self.read_bits(count)?))
+    }
+
+    /// The position in bits.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The amount of bytes started so far, including a partially read byte.
+    pub fn bytes_read(&self) -> usize {
+        self.position.div_ceil(8)
+    }
+}
@@ -0,0 +1,105 @@
+//! Composing values that borrow from the source instead of copying it.
+//!
+//! `Streamable::compose` returns owned values, so every `String` or `Vec<u8>`
+//! field costs an allocation and a copy. `StreamableRef` is its counterpart
+//! for types holding references into the buffer being decoded, e.g. `&str`
+//! and `&[u8]`, which are read in place. They are laid out on the wire like
+//! `String` and `Vec<u8>`, so the same bytes can be decoded either way.
+//!
+//! Every `Streamable` type is also a `StreamableRef`, so borrowed packets can
+//! mix both kinds of fields:
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::borrowed::StreamableRef;
+//! use binary_utils::{BinaryStream, BinaryStreamRef, Streamable};
+//!
+//! #[derive(BinaryStream)]
+//! pub struct Chat {
+//!     pub sender: u64,
+//!     pub message: String,
+//! }
+//!
+//! #[derive(Debug, PartialEq, BinaryStreamRef)]
+//! pub struct ChatRef<'a> {
+//!     pub sender: u64,
+//!     pub message: &'a str,
+//! }
+//!
+//! let bytes = Chat { sender: 7, message: "hello".into() }.parse().unwrap();
+//! let chat = ChatRef::compose_ref(&bytes, &mut 0).unwrap();
+//! assert_eq!(chat, ChatRef { sender: 7, message: "hello" });
+//!
+//! let mut out = Vec::new();
+//! chat.parse_ref(&mut out).unwrap();
+//! assert_eq!(out, bytes);
+//! ```
+use crate::error::BinaryError;
+use crate::profile::WireProfile;
+use crate::Streamable;
+
+/// A type that can be composed from a source it borrows from.
+pub trait StreamableRef<'a>: Sized {
+    /// Appends `self` to `out`.
+    fn parse_ref(&self, out: &mut Vec<u8>) -> Result<(), BinaryError>;
+
+    /// Reads a value from `source` at `position`, advancing it past the
+    /// value. The value may keep references into `source`.
+    fn compose_ref(source: &'a [u8], position: &mut usize) -> Result<Self, BinaryError>;
+}
+
+impl<'a, T: Streamable> StreamableRef<'a> for T {
+    fn parse_ref(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        out.extend_from_slice(&self.parse()?);
+        Ok(())
+    }
+
+    fn compose_ref(source: &'a [u8], position: &mut usize) -> Result<Self, BinaryError> {
+        T::compose(source, position)
+    }
+}
+
+/// Written like a `String`, prefixed with the profile's `string_prefix`.
+impl<'a> StreamableRef<'a> for &'a str {
+    fn parse_ref(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        WireProfile::write_length(WireProfile::current().string_prefix, self.len(), out)?;
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
+    }
+
+    fn compose_ref(source: &'a [u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let mut start = *position;
+        let length =
+            WireProfile::read_length(WireProfile::current().string_prefix, source, &mut start)?;
+        let bytes = take(source, start, length)?;
+        // unlike a `String`, there's no copy to fix up, so it has to be valid.
+        let value = std::str::from_utf8(bytes)
+            .map_err(|e| BinaryError::RecoverableKnown(format!("Invalid UTF-8: {}", e)))?;
+        *position = start + length;
+        Ok(value)
+    }
+}
+
+/// Written like a `Vec<u8>`, prefixed with the profile's `vec_prefix`.
+impl<'a> StreamableRef<'a> for &'a [u8] {
+    fn parse_ref(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        WireProfile::write_length(WireProfile::current().vec_prefix, self.len(), out)?;
+        out.extend_from_slice(self);
+        Ok(())
+    }
+
+    fn compose_ref(source: &'a [u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let mut start = *position;
+        let length =
+            WireProfile::read_length(WireProfile::current().vec_prefix, source, &mut start)?;
+        let bytes = take(source, start, length)?;
+        *position = start + length;
+        Ok(bytes)
+    }
+}
+
+fn take(source: &[u8], start: usize, length: usize) -> Result<&[u8], BinaryError> {
+    start
+        .checked_add(length)
+        .and_then(|end| source.get(start..end))
+        .ok_or(BinaryError::EOF(source.len()))
+}
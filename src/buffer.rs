@@ -0,0 +1,452 @@
+//! Object safe reader and writer traits.
+//!
+//! `io::BinaryReader` and `io::BinaryWriter` are generic over the byte order
+//! and need `Clone`, so they can't be used as trait objects. `IBufferRead`
+//! and `IBufferWrite` only need a way to move raw bytes, and every method
+//! has a fixed byte order, so plugins compiled separately can read and write
+//! through `&mut dyn IBufferRead` without knowing the stream behind it.
+//!
+//! Both are implemented for the crate's buffers, such as `BinaryStream`, and
+//! any other `io::Read` or `io::Write` can be wrapped in an `IoBuffer`. The
+//! methods are named after the wire types (`short`, `triad`, `long`, ...)
+//! rather than `u16` and friends, so they don't clash with byteorder's
+//! extension traits on streams that implement both.
+//! ```rust
+//! use std::io::Cursor;
+//! use binary_utils::buffer::{IBufferRead, IBufferWrite, IoBuffer};
+//!
+//! fn write_login(out: &mut dyn IBufferWrite) -> std::io::Result<()> {
+//!     out.write_unsigned_int(589)?;
+//!     out.write_string("Netrex")
+//! }
+//!
+//! let mut out = IoBuffer(Vec::new());
+//! write_login(&mut out).unwrap();
+//!
+//! let source: &mut dyn IBufferRead = &mut IoBuffer(Cursor::new(out.0));
+//! assert_eq!(source.read_unsigned_int().unwrap(), 589);
+//! assert_eq!(source.read_string().unwrap(), "Netrex");
+//! ```
+use std::io;
+
+use crate::error::BinaryError;
+use crate::{alloc, string_from_utf8, u24, Streamable, VarInt};
+
+/// Reads a number of the given type in each byte order.
+macro_rules! read_numbers {
+    ($($ty: ty => $be: ident, $le: ident;)*) => {
+        $(
+            fn $be(&mut self) -> io::Result<$ty> {
+                let mut bytes = [0; ::std::mem::size_of::<$ty>()];
+                self.read_into(&mut bytes)?;
+                Ok(<$ty>::from_be_bytes(bytes))
+            }
+
+            fn $le(&mut self) -> io::Result<$ty> {
+                let mut bytes = [0; ::std::mem::size_of::<$ty>()];
+                self.read_into(&mut bytes)?;
+                Ok(<$ty>::from_le_bytes(bytes))
+            }
+        )*
+    };
+}
+
+/// Writes a number of the given type in each byte order.
+macro_rules! write_numbers {
+    ($($ty: ty => $be: ident, $le: ident;)*) => {
+        $(
+            fn $be(&mut self, value: $ty) -> io::Result<()> {
+                self.write_from(&value.to_be_bytes())
+            }
+
+            fn $le(&mut self, value: $ty) -> io::Result<()> {
+                self.write_from(&value.to_le_bytes())
+            }
+        )*
+    };
+}
+
+/// A source of bytes that can be read through a trait object.
+pub trait IBufferRead {
+    /// Fills `out` with the next bytes, failing if there aren't enough.
+    fn read_into(&mut self, out: &mut [u8]) -> io::Result<()>;
+
+    fn read_byte(&mut self) -> io::Result<u8> {
+        let mut byte = [0];
+        self.read_into(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_signed_byte(&mut self) -> io::Result<i8> {
+        self.read_byte().map(|byte| byte as i8)
+    }
+
+    fn read_bool(&mut self) -> io::Result<bool> {
+        self.read_byte().map(|byte| byte != 0)
+    }
+
+    read_numbers! {
+        u16 => read_short, read_short_le;
+        i16 => read_signed_short, read_signed_short_le;
+        u32 => read_unsigned_int, read_unsigned_int_le;
+        i32 => read_signed_int, read_signed_int_le;
+        u64 => read_long, read_long_le;
+        i64 => read_signed_long, read_signed_long_le;
+        f32 => read_float, read_float_le;
+        f64 => read_double, read_double_le;
+    }
+
+    /// Reads a big endian 3 byte integer.
+    fn read_triad(&mut self) -> io::Result<u24> {
+        let mut bytes = [0; 3];
+        self.read_into(&mut bytes)?;
+        Ok(u24::from_be_bytes(&bytes))
+    }
+
+    /// Reads a little endian 3 byte integer, as RakNet writes sequence numbers.
+    fn read_triad_le(&mut self) -> io::Result<u24> {
+        let mut bytes = [0; 3];
+        self.read_into(&mut bytes)?;
+        Ok(u24::from_le_bytes(&bytes))
+    }
+
+    /// Reads a `u32` variable length integer.
+    fn read_var_int(&mut self) -> io::Result<u32> {
+        self.read_var_long()?
+            .try_into()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "VarInt is too long"))
+    }
+
+    /// Reads a `u64` variable length integer.
+    fn read_var_long(&mut self) -> io::Result<u64> {
+        let mut value = 0;
+        for shift in (0..70).step_by(7) {
+            let byte = self.read_byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "VarInt is too long",
+        ))
+    }
+
+    /// Reads `length` bytes.
+    fn read_vec(&mut self, length: usize) -> io::Result<Vec<u8>> {
+        let mut out = vec![0; length];
+        self.read_into(&mut out)?;
+        Ok(out)
+    }
+
+    /// Reads a string sized by a big endian `u16`, as `String` is by default.
+    fn read_string(&mut self) -> io::Result<String> {
+        let length = self.read_short()? as usize;
+        let bytes = self.read_vec(length)?;
+        string_from_utf8(bytes).map_err(io::Error::from)
+    }
+}
+
+/// A sink for bytes that can be written through a trait object.
+pub trait IBufferWrite {
+    /// Writes all of `bytes`.
+    fn write_from(&mut self, bytes: &[u8]) -> io::Result<()>;
+
+    fn write_byte(&mut self, value: u8) -> io::Result<()> {
+        self.write_from(&[value])
+    }
+
+    fn write_signed_byte(&mut self, value: i8) -> io::Result<()> {
+        self.write_byte(value as u8)
+    }
+
+    fn write_bool(&mut self, value: bool) -> io::Result<()> {
+        self.write_byte(value as u8)
+    }
+
+    write_numbers! {
+        u16 => write_short, write_short_le;
+        i16 => write_signed_short, write_signed_short_le;
+        u32 => write_unsigned_int, write_unsigned_int_le;
+        i32 => write_signed_int, write_signed_int_le;
+        u64 => write_long, write_long_le;
+        i64 => write_signed_long, write_signed_long_le;
+        f32 => write_float, write_float_le;
+        f64 => write_double, write_double_le;
+    }
+
+    /// Writes a big endian 3 byte integer.
+    fn write_triad(&mut self, value: u24) -> io::Result<()> {
+        self.write_from(&value.to_be_bytes())
+    }
+
+    /// Writes a little endian 3 byte integer.
+    fn write_triad_le(&mut self, value: u24) -> io::Result<()> {
+        self.write_from(&value.to_le_bytes())
+    }
+
+    /// Writes a `u32` variable length integer.
+    fn write_var_int(&mut self, value: u32) -> io::Result<()> {
+        self.write_from(&VarInt(value).to_be_bytes())
+    }
+
+    /// Writes a `u64` variable length integer.
+    fn write_var_long(&mut self, value: u64) -> io::Result<()> {
+        self.write_from(&VarInt(value).to_be_bytes())
+    }
+
+    /// Writes a string sized by a big endian `u16`, as `String` is by default.
+    fn write_string(&mut self, value: &str) -> io::Result<()> {
+        let length = u16::try_from(value.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "String is too long"))?;
+        self.write_short(length)?;
+        self.write_from(value.as_bytes())
+    }
+
+    /// Writes `value` as it is encoded under the current profile.
+    fn write_value(&mut self, value: &dyn Streamable) -> io::Result<()> {
+        let bytes = value.parse().map_err(io::Error::from)?;
+        self.write_from(&bytes)
+    }
+}
+
+/// Reads and writes the buffer traits through any `io::Read` or `io::Write`.
+///
+/// The traits aren't implemented for every stream, so this wrapper is what
+/// hands a socket or a file to a plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IoBuffer<T>(pub T);
+
+impl<T> IoBuffer<T> {
+    /// Grabs the `inner` stream, similar to `unwrap`.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<R: io::Read> IBufferRead for IoBuffer<R> {
+    fn read_into(&mut self, out: &mut [u8]) -> io::Result<()> {
+        self.0.read_exact(out)
+    }
+}
+
+impl<W: io::Write> IBufferWrite for IoBuffer<W> {
+    fn write_from(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.0.write_all(bytes)
+    }
+}
+
+/// A byte buffer that is written to at its end and read from its front.
+///
+/// Writes grow the buffer as needed, and reads are bounds checked and move
+/// a read offset forward, so the same stream can encode a packet and decode
+/// one. Nothing on it panics on a short or malformed buffer; the `try_`
+/// methods return an error instead.
+/// ```rust
+/// use binary_utils::buffer::{BinaryStream, IBufferRead, IBufferWrite};
+/// use binary_utils::u24;
+///
+/// let mut stream = BinaryStream::new();
+/// stream.write_byte(0x84).unwrap();
+/// stream.write_triad_le(u24(7)).unwrap();
+/// stream.write_string("hi").unwrap();
+/// assert_eq!(stream.as_slice(), &[0x84, 7, 0, 0, 0, 2, b'h', b'i']);
+///
+/// assert_eq!(stream.peek().unwrap(), 0x84);
+/// assert_eq!(stream.read_byte().unwrap(), 0x84);
+/// assert_eq!(stream.read_triad_le().unwrap(), u24(7));
+/// assert_eq!(stream.remaining(), 4);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BinaryStream {
+    buffer: Vec<u8>,
+    offset: usize,
+    /// The offset `reset` moves back to.
+    mark: usize,
+}
+
+impl BinaryStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty stream with room for `capacity` bytes before it grows.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: alloc::allocate(capacity),
+            offset: 0,
+            mark: 0,
+        }
+    }
+
+    /// Every byte written, including the ones already read.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// The read offset.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.offset
+    }
+
+    /// Composes a `T` at the read offset, moving past it.
+    pub fn read<T: Streamable>(&mut self) -> Result<T, BinaryError> {
+        T::compose(&self.buffer, &mut self.offset)
+    }
+
+    /// The next `length` bytes, moving past them.
+    ///
+    /// Fails without moving if fewer than `length` bytes are left.
+    pub fn try_read(&mut self, length: usize) -> Result<&[u8], BinaryError> {
+        let start = self.offset;
+        crate::bytes_at(&self.buffer, start, length)?;
+        self.offset += length;
+        Ok(&self.buffer[start..self.offset])
+    }
+
+    /// Moves the read offset to `offset`, which may be the end of the buffer
+    /// but not past it.
+    pub fn try_set_offset(&mut self, offset: usize) -> Result<(), BinaryError> {
+        if offset > self.buffer.len() {
+            return Err(BinaryError::OutOfBounds(
+                offset,
+                self.buffer.len(),
+                "Can't move the read offset past the end of the stream",
+            ));
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Moves the read offset by `distance` bytes, backwards if it is negative.
+    ///
+    /// Fails without moving if that would go before the start or past the end.
+    pub fn seek(&mut self, distance: i64) -> Result<(), BinaryError> {
+        let offset = i64::try_from(self.offset)
+            .ok()
+            .and_then(|offset| offset.checked_add(distance))
+            .and_then(|offset| usize::try_from(offset).ok())
+            .ok_or(BinaryError::OutOfBounds(
+                self.offset,
+                self.buffer.len(),
+                "Can't seek before the start of the stream",
+            ))?;
+        self.try_set_offset(offset)
+    }
+
+    /// Remembers the read offset, to go back to with `reset`, e.g. before a
+    /// speculative parse.
+    pub fn mark(&mut self) {
+        self.mark = self.offset;
+    }
+
+    /// Moves the read offset back to the last `mark`, or the start if there
+    /// was none.
+    pub fn reset(&mut self) {
+        self.offset = self.mark;
+    }
+
+    /// The byte at `index` from the start of the buffer, regardless of the
+    /// read offset.
+    pub fn try_index(&self, index: usize) -> Result<u8, BinaryError> {
+        self.buffer
+            .get(index)
+            .copied()
+            .ok_or(BinaryError::OutOfBounds(
+                index,
+                self.buffer.len(),
+                "Index is past the end of the stream",
+            ))
+    }
+
+    /// The next byte, without moving past it, e.g. to look at a packet id
+    /// before choosing its decoder.
+    pub fn peek(&self) -> Result<u8, BinaryError> {
+        self.try_index(self.offset)
+    }
+
+    /// The next `length` bytes, without moving past them.
+    pub fn peek_n(&self, length: usize) -> Result<&[u8], BinaryError> {
+        crate::bytes_at(&self.buffer, self.offset, length)
+    }
+
+    /// The next big endian `u16`, without moving past it.
+    pub fn peek_short(&self) -> Result<u16, BinaryError> {
+        let bytes = self.peek_n(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// The next `u32` variable length integer, without moving past it.
+    pub fn peek_var_int(&self) -> Result<u32, BinaryError> {
+        self.peek_value::<VarInt<u32>>().map(|value| value.0)
+    }
+
+    /// Composes a `T` at the read offset without moving past it.
+    pub fn peek_value<T: Streamable>(&self) -> Result<T, BinaryError> {
+        T::compose(&self.buffer, &mut self.offset.clone())
+    }
+
+    /// Empties the stream, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.offset = 0;
+        self.mark = 0;
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+impl IBufferRead for BinaryStream {
+    fn read_into(&mut self, out: &mut [u8]) -> io::Result<()> {
+        let bytes = self
+            .try_read(out.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::UnexpectedEof, e.get_message()))?;
+        out.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl IBufferWrite for BinaryStream {
+    fn write_from(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.buffer.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl From<Vec<u8>> for BinaryStream {
+    fn from(buffer: Vec<u8>) -> Self {
+        Self {
+            buffer,
+            offset: 0,
+            mark: 0,
+        }
+    }
+}
+
+impl io::Read for BinaryStream {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let read = out.len().min(self.remaining());
+        out[..read].copy_from_slice(&self.buffer[self.offset..self.offset + read]);
+        self.offset += read;
+        Ok(read)
+    }
+}
+
+impl io::Write for BinaryStream {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+//! Packing small frames into as few datagrams as possible.
+//!
+//! `Coalescer` collects encoded frames, each written behind a length prefix,
+//! into a datagram until the next frame would take it over the MTU, or until
+//! the first frame in it has waited longer than the allowed delay. The
+//! receiving side splits a datagram back up with `framing::split_frames`.
+//! ```rust
+//! use std::time::{Duration, Instant};
+//! use binary_utils::coalesce::Coalescer;
+//! use binary_utils::framing::{split_frames, LengthPrefix};
+//!
+//! let mut coalescer = Coalescer::new(8, LengthPrefix::U16, Duration::from_millis(5));
+//! assert_eq!(coalescer.push(&[1, 2]).unwrap(), None);
+//! assert_eq!(coalescer.push(&[3, 4]).unwrap(), None);
+//! // a third frame doesn't fit in 8 bytes, so the first datagram is done.
+//! let datagram = coalescer.push(&[5]).unwrap().unwrap();
+//! assert_eq!(split_frames(&datagram, LengthPrefix::U16).unwrap(), vec![&[1, 2][..], &[3, 4][..]]);
+//!
+//! // the last frame is sent once it has waited long enough.
+//! let later = Instant::now() + Duration::from_millis(5);
+//! assert_eq!(coalescer.poll(later), Some(vec![0, 1, 5]));
+//! ```
+use std::time::{Duration, Instant};
+
+use crate::delivery::FrameHandle;
+use crate::error::BinaryError;
+use crate::framing::LengthPrefix;
+
+/// Batches length-prefixed frames into datagrams of at most `mtu` bytes.
+#[derive(Debug)]
+pub struct Coalescer {
+    mtu: usize,
+    prefix: LengthPrefix,
+    max_delay: Duration,
+    current: Vec<u8>,
+    /// When the first frame of `current` was pushed.
+    started: Option<Instant>,
+    /// The sequence numbers of the next frame and of `current`.
+    frames: u64,
+    datagrams: u64,
+}
+
+impl Coalescer {
+    /// Creates a coalescer writing datagrams of up to `mtu` bytes, holding a
+    /// frame for at most `max_delay` before its datagram is due.
+    pub fn new(mtu: usize, prefix: LengthPrefix, max_delay: Duration) -> Self {
+        Self {
+            mtu,
+            prefix,
+            max_delay,
+            current: Vec::with_capacity(mtu),
+            started: None,
+            frames: 0,
+            datagrams: 0,
+        }
+    }
+
+    /// Adds `frame` to the current datagram.
+    ///
+    /// If it doesn't fit, the current datagram is finished and returned, and
+    /// `frame` starts the next one. Frames that wouldn't fit in a datagram
+    /// on their own are rejected.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, BinaryError> {
+        let mut header = Vec::with_capacity(crate::VAR_INT_32_BYTE_MAX);
+        self.prefix.write(frame.len(), &mut header)?;
+        let size = header.len() + frame.len();
+        if size > self.mtu {
+            return Err(BinaryError::OutOfBounds(
+                size,
+                self.mtu,
+                "Frame does not fit in a datagram.",
+            ));
+        }
+
+        let finished = if self.current.len() + size > self.mtu {
+            self.flush()
+        } else {
+            None
+        };
+        if self.current.is_empty() {
+            self.started = Some(Instant::now());
+        }
+        self.current.extend_from_slice(&header);
+        self.current.extend_from_slice(frame);
+        self.frames += 1;
+        Ok(finished)
+    }
+
+    /// Adds `frame` like `push`, also returning where it was written so its
+    /// delivery can be tracked.
+    pub fn push_tracked(
+        &mut self,
+        frame: &[u8],
+    ) -> Result<(FrameHandle, Option<Vec<u8>>), BinaryError> {
+        let before = self.current.len();
+        let finished = self.push(frame)?;
+        let start = if finished.is_some() { 0 } else { before };
+        let handle = FrameHandle {
+            sequence: self.frames - 1,
+            datagram: self.datagrams,
+            range: start..self.current.len(),
+        };
+        Ok((handle, finished))
+    }
+
+    /// Finishes the current datagram if its deadline has passed by `now`.
+    pub fn poll(&mut self, now: Instant) -> Option<Vec<u8>> {
+        match self.deadline() {
+            Some(deadline) if now >= deadline => self.flush(),
+            _ => None,
+        }
+    }
+
+    /// Finishes the current datagram, if it holds any frames.
+    pub fn flush(&mut self) -> Option<Vec<u8>> {
+        self.started = None;
+        if self.current.is_empty() {
+            return None;
+        }
+        self.datagrams += 1;
+        let next = Vec::with_capacity(self.mtu);
+        Some(std::mem::replace(&mut self.current, next))
+    }
+
+    /// When the current datagram is due, to sleep until in a send loop.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.started.map(|started| started + self.max_delay)
+    }
+
+    /// The bytes in the current datagram.
+    pub fn len(&self) -> usize {
+        self.current.len()
+    }
+
+    /// Whether the current datagram holds no frames.
+    pub fn is_empty(&self) -> bool {
+        self.current.is_empty()
+    }
+}
@@ -0,0 +1,123 @@
+//! A tokio-util codec for length-prefixed packets.
+//!
+//! `StreamableCodec` frames packets the same way as `PacketSink` and
+//! `PacketStream`, so it can be handed to `tokio_util::codec::Framed` to get
+//! a `Sink` and `Stream` of packets over any `AsyncRead + AsyncWrite`.
+//!
+//! This module is only available with the `codec` feature.
+//! ```rust ignore
+//! use futures::{SinkExt, StreamExt};
+//! use tokio_util::codec::Framed;
+//! use binary_utils::{codec::StreamableCodec, framing::LengthPrefix};
+//!
+//! let mut framed = Framed::new(socket, StreamableCodec::<MyPacket>::new(LengthPrefix::VarInt));
+//! framed.send(packet).await?;
+//! while let Some(packet) = framed.next().await {
+//!     handle(packet?);
+//! }
+//! ```
+use std::io;
+use std::marker::PhantomData;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::framing::{encode_frame, LengthPrefix};
+use crate::scratch::with_scratch;
+use crate::Streamable;
+
+/// Encodes and decodes `T` as length-prefixed frames.
+///
+/// Frames larger than the configured maximum are rejected with
+/// `InvalidData` before their body is buffered.
+pub struct StreamableCodec<T> {
+    prefix: LengthPrefix,
+    max_frame: usize,
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T: Streamable> StreamableCodec<T> {
+    /// The default maximum frame body size, 8 MiB.
+    pub const DEFAULT_MAX_FRAME: usize = 8 * 1024 * 1024;
+
+    /// Creates a codec with the default maximum frame size.
+    pub fn new(prefix: LengthPrefix) -> Self {
+        Self::with_max_frame(prefix, Self::DEFAULT_MAX_FRAME)
+    }
+
+    /// Creates a codec rejecting frame bodies larger than `max_frame` bytes.
+    pub fn with_max_frame(prefix: LengthPrefix, max_frame: usize) -> Self {
+        Self {
+            prefix,
+            max_frame,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The prefix written ahead of every frame.
+    pub fn prefix(&self) -> LengthPrefix {
+        self.prefix
+    }
+}
+
+impl<T> Clone for StreamableCodec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            prefix: self.prefix,
+            max_frame: self.max_frame,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for StreamableCodec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamableCodec")
+            .field("prefix", &self.prefix)
+            .field("max_frame", &self.max_frame)
+            .finish()
+    }
+}
+
+impl<T: Streamable> Decoder for StreamableCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<T>> {
+        let (length, header) = match self.prefix.read(src)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        if length > self.max_frame {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Frame of {} bytes exceeds the maximum of {} bytes.",
+                    length, self.max_frame
+                ),
+            ));
+        }
+
+        if src.len() < header + length {
+            src.reserve(header + length - src.len());
+            return Ok(None);
+        }
+
+        src.advance(header);
+        let frame = src.split_to(length);
+        Ok(Some(T::compose(&frame, &mut 0)?))
+    }
+}
+
+impl<T: Streamable> Encoder<T> for StreamableCodec<T> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        with_scratch(|frame| {
+            encode_frame(&item, self.prefix, frame)?;
+            dst.extend_from_slice(frame);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,339 @@
+//! Bookkeeping for frames that need to be acknowledged.
+//!
+//! `Coalescer::push_tracked` returns a `FrameHandle` for every frame, naming
+//! the frame's sequence number and where it was written. A
+//! `DeliveryTracker` holds the handles of frames in flight until the peer
+//! acknowledges them, or they are scheduled to be sent again, and a
+//! `ResendQueue` keeps their encoded bytes to send again. Acknowledgements
+//! are exchanged as `AckRanges`.
+//! ```rust
+//! use std::time::Duration;
+//! use binary_utils::coalesce::Coalescer;
+//! use binary_utils::delivery::DeliveryTracker;
+//! use binary_utils::framing::LengthPrefix;
+//!
+//! let mut coalescer = Coalescer::new(1200, LengthPrefix::U16, Duration::ZERO);
+//! let mut tracker = DeliveryTracker::new();
+//! let (first, _) = coalescer.push_tracked(&[1, 2]).unwrap();
+//! let (second, _) = coalescer.push_tracked(&[3]).unwrap();
+//! tracker.track(first.clone());
+//! tracker.track(second.clone());
+//!
+//! assert!(tracker.ack(first.sequence));
+//! tracker.retransmit(second.sequence);
+//! assert_eq!(tracker.take_retransmits(), vec![second]);
+//! assert!(tracker.is_empty());
+//! ```
+use std::collections::BTreeMap;
+use std::ops::{Range, RangeInclusive};
+use std::time::{Duration, Instant};
+
+use crate::error::BinaryError;
+use crate::{Streamable, VarInt};
+
+/// Where a frame was written by a `Coalescer`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrameHandle {
+    /// The sequence number of the frame, counting every frame pushed.
+    pub sequence: u64,
+    /// The sequence number of the datagram the frame was written to.
+    pub datagram: u64,
+    /// The bytes of the frame in its datagram, including its length prefix.
+    pub range: Range<usize>,
+}
+
+/// The state of a tracked frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Sent and waiting for an acknowledgement.
+    Pending,
+    /// Scheduled to be sent again.
+    Retransmit,
+}
+
+/// Frames that have been sent but not acknowledged yet, by sequence number.
+#[derive(Debug, Default)]
+pub struct DeliveryTracker {
+    frames: BTreeMap<u64, (FrameHandle, Delivery)>,
+}
+
+impl DeliveryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracking a frame that was sent.
+    pub fn track(&mut self, handle: FrameHandle) {
+        self.frames
+            .insert(handle.sequence, (handle, Delivery::Pending));
+    }
+
+    /// Marks the frame with `sequence` as delivered, returning whether it was tracked.
+    pub fn ack(&mut self, sequence: u64) -> bool {
+        self.frames.remove(&sequence).is_some()
+    }
+
+    /// Schedules the frame with `sequence` to be sent again, returning
+    /// whether it was tracked.
+    pub fn retransmit(&mut self, sequence: u64) -> bool {
+        match self.frames.get_mut(&sequence) {
+            Some((_, delivery)) => {
+                *delivery = Delivery::Retransmit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The state of the frame with `sequence`, `None` once acknowledged.
+    pub fn state(&self, sequence: u64) -> Option<Delivery> {
+        self.frames.get(&sequence).map(|(_, delivery)| *delivery)
+    }
+
+    /// Removes the frames scheduled to be sent again, oldest first.
+    ///
+    /// They are no longer tracked; track the handles of the new sends instead.
+    pub fn take_retransmits(&mut self) -> Vec<FrameHandle> {
+        let sequences = self
+            .frames
+            .iter()
+            .filter(|(_, (_, delivery))| *delivery == Delivery::Retransmit)
+            .map(|(sequence, _)| *sequence)
+            .collect::<Vec<_>>();
+        sequences
+            .into_iter()
+            .filter_map(|sequence| self.frames.remove(&sequence))
+            .map(|(handle, _)| handle)
+            .collect()
+    }
+
+    /// The handles of the frames still waiting for an acknowledgement, oldest first.
+    pub fn pending(&self) -> impl Iterator<Item = &FrameHandle> + '_ {
+        self.frames
+            .values()
+            .filter(|(_, delivery)| *delivery == Delivery::Pending)
+            .map(|(handle, _)| handle)
+    }
+
+    /// The amount of frames tracked.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether every tracked frame has been acknowledged or taken to retransmit.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// A set of sequence numbers, as the inclusive ranges they form.
+///
+/// Acknowledgements list every sequence number received since the last one
+/// was sent, which mostly form a handful of runs, so they are written as a
+/// count followed by the start and end of each range, all as VarInts.
+/// ```rust
+/// use binary_utils::delivery::AckRanges;
+/// use binary_utils::Streamable;
+///
+/// let acks = AckRanges::from_sequences([4, 1, 2, 3, 9]);
+/// assert_eq!(acks.ranges(), &[1..=4, 9..=9]);
+/// assert_eq!(acks.parse().unwrap(), vec![2, 1, 4, 9, 9]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AckRanges {
+    ranges: Vec<RangeInclusive<u64>>,
+}
+
+impl AckRanges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collects `sequences`, in any order and with repeats, into ranges.
+    pub fn from_sequences(sequences: impl IntoIterator<Item = u64>) -> Self {
+        let mut sequences = sequences.into_iter().collect::<Vec<_>>();
+        sequences.sort_unstable();
+        sequences.dedup();
+
+        let mut ranges: Vec<RangeInclusive<u64>> = Vec::new();
+        for sequence in sequences {
+            match ranges.last_mut() {
+                Some(last) if *last.end() + 1 == sequence => {
+                    *last = *last.start()..=sequence;
+                }
+                _ => ranges.push(sequence..=sequence),
+            }
+        }
+        Self { ranges }
+    }
+
+    /// The ranges, in ascending order and without overlaps.
+    pub fn ranges(&self) -> &[RangeInclusive<u64>] {
+        &self.ranges
+    }
+
+    /// Whether `sequence` is in one of the ranges.
+    pub fn contains(&self, sequence: u64) -> bool {
+        self.ranges.iter().any(|range| range.contains(&sequence))
+    }
+
+    /// Every sequence number in the ranges, in ascending order.
+    pub fn sequences(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ranges.iter().flat_map(|range| range.clone())
+    }
+
+    /// Whether there are no sequence numbers.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The start and end of every range.
+    #[cfg(any(feature = "migrate", feature = "msgpack"))]
+    pub(crate) fn pairs(&self) -> Vec<(u64, u64)> {
+        self.ranges
+            .iter()
+            .map(|range| (*range.start(), *range.end()))
+            .collect()
+    }
+
+    /// Ranges read from the wire, which must be ascending and apart as
+    /// `from_sequences` makes them.
+    pub(crate) fn from_pairs(pairs: Vec<(u64, u64)>) -> Result<Self, BinaryError> {
+        let mut ranges: Vec<RangeInclusive<u64>> = Vec::with_capacity(pairs.len());
+        for (start, end) in pairs {
+            let after_last = ranges.last().is_none_or(|last| start > *last.end());
+            if start > end || !after_last {
+                return Err(BinaryError::RecoverableKnown(format!(
+                    "Ack range {}..={} is out of order.",
+                    start, end
+                )));
+            }
+            ranges.push(start..=end);
+        }
+        Ok(Self { ranges })
+    }
+}
+
+impl Streamable for AckRanges {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut out = crate::alloc::allocate(self.size_hint().unwrap_or(0));
+        self.parse_into(&mut out)?;
+        Ok(out)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        VarInt(self.ranges.len() as u32).parse_into(out)?;
+        for range in &self.ranges {
+            VarInt(*range.start()).parse_into(out)?;
+            VarInt(*range.end()).parse_into(out)?;
+        }
+        Ok(())
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let count = VarInt::<u32>::compose(source, position)?.0;
+        let mut pairs = Vec::new();
+        for _ in 0..count {
+            let start = VarInt::<u64>::compose(source, position)?.0;
+            let end = VarInt::<u64>::compose(source, position)?.0;
+            pairs.push((start, end));
+        }
+        Self::from_pairs(pairs)
+    }
+}
+
+/// The encoded bytes of frames that haven't been acknowledged yet, by
+/// sequence number, so they can be sent again without encoding them again.
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use binary_utils::delivery::{AckRanges, ResendQueue};
+///
+/// let start = Instant::now();
+/// let mut queue = ResendQueue::new(Duration::from_millis(100));
+/// queue.insert(0, vec![1, 2], start);
+/// queue.insert(1, vec![3], start);
+/// queue.ack_ranges(&AckRanges::from_sequences([0]));
+///
+/// let later = start + Duration::from_millis(100);
+/// let due = queue.due(later).map(|(sequence, bytes)| (sequence, bytes.to_vec())).collect::<Vec<_>>();
+/// assert_eq!(due, vec![(1, vec![3])]);
+/// // sending it again restarts its timeout.
+/// assert_eq!(queue.due(later).count(), 0);
+/// ```
+#[derive(Debug)]
+pub struct ResendQueue {
+    timeout: Duration,
+    frames: BTreeMap<u64, Unacked>,
+}
+
+#[derive(Debug)]
+struct Unacked {
+    bytes: Vec<u8>,
+    sent: Instant,
+    sends: u32,
+}
+
+impl ResendQueue {
+    /// Creates a queue that resends frames unacknowledged for `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            frames: BTreeMap::new(),
+        }
+    }
+
+    /// Holds on to the encoded frame `bytes`, which were sent at `sent`.
+    pub fn insert(&mut self, sequence: u64, bytes: Vec<u8>, sent: Instant) {
+        self.frames.insert(
+            sequence,
+            Unacked {
+                bytes,
+                sent,
+                sends: 1,
+            },
+        );
+    }
+
+    /// Drops the frame with `sequence`, returning its bytes if it was queued.
+    pub fn ack(&mut self, sequence: u64) -> Option<Vec<u8>> {
+        self.frames.remove(&sequence).map(|frame| frame.bytes)
+    }
+
+    /// Drops every frame in `acks`, returning how many were queued.
+    pub fn ack_ranges(&mut self, acks: &AckRanges) -> usize {
+        let before = self.frames.len();
+        self.frames.retain(|sequence, _| !acks.contains(*sequence));
+        before - self.frames.len()
+    }
+
+    /// The frames unacknowledged for the timeout at `now`, oldest sequence first.
+    ///
+    /// Every frame returned is counted as sent again at `now`.
+    pub fn due(&mut self, now: Instant) -> impl Iterator<Item = (u64, &[u8])> + '_ {
+        let timeout = self.timeout;
+        self.frames
+            .iter_mut()
+            .filter(move |(_, frame)| now.saturating_duration_since(frame.sent) >= timeout)
+            .map(move |(sequence, frame)| {
+                frame.sent = now;
+                frame.sends += 1;
+                let frame: &Unacked = frame;
+                (*sequence, &frame.bytes[..])
+            })
+    }
+
+    /// How many times the frame with `sequence` has been sent.
+    pub fn sends(&self, sequence: u64) -> Option<u32> {
+        self.frames.get(&sequence).map(|frame| frame.sends)
+    }
+
+    /// The amount of frames waiting for an acknowledgement.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether every frame has been acknowledged.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
@@ -0,0 +1,201 @@
+//! Binary deltas between two buffers.
+//!
+//! `binary_diff` describes a new buffer as copies from an old one plus the
+//! bytes that are new, so only the difference has to be sent when both sides
+//! already have the old buffer. The `Patch` itself is `Streamable`.
+//! ```rust
+//! use binary_utils::diff::{apply_patch, binary_diff, Patch};
+//! use binary_utils::Streamable;
+//!
+//! let old = b"resource pack v1, lots of unchanged bytes here".to_vec();
+//! let new = b"resource pack v2, lots of unchanged bytes here!".to_vec();
+//!
+//! let patch = binary_diff(&old, &new);
+//! let bytes = patch.parse().unwrap();
+//! assert!(bytes.len() < new.len());
+//!
+//! let patch = Patch::compose(&bytes, &mut 0).unwrap();
+//! assert_eq!(apply_patch(&old, &patch).unwrap(), new);
+//! ```
+use std::collections::HashMap;
+
+use crate::error::BinaryError;
+use crate::{Streamable, VarInt};
+
+/// The shortest run of old bytes worth a copy instead of an insert.
+const BLOCK: usize = 8;
+/// The amount of earlier occurrences of a block tried when looking for a match.
+const CANDIDATES: usize = 8;
+
+/// A single step in rebuilding the new buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// Copies `length` bytes of the old buffer starting at `offset`.
+    Copy { offset: u32, length: u32 },
+    /// Appends bytes that aren't in the old buffer.
+    Insert(Vec<u8>),
+}
+
+/// The operations turning one buffer into another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    /// The length of the buffer the patch applies to.
+    pub source_len: u32,
+    /// The length of the buffer the patch produces.
+    pub target_len: u32,
+    pub ops: Vec<Op>,
+}
+
+/// Computes the patch turning `old` into `new`.
+///
+/// Both buffers must be smaller than 4 GiB.
+pub fn binary_diff(old: &[u8], new: &[u8]) -> Patch {
+    let mut index: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    if old.len() >= BLOCK {
+        for offset in 0..=old.len() - BLOCK {
+            let positions = index.entry(&old[offset..offset + BLOCK]).or_default();
+            if positions.len() < CANDIDATES {
+                positions.push(offset);
+            }
+        }
+    }
+
+    let mut ops = Vec::new();
+    let mut insert = Vec::new();
+    let mut position = 0;
+    while position < new.len() {
+        let best = new
+            .get(position..position + BLOCK)
+            .and_then(|block| index.get(block))
+            .and_then(|candidates| {
+                candidates
+                    .iter()
+                    .map(|&offset| {
+                        let length = old[offset..]
+                            .iter()
+                            .zip(&new[position..])
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        (offset, length)
+                    })
+                    .max_by_key(|&(_, length)| length)
+            });
+
+        match best {
+            Some((offset, length)) => {
+                if !insert.is_empty() {
+                    ops.push(Op::Insert(std::mem::take(&mut insert)));
+                }
+                ops.push(Op::Copy {
+                    offset: offset as u32,
+                    length: length as u32,
+                });
+                position += length;
+            }
+            None => {
+                insert.push(new[position]);
+                position += 1;
+            }
+        }
+    }
+    if !insert.is_empty() {
+        ops.push(Op::Insert(insert));
+    }
+
+    Patch {
+        source_len: old.len() as u32,
+        target_len: new.len() as u32,
+        ops,
+    }
+}
+
+/// Rebuilds the new buffer from `old` and a patch made by `binary_diff`.
+///
+/// Fails if `old` isn't the buffer the patch was made from (by length), or
+/// if the patch copies outside of it.
+pub fn apply_patch(old: &[u8], patch: &Patch) -> Result<Vec<u8>, BinaryError> {
+    if old.len() != patch.source_len as usize {
+        return Err(BinaryError::RecoverableKnown(format!(
+            "Patch applies to {} bytes, got {}",
+            patch.source_len,
+            old.len()
+        )));
+    }
+
+    let mut out = crate::alloc::allocate(patch.target_len as usize);
+    for op in &patch.ops {
+        match op {
+            Op::Copy { offset, length } => {
+                let start = *offset as usize;
+                let end = start + *length as usize;
+                let bytes = old.get(start..end).ok_or(BinaryError::OutOfBounds(
+                    end,
+                    old.len(),
+                    "Patch copies past the end of the source.",
+                ))?;
+                out.extend_from_slice(bytes);
+            }
+            Op::Insert(bytes) => out.extend_from_slice(bytes),
+        }
+    }
+
+    if out.len() != patch.target_len as usize {
+        return Err(BinaryError::RecoverableKnown(format!(
+            "Patch produced {} bytes, expected {}",
+            out.len(),
+            patch.target_len
+        )));
+    }
+    Ok(out)
+}
+
+impl Streamable for Patch {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut out = crate::alloc::allocate(0);
+        out.extend_from_slice(&VarInt(self.source_len).parse()?);
+        out.extend_from_slice(&VarInt(self.target_len).parse()?);
+        out.extend_from_slice(&VarInt(self.ops.len() as u32).parse()?);
+        for op in &self.ops {
+            match op {
+                Op::Copy { offset, length } => {
+                    out.push(0);
+                    out.extend_from_slice(&VarInt(*offset).parse()?);
+                    out.extend_from_slice(&VarInt(*length).parse()?);
+                }
+                Op::Insert(bytes) => {
+                    out.push(1);
+                    out.extend_from_slice(&bytes.parse()?);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let source_len = VarInt::<u32>::compose(source, position)?.0;
+        let target_len = VarInt::<u32>::compose(source, position)?.0;
+        let count = VarInt::<u32>::compose(source, position)?.0;
+        let mut ops = Vec::new();
+        for _ in 0..count {
+            let op = match u8::compose(source, position)? {
+                0 => Op::Copy {
+                    offset: VarInt::<u32>::compose(source, position)?.0,
+                    length: VarInt::<u32>::compose(source, position)?.0,
+                },
+                1 => Op::Insert(Vec::<u8>::compose(source, position)?),
+                tag => {
+                    return Err(BinaryError::RecoverableKnown(format!(
+                        "Unknown patch op: {}",
+                        tag
+                    )))
+                }
+            };
+            ops.push(op);
+        }
+        Ok(Patch {
+            source_len,
+            target_len,
+            ops,
+        })
+    }
+}
@@ -1,3 +1,6 @@
+use crate::prelude::*;
+use crate::Streamable;
+
 /// An enum consisting of a Binary Error
 /// (recoverable)
 #[derive(Debug, PartialEq)]
@@ -17,12 +20,49 @@ pub enum BinaryError {
     /// - `usize` = Stream length.
     EOF(usize),
 
+    /// A value needed more bytes than the source had left, e.g. because the
+    /// rest of the frame hasn't arrived yet.
+    NotEnoughBytes {
+        /// The bytes the value needed from where it started.
+        needed: usize,
+        /// The bytes the source had left from there.
+        available: usize,
+    },
+
     /// A known error that was recoverable to safely proceed the stack.
     RecoverableKnown(String),
 
+    /// A map held the same key twice while duplicate keys were rejected.
+    DuplicateKey {
+        /// The key, formatted for display.
+        key_repr: String,
+        /// The offset the repeated entry starts at.
+        offset: usize,
+    },
+
     /// An unknown error occurred, but it wasn't critical,
     /// we can safely proceed on the stack.
     RecoverableUnknown,
+
+    /// `error` happened while composing a `type_name` starting at `offset`.
+    ///
+    /// Derived impls wrap the errors of their fields in this, so a failure
+    /// deep in a packet still says where it was.
+    At {
+        /// The offset the value that failed started at.
+        offset: usize,
+        /// The type that was being composed.
+        type_name: &'static str,
+        error: Box<BinaryError>,
+    },
+
+    /// `error` happened composing the field at `path`, e.g. `Frame.header.sequence`.
+    ///
+    /// Derived impls wrap the errors of their fields in this.
+    Field {
+        path: String,
+        error: Box<BinaryError>,
+    },
 }
 
 impl BinaryError {
@@ -32,20 +72,166 @@ impl BinaryError {
                 format!("Offset {} out of range for a buffer size with: {}. {}", offset, length, append)
             },
             Self::EOF(length) => format!("Buffer reached End Of File at offset: {}", length),
+            Self::NotEnoughBytes { needed, available } => format!("Needed {} bytes but only {} are left", needed, available),
             Self::RecoverableKnown(msg) => msg.clone(),
-            Self::RecoverableUnknown => "An interruption occurred when performing a binary operation, however this error was recovered safely.".to_string()
+            Self::DuplicateKey { key_repr, offset } => format!("Duplicate key {} at offset: {}", key_repr, offset),
+            Self::RecoverableUnknown => "An interruption occurred when performing a binary operation, however this error was recovered safely.".to_string(),
+            Self::Field { path, error } => format!("{}: {}", path, error.get_message()),
+            Self::At { offset, type_name, error } => format!("{} (composing {} at offset: {})", error.get_message(), type_name, offset),
+        }
+    }
+
+    /// Records that the error happened composing a `type_name` at `offset`.
+    ///
+    /// An error that already has a location keeps it, as the innermost
+    /// value is the most precise.
+    pub fn at(self, offset: usize, type_name: &'static str) -> Self {
+        if self.offset().is_some() {
+            return self;
+        }
+        Self::At {
+            offset,
+            type_name,
+            error: Box::new(self),
+        }
+    }
+
+    /// Records that the error happened composing `field` of `owner`.
+    ///
+    /// Called on an error that already has a path, as a nested struct
+    /// fails, it becomes `owner.field.<rest of the path>`.
+    pub fn with_field(self, owner: &str, field: &str) -> Self {
+        match self {
+            Self::Field { path, error } => {
+                let rest = path.find('.').map_or("", |dot| &path[dot..]);
+                Self::Field {
+                    path: format!("{}.{}{}", owner, field, rest),
+                    error,
+                }
+            }
+            error => Self::Field {
+                path: format!("{}.{}", owner, field),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// The offset the failing value started at, if it is known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::At { offset, .. } => Some(*offset),
+            Self::Field { error, .. } => error.offset(),
+            _ => None,
+        }
+    }
+
+    /// The type that failed to compose, if it is known.
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            Self::At { type_name, .. } => Some(type_name),
+            Self::Field { error, .. } => error.type_name(),
+            _ => None,
+        }
+    }
+
+    /// The path of the field that failed to compose, e.g. `Frame.header.sequence`.
+    pub fn field_path(&self) -> Option<&str> {
+        match self {
+            Self::Field { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Whether the source ended early, so the value may still decode once
+    /// more bytes arrive, rather than being malformed.
+    pub fn is_incomplete(&self) -> bool {
+        matches!(self.root(), Self::EOF(_) | Self::NotEnoughBytes { .. })
+    }
+
+    /// The error without its location.
+    pub fn root(&self) -> &BinaryError {
+        match self {
+            Self::At { error, .. } | Self::Field { error, .. } => error.root(),
+            error => error,
         }
     }
 }
 
+/// Adds context to the error of a `Result`, for hand written impls.
+/// ```rust
+/// use binary_utils::error::{BinaryError, ErrorContext};
+/// use binary_utils::Streamable;
+///
+/// fn read_channel(source: &[u8], position: &mut usize) -> Result<u8, BinaryError> {
+///     u8::compose(source, position).with_field("FramePacket", "split_channel")
+/// }
+///
+/// let error = read_channel(&[], &mut 0).unwrap_err();
+/// assert_eq!(error.field_path(), Some("FramePacket.split_channel"));
+/// assert!(error.to_string().starts_with("FramePacket.split_channel: "));
+/// ```
+pub trait ErrorContext {
+    /// See `BinaryError::with_field`.
+    fn with_field(self, owner: &str, field: &str) -> Self;
+
+    /// See `BinaryError::at`.
+    fn at(self, offset: usize, type_name: &'static str) -> Self;
+}
+
+impl<T> ErrorContext for Result<T, BinaryError> {
+    fn with_field(self, owner: &str, field: &str) -> Self {
+        self.map_err(|error| error.with_field(owner, field))
+    }
+
+    fn at(self, offset: usize, type_name: &'static str) -> Self {
+        self.map_err(|error| error.at(offset, type_name))
+    }
+}
+
+/// Composes into `value` like `T::compose_into`, recording where it started if it fails.
+pub fn compose_into_at<T: Streamable>(
+    value: &mut T,
+    source: &[u8],
+    position: &mut usize,
+) -> Result<(), BinaryError> {
+    let start = *position;
+    value
+        .compose_into(source, position)
+        .map_err(|error| error.at(start, core::any::type_name::<T>()))
+}
+
+/// Composes a `T` like `T::compose`, recording where it started if it fails.
+pub fn compose_at<T: Streamable>(source: &[u8], position: &mut usize) -> Result<T, BinaryError> {
+    let start = *position;
+    T::compose(source, position).map_err(|error| error.at(start, core::any::type_name::<T>()))
+}
+
+#[cfg(feature = "std")]
 impl From<std::io::Error> for BinaryError {
     fn from(_error: std::io::Error) -> Self {
         Self::RecoverableUnknown
     }
 }
 
-impl std::fmt::Display for BinaryError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "{}", self.get_message())
     }
 }
+
+#[cfg(feature = "std")]
+impl std::error::Error for BinaryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::At { error, .. } | Self::Field { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<BinaryError> for std::io::Error {
+    fn from(error: BinaryError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, error.get_message())
+    }
+}
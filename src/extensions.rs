@@ -0,0 +1,125 @@
+//! Optional extensions appended after the fixed body of a packet.
+//!
+//! Each extension is a record of `[id][length][bytes]`, with the id and
+//! length written as `VarInt<u32>`. Receivers decode the extensions they know
+//! and keep the rest as raw bytes, so unknown extensions survive a round trip.
+//!
+//! **Notice:**
+//! Like `Lazy`, `Extensions` reads every remaining byte of the source when
+//! composed, so it must be the last field of a packet.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::extensions::{Extension, Extensions};
+//! use binary_utils::{BinaryStream, Streamable};
+//!
+//! #[derive(Debug, PartialEq, BinaryStream)]
+//! pub struct PingExtension {
+//!     pub latency: u16,
+//! }
+//!
+//! impl Extension for PingExtension {
+//!     const ID: u32 = 3;
+//! }
+//!
+//! #[derive(BinaryStream)]
+//! pub struct Handshake {
+//!     pub version: u8,
+//!     pub extensions: Extensions,
+//! }
+//!
+//! let mut extensions = Extensions::new();
+//! extensions.insert(&PingExtension { latency: 20 }).unwrap();
+//! let bytes = Handshake { version: 1, extensions }.parse().unwrap();
+//! assert_eq!(bytes, vec![1, 3, 2, 0, 20]);
+//!
+//! let handshake = Handshake::compose(&bytes, &mut 0).unwrap();
+//! let ping = handshake.extensions.get::<PingExtension>().unwrap();
+//! assert_eq!(ping, Some(PingExtension { latency: 20 }));
+//! ```
+use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::tlv::Tlv;
+use crate::Streamable;
+
+/// A type that can be carried in `Extensions` under a fixed id.
+pub trait Extension: Streamable {
+    /// The id the extension is written with.
+    const ID: u32;
+}
+
+/// A list of extension records, in the order they were written.
+///
+/// This is a `Tlv` with `VarInt<u32>` ids and lengths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extensions {
+    records: Tlv,
+}
+
+impl Extensions {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the extension `E`, if it is present.
+    pub fn get<E: Extension>(&self) -> Result<Option<E>, BinaryError> {
+        match self.raw(E::ID) {
+            Some(bytes) => Ok(Some(E::compose(bytes, &mut 0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether an extension with the id of `E` is present.
+    pub fn contains<E: Extension>(&self) -> bool {
+        self.raw(E::ID).is_some()
+    }
+
+    /// Encodes `extension`, replacing any extension with the same id.
+    pub fn insert<E: Extension>(&mut self, extension: &E) -> Result<(), BinaryError> {
+        self.insert_raw(E::ID, extension.parse()?);
+        Ok(())
+    }
+
+    /// Removes the extension `E`, returning whether it was present.
+    pub fn remove<E: Extension>(&mut self) -> bool {
+        self.records.remove_raw(E::ID as u64)
+    }
+
+    /// The bytes of the record with `id`, the last one wins if it is repeated.
+    pub fn raw(&self, id: u32) -> Option<&[u8]> {
+        self.records.raw(id as u64)
+    }
+
+    /// Sets the bytes of the record with `id`, adding it to the end if it isn't present.
+    pub fn insert_raw(&mut self, id: u32, bytes: Vec<u8>) {
+        self.records.insert_raw(id as u64, bytes)
+    }
+
+    /// Every record as its id and bytes, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &[u8])> {
+        // ids are read as `VarInt<u32>`, so they always fit.
+        self.records.iter().map(|(id, bytes)| (id as u32, bytes))
+    }
+
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl Streamable for Extensions {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        self.records.parse()
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        Ok(Self {
+            records: Tlv::compose(source, position)?,
+        })
+    }
+}
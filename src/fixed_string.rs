@@ -0,0 +1,105 @@
+use core::fmt;
+use core::ops::Deref;
+
+use crate::error::BinaryError;
+use crate::layout::FixedSize;
+use crate::prelude::*;
+use crate::profile::WireProfile;
+use crate::Streamable;
+
+/// A UTF-8 string that always takes exactly `N` bytes on the wire.
+///
+/// Shorter strings are padded with zeros, which are stripped again when
+/// composed, so a string can't end with a NUL character. Strings longer
+/// than `N` bytes are rejected instead of truncated.
+/// ```rust
+/// use binary_utils::{FixedString, Streamable};
+///
+/// let name = FixedString::<8>::new("steve").unwrap();
+/// assert_eq!(name.parse().unwrap(), b"steve\0\0\0".to_vec());
+/// assert_eq!(FixedString::<8>::compose(b"steve\0\0\0", &mut 0).unwrap(), name);
+/// assert!(FixedString::<4>::new("steve").is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FixedString<const N: usize>(String);
+
+impl<const N: usize> FixedString<N> {
+    /// Wraps `value`, failing if it is longer than `N` bytes.
+    pub fn new(value: impl Into<String>) -> Result<Self, BinaryError> {
+        let value = value.into();
+        if value.len() > N {
+            return Err(BinaryError::OutOfBounds(
+                value.len(),
+                N,
+                "String is longer than the fixed size.",
+            ));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Grabs the `inner` string.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl<const N: usize> Streamable for FixedString<N> {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        // a lossy decode can leave the string longer than it was on the wire.
+        let mut out = WireProfile::current()
+            .utf8_policy
+            .encode(&self.0)
+            .into_owned();
+        if out.len() > N {
+            return Err(BinaryError::OutOfBounds(
+                out.len(),
+                N,
+                "String is longer than the fixed size.",
+            ));
+        }
+        out.resize(N, 0);
+        Ok(out)
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let bytes = crate::bytes_at(source, *position, N)?;
+        let length = bytes.iter().rposition(|b| *b != 0).map_or(0, |end| end + 1);
+        let value = crate::string_from_utf8(bytes[..length].to_vec())?;
+        *position += N;
+        Ok(Self(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(N)
+    }
+}
+
+impl<const N: usize> FixedSize for FixedString<N> {
+    const WIRE_SIZE: usize = N;
+}
+
+impl<const N: usize> Deref for FixedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for FixedString<N> {
+    type Error = BinaryError;
+
+    fn try_from(value: &str) -> Result<Self, BinaryError> {
+        Self::new(value)
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,173 @@
+//! Length-prefixed framing shared by the stream and sink adapters.
+use crate::error::BinaryError;
+use crate::prelude::*;
+#[cfg(feature = "rayon")]
+use crate::profile::WireProfile;
+#[cfg(feature = "std")]
+use crate::scratch::with_scratch;
+use crate::{Streamable, VarInt};
+
+/// How the length of a frame is written ahead of its body.
+///
+/// All fixed width prefixes are written in Big Endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// A `u16` length, frames can be at most 65535 bytes.
+    U16,
+    /// A `u32` length.
+    U32,
+    /// A `u32` variable length integer.
+    VarInt,
+}
+
+impl LengthPrefix {
+    /// The largest body length this prefix can describe.
+    pub fn max_length(&self) -> usize {
+        match self {
+            Self::U16 => u16::MAX as usize,
+            Self::U32 | Self::VarInt => u32::MAX as usize,
+        }
+    }
+
+    /// Writes `length` to the end of `out`.
+    pub fn write(&self, length: usize, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        if length > self.max_length() {
+            return Err(BinaryError::OutOfBounds(
+                length,
+                self.max_length(),
+                "Frame is too large for its length prefix.",
+            ));
+        }
+
+        match self {
+            Self::U16 => out.extend_from_slice(&(length as u16).to_be_bytes()),
+            Self::U32 => out.extend_from_slice(&(length as u32).to_be_bytes()),
+            Self::VarInt => return VarInt(length as u32).parse_into(out),
+        }
+        Ok(())
+    }
+
+    /// Reads a length from the start of `source`.
+    ///
+    /// Returns the body length and the size of the prefix itself,
+    /// or `None` if `source` does not hold the whole prefix yet.
+    pub fn read(&self, source: &[u8]) -> Result<Option<(usize, usize)>, BinaryError> {
+        match self {
+            Self::U16 => Ok(source
+                .get(..2)
+                .map(|b| (u16::from_be_bytes([b[0], b[1]]) as usize, 2))),
+            Self::U32 => Ok(source
+                .get(..4)
+                .map(|b| (u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize, 4))),
+            Self::VarInt => {
+                let mut value: u32 = 0;
+                for (i, byte) in source.iter().enumerate() {
+                    if i >= crate::VAR_INT_32_BYTE_MAX {
+                        break;
+                    }
+                    value |= ((byte & 0x7f) as u32) << (7 * i);
+                    if byte & 0x80 == 0 {
+                        return Ok(Some((value as usize, i + 1)));
+                    }
+                }
+
+                if source.len() >= crate::VAR_INT_32_BYTE_MAX {
+                    Err(BinaryError::RecoverableKnown(
+                        "VarInt length prefix is longer than 5 bytes.".to_owned(),
+                    ))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+
+/// Without `std` there is no scratch pool, so the body gets its own buffer.
+#[cfg(not(feature = "std"))]
+fn with_scratch<R>(f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    f(&mut Vec::new())
+}
+
+/// Encodes `value` and appends it to `out` as a single frame.
+///
+/// The body is encoded into a scratch buffer first, as the prefix needs its length.
+pub fn encode_frame<T: Streamable>(
+    value: &T,
+    prefix: LengthPrefix,
+    out: &mut Vec<u8>,
+) -> Result<(), BinaryError> {
+    with_scratch(|body| {
+        value.parse_into(body)?;
+        prefix.write(body.len(), out)?;
+        out.extend_from_slice(&body[..]);
+        Ok(())
+    })
+}
+
+/// Walks the length prefixes of consecutive frames without decoding them,
+/// returning the body of each frame.
+///
+/// The frames can then be decoded independently, e.g. in parallel.
+///
+/// This takes the `LengthPrefix` rather than a `WireProfile`: a profile only
+/// describes how values are written, while the frame prefix is chosen by the
+/// transport (see `registry::Framing`), so the profile has nothing to offer here.
+/// ```rust
+/// use binary_utils::framing::{encode_frame, split_frames, LengthPrefix};
+///
+/// let mut buffer = Vec::new();
+/// encode_frame(&1u8, LengthPrefix::U16, &mut buffer).unwrap();
+/// encode_frame(&2u16, LengthPrefix::U16, &mut buffer).unwrap();
+///
+/// let frames = split_frames(&buffer, LengthPrefix::U16).unwrap();
+/// assert_eq!(frames, vec![&[1][..], &[0, 2][..]]);
+/// ```
+pub fn split_frames(source: &[u8], prefix: LengthPrefix) -> Result<Vec<&[u8]>, BinaryError> {
+    let mut frames = Vec::new();
+    let mut position: usize = 0;
+
+    while position < source.len() {
+        let (length, header) = prefix
+            .read(&source[position..])?
+            .ok_or(BinaryError::EOF(source.len()))?;
+        let start = position + header;
+        let body = source
+            .get(start..start + length)
+            .ok_or(BinaryError::EOF(source.len()))?;
+        frames.push(body);
+        position = start + length;
+    }
+
+    Ok(frames)
+}
+
+/// Splits `source` into frames and decodes each of them on the rayon thread pool.
+///
+/// The output keeps the order of the frames in `source`, and every frame is
+/// decoded under the caller's profile, not the worker thread's. Each value has
+/// to use up its whole frame, bytes left over after it are an error.
+#[cfg(feature = "rayon")]
+pub fn decode_parallel<T>(source: &[u8], prefix: LengthPrefix) -> Result<Vec<T>, BinaryError>
+where
+    T: Streamable + Send,
+{
+    use rayon::prelude::*;
+
+    let profile = WireProfile::current();
+    split_frames(source, prefix)?
+        .into_par_iter()
+        .map(|frame| {
+            let mut position = 0;
+            let value = profile.decode(frame, &mut position)?;
+            if position != frame.len() {
+                return Err(BinaryError::OutOfBounds(
+                    position,
+                    frame.len(),
+                    "Frame has bytes left over after its value.",
+                ));
+            }
+            Ok(value)
+        })
+        .collect()
+}
@@ -16,50 +16,50 @@ pub trait BinaryReader: ReadBytesExt + Clone {
         let three = &mut [0; 3];
         let two = &mut [0; 2];
 
-        if let Err(_) = ref_to.read_exact(&mut most[..]) {
+        if ref_to.read_exact(&mut most[..]).is_err() {
             // there was an error with the buffer size.
             // we're going to incrementally, decrease the required size until 0
-            if let Ok(_) = ref_to.read_exact(&mut four[..]) {
+            if ref_to.read_exact(&mut four[..]).is_ok() {
                 if let Ok(var) = VarInt::<u32>::compose(&four[..], &mut 0) {
-                    return Ok(var);
+                    Ok(var)
                 } else {
-                    return Err(io::Error::new(
+                    Err(io::Error::new(
                         io::ErrorKind::InvalidData,
                         "Could not read varint",
-                    ));
+                    ))
                 }
-            } else if let Ok(_) = ref_to.read_exact(&mut three[..]) {
+            } else if ref_to.read_exact(&mut three[..]).is_ok() {
                 if let Ok(var) = VarInt::<u32>::compose(&three[..], &mut 0) {
-                    return Ok(var);
+                    Ok(var)
                 } else {
-                    return Err(io::Error::new(
+                    Err(io::Error::new(
                         io::ErrorKind::InvalidData,
                         "Could not read varint",
-                    ));
+                    ))
                 }
-            } else if let Ok(_) = ref_to.read_exact(&mut two[..]) {
+            } else if ref_to.read_exact(&mut two[..]).is_ok() {
                 if let Ok(var) = VarInt::<u32>::compose(&two[..], &mut 0) {
-                    return Ok(var);
+                    Ok(var)
                 } else {
-                    return Err(io::Error::new(
+                    Err(io::Error::new(
                         io::ErrorKind::InvalidData,
                         "Could not read varint",
-                    ));
+                    ))
                 }
             } else {
-                return Err(io::Error::new(
+                Err(io::Error::new(
                     io::ErrorKind::UnexpectedEof,
                     "unable to read varint",
-                ));
+                ))
             }
         } else {
             if let Ok(var) = VarInt::<u32>::compose(&most[..], &mut 0) {
-                return Ok(var);
+                Ok(var)
             } else {
-                return Err(io::Error::new(
+                Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     "Could not read varint",
-                ));
+                ))
             }
         }
     }
@@ -81,10 +81,10 @@ pub trait BinaryReader: ReadBytesExt + Clone {
             }
         }
 
-        return Err(io::Error::new(
+        Err(io::Error::new(
             io::ErrorKind::InvalidData,
             "Could not read varint",
-        ));
+        ))
     }
 
     /// Reads a string sized by a `u16`.
@@ -93,12 +93,10 @@ pub trait BinaryReader: ReadBytesExt + Clone {
     where
         Endianess: ByteOrder,
     {
-        let t = self.clone();
         let length = self.read_u16::<Endianess>()?;
-        let mut string_data = Vec::new();
-        t.take(length as u64).read_to_end(&mut string_data)?;
-        self.read(&mut string_data[..])?;
-        Ok(unsafe { String::from_utf8_unchecked(string_data.to_vec()) })
+        let mut string_data = vec![0; length as usize];
+        self.read_exact(&mut string_data[..])?;
+        Ok(string_from_utf8(string_data)?)
     }
 
     /// Reads a string sized by a `u32`.
@@ -107,12 +105,10 @@ pub trait BinaryReader: ReadBytesExt + Clone {
     where
         Endianess: ByteOrder,
     {
-        let t = self.clone();
         let length = self.read_u32::<Endianess>()?;
-        let mut string_data = Vec::new();
-        t.take(length as u64).read_to_end(&mut string_data)?;
-        self.read(&mut string_data[..])?;
-        Ok(unsafe { String::from_utf8_unchecked(string_data.to_vec()) })
+        let mut string_data = vec![0; length as usize];
+        self.read_exact(&mut string_data[..])?;
+        Ok(string_from_utf8(string_data)?)
     }
 
     /// Reads a string that will be sized by a u64.
@@ -127,12 +123,10 @@ pub trait BinaryReader: ReadBytesExt + Clone {
     where
         Endianess: ByteOrder,
     {
-        let t = self.clone();
         let length = self.read_u64::<Endianess>()?;
-        let mut string_data = Vec::new();
-        t.take(length as u64).read_to_end(&mut string_data)?;
-        self.read(&mut string_data[..])?;
-        Ok(unsafe { String::from_utf8_unchecked(string_data.to_vec()) })
+        let mut string_data = vec![0; length as usize];
+        self.read_exact(&mut string_data[..])?;
+        Ok(string_from_utf8(string_data)?)
     }
 
     // /// Reads an array to the stream. This array will
@@ -255,6 +249,9 @@ pub trait BinaryReader: ReadBytesExt + Clone {
 
 /// All types that implement `Write` get methods defined in `BinaryWriter`
 /// for free.
+// `Clone` already implies `Sized`, but the bound is kept so the signature
+// matches `BinaryWriter`'s.
+#[allow(clippy::needless_maybe_sized)]
 impl<R: io::Read + Clone + ?Sized> BinaryReader for R {}
 
 pub trait BinaryWriter: WriteBytesExt {
@@ -439,16 +436,17 @@ pub trait BinaryWriter: WriteBytesExt {
     }
 
     /// Writes a socket addres to the stream.
+    #[cfg(feature = "net-addrs")]
     #[inline]
     fn write_socket_addr(&mut self, address: SocketAddr) -> Result<()> {
         if let Ok(v) = address.parse() {
             self.write_all(&v[..])?;
-            return Ok(());
+            Ok(())
         } else {
-            return Err(io::Error::new(
+            Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "Invalid Socket Address.",
-            ));
+            ))
         }
     }
 
@@ -469,3 +467,249 @@ pub trait BinaryWriter: WriteBytesExt {
 /// All types that implement `Write` get methods defined in `BinaryWriter`
 /// for free.
 impl<W: io::Write + ?Sized> BinaryWriter for W {}
+
+/// A bounded source of decompressed bytes that `Streamable` types can be
+/// composed from on demand.
+///
+/// Wrap your decompressing reader (e.g. a deflate decoder) in an `InflateSource`
+/// and it will only ever hold a small window of inflated bytes at a time, instead
+/// of inflating an entire batch into a `Vec` before decoding begins.
+///
+/// ⚠️ The window must be at least as large as the largest single item composed
+/// from the source, otherwise the item can not be decoded.
+/// ```rust
+/// use binary_utils::{io::InflateSource, Streamable};
+///
+/// let data: Vec<u8> = vec![0, 1, 0, 2, 0, 3];
+/// let mut source = InflateSource::with_window(&data[..], 4);
+/// assert_eq!(source.compose::<u16>().unwrap(), 1);
+/// assert_eq!(source.compose::<u16>().unwrap(), 2);
+/// assert_eq!(source.compose::<u16>().unwrap(), 3);
+/// assert!(source.is_empty().unwrap());
+/// ```
+pub struct InflateSource<R: io::Read> {
+    inner: R,
+    buffer: Vec<u8>,
+    start: usize,
+    window: usize,
+    eof: bool,
+    consumed: u64,
+}
+
+impl<R: io::Read> InflateSource<R> {
+    /// The default window size, 64 KiB.
+    pub const DEFAULT_WINDOW: usize = 64 * 1024;
+
+    /// Creates a source with the default window size.
+    pub fn new(inner: R) -> Self {
+        Self::with_window(inner, Self::DEFAULT_WINDOW)
+    }
+
+    /// Creates a source that holds at most `window` decompressed bytes.
+    pub fn with_window(inner: R, window: usize) -> Self {
+        Self {
+            inner,
+            buffer: crate::alloc::allocate(window),
+            start: 0,
+            window,
+            eof: false,
+            consumed: 0,
+        }
+    }
+
+    /// The maximum amount of decompressed bytes held at any time.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The total amount of decompressed bytes consumed so far.
+    pub fn consumed(&self) -> u64 {
+        self.consumed
+    }
+
+    /// Whether the inner reader, and the window, have been fully consumed.
+    pub fn is_empty(&mut self) -> Result<bool> {
+        Ok(io::BufRead::fill_buf(self)?.is_empty())
+    }
+
+    /// Composes the next `T` from the decompressed stream, pulling more bytes
+    /// from the inner reader only when the window runs low.
+    pub fn compose<T: Streamable>(&mut self) -> std::result::Result<T, BinaryError> {
+        io::BufRead::fill_buf(self)?;
+        loop {
+            let mut position: usize = 0;
+            match T::compose(&self.buffer[self.start..], &mut position) {
+                // the item runs past the unread tail, which can still grow.
+                Err(e) if e.is_incomplete() && !self.is_full() => self.top_up()?,
+                result => {
+                    let value = result?;
+                    io::BufRead::consume(self, position);
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    /// Whether the unread tail can't grow anymore, either because it spans the
+    /// whole window or because the inner reader is exhausted.
+    fn is_full(&self) -> bool {
+        self.eof || (self.start == 0 && self.buffer.len() >= self.window)
+    }
+
+    /// Shifts the unread tail to the front of the window and fills the rest
+    /// of it from the inner reader.
+    fn top_up(&mut self) -> Result<()> {
+        if self.start > 0 {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+
+        while !self.eof && self.buffer.len() < self.window {
+            let filled = self.buffer.len();
+            self.buffer.resize(self.window, 0);
+            match self.inner.read(&mut self.buffer[filled..]) {
+                Ok(0) => {
+                    self.buffer.truncate(filled);
+                    self.eof = true;
+                }
+                Ok(read) => self.buffer.truncate(filled + read),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => self.buffer.truncate(filled),
+                Err(e) => {
+                    self.buffer.truncate(filled);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Unwraps this source, returning the inner reader.
+    ///
+    /// Any bytes left in the window are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: io::Read> io::Read for InflateSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let available = io::BufRead::fill_buf(self)?;
+        let amount = available.len().min(buf.len());
+        buf[..amount].copy_from_slice(&available[..amount]);
+        io::BufRead::consume(self, amount);
+        Ok(amount)
+    }
+}
+
+impl<R: io::Read> io::BufRead for InflateSource<R> {
+    /// Returns the unread bytes, topping the window up from the inner reader
+    /// once they run out or more than half of the window has been consumed.
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.start >= self.buffer.len() || self.start > self.window / 2 {
+            self.top_up()?;
+        }
+        Ok(&self.buffer[self.start..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let amount = amount.min(self.buffer.len() - self.start);
+        self.start += amount;
+        self.consumed += amount as u64;
+    }
+}
+
+/// An error raised while copying items with `copy_streamable`.
+#[derive(Debug)]
+pub struct CopyError {
+    /// The index of the item that failed.
+    pub item: usize,
+    /// The offset in the source where the failing item starts.
+    pub offset: u64,
+    /// What went wrong.
+    pub error: io::Error,
+}
+
+impl std::fmt::Display for CopyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not copy item {} at offset {}: {}",
+            self.item, self.offset, self.error
+        )
+    }
+}
+
+impl std::error::Error for CopyError {}
+
+impl From<CopyError> for io::Error {
+    fn from(error: CopyError) -> Self {
+        io::Error::new(error.error.kind(), error.to_string())
+    }
+}
+
+/// Reads up to `limit` items of `T` from `reader` and writes them to `writer`.
+///
+/// Stops early when the reader runs out, returning the amount of items copied.
+/// The reader is consumed through an `InflateSource`, so no item may be larger
+/// than its default window.
+/// ```rust
+/// use binary_utils::{io::copy_streamable, Streamable};
+///
+/// let source: Vec<u8> = vec![0, 1, 0, 2, 0, 3];
+/// let mut sink = Vec::new();
+/// assert_eq!(copy_streamable::<u16, _, _>(&source[..], &mut sink, 2).unwrap(), 2);
+/// assert_eq!(sink, vec![0, 1, 0, 2]);
+/// ```
+pub fn copy_streamable<T, R, W>(
+    reader: R,
+    writer: &mut W,
+    limit: usize,
+) -> std::result::Result<usize, CopyError>
+where
+    T: Streamable,
+    R: io::Read,
+    W: io::Write,
+{
+    copy_streamable_with::<T, T, R, W, _>(reader, writer, limit, Ok)
+}
+
+/// Like `copy_streamable`, transforming every item with `transform`
+/// before it is written. This is how log files are migrated between formats.
+pub fn copy_streamable_with<T, U, R, W, F>(
+    reader: R,
+    writer: &mut W,
+    limit: usize,
+    mut transform: F,
+) -> std::result::Result<usize, CopyError>
+where
+    T: Streamable,
+    U: Streamable,
+    R: io::Read,
+    W: io::Write,
+    F: FnMut(T) -> std::result::Result<U, BinaryError>,
+{
+    let mut source = InflateSource::new(reader);
+    let mut copied: usize = 0;
+
+    while copied < limit {
+        let offset = source.consumed();
+        let fail = |error: io::Error| CopyError {
+            item: copied,
+            offset,
+            error,
+        };
+
+        if source.is_empty().map_err(fail)? {
+            break;
+        }
+
+        let item = source.compose::<T>().map_err(|e| fail(e.into()))?;
+        let bytes = transform(item)
+            .and_then(|item| item.parse())
+            .map_err(|e| fail(e.into()))?;
+        writer.write_all(&bytes[..]).map_err(fail)?;
+        copied += 1;
+    }
+
+    Ok(copied)
+}
@@ -0,0 +1,131 @@
+//! Wire sizes known at compile time.
+//!
+//! `FixedSize` is implemented for every type that always encodes to the same
+//! number of bytes. Deriving `BinaryStream` implements it for structs whose
+//! fields are all `FixedSize`, and for enums by their `#[repr]`, so the size
+//! of a fixed header can be pinned with `assert_wire_size!`.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::{assert_wire_size, BinaryStream, Streamable, u24};
+//!
+//! #[derive(BinaryStream)]
+//! pub struct LoginHeader {
+//!     pub id: u8,
+//!     pub protocol: u32,
+//!     pub sequence: u24,
+//!     pub flags: u16,
+//!     pub encrypted: bool,
+//! }
+//!
+//! assert_wire_size!(LoginHeader, 11);
+//! ```
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::{AtomicBool, AtomicU32};
+
+use crate::{i24, u24, BE, LE};
+
+/// A type that always encodes to `WIRE_SIZE` bytes.
+pub trait FixedSize {
+    /// The number of bytes the type is written with.
+    const WIRE_SIZE: usize;
+}
+
+/// Fails the build if `$ty` doesn't encode to exactly `$size` bytes.
+///
+/// `$ty` must implement `FixedSize`; types with a variable size, such as a
+/// struct with a `String` field, fail the build as well.
+/// ```compile_fail
+/// use binary_utils::{assert_wire_size, BinaryStream, Streamable};
+///
+/// #[derive(BinaryStream)]
+/// pub struct Named {
+///     pub name: String,
+/// }
+///
+/// assert_wire_size!(Named, 2);
+/// ```
+/// ```compile_fail
+/// use binary_utils::{assert_wire_size, BinaryStream, Streamable};
+///
+/// #[derive(BinaryStream)]
+/// pub struct Header {
+///     pub id: u8,
+///     pub flags: u16,
+/// }
+///
+/// assert_wire_size!(Header, 4);
+/// ```
+#[macro_export]
+macro_rules! assert_wire_size {
+    ($ty: ty, $size: expr) => {
+        const _: () = assert!(
+            <$ty as $crate::layout::FixedSize>::WIRE_SIZE == $size,
+            concat!(
+                "the wire size of ",
+                stringify!($ty),
+                " is not ",
+                stringify!($size)
+            )
+        );
+    };
+}
+
+macro_rules! impl_fixed_size {
+    ($($ty: ty),*) => {
+        $(
+            impl FixedSize for $ty {
+                const WIRE_SIZE: usize = core::mem::size_of::<$ty>();
+            }
+        )*
+    };
+}
+
+impl_fixed_size!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl FixedSize for bool {
+    const WIRE_SIZE: usize = 1;
+}
+
+impl FixedSize for u24 {
+    const WIRE_SIZE: usize = 3;
+}
+
+impl FixedSize for i24 {
+    const WIRE_SIZE: usize = 3;
+}
+
+impl<T: FixedSize> FixedSize for LE<T> {
+    const WIRE_SIZE: usize = T::WIRE_SIZE;
+}
+
+impl<T: FixedSize> FixedSize for BE<T> {
+    const WIRE_SIZE: usize = T::WIRE_SIZE;
+}
+
+impl FixedSize for AtomicU32 {
+    const WIRE_SIZE: usize = 4;
+}
+
+#[cfg(target_has_atomic = "64")]
+impl FixedSize for AtomicU64 {
+    const WIRE_SIZE: usize = 8;
+}
+
+impl FixedSize for AtomicBool {
+    const WIRE_SIZE: usize = 1;
+}
+
+impl<T: FixedSize, const N: usize> FixedSize for [T; N] {
+    const WIRE_SIZE: usize = T::WIRE_SIZE * N;
+}
+
+macro_rules! impl_fixed_size_tuple {
+    ($($ty: ident $index: tt),+) => {
+        impl<$($ty: FixedSize),+> FixedSize for ($($ty,)+) {
+            const WIRE_SIZE: usize = 0 $(+ $ty::WIRE_SIZE)+;
+        }
+    };
+}
+
+crate::for_each_tuple!(impl_fixed_size_tuple);
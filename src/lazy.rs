@@ -0,0 +1,110 @@
+use core::cell::OnceCell;
+use core::fmt;
+
+use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::Streamable;
+
+/// A field that keeps its raw bytes and only decodes `T` on first access.
+///
+/// Heavy, rarely inspected fields (like NBT blobs) cost a single copy on the
+/// fast path instead of a full decode, as long as `T` has a `validate` that
+/// skips its bytes without composing them. Writing a `Lazy` that was never decoded
+/// writes the original bytes back unchanged.
+///
+/// When composed, `T::validate` finds where the field ends without decoding
+/// it, and exactly those bytes are kept, so fields after it decode as usual.
+/// ```rust
+/// use binary_utils::{Lazy, Streamable};
+///
+/// let bytes = "a large blob".to_string().parse().unwrap();
+/// let lazy = Lazy::<String>::compose(&bytes, &mut 0).unwrap();
+/// assert!(!lazy.is_decoded());
+/// assert_eq!(lazy.get().unwrap(), "a large blob");
+/// assert!(lazy.is_decoded());
+/// ```
+pub struct Lazy<T> {
+    raw: Vec<u8>,
+    value: OnceCell<T>,
+}
+
+impl<T: Streamable> Lazy<T> {
+    /// Wraps an already decoded value, encoding it once up front.
+    pub fn new(value: T) -> Result<Self, BinaryError> {
+        let raw = value.parse()?;
+        Ok(Self {
+            raw,
+            value: OnceCell::from(value),
+        })
+    }
+
+    /// Wraps raw bytes that will be decoded as `T` when first accessed.
+    pub fn from_raw(raw: Vec<u8>) -> Self {
+        Self {
+            raw,
+            value: OnceCell::new(),
+        }
+    }
+
+    /// The bytes this field was composed from.
+    pub fn raw(&self) -> &[u8] {
+        &self.raw[..]
+    }
+
+    /// Whether the value has been decoded yet.
+    pub fn is_decoded(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Decodes the value if needed and returns it.
+    pub fn get(&self) -> Result<&T, BinaryError> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+
+        let value = T::compose(&self.raw[..], &mut 0)?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// Decodes the value if needed and takes it out of the wrapper.
+    pub fn into_inner(self) -> Result<T, BinaryError> {
+        match self.value.into_inner() {
+            Some(value) => Ok(value),
+            None => T::compose(&self.raw[..], &mut 0),
+        }
+    }
+}
+
+impl<T: Streamable> Streamable for Lazy<T> {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        Ok(self.raw.clone())
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let start = *position;
+        T::validate(source, position)?;
+        Ok(Self::from_raw(source[start..*position].to_vec()))
+    }
+
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        T::validate(source, position)
+    }
+}
+
+impl<T: Clone> Clone for Lazy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => f.debug_tuple("Lazy").field(value).finish(),
+            None => write!(f, "Lazy(<{} bytes>)", self.raw.len()),
+        }
+    }
+}
@@ -1,26 +1,127 @@
-// #![feature(log_syntax)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "forbid-unsafe", forbid(unsafe_code))]
 
-use std::any::type_name;
-use std::convert::{From, Into, TryInto};
-use std::io as std_io;
+// `alloc` is taken by the buffer pooling module.
+extern crate alloc as alloc_crate;
+
+use core::any::type_name;
+use core::convert::{From, TryInto};
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+#[cfg(feature = "net-addrs")]
 use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
 
+use alloc_crate::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use prelude::*;
+
+#[cfg(feature = "derive")]
 pub use bin_macro::*;
 
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use error::BinaryError;
-use std::io::{Cursor, Read, Write};
+use position::Position;
+use profile::{BoolMode, DuplicateKeys, Endian, WireProfile};
 
+#[cfg(feature = "net-addrs")]
+mod address;
+pub mod alloc;
+#[cfg(feature = "registry")]
+pub mod analysis;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+pub mod bits;
+#[cfg(feature = "std")]
+pub mod borrowed;
+#[cfg(feature = "std")]
+pub mod buffer;
+#[cfg(feature = "std")]
+pub mod coalesce;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod delivery;
+#[cfg(feature = "std")]
+pub mod diff;
 /// Error utilities for Binary Utils.
 /// This allows better handling of errors.
 ///
 /// By default, errors **can** be converted to: `std::io::Error`
 pub mod error;
+pub mod extensions;
+mod fixed_string;
+pub mod framing;
+pub mod hash;
+#[cfg(feature = "io-streams")]
 pub mod io;
+pub mod layout;
+mod lazy;
+#[cfg(feature = "migrate")]
+pub mod migrate;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+mod packet_enum;
+pub mod partial;
+mod passthrough;
+mod payload;
+pub mod position;
+pub mod profile;
+pub mod proto;
+#[cfg(feature = "quarantine")]
+pub mod quarantine;
+#[cfg(feature = "io-streams")]
+pub mod recv;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod resync;
+#[cfg(feature = "std")]
+mod scaled;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod scratch;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod shared;
+#[cfg(feature = "io-streams")]
+pub mod tcp;
+#[cfg(feature = "std")]
+pub mod testing;
+pub mod tlv;
+#[cfg(feature = "std")]
+pub mod tracked;
 mod u24_impl;
+#[cfg(feature = "io-streams")]
+pub mod udp;
 pub mod varint;
+#[cfg(feature = "ws")]
+pub mod ws;
 
-pub use self::{u24_impl::*, varint::*};
+#[cfg(feature = "net-addrs")]
+pub use self::address::{PlainAddr, RakNetAddr};
+#[cfg(feature = "std")]
+pub use self::scaled::{Scaled, ScaledInt};
+pub use self::{
+    fixed_string::FixedString, lazy::Lazy, passthrough::PassthroughOr, payload::Payload,
+    shared::SharedSource, u24_impl::*, varint::*,
+};
+
+/// The `alloc` types `std`'s prelude would otherwise bring into scope.
+mod prelude {
+    pub(crate) use alloc_crate::{
+        borrow::ToOwned,
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+}
 
 macro_rules! includes {
     ($var: ident, $method: ident, $values: expr) => {{
@@ -32,7 +133,8 @@ macro_rules! includes {
 /// A trait to parse and unparse header structs from a given buffer.
 ///
 /// **Example:**
-/// ```rust
+#[cfg_attr(feature = "std", doc = "```rust")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
 /// use binary_utils::{Streamable, error::BinaryError};
 ///
 /// struct Foo {
@@ -69,11 +171,46 @@ pub trait Streamable {
         self.parse().unwrap()
     }
 
+    /// Writes `self` to the end of `out`.
+    ///
+    /// Composite types write each of their parts into the same buffer with
+    /// this, rather than allocating a buffer per part. By default this copies
+    /// the result of `parse`.
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        out.extend_from_slice(&self.parse()?[..]);
+        Ok(())
+    }
+
     /// Reads `self` from the given buffer.
     fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError>
     where
         Self: Sized;
 
+    /// Reads a value from the given buffer over `self`, reusing what it can
+    /// of the existing value, e.g. the capacity of a `String`.
+    ///
+    /// Packets decoded into the same slot every tick stop allocating once
+    /// their buffers are large enough. If this fails, `self` may be left
+    /// partly overwritten. By default this replaces `self` with `compose`.
+    fn compose_into(&mut self, source: &[u8], position: &mut usize) -> Result<(), BinaryError>
+    where
+        Self: Sized,
+    {
+        *self = Self::compose(source, position)?;
+        Ok(())
+    }
+
+    /// Reads `self` from the given buffer at a typed `Position`.
+    ///
+    /// Same as `compose`, but the position can't be copied or moved backwards
+    /// by accident; debug builds check that `compose` only moved it forward.
+    fn compose_from(source: &[u8], position: &mut Position) -> Result<Self, BinaryError>
+    where
+        Self: Sized,
+    {
+        position.with_offset(|offset| Self::compose(source, offset))
+    }
+
     /// Reads and unwraps `self` from the given buffer.
     ///
     /// ⚠️ This method is not fail safe, and will panic if result is Err.
@@ -83,6 +220,52 @@ pub trait Streamable {
     {
         Self::compose(source, position).unwrap()
     }
+
+    /// The number of bytes `parse` will write, if it is cheap to know ahead of time.
+    ///
+    /// Used to pre-allocate the output buffer, so large packets don't grow
+    /// it repeatedly. A hint that is off only costs an extra allocation.
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// Checks a valid `Self` can be read at `position` without constructing it,
+    /// advancing `position` past it.
+    ///
+    /// By default this composes the value and drops it, types that can walk
+    /// their layout more cheaply override it.
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError>
+    where
+        Self: Sized,
+    {
+        Self::compose(source, position).map(|_| ())
+    }
+}
+
+/// Advances `position` past `length` bytes, failing if `source` is too short.
+fn skip(source: &[u8], position: &mut usize, length: usize) -> Result<(), BinaryError> {
+    match position.checked_add(length) {
+        Some(end) if end <= source.len() => {
+            *position = end;
+            Ok(())
+        }
+        _ => Err(BinaryError::EOF(source.len())),
+    }
+}
+
+/// The `length` bytes of `source` at `position`, failing with how many are missing.
+pub(crate) fn bytes_at(
+    source: &[u8],
+    position: usize,
+    length: usize,
+) -> Result<&[u8], BinaryError> {
+    source
+        .get(position..)
+        .and_then(|rest| rest.get(..length))
+        .ok_or(BinaryError::NotEnoughBytes {
+            needed: length,
+            available: source.len().saturating_sub(position),
+        })
 }
 
 /// Little Endian Type
@@ -119,10 +302,17 @@ where
     T: Streamable + Sized,
 {
     fn parse(&self) -> Result<Vec<u8>, BinaryError> {
-        let bytes = self.0.parse()?;
+        // the inner type is written as BE and flipped, regardless of the profile.
+        let bytes = WireProfile::current()
+            .with_endian(Endian::Big)
+            .encode(&self.0)?;
         Ok(reverse_vec(bytes))
     }
 
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+
     fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
         // If the source is expected to be LE we can swap it to BE bytes
         // Doing this makes the byte stream officially BE.
@@ -132,17 +322,29 @@ where
             // if we can get the value of the type we do so here.
             let name = type_name::<T>();
 
+            // a u24 or i24 is stored in 4 bytes but written as 3.
+            let size = if name.ends_with("u24") || name.ends_with("i24") {
+                3
+            } else {
+                core::mem::size_of::<T>()
+            };
+
             if includes!(
                 name,
                 contains,
                 [
-                    "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128", "f32",
-                    "f64"
+                    "u8", "u16", "u24", "u32", "u64", "u128", "i8", "i16", "i24", "i32", "i64",
+                    "i128", "f32", "f64"
                 ]
             ) {
-                reverse_vec(source[*position..(*position + ::std::mem::size_of::<T>())].to_vec())
+                reverse_vec(bytes_at(source, *position, size)?.to_vec())
             } else {
-                reverse_vec(source[*position..].to_vec())
+                reverse_vec(
+                    source
+                        .get(*position..)
+                        .ok_or(BinaryError::EOF(source.len()))?
+                        .to_vec(),
+                )
             }
         };
 
@@ -150,26 +352,19 @@ where
         // todo Get rid of this NASTY hack!
         // we need to get the stream releative to the current source, and "inject" into the current source.
         // we can do this by getting the position and the length of the stream.
-        let mut hacked_stream = Vec::<u8>::new();
-        let (q1, q2) = (
-            hacked_stream.write_all(&source[..*position]),
-            hacked_stream.write_all(&stream),
-        );
-
-        // check if any of the queries were invalid or failed.
-        if q1.is_err() || q2.is_err() {
-            Err(BinaryError::RecoverableKnown(
-                "Write operation was interupted.".to_owned(),
-            ))
-        } else {
-            Ok(LE(T::compose(&hacked_stream[..], position)?))
-        }
+        let mut hacked_stream = Vec::<u8>::with_capacity(*position + stream.len());
+        hacked_stream.extend_from_slice(&source[..*position]);
+        hacked_stream.extend_from_slice(&stream);
+
+        Ok(LE(WireProfile::current()
+            .with_endian(Endian::Big)
+            .decode(&hacked_stream[..], position)?))
     }
 }
 
 /// Reverses the bytes in a given vector
 pub fn reverse_vec(bytes: Vec<u8>) -> Vec<u8> {
-    let mut ret: Vec<u8> = Vec::new();
+    let mut ret = alloc::allocate(bytes.len());
 
     for x in (0..bytes.len()).rev() {
         ret.push(*bytes.get(x).unwrap());
@@ -177,24 +372,86 @@ pub fn reverse_vec(bytes: Vec<u8>) -> Vec<u8> {
     ret
 }
 
-/// Big Endian Encoding
+/// Big Endian Type
+///
+/// Always writes the inner type in Big Endian,
+/// regardless of the current `WireProfile`.
+#[derive(Debug, Clone, Copy)]
 pub struct BE<T>(pub T);
 
+impl<T> BE<T> {
+    /// Grabs the `inner` type, similar to `unwrap`.
+    pub fn inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Streamable for BE<T>
+where
+    T: Streamable + Sized,
+{
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        WireProfile::current()
+            .with_endian(Endian::Big)
+            .encode(&self.0)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        Ok(BE(WireProfile::current()
+            .with_endian(Endian::Big)
+            .decode(source, position)?))
+    }
+}
+
 macro_rules! impl_streamable_primitive {
     ($ty: ty) => {
+        impl_streamable_primitive!($ty, |value: $ty| value);
+    };
+    ($ty: ty, $canonical: expr) => {
         impl Streamable for $ty {
             fn parse(&self) -> Result<Vec<u8>, BinaryError> {
-                Ok(self.to_be_bytes().to_vec())
+                let mut out = alloc::allocate(core::mem::size_of::<$ty>());
+                self.parse_into(&mut out)?;
+                Ok(out)
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                let profile = WireProfile::current();
+                let value = if profile.canonical {
+                    ($canonical)(*self)
+                } else {
+                    *self
+                };
+                match profile.default_endian {
+                    Endian::Big => out.extend_from_slice(&value.to_be_bytes()),
+                    Endian::Little => out.extend_from_slice(&value.to_le_bytes()),
+                }
+                Ok(())
             }
 
             fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
                 // get the size
-                let size = ::std::mem::size_of::<$ty>();
-                let range = position.clone()..(size + position.clone());
-                let data = <$ty>::from_be_bytes(source.get(range).unwrap().try_into().unwrap());
+                let size = core::mem::size_of::<$ty>();
+                let bytes = bytes_at(source, *position, size)?.try_into().unwrap();
+                let data = match WireProfile::current().default_endian {
+                    Endian::Big => <$ty>::from_be_bytes(bytes),
+                    Endian::Little => <$ty>::from_le_bytes(bytes),
+                };
                 *position += size;
                 Ok(data)
             }
+
+            fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+                skip(source, position, core::mem::size_of::<$ty>())
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(core::mem::size_of::<$ty>())
+            }
         }
 
         // impl Streamable for LE<$ty> {
@@ -207,7 +464,7 @@ macro_rules! impl_streamable_primitive {
         //         // Doing this makes the byte stream officially BE.
         //         // We actually need to do some hacky stuff here,
         //         // we need to get the size of `T` (in bytes)
-        //         let stream = reverse_vec(source[*position..(*position + ::std::mem::size_of::<$ty>())].to_vec());
+        //         let stream = reverse_vec(source[*position..(*position + core::mem::size_of::<$ty>())].to_vec());
         //         LE(<$ty>::compose(&stream[..], position))
         //     }
         // }
@@ -217,9 +474,17 @@ macro_rules! impl_streamable_primitive {
 impl_streamable_primitive!(u8);
 impl_streamable_primitive!(u16);
 impl_streamable_primitive!(u32);
-impl_streamable_primitive!(f32);
+impl_streamable_primitive!(f32, |value: f32| if value.is_nan() {
+    f32::from_bits(0x7FC0_0000)
+} else {
+    value
+});
 impl_streamable_primitive!(u64);
-impl_streamable_primitive!(f64);
+impl_streamable_primitive!(f64, |value: f64| if value.is_nan() {
+    f64::from_bits(0x7FF8_0000_0000_0000)
+} else {
+    value
+});
 impl_streamable_primitive!(u128);
 impl_streamable_primitive!(i8);
 impl_streamable_primitive!(i16);
@@ -231,25 +496,24 @@ macro_rules! impl_streamable_vec_primitive {
     ($ty: ty) => {
         impl Streamable for Vec<$ty> {
             fn parse(&self) -> Result<Vec<u8>, BinaryError> {
-                use ::std::io::Write;
-                // write the length as a varint
-                let mut v: Vec<u8> = Vec::new();
-                v.write_all(&VarInt(v.len() as u32).to_be_bytes()[..])
-                    .unwrap();
+                // write the length with the profile's vector prefix
+                let mut v = alloc::allocate(self.size_hint().unwrap_or(0));
+                self.parse_into(&mut v)?;
+                Ok(v)
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                WireProfile::write_length(WireProfile::current().vec_prefix, self.len(), out)?;
                 for x in self.iter() {
-                    v.extend(x.parse()?.iter());
+                    x.parse_into(out)?;
                 }
-                Ok(v)
+                Ok(())
             }
 
             fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
-                // use ::std::io::Read;
-                // read a var_int
+                let length =
+                    WireProfile::read_length(WireProfile::current().vec_prefix, source, position)?;
                 let mut ret: Vec<$ty> = Vec::new();
-                let varint = VarInt::<u32>::from_be_bytes(source)?;
-                let length: u32 = varint.into();
-
-                *position += varint.get_byte_length() as usize;
 
                 // read each length
                 for _ in 0..length {
@@ -257,6 +521,31 @@ macro_rules! impl_streamable_vec_primitive {
                 }
                 Ok(ret)
             }
+
+            fn compose_into(
+                &mut self,
+                source: &[u8],
+                position: &mut usize,
+            ) -> Result<(), BinaryError> {
+                compose_into_vec(self, source, position)
+            }
+
+            fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+                let length =
+                    WireProfile::read_length(WireProfile::current().vec_prefix, source, position)?;
+                let size = length
+                    .checked_mul(core::mem::size_of::<$ty>())
+                    .ok_or(BinaryError::EOF(source.len()))?;
+                skip(source, position, size)
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                let prefix = WireProfile::current().vec_prefix;
+                Some(
+                    WireProfile::length_size(prefix, self.len())
+                        + self.len() * core::mem::size_of::<$ty>(),
+                )
+            }
         }
     };
 }
@@ -274,133 +563,251 @@ impl_streamable_vec_primitive!(i32);
 impl_streamable_vec_primitive!(i64);
 impl_streamable_vec_primitive!(i128);
 
+/// Composes a vector of `T` written like `Vec<T>`, into `out`.
+///
+/// `out` is cleared first, and keeps its capacity, so decoding a similar
+/// list every tick stops allocating once it has grown large enough. If an
+/// item fails to compose, `out` is left with the items before it.
+/// ```rust
+/// use binary_utils::{compose_into_vec, Streamable};
+///
+/// let mut items: Vec<u16> = Vec::with_capacity(8);
+/// let capacity = items.capacity();
+/// for tick in 0..3u16 {
+///     let bytes = vec![tick, tick + 1].parse().unwrap();
+///     compose_into_vec(&mut items, &bytes, &mut 0).unwrap();
+///     assert_eq!(items, vec![tick, tick + 1]);
+/// }
+/// assert_eq!(items.capacity(), capacity);
+/// ```
+pub fn compose_into_vec<T: Streamable>(
+    out: &mut Vec<T>,
+    source: &[u8],
+    position: &mut usize,
+) -> Result<(), BinaryError> {
+    out.clear();
+    let length = WireProfile::read_length(WireProfile::current().vec_prefix, source, position)?;
+    for _ in 0..length {
+        out.push(T::compose(source, position)?);
+    }
+    Ok(())
+}
+
+/// Encodes each of `values` on the rayon thread pool, e.g. to serialize the
+/// same tick's packets for many players at once.
+///
+/// The output keeps the order of `values`, and every value is encoded under
+/// the caller's profile, not the worker thread's. If any value fails to
+/// encode, one of the errors is returned.
+/// ```rust
+/// use binary_utils::encode_all_parallel;
+///
+/// let encoded = encode_all_parallel(&[1u16, 2, 3]).unwrap();
+/// assert_eq!(encoded, vec![vec![0, 1], vec![0, 2], vec![0, 3]]);
+/// ```
+#[cfg(feature = "rayon")]
+pub fn encode_all_parallel<T>(values: &[T]) -> Result<Vec<Vec<u8>>, BinaryError>
+where
+    T: Streamable + Sync,
+{
+    use rayon::prelude::*;
+
+    let profile = WireProfile::current();
+    values
+        .par_iter()
+        .map(|value| profile.encode(value))
+        .collect()
+}
+
 // implements bools
 impl Streamable for bool {
     fn parse(&self) -> Result<Vec<u8>, BinaryError> {
-        Ok(vec![if *self { 1 } else { 0 }])
+        let mut out = alloc::allocate(1);
+        self.parse_into(&mut out)?;
+        Ok(out)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        out.push(if *self { 1 } else { 0 });
+        Ok(())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
     }
 
     fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let byte = bytes_at(source, *position, 1)?[0];
         // header validation
-        if source[*position] > 1 {
+        if byte > 1 && WireProfile::current().bool_mode == BoolMode::Strict {
             Err(BinaryError::RecoverableKnown(format!(
                 "Tried composing binary from non-binary byte: {}",
-                source[*position]
+                byte
             )))
         } else {
-            let v = source[*position] == 1;
+            let v = byte != 0;
             *position += 1;
             Ok(v)
         }
     }
 }
 
+/// The ordering atomics are loaded with under the current profile.
+fn atomic_load_ordering() -> Ordering {
+    match WireProfile::current().atomic_ordering {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        ordering => ordering,
+    }
+}
+
+// atomics are written as the value they hold, and read into a fresh atomic.
+macro_rules! impl_streamable_atomic {
+    ($atomic: ty, $ty: ty) => {
+        impl Streamable for $atomic {
+            fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+                self.load(atomic_load_ordering()).parse()
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                self.load(atomic_load_ordering()).parse_into(out)
+            }
+
+            fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+                Ok(<$atomic>::new(<$ty>::compose(source, position)?))
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(core::mem::size_of::<$ty>())
+            }
+        }
+    };
+}
+
+impl_streamable_atomic!(AtomicU32, u32);
+#[cfg(target_has_atomic = "64")]
+impl_streamable_atomic!(AtomicU64, u64);
+impl_streamable_atomic!(AtomicBool, bool);
+
 impl Streamable for String {
     fn parse(&self) -> Result<Vec<u8>, BinaryError> {
-        let mut buffer = Vec::<u8>::new();
-        buffer.write_u16::<BigEndian>(self.len() as u16)?;
-        buffer.write_all(self.as_bytes())?;
+        let mut buffer = alloc::allocate(self.size_hint().unwrap_or(0));
+        self.parse_into(&mut buffer)?;
         Ok(buffer)
     }
 
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        let profile = WireProfile::current();
+        let bytes = profile.utf8_policy.encode(self);
+        WireProfile::write_length(profile.string_prefix, bytes.len(), out)?;
+        out.extend_from_slice(&bytes);
+        Ok(())
+    }
+
     fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
-        let mut stream = Cursor::new(source);
-        stream.set_position(position.clone() as u64);
-        // Maybe do this in the future?
-        let len: usize = stream.read_u16::<BigEndian>()?.into();
-        *position = (stream.position() as usize) + len;
-
-        unsafe {
-            // todo: Remove this nasty hack.
-            // todo: The hack being, remove the 2 from indexing on read_short
-            // todo: And utilize stream.
-            Ok(String::from_utf8_unchecked(
-                stream.get_ref()[2..len + stream.position() as usize].to_vec(),
-            ))
+        let mut start = *position;
+        let len =
+            WireProfile::read_length(WireProfile::current().string_prefix, source, &mut start)?;
+        let bytes = bytes_at(source, start, len)?;
+        *position = start + len;
+
+        string_from_utf8(bytes.to_vec())
+    }
+
+    fn compose_into(&mut self, source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        let mut start = *position;
+        let len =
+            WireProfile::read_length(WireProfile::current().string_prefix, source, &mut start)?;
+        let bytes = bytes_at(source, start, len)?;
+        match core::str::from_utf8(bytes) {
+            Ok(value) => {
+                self.clear();
+                self.push_str(value);
+            }
+            // let the profile's policy handle it
+            Err(_) => *self = string_from_utf8(bytes.to_vec())?,
         }
+        *position = start + len;
+        Ok(())
+    }
+
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        let len = WireProfile::read_length(WireProfile::current().string_prefix, source, position)?;
+        skip(source, position, len)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        let profile = WireProfile::current();
+        let length = profile.utf8_policy.encode(self).len();
+        Some(WireProfile::length_size(profile.string_prefix, length) + length)
     }
 }
 
+#[cfg(feature = "net-addrs")]
 impl Streamable for SocketAddr {
     fn parse(&self) -> Result<Vec<u8>, BinaryError> {
-        let mut stream = Vec::<u8>::new();
+        let mut stream = alloc::allocate(self.size_hint().unwrap_or(0));
         match *self {
             Self::V4(_) => {
-                stream.write_u8(4)?;
+                stream.push(4);
                 let partstr = self.to_string();
                 let actstr = partstr.split(":").collect::<Vec<&str>>()[0];
                 let parts: Vec<&str> = actstr.split(".").collect();
                 for part in parts {
                     let mask = part.parse::<u8>().unwrap_or(0);
-                    stream.write_u8(mask)?;
+                    stream.push(mask);
                 }
-                stream
-                    .write_u16::<BigEndian>(self.port())
-                    .expect("Could not write port to stream.");
+                stream.extend_from_slice(&self.port().to_be_bytes());
                 Ok(stream)
             }
             Self::V6(addr) => {
-                stream.write_u8(6)?;
+                stream.push(6);
                 // family? or length??
-                stream.write_u16::<BigEndian>(0)?;
+                stream.extend_from_slice(&0u16.to_be_bytes());
                 // port
-                stream.write_u16::<BigEndian>(self.port())?;
+                stream.extend_from_slice(&self.port().to_be_bytes());
                 // flow
-                stream.write_u32::<BigEndian>(addr.flowinfo())?;
+                stream.extend_from_slice(&addr.flowinfo().to_be_bytes());
                 // actual address here
-                stream.write(&addr.ip().octets())?;
+                stream.extend_from_slice(&addr.ip().octets());
                 // scope
-                stream.write_u32::<BigEndian>(addr.scope_id())?;
+                stream.extend_from_slice(&addr.scope_id().to_be_bytes());
                 Ok(stream)
             }
         }
     }
 
     fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
-        let mut stream = Cursor::new(source);
-        stream.set_position(*position as u64);
-        match stream.read_u8()? {
+        // every part is big endian whatever the profile is
+        let mut start = *position;
+        let mut take = |length: usize| {
+            let bytes = bytes_at(source, start, length)?;
+            start += length;
+            Ok::<_, BinaryError>(bytes)
+        };
+        let address = match take(1)?[0] {
             4 => {
-                let from = stream.position() as usize;
-                let to = stream.position() as usize + 4;
-                let parts = &source[from..to];
-                stream.set_position(to as u64);
-                let port = stream.read_u16::<BigEndian>().unwrap();
-                *position = stream.position() as usize;
-                Ok(SocketAddr::new(
-                    IpAddr::from([parts[0], parts[1], parts[2], parts[3]]),
-                    port,
-                ))
+                let parts: [u8; 4] = take(4)?.try_into().unwrap();
+                let port = u16::from_be_bytes(take(2)?.try_into().unwrap());
+                SocketAddr::new(IpAddr::from(parts), port)
             }
             6 => {
-                let _family = stream.read_u16::<BigEndian>().unwrap();
-                let port = stream.read_u16::<BigEndian>().unwrap();
-                let flow = stream.read_u32::<BigEndian>().unwrap();
-                let mut parts: [u8; 16] = [0; 16];
-                stream.read(&mut parts).unwrap();
-                // we need to read parts into address
-                let address = {
-                    let mut s = Cursor::new(parts);
-                    let (a, b, c, d, e, f, g, h) = (
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                        s.read_u16::<BigEndian>().unwrap_or(0),
-                    );
-                    Ipv6Addr::new(a, b, c, d, e, f, g, h)
-                };
-                let scope = stream.read_u32::<BigEndian>().unwrap();
-                *position = stream.position() as usize;
-                Ok(SocketAddr::from(SocketAddrV6::new(
-                    address, port, flow, scope,
+                let _family = take(2)?;
+                let port = u16::from_be_bytes(take(2)?.try_into().unwrap());
+                let flow = u32::from_be_bytes(take(4)?.try_into().unwrap());
+                let parts: [u8; 16] = take(16)?.try_into().unwrap();
+                let scope = u32::from_be_bytes(take(4)?.try_into().unwrap());
+                SocketAddr::from(SocketAddrV6::new(Ipv6Addr::from(parts), port, flow, scope))
+            }
+            version => {
+                return Err(BinaryError::RecoverableKnown(format!(
+                    "Unknown address version: {}",
+                    version
                 )))
             }
-            _ => panic!("Unknown Address type!"),
-        }
+        };
+        *position = start;
+        Ok(address)
     }
 }
 
@@ -411,8 +818,8 @@ where
 {
     fn parse(&self) -> Result<Vec<u8>, BinaryError> {
         // write the length as a varint
-        let mut v: Vec<u8> = Vec::new();
-        v.write_u16::<BigEndian>(self.len() as u16)?;
+        let mut v = alloc::allocate(0);
+        v.extend_from_slice(&(self.len() as u16).to_be_bytes());
         for x in self.iter() {
             v.extend(x.parse()?.iter());
         }
@@ -420,15 +827,210 @@ where
     }
 
     fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
-        // read a var_int
-        let mut stream = Cursor::new(source);
+        let length = u16::from_be_bytes(bytes_at(source, *position, 2)?.try_into().unwrap());
+        *position += 2;
         let mut ret: Vec<LE<T>> = Vec::new();
-        let length = stream.read_u16::<BigEndian>()?;
-        *position = stream.position() as usize;
         // read each length
         for _ in 0..length {
-            ret.push(LE::<T>::compose(&source[*position..], &mut 0)?);
+            ret.push(LE::<T>::compose(source, position)?);
         }
         Ok(ret)
     }
 }
+
+/// Writes a fixed length array as its items, without a length prefix.
+///
+/// Useful for fixed-length fields like magics, tokens or IPv6 octets.
+/// ```rust
+/// use binary_utils::Streamable;
+///
+/// let magic: [u8; 4] = [0xFE, 0xFD, 0xFC, 0xFB];
+/// assert_eq!(magic.parse().unwrap(), vec![0xFE, 0xFD, 0xFC, 0xFB]);
+/// assert_eq!(<[u16; 2]>::compose(&[0, 1, 0, 2], &mut 0).unwrap(), [1, 2]);
+/// ```
+impl<T: Streamable, const N: usize> Streamable for [T; N] {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut out = alloc::allocate(self.size_hint().unwrap_or(0));
+        self.parse_into(&mut out)?;
+        Ok(out)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        for item in self {
+            item.parse_into(out)?;
+        }
+        Ok(())
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(T::compose(source, position)?);
+        }
+        Ok(collect_array(items))
+    }
+
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        for _ in 0..N {
+            T::validate(source, position)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.iter().map(Streamable::size_hint).sum()
+    }
+}
+
+// maps are written as the profile's vector prefix with the number of entries,
+// followed by each key and its value.
+macro_rules! impl_streamable_map {
+    ($map: ident, $($bound: path),+) => {
+        impl<K: Streamable $(+ $bound)+, V: Streamable> Streamable for $map<K, V> {
+            fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+                let mut out = alloc::allocate(self.size_hint().unwrap_or(0));
+                self.parse_into(&mut out)?;
+                Ok(out)
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                let profile = WireProfile::current();
+                WireProfile::write_length(profile.vec_prefix, self.len(), out)?;
+                if profile.canonical {
+                    return parse_sorted_entries(self.iter(), out);
+                }
+                for (key, value) in self {
+                    key.parse_into(out)?;
+                    value.parse_into(out)?;
+                }
+                Ok(())
+            }
+
+            fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+                let profile = WireProfile::current();
+                let length = WireProfile::read_length(profile.vec_prefix, source, position)?;
+                let mut map = $map::new();
+                for _ in 0..length {
+                    let start = *position;
+                    let key = K::compose(source, position)?;
+                    if profile.duplicate_keys == DuplicateKeys::Error && map.contains_key(&key) {
+                        return Err(BinaryError::DuplicateKey {
+                            key_repr: hex(&source[start..*position]),
+                            offset: start,
+                        });
+                    }
+                    let value = V::compose(source, position)?;
+                    map.insert(key, value);
+                }
+                Ok(map)
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                let mut size = WireProfile::length_size(WireProfile::current().vec_prefix, self.len());
+                for (key, value) in self {
+                    size += key.size_hint()? + value.size_hint()?;
+                }
+                Some(size)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "std")]
+impl_streamable_map!(HashMap, Eq, core::hash::Hash);
+impl_streamable_map!(BTreeMap, Ord);
+
+/// Writes map entries ordered by their encoded keys, so equal maps are
+/// written the same whatever order they iterate in.
+fn parse_sorted_entries<'a, K, V>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    out: &mut Vec<u8>,
+) -> Result<(), BinaryError>
+where
+    K: Streamable + 'a,
+    V: Streamable + 'a,
+{
+    let mut entries = entries
+        .map(|(key, value)| Ok((key.parse()?, value)))
+        .collect::<Result<Vec<_>, BinaryError>>()?;
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    for (key, value) in entries {
+        out.extend_from_slice(&key);
+        value.parse_into(out)?;
+    }
+    Ok(())
+}
+
+/// Formats encoded bytes as `0x` followed by their hex digits.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::from("0x"), |mut out, byte| {
+        out.push_str(&format!("{:02x}", byte));
+        out
+    })
+}
+
+/// Calls `$m!` with the type parameters and field indexes of every tuple
+/// from 1 to 12 items, e.g. `$m!(A 0, B 1)` for pairs.
+macro_rules! for_each_tuple {
+    ($m: ident) => {
+        $m!(A 0);
+        $m!(A 0, B 1);
+        $m!(A 0, B 1, C 2);
+        $m!(A 0, B 1, C 2, D 3);
+        $m!(A 0, B 1, C 2, D 3, E 4);
+        $m!(A 0, B 1, C 2, D 3, E 4, F 5);
+        $m!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+        $m!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+        $m!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+        $m!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+        $m!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+        $m!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+    };
+}
+pub(crate) use for_each_tuple;
+
+// tuples are written as their items in order, like a struct of the same fields.
+macro_rules! impl_streamable_tuple {
+    ($($ty: ident $index: tt),+) => {
+        impl<$($ty: Streamable),+> Streamable for ($($ty,)+) {
+            fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+                let mut out = alloc::allocate(self.size_hint().unwrap_or(0));
+                self.parse_into(&mut out)?;
+                Ok(out)
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                $(self.$index.parse_into(out)?;)+
+                Ok(())
+            }
+
+            fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+                Ok(($($ty::compose(source, position)?,)+))
+            }
+
+            fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+                $($ty::validate(source, position)?;)+
+                Ok(())
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(0 $(+ self.$index.size_hint()?)+)
+            }
+        }
+    };
+}
+
+for_each_tuple!(impl_streamable_tuple);
+
+/// Turns decoded bytes into a `String`, following the current `Utf8Policy`.
+pub(crate) fn string_from_utf8(bytes: Vec<u8>) -> Result<String, BinaryError> {
+    WireProfile::current().utf8_policy.decode(bytes)
+}
+
+/// Turns a `Vec` known to hold `N` items into an array.
+pub(crate) fn collect_array<T, const N: usize>(items: Vec<T>) -> [T; N] {
+    match items.try_into() {
+        Ok(array) => array,
+        Err(items) => panic!("expected {} items, found {}", N, items.len()),
+    }
+}
@@ -0,0 +1,609 @@
+//! Shims for moving data persisted with bincode or postcard onto the `Streamable` wire.
+//!
+//! With the `migrate` feature the derive also implements `Migrate`, which reads
+//! and writes a type the way `#[derive(Serialize, Deserialize)]` would under
+//! bincode 1 (`bincode::serialize`) and postcard 1, without depending on serde.
+//! Fields are visited in declaration order and enums by variant index,
+//! followed by the fields of the variant.
+//! ```rust
+//! use binary_utils::{migrate, BinaryStream, Streamable};
+//!
+//! #[derive(Debug, PartialEq, BinaryStream)]
+//! pub struct Save {
+//!     pub level: u16,
+//!     pub name: String,
+//! }
+//!
+//! // written by an older version with `bincode::serialize`.
+//! let old = vec![3, 0, 1, 0, 0, 0, 0, 0, 0, 0, b'a'];
+//! let save: Save = migrate::from_bincode(&old).unwrap();
+//! assert_eq!(save, Save { level: 3, name: "a".to_string() });
+//! assert_eq!(migrate::to_postcard(&save).unwrap(), vec![3, 1, b'a']);
+//! assert_eq!(save.parse().unwrap(), vec![0, 3, 0, 1, b'a']);
+//! ```
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "net-addrs")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use crate::delivery::AckRanges;
+use crate::error::BinaryError;
+use crate::extensions::Extensions;
+use crate::tlv::{Tlv, TlvInt};
+use crate::{
+    i24, u24, FixedString, Lazy, PassthroughOr, Payload, SVarInt, Scaled, ScaledInt, Streamable,
+    VarInt, BE, LE,
+};
+#[cfg(feature = "net-addrs")]
+use crate::{PlainAddr, RakNetAddr};
+
+/// A serialization format data can be migrated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// bincode 1 with its default options: little endian fixed width
+    /// integers and `u64` lengths.
+    Bincode,
+    /// postcard 1: varint integers (zigzag for signed) and varint lengths.
+    Postcard,
+}
+
+/// A type that can be read from and written to the formats in `Format`.
+pub trait Migrate: Sized {
+    /// Appends `self` in `format` to `out`.
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError>;
+
+    /// Reads a value in `format` from the front of `source`, advancing it.
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError>;
+}
+
+/// Reads a `T` written with `bincode::serialize`.
+pub fn from_bincode<T: Migrate>(mut source: &[u8]) -> Result<T, BinaryError> {
+    T::read_format(Format::Bincode, &mut source)
+}
+
+/// Writes `value` the way `bincode::serialize` would.
+pub fn to_bincode<T: Migrate>(value: &T) -> Result<Vec<u8>, BinaryError> {
+    let mut out = Vec::new();
+    value.write_format(Format::Bincode, &mut out)?;
+    Ok(out)
+}
+
+/// Reads a `T` written with `postcard::to_allocvec`.
+pub fn from_postcard<T: Migrate>(mut source: &[u8]) -> Result<T, BinaryError> {
+    T::read_format(Format::Postcard, &mut source)
+}
+
+/// Writes `value` the way `postcard::to_allocvec` would.
+pub fn to_postcard<T: Migrate>(value: &T) -> Result<Vec<u8>, BinaryError> {
+    let mut out = Vec::new();
+    value.write_format(Format::Postcard, &mut out)?;
+    Ok(out)
+}
+
+fn take<'a>(source: &mut &'a [u8], length: usize) -> Result<&'a [u8], BinaryError> {
+    if source.len() < length {
+        return Err(BinaryError::EOF(source.len()));
+    }
+    let (bytes, rest) = source.split_at(length);
+    *source = rest;
+    Ok(bytes)
+}
+
+fn write_varint(mut value: u128, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(source: &mut &[u8], bits: u32) -> Result<u128, BinaryError> {
+    let mut value = 0u128;
+    // the number of bytes a varint of `bits` can take.
+    let max = bits.div_ceil(7) as usize;
+    for i in 0..max {
+        let byte = take(source, 1)?[0];
+        value |= ((byte & 0x7F) as u128) << (i * 7);
+        if byte & 0x80 == 0 {
+            if bits < 128 && value >> bits != 0 {
+                break;
+            }
+            return Ok(value);
+        }
+    }
+    Err(BinaryError::RecoverableKnown(format!(
+        "Varint is too large for a {} bit integer",
+        bits
+    )))
+}
+
+/// Writes a variant index, `u32` in bincode and a varint in postcard.
+#[doc(hidden)]
+pub fn write_variant(index: u32, format: Format, out: &mut Vec<u8>) {
+    match format {
+        Format::Bincode => out.extend_from_slice(&index.to_le_bytes()),
+        Format::Postcard => write_varint(index as u128, out),
+    }
+}
+
+/// Reads a variant index written by `write_variant`.
+#[doc(hidden)]
+pub fn read_variant(format: Format, source: &mut &[u8]) -> Result<u32, BinaryError> {
+    u32::read_format(format, source)
+}
+
+#[doc(hidden)]
+pub fn unknown_variant(index: u32) -> BinaryError {
+    BinaryError::RecoverableKnown(format!("No variant with index {}", index))
+}
+
+fn write_length(length: usize, format: Format, out: &mut Vec<u8>) {
+    match format {
+        Format::Bincode => out.extend_from_slice(&(length as u64).to_le_bytes()),
+        Format::Postcard => write_varint(length as u128, out),
+    }
+}
+
+fn read_length(format: Format, source: &mut &[u8]) -> Result<usize, BinaryError> {
+    let length = u64::read_format(format, source)?;
+    usize::try_from(length)
+        .map_err(|_| BinaryError::OutOfBounds(0, usize::MAX, "Length is too large."))
+}
+
+macro_rules! impl_migrate_uint {
+    ($ty: ty) => {
+        impl Migrate for $ty {
+            fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                match format {
+                    Format::Bincode => out.extend_from_slice(&self.to_le_bytes()),
+                    Format::Postcard => write_varint(*self as u128, out),
+                }
+                Ok(())
+            }
+
+            fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                match format {
+                    Format::Bincode => {
+                        let bytes = take(source, ::std::mem::size_of::<$ty>())?;
+                        Ok(<$ty>::from_le_bytes(bytes.try_into().unwrap()))
+                    }
+                    Format::Postcard => Ok(read_varint(source, <$ty>::BITS)? as $ty),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_migrate_sint {
+    ($ty: ty, $unsigned: ty) => {
+        impl Migrate for $ty {
+            fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                match format {
+                    Format::Bincode => out.extend_from_slice(&self.to_le_bytes()),
+                    Format::Postcard => {
+                        // zigzag, so small negative numbers stay small.
+                        let zigzag = ((self << 1) ^ (self >> (<$ty>::BITS - 1))) as $unsigned;
+                        write_varint(zigzag as u128, out);
+                    }
+                }
+                Ok(())
+            }
+
+            fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                match format {
+                    Format::Bincode => {
+                        let bytes = take(source, ::std::mem::size_of::<$ty>())?;
+                        Ok(<$ty>::from_le_bytes(bytes.try_into().unwrap()))
+                    }
+                    Format::Postcard => {
+                        let zigzag = read_varint(source, <$ty>::BITS)? as $unsigned;
+                        Ok((zigzag >> 1) as $ty ^ -((zigzag & 1) as $ty))
+                    }
+                }
+            }
+        }
+    };
+}
+
+// a single byte is written as is in both formats.
+macro_rules! impl_migrate_byte {
+    ($ty: ty) => {
+        impl Migrate for $ty {
+            fn write_format(&self, _: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                out.push(*self as u8);
+                Ok(())
+            }
+
+            fn read_format(_: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                Ok(take(source, 1)?[0] as $ty)
+            }
+        }
+    };
+}
+
+impl_migrate_byte!(u8);
+impl_migrate_byte!(i8);
+impl_migrate_uint!(u16);
+impl_migrate_uint!(u32);
+impl_migrate_uint!(u64);
+impl_migrate_uint!(u128);
+impl_migrate_sint!(i16, u16);
+impl_migrate_sint!(i32, u32);
+impl_migrate_sint!(i64, u64);
+impl_migrate_sint!(i128, u128);
+
+macro_rules! impl_migrate_float {
+    ($ty: ty) => {
+        impl Migrate for $ty {
+            fn write_format(&self, _: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                out.extend_from_slice(&self.to_le_bytes());
+                Ok(())
+            }
+
+            fn read_format(_: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                let bytes = take(source, ::std::mem::size_of::<$ty>())?;
+                Ok(<$ty>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    };
+}
+
+impl_migrate_float!(f32);
+impl_migrate_float!(f64);
+
+impl Migrate for bool {
+    fn write_format(&self, _: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        out.push(*self as u8);
+        Ok(())
+    }
+
+    fn read_format(_: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        match take(source, 1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(BinaryError::RecoverableKnown(format!(
+                "Invalid bool value: {}",
+                v
+            ))),
+        }
+    }
+}
+
+impl Migrate for String {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        write_length(self.len(), format, out);
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let length = read_length(format, source)?;
+        String::from_utf8(take(source, length)?.to_vec())
+            .map_err(|_| BinaryError::RecoverableKnown("String is not valid UTF-8".to_string()))
+    }
+}
+
+impl<T: Migrate> Migrate for Vec<T> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        write_length(self.len(), format, out);
+        for item in self {
+            item.write_format(format, out)?;
+        }
+        Ok(())
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let length = read_length(format, source)?;
+        (0..length)
+            .map(|_| T::read_format(format, source))
+            .collect()
+    }
+}
+
+// serde writes maps like a vector of key value pairs.
+macro_rules! impl_migrate_map {
+    ($map: ident, $($bound: path),+) => {
+        impl<K: Migrate $(+ $bound)+, V: Migrate> Migrate for $map<K, V> {
+            fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                write_length(self.len(), format, out);
+                for (key, value) in self {
+                    key.write_format(format, out)?;
+                    value.write_format(format, out)?;
+                }
+                Ok(())
+            }
+
+            fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                let length = read_length(format, source)?;
+                (0..length)
+                    .map(|_| Ok((K::read_format(format, source)?, V::read_format(format, source)?)))
+                    .collect()
+            }
+        }
+    };
+}
+
+impl_migrate_map!(HashMap, Eq, std::hash::Hash);
+impl_migrate_map!(BTreeMap, Ord);
+
+// serde writes arrays and tuples without a length.
+impl<T: Migrate, const N: usize> Migrate for [T; N] {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        for item in self {
+            item.write_format(format, out)?;
+        }
+        Ok(())
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let items = (0..N)
+            .map(|_| T::read_format(format, source))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(crate::collect_array(items))
+    }
+}
+
+macro_rules! impl_migrate_tuple {
+    ($($ty: ident $index: tt),+) => {
+        impl<$($ty: Migrate),+> Migrate for ($($ty,)+) {
+            fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                $(self.$index.write_format(format, out)?;)+
+                Ok(())
+            }
+
+            fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                Ok(($($ty::read_format(format, source)?,)+))
+            }
+        }
+    };
+}
+
+crate::for_each_tuple!(impl_migrate_tuple);
+
+impl<const N: usize> Migrate for FixedString<N> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        write_length(self.len(), format, out);
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        FixedString::new(String::read_format(format, source)?)
+    }
+}
+
+impl<T: ScaledInt, const NUM: i64, const DEN: i64> Migrate for Scaled<T, NUM, DEN> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.get().write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        f64::read_format(format, source).map(Scaled::new)
+    }
+}
+
+// wrappers are written as their inner value, like a serde newtype.
+macro_rules! impl_migrate_wrapper {
+    ($wrapper: ident < $ty: ty >) => {
+        impl Migrate for $wrapper<$ty> {
+            fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                self.0.write_format(format, out)
+            }
+
+            fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                Ok($wrapper(<$ty>::read_format(format, source)?))
+            }
+        }
+    };
+}
+
+impl_migrate_wrapper!(VarInt<u32>);
+impl_migrate_wrapper!(VarInt<i32>);
+impl_migrate_wrapper!(VarInt<u64>);
+impl_migrate_wrapper!(VarInt<i64>);
+impl_migrate_wrapper!(SVarInt<i32>);
+impl_migrate_wrapper!(SVarInt<i64>);
+
+impl Migrate for u24 {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let value = u32::read_format(format, source)?;
+        if value > 0x00FF_FFFF {
+            return Err(BinaryError::OutOfBounds(
+                value as usize,
+                0x00FF_FFFF,
+                "Value does not fit in a u24.",
+            ));
+        }
+        Ok(u24(value))
+    }
+}
+
+impl Migrate for i24 {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let value = i32::read_format(format, source)?;
+        i24::try_from(value)
+    }
+}
+
+impl<T: Migrate> Migrate for LE<T> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(LE(T::read_format(format, source)?))
+    }
+}
+
+impl<T: Migrate> Migrate for BE<T> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(BE(T::read_format(format, source)?))
+    }
+}
+
+impl<T: Streamable + Migrate> Migrate for Lazy<T> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.get()?.write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Lazy::new(T::read_format(format, source)?)
+    }
+}
+
+impl<T: Streamable + Migrate> Migrate for PassthroughOr<T> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        (**self).write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(PassthroughOr::new(T::read_format(format, source)?))
+    }
+}
+
+// written like a `Vec<(u32, Vec<u8>)>` of the records.
+impl Migrate for Extensions {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        write_length(self.len(), format, out);
+        for (id, bytes) in self.iter() {
+            id.write_format(format, out)?;
+            write_length(bytes.len(), format, out);
+            out.extend_from_slice(bytes);
+        }
+        Ok(())
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let mut extensions = Extensions::new();
+        for _ in 0..read_length(format, source)? {
+            let id = u32::read_format(format, source)?;
+            extensions.insert_raw(id, Vec::<u8>::read_format(format, source)?);
+        }
+        Ok(extensions)
+    }
+}
+
+// written like a `Vec<(u64, Vec<u8>)>` of the records.
+impl<I: TlvInt, L: TlvInt> Migrate for Tlv<I, L> {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        write_length(self.len(), format, out);
+        for (id, bytes) in self.iter() {
+            id.write_format(format, out)?;
+            write_length(bytes.len(), format, out);
+            out.extend_from_slice(bytes);
+        }
+        Ok(())
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let mut tlv = Tlv::new();
+        for _ in 0..read_length(format, source)? {
+            let id = u64::read_format(format, source)?;
+            tlv.insert_raw(id, Vec::<u8>::read_format(format, source)?);
+        }
+        Ok(tlv)
+    }
+}
+
+// ack ranges are written as a vector of `(start, end)` pairs.
+impl Migrate for AckRanges {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.pairs().write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        AckRanges::from_pairs(Vec::read_format(format, source)?)
+    }
+}
+
+// serde writes socket addresses as `enum { V4(octets, port), V6(octets, port) }`
+// in binary formats.
+#[cfg(feature = "net-addrs")]
+impl Migrate for SocketAddr {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        match self.ip() {
+            IpAddr::V4(ip) => {
+                write_variant(0, format, out);
+                out.extend_from_slice(&ip.octets());
+            }
+            IpAddr::V6(ip) => {
+                write_variant(1, format, out);
+                out.extend_from_slice(&ip.octets());
+            }
+        }
+        self.port().write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let ip = match read_variant(format, source)? {
+            0 => IpAddr::V4(Ipv4Addr::from(
+                <[u8; 4]>::try_from(take(source, 4)?).unwrap(),
+            )),
+            1 => IpAddr::V6(Ipv6Addr::from(
+                <[u8; 16]>::try_from(take(source, 16)?).unwrap(),
+            )),
+            index => return Err(unknown_variant(index)),
+        };
+        Ok(SocketAddr::new(ip, u16::read_format(format, source)?))
+    }
+}
+
+macro_rules! impl_migrate_address {
+    ($($wrapper: ident),*) => {
+        $(
+            #[cfg(feature = "net-addrs")]
+            impl Migrate for $wrapper {
+                fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                    self.0.write_format(format, out)
+                }
+
+                fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                    SocketAddr::read_format(format, source).map($wrapper)
+                }
+            }
+        )*
+    };
+}
+
+impl_migrate_address!(RakNetAddr, PlainAddr);
+
+// serde sees the `Vec<u8>` a payload replaces.
+impl Migrate for Payload {
+    fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.to_vec().write_format(format, out)
+    }
+
+    fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Vec::<u8>::read_format(format, source).map(Payload::from)
+    }
+}
+
+macro_rules! impl_migrate_atomic {
+    ($atomic: ty, $ty: ty) => {
+        impl Migrate for $atomic {
+            fn write_format(&self, format: Format, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                self.load(Ordering::SeqCst).write_format(format, out)
+            }
+
+            fn read_format(format: Format, source: &mut &[u8]) -> Result<Self, BinaryError> {
+                Ok(<$atomic>::new(<$ty>::read_format(format, source)?))
+            }
+        }
+    };
+}
+
+impl_migrate_atomic!(AtomicU32, u32);
+impl_migrate_atomic!(AtomicU64, u64);
+impl_migrate_atomic!(AtomicBool, bool);
@@ -0,0 +1,529 @@
+//! MessagePack encoding for `BinaryStream` types.
+//!
+//! With the `msgpack` feature the derive also implements `MsgPack`, so the same
+//! packet structs can be handed to tools that speak MessagePack. Structs are
+//! written as maps keyed by field name, unit enums as their `#[repr]` integer
+//! and enums with fields as an array of the discriminant followed by the fields.
+//! ```rust
+//! use binary_utils::{msgpack, BinaryStream, Streamable};
+//!
+//! #[derive(Debug, PartialEq, BinaryStream)]
+//! pub struct Ping {
+//!     pub id: u16,
+//!     pub label: String,
+//! }
+//!
+//! let ping = Ping { id: 1, label: "a".to_string() };
+//! let bytes = msgpack::to_vec(&ping).unwrap();
+//! assert_eq!(bytes, vec![0x82, 0xA2, b'i', b'd', 1, 0xA5, b'l', b'a', b'b', b'e', b'l', 0xA1, b'a']);
+//! assert_eq!(msgpack::from_slice::<Ping>(&bytes).unwrap(), ping);
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+#[cfg(feature = "net-addrs")]
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use rmp::{decode, encode};
+
+use crate::delivery::AckRanges;
+use crate::error::BinaryError;
+use crate::extensions::Extensions;
+use crate::tlv::{Tlv, TlvInt};
+use crate::{
+    i24, u24, FixedString, Lazy, PassthroughOr, Payload, SVarInt, Scaled, ScaledInt, Streamable,
+    VarInt, BE, LE,
+};
+#[cfg(feature = "net-addrs")]
+use crate::{PlainAddr, RakNetAddr};
+
+/// A type that can be written to and read from MessagePack.
+pub trait MsgPack: Sized {
+    /// Appends the MessagePack form of `self` to `out`.
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError>;
+
+    /// Reads a value from the front of `source`, advancing it past the value.
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError>;
+}
+
+/// Writes `value` to a new MessagePack buffer.
+pub fn to_vec<T: MsgPack>(value: &T) -> Result<Vec<u8>, BinaryError> {
+    let mut out = Vec::new();
+    value.to_msgpack(&mut out)?;
+    Ok(out)
+}
+
+/// Reads a `T` from a MessagePack buffer.
+pub fn from_slice<T: MsgPack>(mut source: &[u8]) -> Result<T, BinaryError> {
+    T::from_msgpack(&mut source)
+}
+
+fn error(error: impl Display) -> BinaryError {
+    BinaryError::RecoverableKnown(format!("MessagePack: {}", error))
+}
+
+#[doc(hidden)]
+pub fn write_map_len(out: &mut Vec<u8>, len: u32) -> Result<(), BinaryError> {
+    encode::write_map_len(out, len).map_err(error)?;
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn read_map_len(source: &mut &[u8]) -> Result<u32, BinaryError> {
+    decode::read_map_len(source).map_err(error)
+}
+
+#[doc(hidden)]
+pub fn write_array_len(out: &mut Vec<u8>, len: u32) -> Result<(), BinaryError> {
+    encode::write_array_len(out, len).map_err(error)?;
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn read_array_len(source: &mut &[u8]) -> Result<u32, BinaryError> {
+    decode::read_array_len(source).map_err(error)
+}
+
+#[doc(hidden)]
+pub fn write_key(out: &mut Vec<u8>, key: &str) -> Result<(), BinaryError> {
+    encode::write_str(out, key).map_err(error)
+}
+
+#[doc(hidden)]
+pub fn read_key(source: &mut &[u8]) -> Result<String, BinaryError> {
+    String::from_msgpack(source)
+}
+
+#[doc(hidden)]
+pub fn unknown_field(name: &str) -> BinaryError {
+    error(format_args!("unknown field `{}`", name))
+}
+
+#[doc(hidden)]
+pub fn missing_field(name: &str) -> BinaryError {
+    error(format_args!("missing field `{}`", name))
+}
+
+#[doc(hidden)]
+pub fn unknown_variant(value: impl Display) -> BinaryError {
+    error(format_args!("no variant for discriminant {}", value))
+}
+
+fn read_bin(source: &mut &[u8]) -> Result<Vec<u8>, BinaryError> {
+    let length = decode::read_bin_len(source).map_err(error)? as usize;
+    if source.len() < length {
+        return Err(BinaryError::EOF(source.len()));
+    }
+    let (bytes, rest) = source.split_at(length);
+    *source = rest;
+    Ok(bytes.to_vec())
+}
+
+macro_rules! impl_msgpack_uint {
+    ($ty: ty) => {
+        impl MsgPack for $ty {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                let value = u64::try_from(*self).map_err(error)?;
+                encode::write_uint(out, value).map_err(error)?;
+                Ok(())
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                decode::read_int(source).map_err(error)
+            }
+        }
+    };
+}
+
+macro_rules! impl_msgpack_sint {
+    ($ty: ty) => {
+        impl MsgPack for $ty {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                let value = i64::try_from(*self).map_err(error)?;
+                encode::write_sint(out, value).map_err(error)?;
+                Ok(())
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                decode::read_int(source).map_err(error)
+            }
+        }
+    };
+}
+
+impl_msgpack_uint!(u8);
+impl_msgpack_uint!(u16);
+impl_msgpack_uint!(u32);
+impl_msgpack_uint!(u64);
+impl_msgpack_uint!(u128);
+impl_msgpack_sint!(i8);
+impl_msgpack_sint!(i16);
+impl_msgpack_sint!(i32);
+impl_msgpack_sint!(i64);
+impl_msgpack_sint!(i128);
+
+impl MsgPack for f32 {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        encode::write_f32(out, *self).map_err(error)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        decode::read_f32(source).map_err(error)
+    }
+}
+
+impl MsgPack for f64 {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        encode::write_f64(out, *self).map_err(error)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        decode::read_f64(source).map_err(error)
+    }
+}
+
+impl MsgPack for bool {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        encode::write_bool(out, *self).map_err(error)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        decode::read_bool(source).map_err(error)
+    }
+}
+
+impl MsgPack for String {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        encode::write_str(out, self).map_err(error)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let length = decode::read_str_len(source).map_err(error)? as usize;
+        if source.len() < length {
+            return Err(BinaryError::EOF(source.len()));
+        }
+        let (bytes, rest) = source.split_at(length);
+        *source = rest;
+        String::from_utf8(bytes.to_vec()).map_err(error)
+    }
+}
+
+impl<const N: usize> MsgPack for FixedString<N> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        encode::write_str(out, self).map_err(error)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        FixedString::new(String::from_msgpack(source)?)
+    }
+}
+
+// the real value, as the scale is only a detail of the binary wire.
+impl<T: ScaledInt, const NUM: i64, const DEN: i64> MsgPack for Scaled<T, NUM, DEN> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.get().to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        f64::from_msgpack(source).map(Scaled::new)
+    }
+}
+
+impl<T: MsgPack> MsgPack for Vec<T> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        let length = u32::try_from(self.len()).map_err(error)?;
+        encode::write_array_len(out, length).map_err(error)?;
+        for item in self {
+            item.to_msgpack(out)?;
+        }
+        Ok(())
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let length = decode::read_array_len(source).map_err(error)?;
+        (0..length).map(|_| T::from_msgpack(source)).collect()
+    }
+}
+
+impl<T: MsgPack, const N: usize> MsgPack for [T; N] {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        let length = u32::try_from(N).map_err(error)?;
+        encode::write_array_len(out, length).map_err(error)?;
+        for item in self {
+            item.to_msgpack(out)?;
+        }
+        Ok(())
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let length = decode::read_array_len(source).map_err(error)?;
+        if length as usize != N {
+            return Err(error(format_args!(
+                "expected an array of {} items, found {}",
+                N, length
+            )));
+        }
+        let items = (0..N)
+            .map(|_| T::from_msgpack(source))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(crate::collect_array(items))
+    }
+}
+
+macro_rules! impl_msgpack_map {
+    ($map: ident, $($bound: path),+) => {
+        impl<K: MsgPack $(+ $bound)+, V: MsgPack> MsgPack for $map<K, V> {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                let length = u32::try_from(self.len()).map_err(error)?;
+                encode::write_map_len(out, length).map_err(error)?;
+                for (key, value) in self {
+                    key.to_msgpack(out)?;
+                    value.to_msgpack(out)?;
+                }
+                Ok(())
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                let length = decode::read_map_len(source).map_err(error)?;
+                (0..length)
+                    .map(|_| Ok((K::from_msgpack(source)?, V::from_msgpack(source)?)))
+                    .collect()
+            }
+        }
+    };
+}
+
+impl_msgpack_map!(HashMap, Eq, std::hash::Hash);
+impl_msgpack_map!(BTreeMap, Ord);
+
+// tuples are written as an array of their items, as rmp-serde does.
+macro_rules! impl_msgpack_tuple {
+    ($($ty: ident $index: tt),+) => {
+        impl<$($ty: MsgPack),+> MsgPack for ($($ty,)+) {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                encode::write_array_len(out, [$($index),+].len() as u32).map_err(error)?;
+                $(self.$index.to_msgpack(out)?;)+
+                Ok(())
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                let expected = [$($index),+].len();
+                let length = decode::read_array_len(source).map_err(error)?;
+                if length as usize != expected {
+                    return Err(error(format_args!(
+                        "expected an array of {} items, found {}",
+                        expected, length
+                    )));
+                }
+                Ok(($($ty::from_msgpack(source)?,)+))
+            }
+        }
+    };
+}
+
+crate::for_each_tuple!(impl_msgpack_tuple);
+
+macro_rules! impl_msgpack_wrapper {
+    ($wrapper: ident < $ty: ty >) => {
+        impl MsgPack for $wrapper<$ty> {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                self.0.to_msgpack(out)
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                Ok($wrapper(<$ty>::from_msgpack(source)?))
+            }
+        }
+    };
+}
+
+impl_msgpack_wrapper!(VarInt<u32>);
+impl_msgpack_wrapper!(VarInt<i32>);
+impl_msgpack_wrapper!(VarInt<u64>);
+impl_msgpack_wrapper!(VarInt<i64>);
+impl_msgpack_wrapper!(SVarInt<i32>);
+impl_msgpack_wrapper!(SVarInt<i64>);
+
+impl MsgPack for u24 {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let value = u32::from_msgpack(source)?;
+        if value > 0x00FF_FFFF {
+            return Err(error(format_args!("{} does not fit in a u24", value)));
+        }
+        Ok(u24(value))
+    }
+}
+
+impl MsgPack for i24 {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let value = i32::from_msgpack(source)?;
+        if !i24::is_i24(value as i64) {
+            return Err(error(format_args!("{} does not fit in an i24", value)));
+        }
+        Ok(i24(value))
+    }
+}
+
+// byte order only matters on the binary wire, so both wrappers write their inner value.
+impl<T: MsgPack> MsgPack for LE<T> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(LE(T::from_msgpack(source)?))
+    }
+}
+
+impl<T: MsgPack> MsgPack for BE<T> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.0.to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(BE(T::from_msgpack(source)?))
+    }
+}
+
+// the same as the `Vec<u8>` a payload replaces.
+impl MsgPack for Payload {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.to_vec().to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Vec::<u8>::from_msgpack(source).map(Payload::from)
+    }
+}
+
+#[cfg(feature = "net-addrs")]
+impl MsgPack for SocketAddr {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.to_string().to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        str::parse(&String::from_msgpack(source)?).map_err(error)
+    }
+}
+
+macro_rules! impl_msgpack_address {
+    ($($wrapper: ident),*) => {
+        $(
+            #[cfg(feature = "net-addrs")]
+            impl MsgPack for $wrapper {
+                fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                    self.0.to_msgpack(out)
+                }
+
+                fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                    SocketAddr::from_msgpack(source).map($wrapper)
+                }
+            }
+        )*
+    };
+}
+
+impl_msgpack_address!(RakNetAddr, PlainAddr);
+
+// the raw wire bytes of a lazy field aren't meaningful to MessagePack readers,
+// so the decoded value is written instead.
+impl<T: Streamable + MsgPack> MsgPack for Lazy<T> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.get()?.to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Lazy::new(T::from_msgpack(source)?)
+    }
+}
+
+impl<T: Streamable + MsgPack> MsgPack for PassthroughOr<T> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        (**self).to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(PassthroughOr::new(T::from_msgpack(source)?))
+    }
+}
+
+// records are written as a map from id to binary.
+impl MsgPack for Extensions {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        let length = u32::try_from(self.len()).map_err(error)?;
+        encode::write_map_len(out, length).map_err(error)?;
+        for (id, bytes) in self.iter() {
+            id.to_msgpack(out)?;
+            encode::write_bin(out, bytes).map_err(error)?;
+        }
+        Ok(())
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let mut extensions = Extensions::new();
+        for _ in 0..decode::read_map_len(source).map_err(error)? {
+            let id = u32::from_msgpack(source)?;
+            extensions.insert_raw(id, read_bin(source)?);
+        }
+        Ok(extensions)
+    }
+}
+
+impl<I: TlvInt, L: TlvInt> MsgPack for Tlv<I, L> {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        let length = u32::try_from(self.len()).map_err(error)?;
+        encode::write_map_len(out, length).map_err(error)?;
+        for (id, bytes) in self.iter() {
+            id.to_msgpack(out)?;
+            encode::write_bin(out, bytes).map_err(error)?;
+        }
+        Ok(())
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        let mut tlv = Tlv::new();
+        for _ in 0..decode::read_map_len(source).map_err(error)? {
+            let id = u64::from_msgpack(source)?;
+            tlv.insert_raw(id, read_bin(source)?);
+        }
+        Ok(tlv)
+    }
+}
+
+// ack ranges are written as an array of `[start, end]` pairs.
+impl MsgPack for AckRanges {
+    fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.pairs().to_msgpack(out)
+    }
+
+    fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        AckRanges::from_pairs(Vec::from_msgpack(source)?)
+    }
+}
+
+macro_rules! impl_msgpack_atomic {
+    ($atomic: ty, $ty: ty) => {
+        impl MsgPack for $atomic {
+            fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+                self.load(Ordering::SeqCst).to_msgpack(out)
+            }
+
+            fn from_msgpack(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                Ok(<$atomic>::new(<$ty>::from_msgpack(source)?))
+            }
+        }
+    };
+}
+
+impl_msgpack_atomic!(AtomicU32, u32);
+impl_msgpack_atomic!(AtomicU64, u64);
+impl_msgpack_atomic!(AtomicBool, bool);
@@ -0,0 +1,149 @@
+/// Declares an enum with one variant per packet, prefixed with the packet id on the wire.
+///
+/// The generated enum implements `Streamable`, writing the id of the variant
+/// followed by the packet, `From` for every packet type, and an `id()`
+/// accessor. Ids are written as `VarInt<u32>` unless another id type is
+/// given after the name; any `TlvInt` type can be used.
+#[cfg_attr(feature = "derive", doc = "```rust")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use binary_utils::{packet_enum, BinaryStream, Streamable};
+///
+/// #[derive(Debug, PartialEq, BinaryStream)]
+/// pub struct Login {
+///     pub protocol: u32,
+/// }
+///
+/// #[derive(Debug, PartialEq, BinaryStream)]
+/// pub struct Disconnect {
+///     pub reason: String,
+/// }
+///
+/// packet_enum! {
+///     #[derive(Debug, PartialEq)]
+///     pub ClientBound: u8 {
+///         0x01 => Login(Login),
+///         0x05 => Disconnect(Disconnect),
+///     }
+/// }
+///
+/// let packet = ClientBound::from(Login { protocol: 589 });
+/// assert_eq!(packet.id(), 0x01);
+///
+/// let bytes = packet.parse().unwrap();
+/// assert_eq!(bytes, vec![0x01, 0, 0, 2, 77]);
+/// assert_eq!(ClientBound::compose(&bytes, &mut 0).unwrap(), packet);
+/// ```
+#[macro_export]
+macro_rules! packet_enum {
+    (
+        $(#[$meta: meta])*
+        $vis: vis $name: ident {
+            $($id: literal => $variant: ident($packet: ty)),* $(,)?
+        }
+    ) => {
+        $crate::packet_enum! {
+            $(#[$meta])*
+            $vis $name: $crate::VarInt<u32> {
+                $($id => $variant($packet)),*
+            }
+        }
+    };
+    (
+        $(#[$meta: meta])*
+        $vis: vis $name: ident: $id_ty: ty {
+            $($id: literal => $variant: ident($packet: ty)),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant($packet)),*
+        }
+
+        impl $name {
+            /// The packet id of this variant.
+            pub fn id(&self) -> u32 {
+                match self {
+                    $(Self::$variant(_) => $id),*
+                }
+            }
+        }
+
+        impl $crate::Streamable for $name {
+            fn parse(&self) -> Result<Vec<u8>, $crate::error::BinaryError> {
+                let id = <$id_ty as $crate::tlv::TlvInt>::from_u64(self.id() as u64)?;
+                let mut out = $crate::Streamable::parse(&id)?;
+                match self {
+                    $(Self::$variant(packet) => {
+                        out.extend_from_slice(&$crate::Streamable::parse(packet)?)
+                    }),*
+                }
+                Ok(out)
+            }
+
+            fn compose(
+                source: &[u8],
+                position: &mut usize,
+            ) -> Result<Self, $crate::error::BinaryError> {
+                let id = <$id_ty as $crate::Streamable>::compose(source, position)?;
+                let id = $crate::tlv::TlvInt::to_u64(&id);
+                match id {
+                    $($id => Ok(Self::$variant(
+                        <$packet as $crate::Streamable>::compose(source, position)?
+                    )),)*
+                    _ => Err($crate::error::BinaryError::RecoverableKnown(format!(
+                        "Unknown {} packet id: {}",
+                        stringify!($name),
+                        id
+                    ))),
+                }
+            }
+
+            fn validate(
+                source: &[u8],
+                position: &mut usize,
+            ) -> Result<(), $crate::error::BinaryError> {
+                let id = <$id_ty as $crate::Streamable>::compose(source, position)?;
+                let id = $crate::tlv::TlvInt::to_u64(&id);
+                match id {
+                    $($id => <$packet as $crate::Streamable>::validate(source, position),)*
+                    _ => Err($crate::error::BinaryError::RecoverableKnown(format!(
+                        "Unknown {} packet id: {}",
+                        stringify!($name),
+                        id
+                    ))),
+                }
+            }
+        }
+
+        impl $crate::schema::Schema for $name
+        where
+            $(for<'__binary> $packet: $crate::schema::Schema,)*
+        {
+            fn describe(out: &mut String) {
+                let variants: &[(&str, fn(&mut String))] = &[
+                    $((stringify!($id), <$packet as $crate::schema::Schema>::describe)),*
+                ];
+                out.push_str("packets<");
+                <$id_ty as $crate::schema::Schema>::describe(out);
+                out.push_str(">{");
+                for (i, (id, describe)) in variants.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(id);
+                    out.push(':');
+                    describe(out);
+                }
+                out.push('}');
+            }
+        }
+
+        $(
+            impl From<$packet> for $name {
+                fn from(packet: $packet) -> Self {
+                    Self::$variant(packet)
+                }
+            }
+        )*
+    };
+}
@@ -0,0 +1,46 @@
+//! Two step decoding, header first.
+//!
+//! Routers usually only need the id or flags of a frame to decide where it
+//! goes. Deriving `BinaryStream` with leading fields marked `#[binary(header)]`
+//! generates a `{Name}Header` struct and implements `PartialDecode`, so those
+//! fields can be read without decoding the entire payload.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::{partial::PartialDecode, *};
+//!
+//! #[derive(BinaryStream)]
+//! pub struct Frame {
+//!     #[binary(header)]
+//!     pub id: u8,
+//!     #[binary(header)]
+//!     pub flags: u8,
+//!     pub payload: String,
+//! }
+//!
+//! let bytes = Frame { id: 3, flags: 1, payload: "data".into() }.parse().unwrap();
+//! let mut position = 0;
+//! let header = Frame::decode_header(&bytes, &mut position).unwrap();
+//! assert_eq!(header.id, 3);
+//!
+//! // later, if the frame is wanted after all:
+//! let frame = Frame::decode_rest(header, &bytes, &mut position).unwrap();
+//! assert_eq!(frame.payload, "data");
+//! ```
+use crate::error::BinaryError;
+use crate::Streamable;
+
+/// A type whose leading fields can be decoded on their own.
+pub trait PartialDecode: Streamable + Sized {
+    /// The leading fields of the type.
+    type Header;
+
+    /// Reads only the header, leaving `position` at the start of the remaining fields.
+    fn decode_header(source: &[u8], position: &mut usize) -> Result<Self::Header, BinaryError>;
+
+    /// Reads the remaining fields, completing a header read by `decode_header`.
+    fn decode_rest(
+        header: Self::Header,
+        source: &[u8],
+        position: &mut usize,
+    ) -> Result<Self, BinaryError>;
+}
@@ -0,0 +1,98 @@
+use core::fmt;
+use core::ops::Deref;
+
+use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::Streamable;
+
+/// A decoded value that is written back as the exact bytes it was read from
+/// unless it has been mutated.
+///
+/// Proxies usually inspect far more packets than they change. Re-encoding an
+/// untouched packet could normalize it (e.g. an over-long varint), so a
+/// `PassthroughOr` only encodes its value again once `get_mut` was called.
+/// ```rust
+/// use binary_utils::{PassthroughOr, Streamable, VarInt};
+///
+/// // 1 written as an over-long varint.
+/// let bytes = vec![0x81, 0x00];
+/// let mut value = PassthroughOr::<VarInt<u32>>::compose(&bytes, &mut 0).unwrap();
+/// assert_eq!(value.0, 1);
+/// assert_eq!(value.parse().unwrap(), bytes);
+///
+/// value.get_mut().0 = 2;
+/// assert_eq!(value.parse().unwrap(), vec![0x02]);
+/// ```
+pub struct PassthroughOr<T> {
+    value: T,
+    raw: Option<Vec<u8>>,
+}
+
+impl<T: Streamable> PassthroughOr<T> {
+    /// Wraps a value that has no original bytes, so it is always encoded.
+    pub fn new(value: T) -> Self {
+        Self { value, raw: None }
+    }
+
+    /// The bytes the value was composed from, if it hasn't been mutated since.
+    pub fn raw(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
+
+    /// Whether the value will be encoded again instead of written as its original bytes.
+    pub fn is_modified(&self) -> bool {
+        self.raw.is_none()
+    }
+
+    /// Mutable access to the value, discarding the original bytes.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.raw = None;
+        &mut self.value
+    }
+
+    /// Takes the value out of the wrapper.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Streamable> Streamable for PassthroughOr<T> {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        match &self.raw {
+            Some(raw) => Ok(raw.clone()),
+            None => self.value.parse(),
+        }
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let start = *position;
+        let value = T::compose(source, position)?;
+        Ok(Self {
+            value,
+            raw: Some(source[start..*position].to_vec()),
+        })
+    }
+}
+
+impl<T> Deref for PassthroughOr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Clone> Clone for PassthroughOr<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            raw: self.raw.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PassthroughOr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PassthroughOr").field(&self.value).finish()
+    }
+}
@@ -0,0 +1,136 @@
+use alloc_crate::sync::Arc;
+use core::fmt;
+use core::ops::Deref;
+
+use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::profile::WireProfile;
+use crate::{bytes_at, skip, Streamable};
+
+/// A reference counted, copy-on-write byte buffer.
+///
+/// `Payload` is written like a `Vec<u8>`, prefixed with the profile's
+/// `vec_prefix`, so it can replace one in a packet without changing the wire
+/// format. Cloning only bumps a count, so a packet holding a large payload
+/// can be handed to many threads or tasks without copying it; the bytes are
+/// only copied when a shared payload is mutated.
+/// ```rust
+/// use binary_utils::{Payload, Streamable};
+///
+/// let payload = Payload::from(vec![1, 2, 3]);
+/// assert_eq!(payload.parse().unwrap(), vec![1u8, 2, 3].parse().unwrap());
+///
+/// let mut copy = payload.clone();
+/// assert!(payload.ptr_eq(&copy));
+/// copy.make_mut()[0] = 9;
+/// assert_eq!(&payload[..], &[1, 2, 3]);
+/// assert_eq!(&copy[..], &[9, 2, 3]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Payload(Arc<[u8]>);
+
+impl Payload {
+    /// Wraps `bytes`, which is shared by every clone of this payload.
+    pub fn new(bytes: impl Into<Arc<[u8]>>) -> Self {
+        Self(bytes.into())
+    }
+
+    /// The bytes of this payload.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The bytes of this payload for writing, copying them first if any
+    /// other clone still shares them.
+    pub fn make_mut(&mut self) -> &mut [u8] {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Whether `self` and `other` share the same buffer.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for Payload {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl Deref for Payload {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for Payload {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for Payload {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<&[u8]> for Payload {
+    fn from(bytes: &[u8]) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<Arc<[u8]>> for Payload {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Payload> for Vec<u8> {
+    fn from(payload: Payload) -> Self {
+        payload.0.to_vec()
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Payload").field(&self.as_slice()).finish()
+    }
+}
+
+impl Streamable for Payload {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut out = crate::alloc::allocate(self.size_hint().unwrap_or(0));
+        self.parse_into(&mut out)?;
+        Ok(out)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        WireProfile::write_length(WireProfile::current().vec_prefix, self.len(), out)?;
+        out.extend_from_slice(self);
+        Ok(())
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let mut start = *position;
+        let length =
+            WireProfile::read_length(WireProfile::current().vec_prefix, source, &mut start)?;
+        let bytes = bytes_at(source, start, length)?;
+        *position = start + length;
+        Ok(Self::from(bytes))
+    }
+
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        let length = WireProfile::read_length(WireProfile::current().vec_prefix, source, position)?;
+        skip(source, position, length)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        let prefix = WireProfile::current().vec_prefix;
+        Some(WireProfile::length_size(prefix, self.len()) + self.len())
+    }
+}
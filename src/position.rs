@@ -0,0 +1,123 @@
+//! A typed read offset, for decoders that juggle positions by hand.
+//!
+//! `Streamable::compose` takes the offset as a plain `&mut usize`, which is
+//! easy to copy, compare against the wrong thing, or move backwards by
+//! accident, e.g. by storing a position before the current field and
+//! assigning it back afterwards. A `Position` can't be copied and only moves
+//! forward, except to a `Checkpoint` it handed out itself, so "where was I"
+//! is always an explicit checkpoint rather than a stale `usize`.
+//!
+//! `Streamable::compose_from` reads at a `Position`. Impls still write
+//! `compose` against the plain offset, so existing ones keep working.
+//! ```rust
+//! use binary_utils::position::Position;
+//!
+//! let source = [0, 2, b'h', b'i', 0xff];
+//! let mut position = Position::new();
+//! let start = position.checkpoint();
+//!
+//! let name: String = position.read(&source).unwrap();
+//! assert_eq!(name, "hi");
+//! assert_eq!(position.since(start), 4);
+//! assert_eq!(position.remaining(source.len()), 1);
+//!
+//! position.rewind(start);
+//! assert_eq!(position.get(), 0);
+//! ```
+use crate::error::BinaryError;
+use crate::Streamable;
+
+/// An offset into a source that only moves forward.
+///
+/// It is deliberately not `Copy` or `Clone`; take a `checkpoint` to come back to.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Position(usize);
+
+/// An offset a `Position` was at, to measure from or `rewind` to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checkpoint(usize);
+
+impl Checkpoint {
+    /// The offset the position was at.
+    pub fn offset(self) -> usize {
+        self.0
+    }
+}
+
+impl Position {
+    /// A position at the start of a source.
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    /// A position at `offset`, e.g. past a header read some other way.
+    pub fn at(offset: usize) -> Self {
+        Self(offset)
+    }
+
+    /// The current offset.
+    pub fn get(&self) -> usize {
+        self.0
+    }
+
+    /// Moves past `amount` bytes.
+    pub fn advance(&mut self, amount: usize) {
+        self.0 += amount;
+    }
+
+    /// The bytes left in a source of `len` bytes, `0` once past its end.
+    pub fn remaining(&self, len: usize) -> usize {
+        len.saturating_sub(self.0)
+    }
+
+    /// Marks the current offset, to measure from or `rewind` to later.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.0)
+    }
+
+    /// The bytes moved past since `checkpoint` was taken.
+    pub fn since(&self, checkpoint: Checkpoint) -> usize {
+        debug_assert!(
+            checkpoint.0 <= self.0,
+            "checkpoint {} is ahead of position {}",
+            checkpoint.0,
+            self.0
+        );
+        self.0 - checkpoint.0
+    }
+
+    /// Moves back to `checkpoint`, e.g. to retry a read as another type.
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        debug_assert!(
+            checkpoint.0 <= self.0,
+            "rewinding forward from {} to {}",
+            self.0,
+            checkpoint.0
+        );
+        self.0 = checkpoint.0;
+    }
+
+    /// The bytes of `source` from the current offset on.
+    pub fn rest<'a>(&self, source: &'a [u8]) -> &'a [u8] {
+        source.get(self.0..).unwrap_or(&[])
+    }
+
+    /// Composes a `T` from `source` at the current offset, moving past it.
+    pub fn read<T: Streamable>(&mut self, source: &[u8]) -> Result<T, BinaryError> {
+        T::compose_from(source, self)
+    }
+
+    /// Runs `f` with the offset as a plain `usize`, for `compose` style
+    /// functions, checking that `f` didn't move it backwards.
+    pub fn with_offset<R>(&mut self, f: impl FnOnce(&mut usize) -> R) -> R {
+        let before = self.0;
+        let result = f(&mut self.0);
+        debug_assert!(
+            self.0 >= before,
+            "position moved backwards from {} to {}",
+            before,
+            self.0
+        );
+        result
+    }
+}
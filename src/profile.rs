@@ -0,0 +1,404 @@
+//! Wire profiles describe the "dialect" a protocol is encoded in.
+//!
+//! The same packet structs can serve two dialects of a protocol by encoding
+//! and decoding them under different profiles, instead of duplicating types.
+use alloc_crate::borrow::Cow;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+
+use crate::error::BinaryError;
+use crate::framing::LengthPrefix;
+use crate::prelude::*;
+use crate::{Streamable, VarInt};
+
+/// The byte order numeric primitives are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+/// How bools are read from the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoolMode {
+    /// Only `0` and `1` are valid, anything else is an error.
+    Strict,
+    /// `0` is `false`, anything else is `true`.
+    Lenient,
+}
+
+/// What happens when a decoded map holds the same key more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// The last value read for a key is kept.
+    LastWins,
+    /// Decoding fails with `BinaryError::DuplicateKey`.
+    Error,
+}
+
+/// How strings holding invalid UTF-8 are read from the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Invalid UTF-8 is an error.
+    Strict,
+    /// Invalid sequences are replaced with `U+FFFD`.
+    Lossy,
+    /// Each invalid byte `b` is kept as the private use character
+    /// `U+10FF00 + b`, which is written back as `b`, so the string is
+    /// written with the bytes it was read from.
+    ///
+    /// Real characters in `U+10FF80..=U+10FFFF` can't be told apart from
+    /// preserved bytes, so under this policy they are written as the raw
+    /// byte too. Write strings that may hold them under `Strict` or `Lossy`.
+    Preserve,
+}
+
+/// The first character `Utf8Policy::Preserve` keeps invalid bytes as.
+const PRESERVED: u32 = 0x10FF00;
+
+impl Utf8Policy {
+    /// Turns bytes read from the wire into a `String`.
+    pub(crate) fn decode(self, bytes: Vec<u8>) -> Result<String, BinaryError> {
+        let error = match String::from_utf8(bytes) {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        match self {
+            Self::Strict => Err(BinaryError::RecoverableKnown(format!(
+                "String is not valid UTF-8: {}",
+                error.utf8_error()
+            ))),
+            Self::Lossy => Ok(String::from_utf8_lossy(error.as_bytes()).into_owned()),
+            Self::Preserve => {
+                let mut value = String::with_capacity(error.as_bytes().len());
+                for chunk in error.as_bytes().utf8_chunks() {
+                    value.push_str(chunk.valid());
+                    value.extend(
+                        chunk
+                            .invalid()
+                            .iter()
+                            .filter_map(|byte| char::from_u32(PRESERVED + *byte as u32)),
+                    );
+                }
+                Ok(value)
+            }
+        }
+    }
+
+    /// The bytes `value` is written as.
+    pub(crate) fn encode(self, value: &str) -> Cow<'_, [u8]> {
+        let preserved = |c: char| (PRESERVED + 0x80..=PRESERVED + 0xFF).contains(&(c as u32));
+        if self != Self::Preserve || !value.chars().any(preserved) {
+            return Cow::Borrowed(value.as_bytes());
+        }
+
+        let mut out = Vec::with_capacity(value.len());
+        for c in value.chars() {
+            if preserved(c) {
+                out.push((c as u32 - PRESERVED) as u8);
+            } else {
+                out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+        }
+        Cow::Owned(out)
+    }
+}
+
+/// A set of wire conventions used by the `Streamable` implementations in this crate.
+///
+/// Every thread has a current profile (`WireProfile::DEFAULT` unless changed),
+/// which can be replaced with `set_thread_default` or temporarily overridden
+/// with `scope`, `encode` and `decode`. Without the `std` feature there are no
+/// thread locals, so one profile is shared by the whole program.
+/// ```rust
+/// use binary_utils::profile::{Endian, WireProfile};
+///
+/// let little = WireProfile {
+///     default_endian: Endian::Little,
+///     ..WireProfile::DEFAULT
+/// };
+/// assert_eq!(little.encode(&1u16).unwrap(), vec![1, 0]);
+/// assert_eq!(WireProfile::DEFAULT.encode(&1u16).unwrap(), vec![0, 1]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireProfile {
+    /// The byte order of numeric primitives (and fixed width length prefixes).
+    pub default_endian: Endian,
+    /// The length prefix written before strings.
+    pub string_prefix: LengthPrefix,
+    /// The length prefix written before vectors.
+    pub vec_prefix: LengthPrefix,
+    /// How bools are read.
+    pub bool_mode: BoolMode,
+    /// Whether values are written in their single canonical form.
+    ///
+    /// Under a canonical profile NaN floats are written as the quiet NaN
+    /// (`0x7FC00000` / `0x7FF8000000000000`) and over-long varints are
+    /// rejected when reading, so equal values always have equal bytes.
+    pub canonical: bool,
+    /// The ordering atomics are loaded with when they are written.
+    /// `Release` loads as `Relaxed` and `AcqRel` as `Acquire`.
+    pub atomic_ordering: Ordering,
+    /// How keyed collections (e.g. `Tlv` records) treat repeated keys when read.
+    pub duplicate_keys: DuplicateKeys,
+    /// How strings holding invalid UTF-8 are read, and preserved ones written.
+    pub utf8_policy: Utf8Policy,
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static CURRENT: Cell<WireProfile> = const { Cell::new(WireProfile::DEFAULT) };
+}
+
+#[cfg(feature = "std")]
+fn load() -> WireProfile {
+    CURRENT.with(|current| current.get())
+}
+
+#[cfg(feature = "std")]
+fn store(profile: WireProfile) {
+    CURRENT.with(|current| current.set(profile));
+}
+
+/// The program wide profile, packed by `WireProfile::to_bits`.
+#[cfg(not(feature = "std"))]
+static CURRENT: AtomicU32 = AtomicU32::new(WireProfile::DEFAULT.to_bits());
+
+#[cfg(not(feature = "std"))]
+fn load() -> WireProfile {
+    WireProfile::from_bits(CURRENT.load(Ordering::Relaxed))
+}
+
+#[cfg(not(feature = "std"))]
+fn store(profile: WireProfile) {
+    CURRENT.store(profile.to_bits(), Ordering::Relaxed);
+}
+
+/// Restores the previous profile when dropped, even if the scope panicked.
+struct ScopeGuard(WireProfile);
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        store(self.0);
+    }
+}
+
+impl WireProfile {
+    /// The conventions this crate has always used:
+    /// Big Endian, `u16` sized strings, VarInt sized vectors and strict bools.
+    /// Strings must be valid UTF-8.
+    pub const DEFAULT: Self = Self {
+        default_endian: Endian::Big,
+        string_prefix: LengthPrefix::U16,
+        vec_prefix: LengthPrefix::VarInt,
+        bool_mode: BoolMode::Strict,
+        canonical: false,
+        atomic_ordering: Ordering::SeqCst,
+        duplicate_keys: DuplicateKeys::LastWins,
+        utf8_policy: Utf8Policy::Strict,
+    };
+
+    /// `DEFAULT` in canonical mode, for bytes that are signed or hashed.
+    /// ```rust
+    /// use binary_utils::profile::WireProfile;
+    ///
+    /// let nan = f32::from_bits(0x7FC0_0001);
+    /// assert_eq!(WireProfile::CANONICAL.encode(&nan).unwrap(), vec![0x7F, 0xC0, 0, 0]);
+    /// ```
+    pub const CANONICAL: Self = Self {
+        canonical: true,
+        ..Self::DEFAULT
+    };
+
+    /// The profile currently in effect on this thread.
+    pub fn current() -> Self {
+        load()
+    }
+
+    /// Replaces the profile used by this thread from now on.
+    pub fn set_thread_default(profile: Self) {
+        store(profile);
+    }
+
+    /// A copy of this profile with a different default endianness.
+    pub fn with_endian(self, default_endian: Endian) -> Self {
+        Self {
+            default_endian,
+            ..self
+        }
+    }
+
+    /// A copy of this profile with canonical mode switched on or off.
+    pub fn with_canonical(self, canonical: bool) -> Self {
+        Self { canonical, ..self }
+    }
+
+    /// A copy of this profile loading atomics with `atomic_ordering`.
+    pub fn with_atomic_ordering(self, atomic_ordering: Ordering) -> Self {
+        Self {
+            atomic_ordering,
+            ..self
+        }
+    }
+
+    /// A copy of this profile treating repeated map keys with `duplicate_keys`.
+    pub fn with_duplicate_keys(self, duplicate_keys: DuplicateKeys) -> Self {
+        Self {
+            duplicate_keys,
+            ..self
+        }
+    }
+
+    /// A copy of this profile reading invalid UTF-8 with `utf8_policy`.
+    /// ```rust
+    /// use binary_utils::profile::{Utf8Policy, WireProfile};
+    ///
+    /// let lossy = WireProfile::DEFAULT.with_utf8_policy(Utf8Policy::Lossy);
+    /// let name: String = lossy.decode(&[0, 3, b'h', 0xFF, b'i'], &mut 0).unwrap();
+    /// assert_eq!(name, "h\u{FFFD}i");
+    /// ```
+    pub fn with_utf8_policy(self, utf8_policy: Utf8Policy) -> Self {
+        Self {
+            utf8_policy,
+            ..self
+        }
+    }
+
+    /// Runs `f` with this profile in effect, restoring the previous one afterwards.
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let _guard = ScopeGuard(load());
+        store(self);
+        f()
+    }
+
+    /// Writes `value` using this profile.
+    pub fn encode<T: Streamable>(self, value: &T) -> Result<Vec<u8>, BinaryError> {
+        self.scope(|| value.parse())
+    }
+
+    /// Reads a `T` from `source` using this profile.
+    pub fn decode<T: Streamable>(
+        self,
+        source: &[u8],
+        position: &mut usize,
+    ) -> Result<T, BinaryError> {
+        self.scope(|| T::compose(source, position))
+    }
+
+    /// Writes a length using `prefix`, fixed width prefixes follow `default_endian`.
+    pub(crate) fn write_length(
+        prefix: LengthPrefix,
+        length: usize,
+        out: &mut Vec<u8>,
+    ) -> Result<(), BinaryError> {
+        if length > prefix.max_length() {
+            return Err(BinaryError::OutOfBounds(
+                length,
+                prefix.max_length(),
+                "Length is too large for its prefix.",
+            ));
+        }
+
+        match prefix {
+            LengthPrefix::U16 => (length as u16).parse_into(out),
+            LengthPrefix::U32 => (length as u32).parse_into(out),
+            LengthPrefix::VarInt => VarInt(length as u32).parse_into(out),
+        }
+    }
+
+    /// The number of bytes `write_length` writes for `length`.
+    pub(crate) fn length_size(prefix: LengthPrefix, length: usize) -> usize {
+        match prefix {
+            LengthPrefix::U16 => 2,
+            LengthPrefix::U32 => 4,
+            LengthPrefix::VarInt => {
+                (usize::BITS - (length | 1).leading_zeros()).div_ceil(7) as usize
+            }
+        }
+    }
+
+    /// Reads a length written by `write_length`.
+    pub(crate) fn read_length(
+        prefix: LengthPrefix,
+        source: &[u8],
+        position: &mut usize,
+    ) -> Result<usize, BinaryError> {
+        Ok(match prefix {
+            LengthPrefix::U16 => u16::compose(source, position)? as usize,
+            LengthPrefix::U32 => u32::compose(source, position)? as usize,
+            LengthPrefix::VarInt => VarInt::<u32>::compose(source, position)?.0 as usize,
+        })
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl WireProfile {
+    /// Packs the profile into a `u32`, two bits per enum and one per flag.
+    const fn to_bits(self) -> u32 {
+        let ordering = match self.atomic_ordering {
+            Ordering::Relaxed => 0,
+            Ordering::Release => 1,
+            Ordering::Acquire => 2,
+            Ordering::AcqRel => 3,
+            _ => 4,
+        };
+        self.default_endian as u32
+            | (self.string_prefix as u32) << 1
+            | (self.vec_prefix as u32) << 3
+            | (self.bool_mode as u32) << 5
+            | (self.canonical as u32) << 6
+            | ordering << 7
+            | (self.duplicate_keys as u32) << 10
+            | (self.utf8_policy as u32) << 11
+    }
+
+    /// Unpacks a profile packed by `to_bits`.
+    fn from_bits(bits: u32) -> Self {
+        let prefix = |bits: u32| match bits & 0b11 {
+            0 => LengthPrefix::U16,
+            1 => LengthPrefix::U32,
+            _ => LengthPrefix::VarInt,
+        };
+        Self {
+            default_endian: if bits & 1 == 0 {
+                Endian::Big
+            } else {
+                Endian::Little
+            },
+            string_prefix: prefix(bits >> 1),
+            vec_prefix: prefix(bits >> 3),
+            bool_mode: if bits >> 5 & 1 == 0 {
+                BoolMode::Strict
+            } else {
+                BoolMode::Lenient
+            },
+            canonical: bits >> 6 & 1 == 1,
+            atomic_ordering: match bits >> 7 & 0b111 {
+                0 => Ordering::Relaxed,
+                1 => Ordering::Release,
+                2 => Ordering::Acquire,
+                3 => Ordering::AcqRel,
+                _ => Ordering::SeqCst,
+            },
+            duplicate_keys: if bits >> 10 & 1 == 0 {
+                DuplicateKeys::LastWins
+            } else {
+                DuplicateKeys::Error
+            },
+            utf8_policy: match bits >> 11 & 0b11 {
+                0 => Utf8Policy::Strict,
+                1 => Utf8Policy::Lossy,
+                _ => Utf8Policy::Preserve,
+            },
+        }
+    }
+}
+
+impl Default for WireProfile {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -0,0 +1,469 @@
+//! The protobuf wire format for structs tagged with `#[binary(proto_tag = N)]`.
+//!
+//! When every field of a `BinaryStream` struct has a `proto_tag`, the derive
+//! also implements `ProtoMessage`, which reads and writes the struct as the
+//! protobuf message with those field numbers. Numeric fields map to
+//! `uint32`/`uint64`/`int32`/`int64`/`bool`/`float`/`double`, `String` to
+//! `string`, `Vec<u8>` to `bytes` and other vectors to `repeated` fields.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::{proto::ProtoMessage, BinaryStream, Streamable};
+//!
+//! #[derive(Debug, PartialEq, BinaryStream)]
+//! pub struct Greeting {
+//!     #[binary(proto_tag = 1)]
+//!     pub id: u32,
+//!     #[binary(proto_tag = 2)]
+//!     pub name: String,
+//! }
+//!
+//! let greeting = Greeting { id: 150, name: "hi".to_string() };
+//! let bytes = greeting.encode_proto();
+//! assert_eq!(bytes, vec![0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i']);
+//! assert_eq!(Greeting::decode_proto(&bytes).unwrap(), greeting);
+//! ```
+use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::{u24, VarInt};
+
+/// How a protobuf field value is laid out on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireType {
+    Varint = 0,
+    Fixed64 = 1,
+    LengthDelimited = 2,
+    Fixed32 = 5,
+}
+
+impl WireType {
+    fn from_bits(bits: u64) -> Result<Self, BinaryError> {
+        match bits {
+            0 => Ok(Self::Varint),
+            1 => Ok(Self::Fixed64),
+            2 => Ok(Self::LengthDelimited),
+            5 => Ok(Self::Fixed32),
+            _ => Err(BinaryError::RecoverableKnown(format!(
+                "Unsupported protobuf wire type: {}",
+                bits
+            ))),
+        }
+    }
+}
+
+/// Writes a base 128 varint.
+pub fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads a base 128 varint from the front of `source`.
+pub fn read_varint(source: &mut &[u8]) -> Result<u64, BinaryError> {
+    let mut value = 0u64;
+    for (i, byte) in source.iter().enumerate().take(10) {
+        value |= ((byte & 0x7F) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            *source = &source[i + 1..];
+            return Ok(value);
+        }
+    }
+    Err(BinaryError::EOF(source.len()))
+}
+
+/// Writes a field key, the field number and its wire type.
+pub fn write_key(tag: u32, wire_type: WireType, out: &mut Vec<u8>) {
+    write_varint(((tag as u64) << 3) | wire_type as u64, out);
+}
+
+/// Reads a field key written by `write_key`.
+pub fn read_key(source: &mut &[u8]) -> Result<(u32, WireType), BinaryError> {
+    let key = read_varint(source)?;
+    Ok(((key >> 3) as u32, WireType::from_bits(key & 7)?))
+}
+
+fn take<'a>(source: &mut &'a [u8], length: usize) -> Result<&'a [u8], BinaryError> {
+    if source.len() < length {
+        return Err(BinaryError::EOF(source.len()));
+    }
+    let (bytes, rest) = source.split_at(length);
+    *source = rest;
+    Ok(bytes)
+}
+
+fn read_length_delimited<'a>(source: &mut &'a [u8]) -> Result<&'a [u8], BinaryError> {
+    let length = read_varint(source)? as usize;
+    take(source, length)
+}
+
+/// Skips the value of a field this message doesn't know about.
+pub fn skip_field(wire_type: WireType, source: &mut &[u8]) -> Result<(), BinaryError> {
+    match wire_type {
+        WireType::Varint => read_varint(source).map(|_| ()),
+        WireType::Fixed64 => take(source, 8).map(|_| ()),
+        WireType::Fixed32 => take(source, 4).map(|_| ()),
+        WireType::LengthDelimited => read_length_delimited(source).map(|_| ()),
+    }
+}
+
+fn expect(expected: WireType, found: WireType) -> Result<(), BinaryError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(BinaryError::RecoverableKnown(format!(
+            "Expected a {:?} protobuf field, found {:?}",
+            expected, found
+        )))
+    }
+}
+
+/// A type that can be a field of a protobuf message.
+pub trait ProtoField: Sized {
+    /// The value of the field when it's missing from the message.
+    fn proto_default() -> Self;
+
+    /// Writes the field as field number `tag`, default scalars are omitted.
+    fn write_field(&self, tag: u32, out: &mut Vec<u8>);
+
+    /// Merges one occurrence of the field into `self`.
+    fn merge_field(&mut self, wire_type: WireType, source: &mut &[u8]) -> Result<(), BinaryError>;
+}
+
+/// A struct encoded as a protobuf message, implemented by the derive.
+pub trait ProtoMessage: Sized {
+    /// The message with every field set to its default.
+    fn empty() -> Self;
+
+    /// Writes every field of the message.
+    fn write_fields(&self, out: &mut Vec<u8>);
+
+    /// Merges field number `tag` into the message, skipping unknown fields.
+    fn merge_tag(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        source: &mut &[u8],
+    ) -> Result<(), BinaryError>;
+
+    /// Encodes the message.
+    fn encode_proto(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_fields(&mut out);
+        out
+    }
+
+    /// Decodes a message, fields may appear in any order.
+    fn decode_proto(source: &[u8]) -> Result<Self, BinaryError> {
+        let mut message = Self::empty();
+        message.merge_fields(source)?;
+        Ok(message)
+    }
+
+    /// Merges every field in `source` into the message.
+    fn merge_fields(&mut self, mut source: &[u8]) -> Result<(), BinaryError> {
+        while !source.is_empty() {
+            let (tag, wire_type) = read_key(&mut source)?;
+            self.merge_tag(tag, wire_type, &mut source)?;
+        }
+        Ok(())
+    }
+}
+
+// nested messages are length delimited.
+impl<T: ProtoMessage> ProtoField for T {
+    fn proto_default() -> Self {
+        T::empty()
+    }
+
+    fn write_field(&self, tag: u32, out: &mut Vec<u8>) {
+        let body = self.encode_proto();
+        write_key(tag, WireType::LengthDelimited, out);
+        write_varint(body.len() as u64, out);
+        out.extend_from_slice(&body);
+    }
+
+    fn merge_field(&mut self, wire_type: WireType, source: &mut &[u8]) -> Result<(), BinaryError> {
+        expect(WireType::LengthDelimited, wire_type)?;
+        self.merge_fields(read_length_delimited(source)?)
+    }
+}
+
+impl<T: ProtoMessage> ProtoField for Vec<T> {
+    fn proto_default() -> Self {
+        Vec::new()
+    }
+
+    fn write_field(&self, tag: u32, out: &mut Vec<u8>) {
+        for item in self {
+            item.write_field(tag, out);
+        }
+    }
+
+    fn merge_field(&mut self, wire_type: WireType, source: &mut &[u8]) -> Result<(), BinaryError> {
+        let mut item = T::empty();
+        item.merge_field(wire_type, source)?;
+        self.push(item);
+        Ok(())
+    }
+}
+
+/// A single protobuf value, which has one wire type.
+trait Scalar: Sized {
+    const WIRE_TYPE: WireType;
+
+    fn zero() -> Self;
+    fn is_zero(&self) -> bool;
+    fn write_value(&self, out: &mut Vec<u8>);
+    fn read_value(source: &mut &[u8]) -> Result<Self, BinaryError>;
+}
+
+macro_rules! impl_scalar_varint {
+    ($ty: ty, $to: expr, $from: expr, $zero: expr) => {
+        impl Scalar for $ty {
+            const WIRE_TYPE: WireType = WireType::Varint;
+
+            fn zero() -> Self {
+                $zero
+            }
+
+            fn is_zero(&self) -> bool {
+                ($to)(self) == 0
+            }
+
+            fn write_value(&self, out: &mut Vec<u8>) {
+                write_varint(($to)(self), out);
+            }
+
+            fn read_value(source: &mut &[u8]) -> Result<Self, BinaryError> {
+                Ok(($from)(read_varint(source)?))
+            }
+        }
+    };
+}
+
+// signed values are sign extended, as `int32` and `int64` are.
+impl_scalar_varint!(u8, |v: &u8| *v as u64, |v: u64| v as u8, 0);
+impl_scalar_varint!(u16, |v: &u16| *v as u64, |v: u64| v as u16, 0);
+impl_scalar_varint!(u32, |v: &u32| *v as u64, |v: u64| v as u32, 0);
+impl_scalar_varint!(u64, |v: &u64| *v, |v: u64| v, 0);
+impl_scalar_varint!(i8, |v: &i8| *v as i64 as u64, |v: u64| v as i8, 0);
+impl_scalar_varint!(i16, |v: &i16| *v as i64 as u64, |v: u64| v as i16, 0);
+impl_scalar_varint!(i32, |v: &i32| *v as i64 as u64, |v: u64| v as i32, 0);
+impl_scalar_varint!(i64, |v: &i64| *v as u64, |v: u64| v as i64, 0);
+impl_scalar_varint!(bool, |v: &bool| *v as u64, |v: u64| v != 0, false);
+impl_scalar_varint!(
+    u24,
+    |v: &u24| v.0 as u64,
+    |v: u64| u24(v as u32 & 0x00FF_FFFF),
+    u24(0)
+);
+impl_scalar_varint!(
+    VarInt<u32>,
+    |v: &VarInt<u32>| v.0 as u64,
+    |v: u64| VarInt(v as u32),
+    VarInt(0)
+);
+impl_scalar_varint!(
+    VarInt<u64>,
+    |v: &VarInt<u64>| v.0,
+    |v: u64| VarInt(v),
+    VarInt(0)
+);
+impl_scalar_varint!(
+    VarInt<i32>,
+    |v: &VarInt<i32>| v.0 as i64 as u64,
+    |v: u64| VarInt(v as i32),
+    VarInt(0)
+);
+impl_scalar_varint!(
+    VarInt<i64>,
+    |v: &VarInt<i64>| v.0 as u64,
+    |v: u64| VarInt(v as i64),
+    VarInt(0)
+);
+
+impl Scalar for f32 {
+    const WIRE_TYPE: WireType = WireType::Fixed32;
+
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn is_zero(&self) -> bool {
+        self.to_bits() == 0
+    }
+
+    fn write_value(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_value(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(f32::from_le_bytes(take(source, 4)?.try_into().unwrap()))
+    }
+}
+
+impl Scalar for f64 {
+    const WIRE_TYPE: WireType = WireType::Fixed64;
+
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn is_zero(&self) -> bool {
+        self.to_bits() == 0
+    }
+
+    fn write_value(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_value(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        Ok(f64::from_le_bytes(take(source, 8)?.try_into().unwrap()))
+    }
+}
+
+impl Scalar for String {
+    const WIRE_TYPE: WireType = WireType::LengthDelimited;
+
+    fn zero() -> Self {
+        String::new()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn write_value(&self, out: &mut Vec<u8>) {
+        write_varint(self.len() as u64, out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_value(source: &mut &[u8]) -> Result<Self, BinaryError> {
+        String::from_utf8(read_length_delimited(source)?.to_vec()).map_err(|_| {
+            BinaryError::RecoverableKnown("Protobuf string is not valid UTF-8".to_string())
+        })
+    }
+}
+
+impl ProtoField for Vec<u8> {
+    fn proto_default() -> Self {
+        Vec::new()
+    }
+
+    fn write_field(&self, tag: u32, out: &mut Vec<u8>) {
+        if !self.is_empty() {
+            write_key(tag, WireType::LengthDelimited, out);
+            write_varint(self.len() as u64, out);
+            out.extend_from_slice(self);
+        }
+    }
+
+    fn merge_field(&mut self, wire_type: WireType, source: &mut &[u8]) -> Result<(), BinaryError> {
+        expect(WireType::LengthDelimited, wire_type)?;
+        *self = read_length_delimited(source)?.to_vec();
+        Ok(())
+    }
+}
+
+macro_rules! impl_proto_scalar {
+    ($ty: ty) => {
+        impl ProtoField for $ty {
+            fn proto_default() -> Self {
+                <$ty as Scalar>::zero()
+            }
+
+            fn write_field(&self, tag: u32, out: &mut Vec<u8>) {
+                if !self.is_zero() {
+                    write_key(tag, <$ty as Scalar>::WIRE_TYPE, out);
+                    self.write_value(out);
+                }
+            }
+
+            fn merge_field(
+                &mut self,
+                wire_type: WireType,
+                source: &mut &[u8],
+            ) -> Result<(), BinaryError> {
+                expect(<$ty as Scalar>::WIRE_TYPE, wire_type)?;
+                *self = <$ty as Scalar>::read_value(source)?;
+                Ok(())
+            }
+        }
+    };
+}
+
+// numeric vectors are written packed, but both layouts are accepted.
+macro_rules! impl_proto_repeated {
+    ($ty: ty) => {
+        impl ProtoField for Vec<$ty> {
+            fn proto_default() -> Self {
+                Vec::new()
+            }
+
+            fn write_field(&self, tag: u32, out: &mut Vec<u8>) {
+                if <$ty as Scalar>::WIRE_TYPE == WireType::LengthDelimited {
+                    for item in self {
+                        write_key(tag, WireType::LengthDelimited, out);
+                        item.write_value(out);
+                    }
+                } else if !self.is_empty() {
+                    let mut packed = Vec::new();
+                    for item in self {
+                        item.write_value(&mut packed);
+                    }
+                    write_key(tag, WireType::LengthDelimited, out);
+                    write_varint(packed.len() as u64, out);
+                    out.extend_from_slice(&packed);
+                }
+            }
+
+            fn merge_field(
+                &mut self,
+                wire_type: WireType,
+                source: &mut &[u8],
+            ) -> Result<(), BinaryError> {
+                if wire_type == <$ty as Scalar>::WIRE_TYPE {
+                    self.push(<$ty as Scalar>::read_value(source)?);
+                } else {
+                    expect(WireType::LengthDelimited, wire_type)?;
+                    let mut packed = read_length_delimited(source)?;
+                    while !packed.is_empty() {
+                        self.push(<$ty as Scalar>::read_value(&mut packed)?);
+                    }
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_proto_scalar!(u8);
+impl_proto_scalar!(u16);
+impl_proto_scalar!(u32);
+impl_proto_scalar!(u64);
+impl_proto_scalar!(i8);
+impl_proto_scalar!(i16);
+impl_proto_scalar!(i32);
+impl_proto_scalar!(i64);
+impl_proto_scalar!(bool);
+impl_proto_scalar!(f32);
+impl_proto_scalar!(f64);
+impl_proto_scalar!(String);
+impl_proto_scalar!(u24);
+impl_proto_scalar!(VarInt<u32>);
+impl_proto_scalar!(VarInt<u64>);
+impl_proto_scalar!(VarInt<i32>);
+impl_proto_scalar!(VarInt<i64>);
+
+impl_proto_repeated!(u16);
+impl_proto_repeated!(u32);
+impl_proto_repeated!(u64);
+impl_proto_repeated!(i8);
+impl_proto_repeated!(i16);
+impl_proto_repeated!(i32);
+impl_proto_repeated!(i64);
+impl_proto_repeated!(bool);
+impl_proto_repeated!(f32);
+impl_proto_repeated!(f64);
+impl_proto_repeated!(String);
@@ -0,0 +1,160 @@
+//! Keeping frames that failed to decode, so they can be reported and replayed.
+//!
+//! A `Quarantine` writes every failing frame to a directory, next to a report
+//! with the decode error and the byte range of each field that was read
+//! before it. Only the last `capacity` frames are kept, the oldest slot is
+//! overwritten first, so leaving it enabled in production can't fill the
+//! disk the way capturing all traffic would.
+//! ```rust
+//! use binary_utils::quarantine::Quarantine;
+//! use binary_utils::{BinaryStream, Streamable};
+//!
+//! #[derive(BinaryStream)]
+//! pub struct Login {
+//!     pub protocol: u32,
+//!     pub name: String,
+//! }
+//!
+//! let dir = std::env::temp_dir().join("binary_utils_quarantine_doc");
+//! # let _ = std::fs::remove_dir_all(&dir);
+//! let quarantine = Quarantine::open(&dir, 16).unwrap();
+//!
+//! // the name is cut off
+//! assert!(quarantine.decode::<Login>(&[0, 0, 2, 77, 0, 5, b'a']).is_err());
+//!
+//! let report = std::fs::read_to_string(dir.join("0.txt")).unwrap();
+//! assert!(report.contains("protocol: 0..4"));
+//! assert!(report.contains("failed at: name (offset 4)"));
+//! assert_eq!(std::fs::read(dir.join("0.bin")).unwrap(), [0, 0, 2, 77, 0, 5, b'a']);
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::BinaryError;
+use crate::tracked::FieldSpans;
+use crate::Streamable;
+
+/// Bytes shown per row of the hex dump in a report.
+const ROW: usize = 16;
+
+/// A bounded on-disk ring of frames that failed to decode.
+///
+/// Slot `n` is written as `n.bin`, the raw frame, and `n.txt`, the report.
+pub struct Quarantine {
+    dir: PathBuf,
+    capacity: usize,
+    next: AtomicUsize,
+}
+
+impl Quarantine {
+    /// Uses `dir` for up to `capacity` frames, creating it if needed.
+    ///
+    /// If `dir` already holds frames, e.g. from before a restart, writing
+    /// continues after the most recent one.
+    pub fn open(dir: impl AsRef<Path>, capacity: usize) -> io::Result<Self> {
+        assert!(
+            capacity > 0,
+            "A quarantine needs room for at least one frame"
+        );
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let next = (0..capacity)
+            .filter_map(|slot| {
+                let modified = fs::metadata(dir.join(format!("{}.txt", slot)))
+                    .and_then(|meta| meta.modified())
+                    .ok()?;
+                Some((modified, slot))
+            })
+            .max()
+            .map_or(0, |(_, slot)| slot + 1);
+
+        Ok(Self {
+            dir,
+            capacity,
+            next: AtomicUsize::new(next),
+        })
+    }
+
+    /// The directory frames are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Composes a `T` from `frame`, quarantining it if that fails.
+    ///
+    /// The report names the field that failed and the range of every field
+    /// before it. A frame that can't be written is dropped, the decode error
+    /// is returned either way.
+    pub fn decode<T: FieldSpans>(&self, frame: &[u8]) -> Result<T, BinaryError> {
+        let mut spans = Vec::with_capacity(T::FIELDS.len());
+        T::compose_spanned(frame, &mut 0, &mut spans).inspect_err(|error| {
+            let failed = T::FIELDS.get(spans.len()).copied();
+            let fields: Vec<_> = T::FIELDS.iter().copied().zip(spans).collect();
+            let _ = self.record(std::any::type_name::<T>(), frame, error, &fields, failed);
+        })
+    }
+
+    /// Composes a `T` from `frame` like `decode`, for types without field
+    /// spans, e.g. enums or hand written impls.
+    pub fn decode_plain<T: Streamable>(&self, frame: &[u8]) -> Result<T, BinaryError> {
+        T::compose(frame, &mut 0).inspect_err(|error| {
+            let _ = self.record(std::any::type_name::<T>(), frame, error, &[], None);
+        })
+    }
+
+    /// Writes `frame` and its report to the next slot, returning the report's path.
+    ///
+    /// `fields` are the fields that were read before the error, in wire
+    /// order, and `failed` the one that was being read when it happened.
+    pub fn record(
+        &self,
+        type_name: &str,
+        frame: &[u8],
+        error: &BinaryError,
+        fields: &[(&str, Range<usize>)],
+        failed: Option<&str>,
+    ) -> io::Result<PathBuf> {
+        let slot = self.next.fetch_add(1, Ordering::Relaxed) % self.capacity;
+        let report = self.dir.join(format!("{}.txt", slot));
+        fs::write(self.dir.join(format!("{}.bin", slot)), frame)?;
+        fs::write(
+            &report,
+            Self::report(type_name, frame, error, fields, failed),
+        )?;
+        Ok(report)
+    }
+
+    fn report(
+        type_name: &str,
+        frame: &[u8],
+        error: &BinaryError,
+        fields: &[(&str, Range<usize>)],
+        failed: Option<&str>,
+    ) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "type: {}", type_name);
+        let _ = writeln!(out, "error: {}", error.get_message());
+        let _ = writeln!(out, "length: {}", frame.len());
+        for (name, span) in fields {
+            let _ = writeln!(out, "{}: {}..{}", name, span.start, span.end);
+        }
+        if let Some(failed) = failed {
+            let offset = fields.last().map_or(0, |(_, span)| span.end);
+            let _ = writeln!(out, "failed at: {} (offset {})", failed, offset);
+        }
+        for (row, bytes) in frame.chunks(ROW).enumerate() {
+            let _ = write!(out, "{:04x} ", row * ROW);
+            for byte in bytes {
+                let _ = write!(out, " {:02x}", byte);
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -0,0 +1,113 @@
+//! A reusable buffer for receiving datagrams.
+//!
+//! `RecvBuffer` is allocated and zeroed once, handed to `recv_from` for every
+//! datagram, and decoded from in place. The received bytes are borrowed from
+//! the buffer, so the borrow checker rejects receiving the next datagram
+//! while anything still points into the previous one.
+//! ```rust
+//! use std::net::UdpSocket;
+//! use binary_utils::recv::RecvBuffer;
+//! use binary_utils::Streamable;
+//!
+//! let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+//! let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+//! client.send_to(&7u32.parse().unwrap(), server.local_addr().unwrap()).unwrap();
+//!
+//! let mut buffer = RecvBuffer::new();
+//! let (datagram, from) = buffer.recv_from(&server).unwrap();
+//! assert_eq!(from, client.local_addr().unwrap());
+//! assert_eq!(datagram.len(), 4);
+//! assert_eq!(buffer.decode::<u32>().unwrap(), 7);
+//! ```
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+
+use crate::error::BinaryError;
+use crate::Streamable;
+
+/// A fixed size, zero initialized buffer that datagrams are received into and decoded from.
+pub struct RecvBuffer {
+    buffer: Box<[u8]>,
+    filled: usize,
+}
+
+impl RecvBuffer {
+    /// The default size, the usual Ethernet MTU.
+    pub const DEFAULT_MTU: usize = 1500;
+
+    /// Creates a buffer of `DEFAULT_MTU` bytes.
+    pub fn new() -> Self {
+        Self::with_mtu(Self::DEFAULT_MTU)
+    }
+
+    /// Creates a buffer that can hold a datagram of up to `mtu` bytes.
+    pub fn with_mtu(mtu: usize) -> Self {
+        Self {
+            buffer: vec![0; mtu].into_boxed_slice(),
+            filled: 0,
+        }
+    }
+
+    /// The largest datagram the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The whole buffer, to receive the next datagram into.
+    ///
+    /// This discards the current datagram; call `set_filled` with the amount
+    /// of bytes received afterwards.
+    pub fn spare_mut(&mut self) -> &mut [u8] {
+        self.filled = 0;
+        &mut self.buffer[..]
+    }
+
+    /// Marks the first `len` bytes of the buffer as the current datagram.
+    ///
+    /// **Panics** if `len` is larger than `capacity`.
+    pub fn set_filled(&mut self, len: usize) {
+        assert!(
+            len <= self.buffer.len(),
+            "{} bytes do not fit in a {} byte RecvBuffer",
+            len,
+            self.buffer.len()
+        );
+        self.filled = len;
+    }
+
+    /// Receives a datagram with `recv`, which is given the whole buffer and
+    /// returns the amount of bytes it wrote.
+    pub fn fill<E>(
+        &mut self,
+        recv: impl FnOnce(&mut [u8]) -> Result<usize, E>,
+    ) -> Result<&[u8], E> {
+        let len = recv(self.spare_mut())?;
+        self.set_filled(len);
+        Ok(self.datagram())
+    }
+
+    /// Receives the next datagram from `socket`.
+    ///
+    /// Datagrams larger than the buffer are truncated, as with `UdpSocket::recv_from`.
+    pub fn recv_from(&mut self, socket: &UdpSocket) -> io::Result<(&[u8], SocketAddr)> {
+        let (len, from) = socket.recv_from(self.spare_mut())?;
+        self.set_filled(len);
+        Ok((self.datagram(), from))
+    }
+
+    /// The current datagram.
+    pub fn datagram(&self) -> &[u8] {
+        &self.buffer[..self.filled]
+    }
+
+    /// Decodes a `T` from the start of the current datagram.
+    pub fn decode<T: Streamable>(&self) -> Result<T, BinaryError> {
+        T::compose(self.datagram(), &mut 0)
+    }
+}
+
+impl Default for RecvBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,636 @@
+//! A registry mapping numeric packet ids to `Streamable` types.
+//!
+//! The registry knows how ids are written and how packets are delimited
+//! (its `Framing`), so the same packet types can be carried over byte streams,
+//! UDP or QUIC datagrams without a bespoke adapter for each transport.
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::BinaryError;
+use crate::framing::LengthPrefix;
+use crate::position::Position;
+use crate::resync::{decode_exact, scan, Resync};
+use crate::scratch::with_scratch;
+use crate::{Streamable, VarInt};
+
+/// How a packet id is written ahead of the packet body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketId {
+    /// A single byte id.
+    U8,
+    /// A `u32` variable length integer id.
+    VarInt,
+}
+
+impl PacketId {
+    /// Writes `id` to the end of `out`.
+    pub fn write(&self, id: u32, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        match self {
+            Self::U8 if id > u8::MAX as u32 => Err(BinaryError::OutOfBounds(
+                id as usize,
+                u8::MAX as usize,
+                "Packet id does not fit in a byte.",
+            )),
+            Self::U8 => {
+                out.push(id as u8);
+                Ok(())
+            }
+            Self::VarInt => VarInt(id).parse_into(out),
+        }
+    }
+
+    /// Reads an id from `source` at `position`.
+    pub fn read(&self, source: &[u8], position: &mut usize) -> Result<u32, BinaryError> {
+        match self {
+            Self::U8 => match source.get(*position) {
+                Some(id) => {
+                    *position += 1;
+                    Ok(*id as u32)
+                }
+                None => Err(BinaryError::EOF(source.len())),
+            },
+            Self::VarInt => {
+                match LengthPrefix::VarInt.read(source.get(*position..).unwrap_or(&[]))? {
+                    Some((id, size)) => {
+                        *position += size;
+                        Ok(id as u32)
+                    }
+                    None => Err(BinaryError::EOF(source.len())),
+                }
+            }
+        }
+    }
+}
+
+/// How packets are delimited by a `Registry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// `[length][id][body]`, for byte streams where packets follow each other.
+    LengthPrefixed(LengthPrefix),
+    /// `[id][body]` with exactly one packet per datagram.
+    ///
+    /// This is the profile used for QUIC datagrams (e.g. quinn's
+    /// `send_datagram`), where the transport already preserves message boundaries.
+    Datagram,
+}
+
+/// How urgently a packet should be sent when the connection is congested.
+///
+/// Queues such as `PacketSink` send higher priorities first, so movement and
+/// combat aren't stuck behind a burst of chunk data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Large transfers that can wait, e.g. chunks.
+    Bulk,
+    #[default]
+    Normal,
+    /// Latency sensitive packets, e.g. movement and combat.
+    High,
+}
+
+/// A packet whose id isn't registered, kept as the bytes that followed the id.
+///
+/// Registries set up with `passthrough_unknown` decode unknown ids to this
+/// instead of failing, so proxies can forward them with `encode_raw` unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPacket {
+    pub id: u32,
+    pub body: Vec<u8>,
+}
+
+/// A moving average of the body sizes of one packet type.
+///
+/// Each new size moves the average an eighth of the way towards it, so it
+/// follows changes within a few dozen packets. The average is kept in
+/// sixteenths of a byte, with `u64::MAX` before the first size.
+#[derive(Debug)]
+struct SizeAverage(AtomicU64);
+
+impl SizeAverage {
+    const EMPTY: u64 = u64::MAX;
+    const SCALE: u64 = 16;
+
+    fn new() -> Self {
+        Self(AtomicU64::new(Self::EMPTY))
+    }
+
+    fn record(&self, size: usize) {
+        let sample = size as u64 * Self::SCALE;
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |average| {
+                Some(match average {
+                    Self::EMPTY => sample,
+                    _ => average - average / 8 + sample / 8,
+                })
+            });
+    }
+
+    fn get(&self) -> Option<usize> {
+        match self.0.load(Ordering::Relaxed) {
+            Self::EMPTY => None,
+            average => Some(average.div_ceil(Self::SCALE) as usize),
+        }
+    }
+}
+
+type Decoder<M> =
+    Box<dyn Fn(&[u8], &mut usize, Option<&Observers>) -> Result<M, BinaryError> + Send + Sync>;
+type Observer = Box<dyn Fn(&dyn Any, &[u8]) + Send + Sync>;
+type Observers = HashMap<TypeId, Vec<Observer>>;
+
+/// Maps packet ids to the types they decode to.
+///
+/// `M` is the type every registered packet is converted into when decoded,
+/// usually an enum with one variant per packet.
+/// ```rust
+/// use binary_utils::registry::Registry;
+///
+/// enum Message {
+///     Ping(u64),
+///     Chat(String),
+/// }
+/// impl From<u64> for Message {
+///     fn from(v: u64) -> Self { Message::Ping(v) }
+/// }
+/// impl From<String> for Message {
+///     fn from(v: String) -> Self { Message::Chat(v) }
+/// }
+///
+/// let mut registry = Registry::<Message>::quic_datagram();
+/// registry.register::<u64>(0x01).register::<String>(0x02);
+///
+/// let datagram = registry.encode(&"hi".to_string()).unwrap();
+/// assert_eq!(datagram, vec![0x02, 0, 2, b'h', b'i']);
+/// assert!(matches!(registry.decode(&datagram).unwrap(), Message::Chat(s) if s == "hi"));
+/// ```
+pub struct Registry<M> {
+    id: PacketId,
+    framing: Framing,
+    decoders: HashMap<u32, Decoder<M>>,
+    ids: HashMap<TypeId, u32>,
+    names: HashMap<u32, &'static str>,
+    observers: Observers,
+    priorities: HashMap<u32, Priority>,
+    sizes: HashMap<u32, SizeAverage>,
+    unknown: Option<fn(RawPacket) -> M>,
+}
+
+impl<M> Registry<M> {
+    /// Creates an empty registry.
+    pub fn new(id: PacketId, framing: Framing) -> Self {
+        Self {
+            id,
+            framing,
+            decoders: HashMap::new(),
+            ids: HashMap::new(),
+            names: HashMap::new(),
+            observers: HashMap::new(),
+            priorities: HashMap::new(),
+            sizes: HashMap::new(),
+            unknown: None,
+        }
+    }
+
+    /// Creates an empty registry using the QUIC datagram profile:
+    /// no length prefix, one packet per datagram, with a VarInt id first.
+    pub fn quic_datagram() -> Self {
+        Self::new(PacketId::VarInt, Framing::Datagram)
+    }
+
+    /// The framing used by this registry.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Registers `T` under `id`, replacing any type previously registered under it.
+    ///
+    /// If `T` was registered under another id before, that id is unregistered,
+    /// so each type is decoded from and encoded with a single id.
+    pub fn register<T>(&mut self, id: u32) -> &mut Self
+    where
+        T: Streamable + Into<M> + 'static,
+    {
+        let ty = TypeId::of::<T>();
+        if let Some(old) = self.ids.get(&ty).copied().filter(|&old| old != id) {
+            self.decoders.remove(&old);
+            self.names.remove(&old);
+            self.priorities.remove(&old);
+            self.sizes.remove(&old);
+        }
+        // the type replaced under `id` can't be encoded with it anymore.
+        self.ids.retain(|other, held| *held != id || *other == ty);
+
+        self.decoders.insert(
+            id,
+            Box::new(|source, position, observers| {
+                let start = *position;
+                let packet = T::compose(source, position)?;
+                let observers = observers.and_then(|observers| observers.get(&TypeId::of::<T>()));
+                for observer in observers.into_iter().flatten() {
+                    observer(&packet, &source[start..*position]);
+                }
+                Ok(packet.into())
+            }),
+        );
+        self.ids.insert(ty, id);
+        self.names.insert(id, std::any::type_name::<T>());
+        self.priorities.remove(&id);
+        self.sizes.insert(id, SizeAverage::new());
+        self
+    }
+
+    /// Registers `T` under `id` with a send priority other than `Normal`.
+    pub fn register_with_priority<T>(&mut self, id: u32, priority: Priority) -> &mut Self
+    where
+        T: Streamable + Into<M> + 'static,
+    {
+        self.register::<T>(id);
+        self.priorities.insert(id, priority);
+        self
+    }
+
+    /// Decodes packets with unregistered ids to a `RawPacket` rather than failing.
+    ///
+    /// The raw packet takes every byte of the frame after the id. `resync` still
+    /// only stops at registered ids.
+    pub fn passthrough_unknown(&mut self) -> &mut Self
+    where
+        M: From<RawPacket>,
+    {
+        self.unknown = Some(M::from);
+        self
+    }
+
+    /// Calls `observer` with every `T` this registry decodes and the bytes it
+    /// was read from, which don't include the packet id.
+    ///
+    /// Observers run in the order they were added, before the packet is
+    /// converted to `M`. Frames tried while looking for the next packet in
+    /// `resync` aren't observed.
+    /// ```rust
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use binary_utils::registry::Registry;
+    ///
+    /// let mut registry = Registry::<u64>::quic_datagram();
+    /// let pings = Arc::new(AtomicUsize::new(0));
+    /// let counter = pings.clone();
+    /// registry.register::<u64>(0x01).on_decode::<u64>(move |_, raw| {
+    ///     assert_eq!(raw.len(), 8);
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    /// });
+    ///
+    /// registry.decode(&registry.encode(&9u64).unwrap()).unwrap();
+    /// assert_eq!(pings.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn on_decode<T: 'static>(
+        &mut self,
+        observer: impl Fn(&T, &[u8]) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.observers
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push(Box::new(move |packet, raw| {
+                if let Some(packet) = packet.downcast_ref::<T>() {
+                    observer(packet, raw);
+                }
+            }));
+        self
+    }
+
+    /// The send priority of packets registered under `id`.
+    pub fn priority_of_id(&self, id: u32) -> Priority {
+        self.priorities.get(&id).copied().unwrap_or_default()
+    }
+
+    /// The send priority `T` was registered with, `Normal` if it isn't registered.
+    pub fn priority_of<T: 'static>(&self) -> Priority {
+        self.id_of::<T>()
+            .map_or(Priority::Normal, |id| self.priority_of_id(id))
+    }
+
+    /// The id `T` was registered under.
+    pub fn id_of<T: 'static>(&self) -> Option<u32> {
+        self.ids.get(&TypeId::of::<T>()).copied()
+    }
+
+    /// The moving average size of the bodies of `T` this registry encoded
+    /// and decoded, without the packet id or framing.
+    ///
+    /// Batching layers can use it to guess whether a packet fits before
+    /// encoding it. `None` until a `T` went through the registry.
+    /// ```rust
+    /// use binary_utils::registry::Registry;
+    ///
+    /// let mut registry = Registry::<String>::quic_datagram();
+    /// registry.register::<String>(0x01);
+    /// assert_eq!(registry.avg_size::<String>(), None);
+    ///
+    /// registry.encode(&"hello".to_string()).unwrap();
+    /// assert_eq!(registry.avg_size::<String>(), Some(7));
+    /// ```
+    pub fn avg_size<T: 'static>(&self) -> Option<usize> {
+        self.id_of::<T>().and_then(|id| self.avg_size_of_id(id))
+    }
+
+    /// The moving average body size of packets registered under `id`.
+    pub fn avg_size_of_id(&self, id: u32) -> Option<usize> {
+        self.sizes.get(&id).and_then(SizeAverage::get)
+    }
+
+    /// The name of the type registered under `id`.
+    pub fn name_of_id(&self, id: u32) -> Option<&'static str> {
+        self.names.get(&id).copied()
+    }
+
+    /// Encodes `packet` as a single frame.
+    pub fn encode<T: Streamable + 'static>(&self, packet: &T) -> Result<Vec<u8>, BinaryError> {
+        let mut out = crate::alloc::allocate(0);
+        self.encode_into(packet, &mut out)?;
+        Ok(out)
+    }
+
+    /// Encodes `packet` as a single frame, appending it to `out`.
+    pub fn encode_into<T: Streamable + 'static>(
+        &self,
+        packet: &T,
+        out: &mut Vec<u8>,
+    ) -> Result<(), BinaryError> {
+        let id = self.id_of::<T>().ok_or_else(|| {
+            BinaryError::RecoverableKnown(format!(
+                "{} is not registered.",
+                std::any::type_name::<T>()
+            ))
+        })?;
+
+        with_scratch(|body| {
+            packet.parse_into(body)?;
+            if let Some(size) = self.sizes.get(&id) {
+                size.record(body.len());
+            }
+            self.write_frame(id, body, out)
+        })
+    }
+
+    /// Encodes a raw packet as a single frame, appending it to `out`.
+    ///
+    /// The body is written as is, so a `RawPacket` decoded by a registry with the
+    /// same framing is written back byte for byte.
+    pub fn encode_raw(&self, packet: &RawPacket, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.write_frame(packet.id, &packet.body, out)
+    }
+
+    fn write_frame(&self, id: u32, packet: &[u8], out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        with_scratch(|body| {
+            self.id.write(id, body)?;
+            body.extend_from_slice(packet);
+
+            match self.framing {
+                Framing::LengthPrefixed(prefix) => prefix.write(body.len(), out)?,
+                Framing::Datagram => {}
+            }
+            out.extend_from_slice(&body[..]);
+            Ok(())
+        })
+    }
+
+    /// Decodes a single frame.
+    ///
+    /// For datagram framing this is the whole datagram.
+    pub fn decode(&self, frame: &[u8]) -> Result<M, BinaryError> {
+        self.decode_from(frame, &mut 0)
+    }
+
+    /// Decodes the frame at `position`, advancing `position` past it.
+    ///
+    /// The packet has to use up the whole frame, bytes left over after it are an error.
+    pub fn decode_from(&self, source: &[u8], position: &mut usize) -> Result<M, BinaryError> {
+        let body = match self.framing {
+            Framing::LengthPrefixed(prefix) => {
+                let available = source.get(*position..).unwrap_or(&[]);
+                let (length, header) = prefix
+                    .read(available)?
+                    .ok_or(BinaryError::EOF(source.len()))?;
+                let body = available
+                    .get(header..header + length)
+                    .ok_or(BinaryError::EOF(source.len()))?;
+                *position += header + length;
+                body
+            }
+            Framing::Datagram => {
+                let body = source.get(*position..).unwrap_or(&[]);
+                *position = source.len();
+                body
+            }
+        };
+
+        let mut position = Position::new();
+        let packet = position.with_offset(|offset| self.decode_body(body, offset, true))?;
+        if position.remaining(body.len()) != 0 {
+            return Err(BinaryError::OutOfBounds(
+                position.get(),
+                body.len(),
+                "Frame has bytes left over after its packet.",
+            ));
+        }
+        Ok(packet)
+    }
+
+    /// Finds the next frame at or after `start` that has a registered id and
+    /// decodes exactly, to carry on after `decode_from` fails on a corrupt frame.
+    pub fn resync(&self, source: &[u8], start: usize) -> Option<Resync<M>> {
+        scan(source, start, |source, position| match self.framing {
+            Framing::LengthPrefixed(prefix) => {
+                decode_exact(source, position, prefix, |body, offset| {
+                    self.decode_body(body, offset, false)
+                })
+            }
+            Framing::Datagram => {
+                let value = self.decode_body(source, position, false)?;
+                if *position != source.len() {
+                    return Err(BinaryError::OutOfBounds(
+                        *position,
+                        source.len(),
+                        "Datagram has trailing bytes.",
+                    ));
+                }
+                Ok(value)
+            }
+        })
+    }
+
+    /// Decodes the frame body `body`, also returning the id that was read and
+    /// the offset decoding stopped at, which is where it failed on an error.
+    pub(crate) fn decode_traced(
+        &self,
+        body: &[u8],
+    ) -> (Option<u32>, usize, Result<M, BinaryError>) {
+        let mut offset = 0;
+        let id = match self.id.read(body, &mut offset) {
+            Ok(id) => id,
+            Err(e) => return (None, offset, Err(e)),
+        };
+        offset = 0;
+        let result = self.decode_body(body, &mut offset, true);
+        (Some(id), offset, result)
+    }
+
+    /// Reads the packet id at `offset` and decodes the packet after it.
+    ///
+    /// Unknown ids become a `RawPacket` when `passthrough` is set and the
+    /// registry was set up with `passthrough_unknown`. Observers are only
+    /// called, and sizes only recorded, when `passthrough` is set, as it
+    /// isn't while scanning.
+    fn decode_body(
+        &self,
+        body: &[u8],
+        offset: &mut usize,
+        passthrough: bool,
+    ) -> Result<M, BinaryError> {
+        let id = self.id.read(body, offset)?;
+        match (self.decoders.get(&id), &self.unknown) {
+            (Some(decoder), _) if passthrough => {
+                let start = *offset;
+                let packet = decoder(body, offset, Some(&self.observers))?;
+                if let Some(size) = self.sizes.get(&id) {
+                    size.record(*offset - start);
+                }
+                Ok(packet)
+            }
+            (Some(decoder), _) => decoder(body, offset, None),
+            (None, Some(unknown)) if passthrough => {
+                let packet = RawPacket {
+                    id,
+                    body: body[*offset..].to_vec(),
+                };
+                *offset = body.len();
+                Ok(unknown(packet))
+            }
+            (None, _) => Err(BinaryError::RecoverableKnown(format!(
+                "Unknown packet id: {}",
+                id
+            ))),
+        }
+    }
+}
+
+/// A registry with a separate id table per protocol version.
+///
+/// Game protocols reshuffle packet ids between versions, so the same type can
+/// be registered under a different id for every version it exists in.
+/// ```rust
+/// use binary_utils::registry::VersionedRegistry;
+///
+/// let mut registry = VersionedRegistry::<u64>::quic_datagram();
+/// registry.register::<u64>(440, 0x01).register::<u64>(475, 0x03);
+///
+/// let datagram = registry.encode(475, &9u64).unwrap();
+/// assert_eq!(datagram[0], 0x03);
+/// assert_eq!(registry.decode(475, &datagram).unwrap(), 9);
+/// assert!(registry.decode(440, &datagram).is_err());
+/// ```
+pub struct VersionedRegistry<M> {
+    id: PacketId,
+    framing: Framing,
+    versions: BTreeMap<u32, Registry<M>>,
+}
+
+impl<M> VersionedRegistry<M> {
+    /// Creates a registry without any versions.
+    pub fn new(id: PacketId, framing: Framing) -> Self {
+        Self {
+            id,
+            framing,
+            versions: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a registry without any versions using the QUIC datagram profile.
+    pub fn quic_datagram() -> Self {
+        Self::new(PacketId::VarInt, Framing::Datagram)
+    }
+
+    /// Registers `T` under `id` for `version` only.
+    pub fn register<T>(&mut self, version: u32, id: u32) -> &mut Self
+    where
+        T: Streamable + Into<M> + 'static,
+    {
+        self.version_mut(version).register::<T>(id);
+        self
+    }
+
+    /// Registers `T` under `id` for `version` with a send priority other than `Normal`.
+    pub fn register_with_priority<T>(
+        &mut self,
+        version: u32,
+        id: u32,
+        priority: Priority,
+    ) -> &mut Self
+    where
+        T: Streamable + Into<M> + 'static,
+    {
+        self.version_mut(version)
+            .register_with_priority::<T>(id, priority);
+        self
+    }
+
+    /// The id table of `version`.
+    pub fn version(&self, version: u32) -> Option<&Registry<M>> {
+        self.versions.get(&version)
+    }
+
+    /// The id table of `version`, created empty if it doesn't exist yet.
+    pub fn version_mut(&mut self, version: u32) -> &mut Registry<M> {
+        let (id, framing) = (self.id, self.framing);
+        self.versions
+            .entry(version)
+            .or_insert_with(|| Registry::new(id, framing))
+    }
+
+    /// Every version with an id table, in ascending order.
+    pub fn versions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.versions.keys().copied()
+    }
+
+    /// Encodes `packet` as a single frame using the ids of `version`.
+    pub fn encode<T: Streamable + 'static>(
+        &self,
+        version: u32,
+        packet: &T,
+    ) -> Result<Vec<u8>, BinaryError> {
+        self.table(version)?.encode(packet)
+    }
+
+    /// Encodes `packet` as a single frame using the ids of `version`, appending it to `out`.
+    pub fn encode_into<T: Streamable + 'static>(
+        &self,
+        version: u32,
+        packet: &T,
+        out: &mut Vec<u8>,
+    ) -> Result<(), BinaryError> {
+        self.table(version)?.encode_into(packet, out)
+    }
+
+    /// Decodes a single frame using the ids of `version`.
+    pub fn decode(&self, version: u32, frame: &[u8]) -> Result<M, BinaryError> {
+        self.table(version)?.decode(frame)
+    }
+
+    /// Decodes the frame at `position` using the ids of `version`, advancing `position` past it.
+    pub fn decode_from(
+        &self,
+        version: u32,
+        source: &[u8],
+        position: &mut usize,
+    ) -> Result<M, BinaryError> {
+        self.table(version)?.decode_from(source, position)
+    }
+
+    fn table(&self, version: u32) -> Result<&Registry<M>, BinaryError> {
+        self.versions.get(&version).ok_or_else(|| {
+            BinaryError::RecoverableKnown(format!("Unknown protocol version: {}", version))
+        })
+    }
+}
@@ -0,0 +1,112 @@
+//! Recovering from corrupted records.
+//!
+//! After a decode failure the functions here scan forward for the next offset
+//! that looks like the start of a record, so one bad record in a binary log
+//! doesn't make everything after it unreadable.
+//! ```rust
+//! use binary_utils::framing::{encode_frame, LengthPrefix};
+//! use binary_utils::resync::resync_framed;
+//!
+//! let mut log = Vec::new();
+//! encode_frame(&1u32, LengthPrefix::U16, &mut log).unwrap();
+//! log.extend_from_slice(&[0xFF, 0x13, 0x37]);
+//! encode_frame(&2u32, LengthPrefix::U16, &mut log).unwrap();
+//!
+//! let found = resync_framed::<u32>(&log, 6, LengthPrefix::U16).unwrap();
+//! assert_eq!((found.offset, found.end, found.value), (9, 15, 2));
+//! ```
+use crate::error::BinaryError;
+use crate::framing::LengthPrefix;
+use crate::prelude::*;
+use crate::Streamable;
+
+/// A record found by a resync scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resync<T> {
+    /// Where the record starts.
+    pub offset: usize,
+    /// Where the record ends, decoding can continue from here.
+    pub end: usize,
+    /// The decoded record.
+    pub value: T,
+}
+
+/// Tries `decode` at every offset from `start`, returning the first one that
+/// succeeds and consumes at least one byte.
+pub(crate) fn scan<T>(
+    source: &[u8],
+    start: usize,
+    mut decode: impl FnMut(&[u8], &mut usize) -> Result<T, BinaryError>,
+) -> Option<Resync<T>> {
+    (start..source.len()).find_map(|offset| {
+        let mut position = offset;
+        match decode(source, &mut position) {
+            Ok(value) if position > offset && position <= source.len() => Some(Resync {
+                offset,
+                end: position,
+                value,
+            }),
+            _ => None,
+        }
+    })
+}
+
+/// Finds the first offset at or after `start` where a `T` decodes.
+///
+/// This is only as strict as `T` is, prefer `resync_magic` or `resync_framed`
+/// when records carry a marker or a length.
+pub fn resync<T: Streamable>(source: &[u8], start: usize) -> Option<Resync<T>> {
+    scan(source, start, T::compose)
+}
+
+/// Finds the first record at or after `start` that begins with `magic`
+/// followed by a `T`. The returned offset is the offset of `magic`.
+pub fn resync_magic<T: Streamable>(source: &[u8], start: usize, magic: &[u8]) -> Option<Resync<T>> {
+    scan(source, start, |source, position| {
+        if !source[*position..].starts_with(magic) {
+            return Err(BinaryError::RecoverableKnown("No magic".to_string()));
+        }
+        *position += magic.len();
+        T::compose(source, position)
+    })
+}
+
+/// Finds the first frame written with `prefix` at or after `start` whose
+/// length fits in `source` and whose body decodes to a `T` exactly.
+pub fn resync_framed<T: Streamable>(
+    source: &[u8],
+    start: usize,
+    prefix: LengthPrefix,
+) -> Option<Resync<T>> {
+    scan(source, start, |source, position| {
+        decode_exact(source, position, prefix, T::compose)
+    })
+}
+
+/// Decodes the frame at `position` with `decode`, failing unless the body is
+/// consumed exactly.
+pub(crate) fn decode_exact<T>(
+    source: &[u8],
+    position: &mut usize,
+    prefix: LengthPrefix,
+    decode: impl FnOnce(&[u8], &mut usize) -> Result<T, BinaryError>,
+) -> Result<T, BinaryError> {
+    let (length, header) = prefix
+        .read(&source[*position..])?
+        .ok_or(BinaryError::EOF(source.len()))?;
+    let body = source
+        .get(*position + header..*position + header + length)
+        .ok_or(BinaryError::EOF(source.len()))?;
+
+    let mut offset = 0;
+    let value = decode(body, &mut offset)?;
+    if offset != body.len() {
+        return Err(BinaryError::OutOfBounds(
+            offset,
+            body.len(),
+            "Frame length does not match its body.",
+        ));
+    }
+    *position += header + length;
+    Ok(value)
+}
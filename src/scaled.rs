@@ -0,0 +1,141 @@
+use core::fmt;
+
+use crate::error::BinaryError;
+use crate::layout::FixedSize;
+use crate::prelude::*;
+use crate::Streamable;
+
+/// An integer type a `Scaled` value can be written as.
+pub trait ScaledInt: Streamable + Copy {
+    fn to_f64(self) -> f64;
+
+    /// The integer closest to `value`, `None` if it is out of range.
+    fn from_f64(value: f64) -> Option<Self>;
+}
+
+macro_rules! impl_scaled_int {
+    ($($ty: ty),*) => {
+        $(
+            impl ScaledInt for $ty {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Option<Self> {
+                    let value = value.round();
+                    if value >= <$ty>::MIN as f64 && value <= <$ty>::MAX as f64 {
+                        Some(value as $ty)
+                    } else {
+                        None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_scaled_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// A number written as the integer `T` after multiplying it by `NUM / DEN`,
+/// and divided again when composed.
+///
+/// Protocols often send positions or angles as fixed point integers, e.g. a
+/// coordinate times 32, and keeping the conversion in the type means business
+/// logic only ever sees the real value. It is rounded to the nearest integer,
+/// and values that don't fit in `T` fail to encode.
+/// ```rust
+/// use binary_utils::{Scaled, Streamable};
+///
+/// // a coordinate sent as an i32 in 1/32ths of a block.
+/// type Coordinate = Scaled<i32, 32, 1>;
+///
+/// assert_eq!(Coordinate::new(1.5).parse().unwrap(), vec![0, 0, 0, 48]);
+/// assert_eq!(Coordinate::compose(&[0, 0, 0, 48], &mut 0).unwrap().get(), 1.5);
+/// assert!(Scaled::<u8, 32, 1>::new(8.0).parse().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Scaled<T, const NUM: i64, const DEN: i64> {
+    value: f64,
+    wire: core::marker::PhantomData<T>,
+}
+
+impl<T: ScaledInt, const NUM: i64, const DEN: i64> Scaled<T, NUM, DEN> {
+    const SCALE: f64 = {
+        assert!(NUM != 0 && DEN != 0, "A scale can't be zero");
+        NUM as f64 / DEN as f64
+    };
+
+    pub fn new(value: f64) -> Self {
+        Self {
+            value,
+            wire: core::marker::PhantomData,
+        }
+    }
+
+    /// The real value.
+    pub fn get(self) -> f64 {
+        self.value
+    }
+
+    /// The integer the value is written as.
+    pub fn to_wire(self) -> Result<T, BinaryError> {
+        T::from_f64(self.value * Self::SCALE).ok_or_else(|| {
+            BinaryError::RecoverableKnown(format!(
+                "{} does not fit in a {} when scaled by {}/{}",
+                self.value,
+                core::any::type_name::<T>(),
+                NUM,
+                DEN
+            ))
+        })
+    }
+
+    /// The value written as `wire`.
+    pub fn from_wire(wire: T) -> Self {
+        Self::new(wire.to_f64() / Self::SCALE)
+    }
+}
+
+impl<T: ScaledInt, const NUM: i64, const DEN: i64> Streamable for Scaled<T, NUM, DEN> {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        self.to_wire()?.parse()
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        self.to_wire()?.parse_into(out)
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        T::compose(source, position).map(Self::from_wire)
+    }
+
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        T::validate(source, position)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.to_wire().ok()?.size_hint()
+    }
+}
+
+impl<T: FixedSize, const NUM: i64, const DEN: i64> FixedSize for Scaled<T, NUM, DEN> {
+    const WIRE_SIZE: usize = T::WIRE_SIZE;
+}
+
+impl<T: ScaledInt, const NUM: i64, const DEN: i64> From<f64> for Scaled<T, NUM, DEN> {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ScaledInt, const NUM: i64, const DEN: i64> From<f32> for Scaled<T, NUM, DEN> {
+    fn from(value: f32) -> Self {
+        Self::new(value.into())
+    }
+}
+
+impl<T, const NUM: i64, const DEN: i64> fmt::Display for Scaled<T, NUM, DEN> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
@@ -0,0 +1,111 @@
+//! Spreading decode work over server ticks.
+//!
+//! A burst of frames (a client flushing its queue, a chunk flood) decoded all
+//! at once shows up as one long tick. `DecodeScheduler` queues frames and
+//! decodes only as many as fit in a time budget per call, leaving the rest
+//! for the next tick.
+//! ```rust
+//! use std::time::Duration;
+//! use binary_utils::schedule::DecodeScheduler;
+//! use binary_utils::Streamable;
+//!
+//! let mut scheduler = DecodeScheduler::new();
+//! scheduler.push(1u16.parse().unwrap());
+//! scheduler.push(2u16.parse().unwrap());
+//!
+//! // a zero budget still makes progress, one frame per tick.
+//! let first = scheduler.tick(Duration::ZERO, |frame| u16::compose(frame, &mut 0));
+//! assert_eq!(first, vec![Ok(1)]);
+//! assert_eq!(scheduler.pending(), 1);
+//! assert_eq!(scheduler.stats().deferred, 1);
+//! ```
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::error::BinaryError;
+
+/// Counters kept by a `DecodeScheduler` since it was created or last reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// Frames that decoded successfully.
+    pub decoded: u64,
+    /// Frames that failed to decode.
+    pub failed: u64,
+    /// Frames left pending at the end of a tick, summed over every tick.
+    pub deferred: u64,
+    /// Calls to `tick`.
+    pub ticks: u64,
+    /// Time spent decoding.
+    pub busy: Duration,
+    /// The longest a single frame took to decode.
+    pub slowest: Duration,
+}
+
+/// Decodes queued frames within a per tick time budget.
+#[derive(Debug, Default)]
+pub struct DecodeScheduler {
+    pending: VecDeque<Vec<u8>>,
+    stats: DecodeStats,
+}
+
+impl DecodeScheduler {
+    /// An empty scheduler with no frames queued and zeroed counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a frame to be decoded on a later tick.
+    pub fn push(&mut self, frame: Vec<u8>) {
+        self.pending.push_back(frame);
+    }
+
+    /// The amount of frames waiting to be decoded.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Decodes frames in the order they were pushed until `budget` is used up.
+    ///
+    /// At least one frame is decoded per call so the queue always drains,
+    /// a frame is never interrupted once started.
+    pub fn tick<M>(
+        &mut self,
+        budget: Duration,
+        mut decode: impl FnMut(&[u8]) -> Result<M, BinaryError>,
+    ) -> Vec<Result<M, BinaryError>> {
+        let start = Instant::now();
+        let mut results = Vec::new();
+
+        while let Some(frame) = self.pending.pop_front() {
+            let began = Instant::now();
+            let result = decode(&frame[..]);
+            let took = began.elapsed();
+
+            match result {
+                Ok(_) => self.stats.decoded += 1,
+                Err(_) => self.stats.failed += 1,
+            }
+            self.stats.slowest = self.stats.slowest.max(took);
+            results.push(result);
+
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+
+        self.stats.ticks += 1;
+        self.stats.deferred += self.pending.len() as u64;
+        self.stats.busy += start.elapsed();
+        results
+    }
+
+    /// The counters collected so far.
+    pub fn stats(&self) -> DecodeStats {
+        self.stats
+    }
+
+    /// Returns the counters collected so far and starts over.
+    pub fn reset_stats(&mut self) -> DecodeStats {
+        std::mem::take(&mut self.stats)
+    }
+}
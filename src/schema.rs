@@ -0,0 +1,233 @@
+//! Fingerprints of wire layouts, to catch unintended changes between releases.
+//!
+//! `Schema` describes the layout a type is written with, the types of its
+//! fields in order, without the names of the type or its fields, since
+//! neither is written. Deriving `BinaryStream` implements it for every struct
+//! and enum whose fields implement it.
+//!
+//! `fingerprint` hashes that description, so it only changes when the wire
+//! layout does, and is the same on every platform and compiler.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::{schema, BinaryStream, Streamable, VarInt};
+//!
+//! #[derive(BinaryStream)]
+//! pub struct Login {
+//!     pub protocol: u32,
+//!     pub name: String,
+//!     pub skins: Vec<u8>,
+//!     pub flags: Flags,
+//! }
+//!
+//! #[derive(BinaryStream)]
+//! pub struct Flags {
+//!     pub id: VarInt<u32>,
+//!     pub hidden: bool,
+//! }
+//!
+//! assert_eq!(
+//!     schema::describe::<Login>(),
+//!     "{u32,string,vec<u8>,{varint<u32>,bool}}"
+//! );
+//! ```
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "net-addrs")]
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
+
+use crate::delivery::AckRanges;
+use crate::extensions::Extensions;
+use crate::hash::xxh64;
+use crate::tlv::{Tlv, TlvInt};
+use crate::{i24, u24, FixedString, Lazy, PassthroughOr, SVarInt, Scaled, VarInt, BE, LE};
+
+/// A type that can describe the layout it is written with.
+pub trait Schema {
+    /// Appends a description of the wire layout of `Self` to `out`.
+    fn describe(out: &mut String);
+}
+
+/// The description of the wire layout of `T`.
+pub fn describe<T: Schema>() -> String {
+    let mut out = String::new();
+    T::describe(&mut out);
+    out
+}
+
+/// A hash of the wire layout of `T`, stable across compiles.
+pub fn fingerprint<T: Schema>() -> u64 {
+    xxh64(describe::<T>().as_bytes(), 0)
+}
+
+/// Panics if the wire layout of `T` differs from the one stored at `path`.
+///
+/// The file holds the fingerprint followed by the description it was taken
+/// from. If the file doesn't exist it is written, as it is when the
+/// `BINARY_UTILS_BLESS` environment variable is set, to accept a change.
+/// ```rust ignore
+/// #[test]
+/// fn login_layout_is_unchanged() {
+///     schema::assert_fingerprint::<Login>("schemas/login.schema");
+/// }
+/// ```
+pub fn assert_fingerprint<T: Schema>(path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let description = describe::<T>();
+    let current = format!("{:016x}", fingerprint::<T>());
+
+    if std::env::var_os("BINARY_UTILS_BLESS").is_some() || !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("Could not create {}: {}", parent.display(), e));
+        }
+        std::fs::write(path, format!("{}\n{}\n", current, description))
+            .unwrap_or_else(|e| panic!("Could not write {}: {}", path.display(), e));
+        return;
+    }
+
+    let file = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+    let mut lines = file.lines();
+    let stored = lines.next().unwrap_or("");
+    if stored != current {
+        panic!(
+            "The wire layout of {} changed.\n  stored:  {} {}\n  current: {} {}\n\
+             Set BINARY_UTILS_BLESS=1 to accept the new layout.",
+            std::any::type_name::<T>(),
+            stored,
+            lines.next().unwrap_or(""),
+            current,
+            description,
+        );
+    }
+}
+
+macro_rules! impl_schema {
+    ($($ty: ty => $name: expr),*) => {
+        $(
+            impl Schema for $ty {
+                fn describe(out: &mut String) {
+                    out.push_str($name);
+                }
+            }
+        )*
+    };
+}
+
+impl_schema!(
+    u8 => "u8", u16 => "u16", u32 => "u32", u64 => "u64", u128 => "u128",
+    i8 => "i8", i16 => "i16", i32 => "i32", i64 => "i64", i128 => "i128",
+    f32 => "f32", f64 => "f64", bool => "bool", u24 => "u24", i24 => "i24", String => "string",
+    Extensions => "extensions", AckRanges => "ack_ranges",
+    AtomicU32 => "u32", AtomicU64 => "u64", AtomicBool => "bool",
+    VarInt<u32> => "varint<u32>", VarInt<i32> => "varint<i32>",
+    VarInt<u64> => "varint<u64>", VarInt<i64> => "varint<i64>",
+    SVarInt<i32> => "svarint<i32>", SVarInt<i64> => "svarint<i64>"
+);
+
+#[cfg(feature = "net-addrs")]
+impl_schema!(SocketAddr => "socket_addr");
+
+/// Describes a type wrapping a single other type, as `name<T>`.
+macro_rules! impl_schema_wrapper {
+    ($($wrapper: ident => $name: expr),*) => {
+        $(
+            impl<T: Schema> Schema for $wrapper<T> {
+                fn describe(out: &mut String) {
+                    out.push_str(concat!($name, "<"));
+                    T::describe(out);
+                    out.push('>');
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_wrapper!(LE => "le", BE => "be", Vec => "vec");
+
+// wrappers that don't change the wire are described as what they hold.
+impl<T: Schema> Schema for Lazy<T> {
+    fn describe(out: &mut String) {
+        T::describe(out)
+    }
+}
+
+impl<T: Schema> Schema for PassthroughOr<T> {
+    fn describe(out: &mut String) {
+        T::describe(out)
+    }
+}
+
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    fn describe(out: &mut String) {
+        out.push('[');
+        T::describe(out);
+        out.push_str(&format!(";{}]", N));
+    }
+}
+
+// both maps have the same layout.
+impl<K: Schema, V: Schema> Schema for HashMap<K, V> {
+    fn describe(out: &mut String) {
+        describe_map::<K, V>(out)
+    }
+}
+
+impl<K: Schema, V: Schema> Schema for BTreeMap<K, V> {
+    fn describe(out: &mut String) {
+        describe_map::<K, V>(out)
+    }
+}
+
+fn describe_map<K: Schema, V: Schema>(out: &mut String) {
+    out.push_str("map<");
+    K::describe(out);
+    out.push(',');
+    V::describe(out);
+    out.push('>');
+}
+
+// tuples have the same layout as a struct with the same fields.
+macro_rules! impl_schema_tuple {
+    ($($ty: ident $index: tt),+) => {
+        impl<$($ty: Schema),+> Schema for ($($ty,)+) {
+            fn describe(out: &mut String) {
+                out.push('{');
+                $(
+                    if $index > 0 {
+                        out.push(',');
+                    }
+                    $ty::describe(out);
+                )+
+                out.push('}');
+            }
+        }
+    };
+}
+
+crate::for_each_tuple!(impl_schema_tuple);
+
+impl<const N: usize> Schema for FixedString<N> {
+    fn describe(out: &mut String) {
+        out.push_str(&format!("fixed_string<{}>", N));
+    }
+}
+
+impl<T: Schema, const NUM: i64, const DEN: i64> Schema for Scaled<T, NUM, DEN> {
+    fn describe(out: &mut String) {
+        out.push_str("scaled<");
+        T::describe(out);
+        out.push_str(&format!(",{}/{}>", NUM, DEN));
+    }
+}
+
+impl<I: TlvInt + Schema, L: TlvInt + Schema> Schema for Tlv<I, L> {
+    fn describe(out: &mut String) {
+        out.push_str("tlv<");
+        I::describe(out);
+        out.push(',');
+        L::describe(out);
+        out.push('>');
+    }
+}
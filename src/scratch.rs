@@ -0,0 +1,113 @@
+//! Thread-local scratch buffers.
+//!
+//! Encoding usually builds a short lived `Vec<u8>` that is copied out or written
+//! to a socket right away. Borrowing a pooled buffer instead keeps its capacity
+//! around for the next call on the same thread, so the buffer doesn't have to
+//! grow from nothing every time.
+//!
+//! A connection that once encoded a huge packet would keep that capacity
+//! forever, so the pool tracks the largest buffer used since it was last
+//! trimmed, and `shrink_to_watermark` gives back anything above it.
+//!
+//! `encode_frame`, the codec's encoder, the registry's writers and the blocking
+//! TCP and UDP senders all encode through this pool. The async writers hold
+//! their bytes across an `.await`, so they keep an owned buffer.
+//!
+//! `parse` has to hand back an owned `Vec`, so it always allocates. When the
+//! bytes are written out right away, encode with `parse_into` into a scratch
+//! buffer instead:
+//! ```rust
+//! use std::io::Write;
+//! use binary_utils::{scratch::with_scratch, Streamable};
+//!
+//! let mut socket = Vec::new();
+//! with_scratch(|buf| {
+//!     "hello".to_string().parse_into(buf)?;
+//!     socket.write_all(buf)?;
+//!     Ok::<_, binary_utils::error::BinaryError>(())
+//! })
+//! .unwrap();
+//! assert_eq!(socket, [0, 5, b'h', b'e', b'l', b'l', b'o']);
+//! ```
+use std::cell::{Cell, RefCell};
+
+use crate::alloc;
+
+/// The amount of buffers kept per thread, deeper nesting allocates.
+const MAX_POOLED: usize = 8;
+
+thread_local! {
+    static POOL: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+    static HIGH_WATER: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Runs `f` with an empty scratch buffer from this thread's pool.
+///
+/// The buffer is returned to the pool afterwards, keeping its capacity.
+/// Buffers come from the `BufferAlloc`, and go back to it when the pool is full.
+/// Calls may be nested, each level gets its own buffer.
+/// ```rust
+/// use binary_utils::scratch::with_scratch;
+///
+/// let length = with_scratch(|buf| {
+///     buf.extend_from_slice(b"hello");
+///     buf.len()
+/// });
+/// assert_eq!(length, 5);
+/// // the next call gets the same (now empty) allocation back.
+/// assert!(with_scratch(|buf| buf.is_empty() && buf.capacity() >= 5));
+/// ```
+pub fn with_scratch<R>(f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    let mut buffer = POOL
+        .with(|pool| pool.borrow_mut().pop())
+        .unwrap_or_else(|| alloc::allocate(0));
+    buffer.clear();
+
+    let result = f(&mut buffer);
+    HIGH_WATER.with(|mark| mark.set(mark.get().max(buffer.len())));
+
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < MAX_POOLED {
+            pool.push(buffer);
+        } else {
+            alloc::release(buffer);
+        }
+    });
+    result
+}
+
+/// The largest length a scratch buffer on this thread reached since the
+/// pool was last trimmed with `shrink_to_watermark`.
+pub fn high_water() -> usize {
+    HIGH_WATER.with(Cell::get)
+}
+
+/// The capacity held by the buffers pooled on this thread, in bytes.
+pub fn pooled_capacity() -> usize {
+    POOL.with(|pool| pool.borrow().iter().map(Vec::capacity).sum())
+}
+
+/// Shrinks the buffers pooled on this thread to the high water mark, then
+/// resets the mark.
+///
+/// Calling this periodically keeps the pool sized for the packets sent
+/// since the last call, rather than the largest packet ever sent.
+/// ```rust
+/// use binary_utils::scratch::{pooled_capacity, shrink_to_watermark, with_scratch};
+///
+/// with_scratch(|buf| buf.resize(1 << 20, 0));
+/// shrink_to_watermark();
+/// // only small packets since, so the megabyte is given back.
+/// with_scratch(|buf| buf.extend_from_slice(b"hello"));
+/// shrink_to_watermark();
+/// assert!(pooled_capacity() < 1 << 20);
+/// ```
+pub fn shrink_to_watermark() {
+    let mark = HIGH_WATER.with(|mark| mark.replace(0));
+    POOL.with(|pool| {
+        for buffer in pool.borrow_mut().iter_mut() {
+            buffer.shrink_to(mark);
+        }
+    });
+}
@@ -0,0 +1,598 @@
+//! A serde backend writing this crate's wire format.
+//!
+//! Types deriving `Serialize` and `Deserialize` can be sent without also
+//! deriving `BinaryStream`. Values are laid out the way the `Streamable`
+//! impls lay them out under the current `WireProfile`: numbers in its byte
+//! order, strings sized by its `string_prefix` and sequences, maps and byte
+//! buffers by its `vec_prefix`. Structs and tuples are their fields in order,
+//! options a `0` or `1` byte before the value and enum variants a `VarInt`
+//! index before their fields.
+//!
+//! Like the derive, the format isn't self describing, so
+//! `deserialize_any` and skipping unknown values aren't supported.
+//! Next to a `use binary_utils::*;` the serde crate has to be named `::serde`,
+//! as this module shadows it.
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use binary_utils::{Streamable, BinaryStream};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Login {
+//!     protocol: u32,
+//!     name: String,
+//! }
+//!
+//! #[derive(BinaryStream)]
+//! struct LoginPacket {
+//!     protocol: u32,
+//!     name: String,
+//! }
+//!
+//! let login = Login { protocol: 589, name: "Netrex".into() };
+//! let bytes = binary_utils::serde::to_bytes(&login).unwrap();
+//! let packet = LoginPacket { protocol: 589, name: "Netrex".into() };
+//! assert_eq!(bytes, packet.parse().unwrap());
+//! assert_eq!(binary_utils::serde::from_bytes::<Login>(&bytes).unwrap(), login);
+//! ```
+use ::serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use ::serde::ser::{self, Serialize};
+
+use crate::error::BinaryError;
+use crate::profile::WireProfile;
+use crate::{bytes_at, string_from_utf8, Streamable, VarInt};
+
+impl ser::Error for BinaryError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        BinaryError::RecoverableKnown(msg.to_string())
+    }
+}
+
+impl de::Error for BinaryError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        BinaryError::RecoverableKnown(msg.to_string())
+    }
+}
+
+/// Encodes `value` under the current profile.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, BinaryError> {
+    let mut out = Vec::new();
+    value.serialize(&mut Serializer::new(&mut out))?;
+    Ok(out)
+}
+
+/// Decodes a `T` from the front of `source` under the current profile.
+pub fn from_bytes<'de, T: de::Deserialize<'de>>(source: &'de [u8]) -> Result<T, BinaryError> {
+    T::deserialize(&mut Deserializer::new(source))
+}
+
+/// Writes values to the end of a buffer.
+pub struct Serializer<'a> {
+    out: &'a mut Vec<u8>,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(out: &'a mut Vec<u8>) -> Self {
+        Self { out }
+    }
+
+    fn length(&mut self, length: Option<usize>) -> Result<(), BinaryError> {
+        let length = length.ok_or_else(|| {
+            BinaryError::RecoverableKnown("Sequences must know their length".to_string())
+        })?;
+        WireProfile::write_length(WireProfile::current().vec_prefix, length, self.out)
+    }
+
+    fn variant(&mut self, index: u32) -> Result<(), BinaryError> {
+        VarInt(index).parse_into(self.out)
+    }
+}
+
+macro_rules! serialize_streamable {
+    ($($method: ident($ty: ty);)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<(), BinaryError> {
+                value.parse_into(self.out)
+            }
+        )*
+    };
+}
+
+impl<'a, 'b> ser::Serializer for &'b mut Serializer<'a> {
+    type Ok = ();
+    type Error = BinaryError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    serialize_streamable! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), BinaryError> {
+        (value as u32).parse_into(self.out)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), BinaryError> {
+        let profile = WireProfile::current();
+        let bytes = profile.utf8_policy.encode(value);
+        WireProfile::write_length(profile.string_prefix, bytes.len(), self.out)?;
+        self.out.extend_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), BinaryError> {
+        self.length(Some(value.len()))?;
+        self.out.extend_from_slice(value);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), BinaryError> {
+        self.out.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), BinaryError> {
+        self.out.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), BinaryError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        _: &'static str,
+    ) -> Result<(), BinaryError> {
+        self.variant(index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        index: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        self.variant(index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<Self, BinaryError> {
+        self.length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, BinaryError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, BinaryError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, BinaryError> {
+        self.variant(index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, length: Option<usize>) -> Result<Self, BinaryError> {
+        self.length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, BinaryError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, BinaryError> {
+        self.variant(index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Implements the compound serializers, which write their parts in order.
+macro_rules! serialize_compound {
+    ($($trait: ident, $method: ident($($name: ident: $ty: ty),*);)*) => {
+        $(
+            impl<'a, 'b> ser::$trait for &'b mut Serializer<'a> {
+                type Ok = ();
+                type Error = BinaryError;
+
+                fn $method<T: Serialize + ?Sized>(
+                    &mut self,
+                    $($name: $ty,)*
+                    value: &T,
+                ) -> Result<(), BinaryError> {
+                    value.serialize(&mut **self)
+                }
+
+                fn end(self) -> Result<(), BinaryError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+serialize_compound! {
+    SerializeSeq, serialize_element();
+    SerializeTuple, serialize_element();
+    SerializeTupleStruct, serialize_field();
+    SerializeTupleVariant, serialize_field();
+    SerializeStruct, serialize_field(_key: &'static str);
+    SerializeStructVariant, serialize_field(_key: &'static str);
+}
+
+impl<'a, 'b> ser::SerializeMap for &'b mut Serializer<'a> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), BinaryError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+/// Reads values from a source, borrowing strings and bytes where it can.
+pub struct Deserializer<'de> {
+    source: &'de [u8],
+    position: usize,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(source: &'de [u8]) -> Self {
+        Self::at(source, 0)
+    }
+
+    /// A deserializer reading `source` from `position`.
+    pub fn at(source: &'de [u8], position: usize) -> Self {
+        Self { source, position }
+    }
+
+    /// The offset of the next value.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn read<T: Streamable>(&mut self) -> Result<T, BinaryError> {
+        T::compose(self.source, &mut self.position)
+    }
+
+    fn length(&mut self) -> Result<usize, BinaryError> {
+        WireProfile::read_length(
+            WireProfile::current().vec_prefix,
+            self.source,
+            &mut self.position,
+        )
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'de [u8], BinaryError> {
+        let bytes = bytes_at(self.source, self.position, length)?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn unsupported(what: &str) -> BinaryError {
+        BinaryError::RecoverableKnown(format!(
+            "{} needs a self describing format, which this isn't",
+            what
+        ))
+    }
+}
+
+macro_rules! deserialize_streamable {
+    ($($method: ident($ty: ty) => $visit: ident;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+                let value = self.read::<$ty>()?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = BinaryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, BinaryError> {
+        Err(Deserializer::unsupported("deserialize_any"))
+    }
+
+    deserialize_streamable! {
+        deserialize_bool(bool) => visit_bool;
+        deserialize_i8(i8) => visit_i8;
+        deserialize_i16(i16) => visit_i16;
+        deserialize_i32(i32) => visit_i32;
+        deserialize_i64(i64) => visit_i64;
+        deserialize_i128(i128) => visit_i128;
+        deserialize_u8(u8) => visit_u8;
+        deserialize_u16(u16) => visit_u16;
+        deserialize_u32(u32) => visit_u32;
+        deserialize_u64(u64) => visit_u64;
+        deserialize_u128(u128) => visit_u128;
+        deserialize_f32(f32) => visit_f32;
+        deserialize_f64(f64) => visit_f64;
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let value = self.read::<u32>()?;
+        let value = char::from_u32(value)
+            .ok_or_else(|| BinaryError::RecoverableKnown(format!("{:#x} is not a char", value)))?;
+        visitor.visit_char(value)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let length = WireProfile::read_length(
+            WireProfile::current().string_prefix,
+            self.source,
+            &mut self.position,
+        )?;
+        let bytes = self.take(length)?;
+        match std::str::from_utf8(bytes) {
+            Ok(value) => visitor.visit_borrowed_str(value),
+            // let the profile's policy handle it
+            Err(_) => visitor.visit_string(string_from_utf8(bytes.to_vec())?),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let length = self.length()?;
+        visitor.visit_borrowed_bytes(self.take(length)?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        match self.read::<u8>()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            tag => Err(BinaryError::RecoverableKnown(format!(
+                "{} is not an option tag",
+                tag
+            ))),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let length = self.length()?;
+        visitor.visit_seq(Items {
+            de: self,
+            remaining: length,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        length: usize,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_seq(Items {
+            de: self,
+            remaining: length,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        length: usize,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        self.deserialize_tuple(length, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let length = self.length()?;
+        visitor.visit_map(Items {
+            de: self,
+            remaining: length,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _: V) -> Result<V::Value, BinaryError> {
+        Err(Deserializer::unsupported("deserialize_identifier"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, BinaryError> {
+        Err(Deserializer::unsupported("deserialize_ignored_any"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The items of a sequence, tuple or map whose length is known.
+struct Items<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for Items<'_, 'de> {
+    type Error = BinaryError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, BinaryError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // don't let a claimed length reserve more than the source could hold
+        Some(self.remaining.min(self.de.source.len() - self.de.position))
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Items<'_, 'de> {
+    type Error = BinaryError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, BinaryError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, BinaryError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(self.de.source.len() - self.de.position))
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = BinaryError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), BinaryError> {
+        let index = self.read::<VarInt<u32>>()?.0;
+        let value = seed.deserialize(IntoDeserializer::<BinaryError>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = BinaryError;
+
+    fn unit_variant(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, BinaryError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        length: usize,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        de::Deserializer::deserialize_tuple(self, length, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
@@ -0,0 +1,156 @@
+use alloc_crate::sync::Arc;
+use core::fmt;
+use core::ops::{Bound, Deref, RangeBounds};
+
+use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::Streamable;
+
+/// A cheaply cloneable, read-only view into a reference counted buffer.
+///
+/// Slicing a `SharedSource` shares the original allocation instead of
+/// copying, so the entries of a batch can be split out of a datagram and
+/// decoded later, each holding only a count on the buffer rather than a
+/// borrow of it.
+/// ```rust
+/// use binary_utils::{SharedSource, Streamable};
+///
+/// // two entries, each prefixed with their u16 length.
+/// let datagram = SharedSource::from(vec![0, 2, 0, 7, 0, 1, 9]);
+/// let mut position = 0;
+/// let mut entries = Vec::new();
+/// while position < datagram.len() {
+///     let length: u16 = datagram.compose_at(&mut position).unwrap();
+///     entries.push(datagram.take(&mut position, length as usize).unwrap());
+/// }
+/// drop(datagram);
+///
+/// assert_eq!(entries[0].decode::<u16>().unwrap(), 7);
+/// assert_eq!(&entries[1][..], &[9]);
+/// ```
+#[derive(Clone)]
+pub struct SharedSource {
+    buffer: Arc<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl SharedSource {
+    /// Wraps `buffer`, sharing it with every slice taken from this source.
+    pub fn new(buffer: impl Into<Arc<[u8]>>) -> Self {
+        let buffer = buffer.into();
+        Self {
+            end: buffer.len(),
+            buffer,
+            start: 0,
+        }
+    }
+
+    /// The bytes of this view.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer[self.start..self.end]
+    }
+
+    /// The number of bytes in this view.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether this view is empty.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// A view of `range` within this view, sharing the same buffer.
+    ///
+    /// Panics if `range` is out of bounds, as slicing a `&[u8]` does.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "range {}..{} out of bounds for a source of {} bytes",
+            start,
+            end,
+            self.len()
+        );
+        Self {
+            buffer: self.buffer.clone(),
+            start: self.start + start,
+            end: self.start + end,
+        }
+    }
+
+    /// Takes the next `length` bytes at `position` as a shared view,
+    /// advancing `position` past them.
+    pub fn take(&self, position: &mut usize, length: usize) -> Result<Self, BinaryError> {
+        match position.checked_add(length) {
+            Some(end) if end <= self.len() => {
+                let view = self.slice(*position..end);
+                *position = end;
+                Ok(view)
+            }
+            _ => Err(BinaryError::EOF(self.len())),
+        }
+    }
+
+    /// Reads a `T` at `position`, advancing it.
+    pub fn compose_at<T: Streamable>(&self, position: &mut usize) -> Result<T, BinaryError> {
+        T::compose(self.as_slice(), position)
+    }
+
+    /// Reads a `T` from the start of this view.
+    pub fn decode<T: Streamable>(&self) -> Result<T, BinaryError> {
+        T::compose(self.as_slice(), &mut 0)
+    }
+}
+
+impl Deref for SharedSource {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for SharedSource {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for SharedSource {
+    fn from(buffer: Vec<u8>) -> Self {
+        Self::new(buffer)
+    }
+}
+
+impl From<&[u8]> for SharedSource {
+    fn from(buffer: &[u8]) -> Self {
+        Self::new(buffer)
+    }
+}
+
+impl PartialEq for SharedSource {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for SharedSource {}
+
+impl fmt::Debug for SharedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedSource")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
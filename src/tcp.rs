@@ -0,0 +1,135 @@
+//! Blocking length-prefixed framing over any `Read + Write`, e.g. a `TcpStream`.
+//!
+//! `FramedSync` is the blocking counterpart of the `PacketSink` and
+//! `PacketStream` adapters, for tools and tests without an async runtime.
+//! ```rust
+//! use std::net::{TcpListener, TcpStream};
+//! use binary_utils::framing::LengthPrefix;
+//! use binary_utils::tcp::FramedSync;
+//!
+//! let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+//! let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+//! let (server, _) = listener.accept().unwrap();
+//!
+//! let mut client = FramedSync::<_, String>::new(client, LengthPrefix::VarInt);
+//! let mut server = FramedSync::<_, String>::new(server, LengthPrefix::VarInt);
+//! client.send(&"hello".to_string()).unwrap();
+//! assert_eq!(server.recv().unwrap().unwrap(), "hello");
+//! ```
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use crate::framing::LengthPrefix;
+use crate::scratch::with_scratch;
+use crate::Streamable;
+
+/// Sends and receives `T` as length-prefixed frames, blocking on the inner stream.
+pub struct FramedSync<S, T> {
+    stream: S,
+    prefix: LengthPrefix,
+    max_frame: usize,
+    buffer: Vec<u8>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<S, T> FramedSync<S, T>
+where
+    S: Read + Write,
+    T: Streamable,
+{
+    /// The default maximum frame body size, 8 MiB.
+    pub const DEFAULT_MAX_FRAME: usize = 8 * 1024 * 1024;
+
+    /// Wraps `stream` with the default maximum frame size.
+    pub fn new(stream: S, prefix: LengthPrefix) -> Self {
+        Self::with_max_frame(stream, prefix, Self::DEFAULT_MAX_FRAME)
+    }
+
+    /// Wraps `stream`, refusing to send or receive frame bodies larger than `max_frame` bytes.
+    pub fn with_max_frame(stream: S, prefix: LengthPrefix, max_frame: usize) -> Self {
+        Self {
+            stream,
+            prefix,
+            max_frame,
+            buffer: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Encodes `packet` as a frame and writes it, flushing the stream.
+    pub fn send(&mut self, packet: &T) -> io::Result<()> {
+        with_scratch(|body| {
+            packet.parse_into(body)?;
+            self.check_length(body.len())?;
+            self.buffer.clear();
+            self.prefix.write(body.len(), &mut self.buffer)?;
+            self.buffer.extend_from_slice(body);
+            io::Result::Ok(())
+        })?;
+        self.stream.write_all(&self.buffer)?;
+        self.stream.flush()
+    }
+
+    /// Reads and decodes the next frame.
+    ///
+    /// Returns `None` if the stream ended cleanly before a new frame started.
+    pub fn recv(&mut self) -> io::Result<Option<T>> {
+        let length = match self.read_length()? {
+            Some(length) => length,
+            None => return Ok(None),
+        };
+        self.check_length(length)?;
+
+        self.buffer.clear();
+        self.buffer.resize(length, 0);
+        self.stream.read_exact(&mut self.buffer)?;
+        Ok(Some(T::compose(&self.buffer, &mut 0)?))
+    }
+
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    /// Reading from or writing to it directly can leave the framing out of sync.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Unwraps this adapter, returning the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn check_length(&self, length: usize) -> io::Result<()> {
+        if length > self.max_frame {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Frame of {} bytes exceeds the maximum of {} bytes.",
+                    length, self.max_frame
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads a length prefix a byte at a time, so nothing past it is consumed.
+    fn read_length(&mut self) -> io::Result<Option<usize>> {
+        let mut prefix = [0u8; crate::VAR_INT_32_BYTE_MAX];
+        let mut read = 0;
+        loop {
+            match self.stream.read_exact(&mut prefix[read..read + 1]) {
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && read == 0 => return Ok(None),
+                result => result?,
+            }
+            read += 1;
+
+            if let Some((length, _)) = self.prefix.read(&prefix[..read])? {
+                return Ok(Some(length));
+            }
+        }
+    }
+}
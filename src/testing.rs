@@ -0,0 +1,490 @@
+//! Helpers for comparing encoded bytes in tests.
+//!
+//! `assert_bytes_eq!` compares two byte buffers and, when they differ, panics
+//! with a side-by-side hex dump instead of two long lists of decimals.
+//! ```rust should_panic
+//! use binary_utils::assert_bytes_eq;
+//!
+//! assert_bytes_eq!([0x0c, 0, 0, 0, b'H', b'i'], vec![0x0c, 0, 0, 0, b'H', b'o']);
+//! // bytes differ at offset 5 (expected 6 bytes, actual 6 bytes)
+//! //   offset | expected                  | actual
+//! // > 0000   | 0c 00 00 00 48[69]        | 0c 00 00 00 48[6f]
+//! ```
+//!
+//! `assert_allocs!` counts the heap allocations a closure makes, so tests can
+//! hold encoders and decoders to an allocation budget. It needs
+//! `CountingAllocator` installed as the global allocator.
+//!
+//! `LoopbackStream` is an in-memory `Read` and `Write` pair that can split,
+//! delay and corrupt what goes through it, to test how codecs cope with a
+//! real network without opening sockets.
+//!
+//! `mutate` damages valid encodings in reproducible ways, and `assert_robust`
+//! checks a type composes every damaged copy without panicking or allocating
+//! without bound.
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::Streamable;
+
+/// Bytes shown per row of the dump.
+const ROW: usize = 8;
+/// Characters a row takes up, including the brackets.
+const WIDTH: usize = ROW * 3 + 1;
+
+/// Compares `expected` with `actual`, returning a hex dump of both if they differ.
+pub fn bytes_diff(expected: &[u8], actual: &[u8]) -> Option<String> {
+    let first = match expected.iter().zip(actual).position(|(e, a)| e != a) {
+        Some(offset) => offset,
+        None if expected.len() == actual.len() => return None,
+        None => expected.len().min(actual.len()),
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "bytes differ at offset {} (expected {} bytes, actual {} bytes)",
+        first,
+        expected.len(),
+        actual.len()
+    );
+    let _ = writeln!(out, "  offset | {:<w$} | actual", "expected", w = WIDTH);
+
+    let rows = expected.len().max(actual.len()).div_ceil(ROW);
+    for row in 0..rows {
+        let start = row * ROW;
+        let marker = if (start..start + ROW).contains(&first) {
+            '>'
+        } else {
+            ' '
+        };
+        let _ = writeln!(
+            out,
+            "{} {:04x}   | {} | {}",
+            marker,
+            start,
+            hex_row(expected, start, first),
+            hex_row(actual, start, first).trim_end()
+        );
+    }
+    Some(out)
+}
+
+/// Formats one row of `bytes`, bracketing the byte at `first`.
+fn hex_row(bytes: &[u8], start: usize, first: usize) -> String {
+    let mut out = String::with_capacity(WIDTH);
+    for offset in start..start + ROW {
+        let byte = match bytes.get(offset) {
+            Some(byte) => format!("{:02x}", byte),
+            None if offset == first => "--".to_string(),
+            None => break,
+        };
+        if offset == first {
+            out.push('[');
+        } else if offset != start && offset != first + 1 {
+            out.push(' ');
+        }
+        out.push_str(&byte);
+        if offset == first {
+            out.push(']');
+        }
+    }
+    format!("{:<w$}", out, w = WIDTH)
+}
+
+/// Asserts two byte buffers are equal, printing a hex dump of both if they aren't.
+///
+/// Both sides can be anything that is `AsRef<[u8]>`, such as `Vec<u8>`,
+/// arrays or slices. The first mismatching byte is bracketed in the dump,
+/// and `[--]` marks where the shorter buffer ran out.
+#[macro_export]
+macro_rules! assert_bytes_eq {
+    ($expected: expr, $actual: expr $(,)?) => {
+        if let Some(diff) = $crate::testing::bytes_diff(
+            ::std::convert::AsRef::<[u8]>::as_ref(&$expected),
+            ::std::convert::AsRef::<[u8]>::as_ref(&$actual),
+        ) {
+            panic!("assertion failed: `assert_bytes_eq!`\n{}", diff);
+        }
+    };
+}
+
+thread_local! {
+    /// The allocations made and bytes requested on this thread while
+    /// `count_allocs` runs.
+    static ALLOCS: std::cell::Cell<Option<(usize, usize)>> = const { std::cell::Cell::new(None) };
+}
+
+/// A global allocator that counts the allocations made inside `count_allocs`.
+///
+/// It allocates from `System`, and only counts on the thread running
+/// `count_allocs`, so other tests running at the same time don't skew the
+/// count. Growing an allocation counts as an allocation.
+#[cfg(not(feature = "forbid-unsafe"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+#[cfg(not(feature = "forbid-unsafe"))]
+impl CountingAllocator {
+    fn record(bytes: usize) {
+        let _ = ALLOCS.try_with(|allocs| {
+            if let Some((count, total)) = allocs.get() {
+                allocs.set(Some((count + 1, total + bytes)));
+            }
+        });
+    }
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        Self::record(layout.size());
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: std::alloc::Layout) -> *mut u8 {
+        Self::record(layout.size());
+        std::alloc::System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        Self::record(new_size);
+        std::alloc::System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+/// Whether `CountingAllocator` is the global allocator.
+pub fn is_counting() -> bool {
+    let outer = ALLOCS.with(|allocs| allocs.replace(Some((0, 0))));
+    drop(std::hint::black_box(Box::new(0u8)));
+    ALLOCS.with(|allocs| allocs.replace(outer)) == Some((1, 1))
+}
+
+/// Runs `f`, returning its result, the allocations it made and the bytes
+/// they requested on this thread, all `0` without `CountingAllocator`.
+fn measure<R>(f: impl FnOnce() -> R) -> (R, usize, usize) {
+    let outer = ALLOCS.with(|allocs| allocs.replace(Some((0, 0))));
+    let result = f();
+    let (count, bytes) = ALLOCS.with(|allocs| allocs.get()).unwrap_or_default();
+    ALLOCS.with(|allocs| allocs.set(outer.map(|(c, b)| (c + count, b + bytes))));
+    (result, count, bytes)
+}
+
+/// Runs `f`, returning its result and the number of heap allocations it made
+/// on this thread.
+///
+/// **Panics** if `CountingAllocator` isn't the global allocator, as every
+/// count would be `0`.
+#[cfg(not(feature = "forbid-unsafe"))]
+pub fn count_allocs<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    assert!(
+        is_counting(),
+        "count_allocs needs `#[global_allocator] static ALLOC: CountingAllocator = CountingAllocator;`"
+    );
+    let (result, count, _) = measure(f);
+    (result, count)
+}
+
+/// Asserts how many heap allocations a closure makes, returning its result.
+///
+/// The bound is a comparison and a number, e.g. `<= 1` or `== 0`.
+/// ```rust
+/// use binary_utils::testing::CountingAllocator;
+/// use binary_utils::{assert_allocs, Streamable};
+///
+/// #[global_allocator]
+/// static ALLOC: CountingAllocator = CountingAllocator;
+///
+/// fn main() {
+///     let bytes = assert_allocs!(<= 1, || 7u32.parse().unwrap());
+///     assert_allocs!(== 0, || u32::compose(&bytes, &mut 0).unwrap());
+/// }
+/// ```
+#[cfg(not(feature = "forbid-unsafe"))]
+#[macro_export]
+macro_rules! assert_allocs {
+    ($op: tt $limit: expr, $f: expr $(,)?) => {{
+        let (result, count) = $crate::testing::count_allocs($f);
+        let limit: usize = $limit;
+        if !(count $op limit) {
+            panic!(
+                "assertion failed: `assert_allocs!`\n{} allocations, expected {} {}",
+                count,
+                stringify!($op),
+                limit
+            );
+        }
+        result
+    }};
+}
+
+/// The bytes written by one end of a `LoopbackStream`, waiting to be read.
+#[derive(Debug, Default)]
+struct Pipe {
+    /// Each write, with when it can be read.
+    chunks: VecDeque<(Instant, Vec<u8>)>,
+    fragment: Option<usize>,
+    latency: Duration,
+    corrupt_every: Option<usize>,
+    /// The bytes written so far, to find the ones to corrupt.
+    written: usize,
+}
+
+/// An in-memory stream for testing request and response codecs.
+///
+/// `new` reads back what it writes, `pair` gives two ends that read what the
+/// other writes, like a client and a server. Reading with nothing written
+/// returns `0`, like the end of a file. The `with_` methods change how the
+/// bytes an end writes arrive at the reader.
+/// ```rust
+/// use std::time::Duration;
+/// use binary_utils::buffer::{IBufferRead, IBufferWrite};
+/// use binary_utils::testing::LoopbackStream;
+///
+/// let (client, mut server) = LoopbackStream::pair();
+/// let mut client = client.with_fragment(1).with_latency(Duration::from_millis(1));
+///
+/// client.write_string("ping").unwrap();
+/// // the string arrives a byte at a time, after a millisecond
+/// assert_eq!(server.read_string().unwrap(), "ping");
+///
+/// server.write_byte(1).unwrap();
+/// assert_eq!(client.read_byte().unwrap(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoopbackStream {
+    inbox: Arc<Mutex<Pipe>>,
+    outbox: Arc<Mutex<Pipe>>,
+}
+
+impl LoopbackStream {
+    /// A stream that reads back what it writes.
+    pub fn new() -> Self {
+        let pipe = Arc::new(Mutex::new(Pipe::default()));
+        Self {
+            inbox: pipe.clone(),
+            outbox: pipe,
+        }
+    }
+
+    /// Two ends that each read what the other writes.
+    pub fn pair() -> (Self, Self) {
+        let (a, b) = (
+            Arc::new(Mutex::new(Pipe::default())),
+            Arc::new(Mutex::new(Pipe::default())),
+        );
+        (
+            Self {
+                inbox: a.clone(),
+                outbox: b.clone(),
+            },
+            Self {
+                inbox: b,
+                outbox: a,
+            },
+        )
+    }
+
+    /// Delivers what this end writes at most `size` bytes per read, so
+    /// readers see it split at arbitrary points.
+    pub fn with_fragment(self, size: usize) -> Self {
+        assert!(size > 0, "A fragment needs at least one byte");
+        self.outbox.lock().unwrap().fragment = Some(size);
+        self
+    }
+
+    /// Delays what this end writes by `latency` before it can be read.
+    ///
+    /// Reads wait for it to arrive rather than returning nothing.
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.outbox.lock().unwrap().latency = latency;
+        self
+    }
+
+    /// Flips every bit of every `every`th byte this end writes.
+    pub fn with_corruption(self, every: usize) -> Self {
+        assert!(every > 0, "Corrupting every 0th byte isn't possible");
+        self.outbox.lock().unwrap().corrupt_every = Some(every);
+        self
+    }
+
+    /// The bytes written to this end's reader that it hasn't read yet.
+    pub fn pending(&self) -> usize {
+        let inbox = self.inbox.lock().unwrap();
+        inbox.chunks.iter().map(|(_, chunk)| chunk.len()).sum()
+    }
+}
+
+impl Default for LoopbackStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl io::Read for LoopbackStream {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let mut inbox = self.inbox.lock().unwrap();
+            let limit = inbox.fragment.unwrap_or(usize::MAX).min(out.len());
+            let (ready, chunk) = match inbox.chunks.pop_front() {
+                Some(front) => front,
+                None => return Ok(0),
+            };
+            let now = Instant::now();
+            if ready > now {
+                inbox.chunks.push_front((ready, chunk));
+                drop(inbox);
+                std::thread::sleep(ready - now);
+                continue;
+            }
+
+            let read = limit.min(chunk.len());
+            out[..read].copy_from_slice(&chunk[..read]);
+            if read < chunk.len() {
+                inbox.chunks.push_front((ready, chunk[read..].to_vec()));
+            }
+            return Ok(read);
+        }
+    }
+}
+
+impl io::Write for LoopbackStream {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let mut outbox = self.outbox.lock().unwrap();
+        let mut chunk = bytes.to_vec();
+        if let Some(every) = outbox.corrupt_every {
+            for (index, byte) in chunk.iter_mut().enumerate() {
+                if (outbox.written + index + 1) % every == 0 {
+                    *byte ^= 0xff;
+                }
+            }
+        }
+        outbox.written += chunk.len();
+        let ready = Instant::now() + outbox.latency;
+        outbox.chunks.push_back((ready, chunk));
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl crate::buffer::IBufferRead for LoopbackStream {
+    fn read_into(&mut self, out: &mut [u8]) -> io::Result<()> {
+        io::Read::read_exact(self, out)
+    }
+}
+
+impl crate::buffer::IBufferWrite for LoopbackStream {
+    fn write_from(&mut self, bytes: &[u8]) -> io::Result<()> {
+        io::Write::write_all(self, bytes)
+    }
+}
+
+/// A way of damaging a valid encoding, for `mutate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mutation {
+    /// Every bit flipped, one at a time.
+    BitFlips,
+    /// Every shorter prefix.
+    Truncation,
+    /// Every 1, 2, 4 and 8 byte run set to `0xff`, as if a length field
+    /// claimed far more than the frame holds.
+    LengthInflation,
+    /// The frame twice over, as a retransmission glued to the original.
+    DuplicateFrames,
+}
+
+impl Mutation {
+    pub const ALL: [Mutation; 4] = [
+        Mutation::BitFlips,
+        Mutation::Truncation,
+        Mutation::LengthInflation,
+        Mutation::DuplicateFrames,
+    ];
+}
+
+/// Every damaged copy of `buf` the `strategy` makes, in a fixed order so
+/// failures are reproducible.
+/// ```rust
+/// use binary_utils::testing::{mutate, Mutation};
+///
+/// let truncated: Vec<_> = mutate(&[1, 2], Mutation::Truncation).collect();
+/// assert_eq!(truncated, vec![vec![], vec![1]]);
+/// assert_eq!(mutate(&[1, 2], Mutation::BitFlips).count(), 16);
+/// ```
+pub fn mutate(buf: &[u8], strategy: Mutation) -> Box<dyn Iterator<Item = Vec<u8>> + '_> {
+    match strategy {
+        Mutation::BitFlips => Box::new((0..buf.len() * 8).map(move |bit| {
+            let mut out = buf.to_vec();
+            out[bit / 8] ^= 1 << (bit % 8);
+            out
+        })),
+        Mutation::Truncation => Box::new((0..buf.len()).map(move |end| buf[..end].to_vec())),
+        Mutation::LengthInflation => Box::new((0..buf.len()).flat_map(move |start| {
+            [1, 2, 4, 8]
+                .into_iter()
+                .filter(move |width| start + width <= buf.len())
+                .map(move |width| {
+                    let mut out = buf.to_vec();
+                    out[start..start + width].fill(0xff);
+                    out
+                })
+        })),
+        Mutation::DuplicateFrames => Box::new(std::iter::once([buf, buf].concat())),
+    }
+}
+
+/// Composes a `T` from every mutation of the `valid` encoding, panicking if
+/// any of them makes `compose` panic or, with `CountingAllocator`
+/// installed, allocate more than `max_bytes`.
+///
+/// Errors are expected and ignored, only panics and runaway allocations,
+/// e.g. trusting an inflated length, fail.
+#[cfg_attr(feature = "derive", doc = "```rust")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use binary_utils::testing::assert_robust;
+/// use binary_utils::{BinaryStream, Streamable};
+///
+/// #[derive(BinaryStream)]
+/// pub struct Chat {
+///     pub sender: u64,
+///     pub message: String,
+/// }
+///
+/// let valid = Chat { sender: 1, message: "hi".into() }.parse().unwrap();
+/// assert_robust::<Chat>(&valid, 64 * 1024);
+/// ```
+pub fn assert_robust<T: Streamable>(valid: &[u8], max_bytes: usize) {
+    let counting = is_counting();
+    for strategy in Mutation::ALL {
+        for (index, mutation) in mutate(valid, strategy).enumerate() {
+            let (result, _, bytes) =
+                measure(|| std::panic::catch_unwind(|| T::compose(&mutation, &mut 0).map(drop)));
+            let failure = if result.is_err() {
+                "panicked"
+            } else if counting && bytes > max_bytes {
+                "allocated too much"
+            } else {
+                continue;
+            };
+            panic!(
+                "composing {} {} on {:?} mutation {} ({} bytes allocated): {}",
+                std::any::type_name::<T>(),
+                failure,
+                strategy,
+                index,
+                bytes,
+                crate::hex(&mutation)
+            );
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Generic type-length-value sections.
+//!
+//! A `Tlv<I, L>` is a list of records written as `[id: I][length: L][bytes]`
+//! until the end of the source. Records are kept as raw bytes and decoded on
+//! lookup, so records with unknown ids are written back unchanged.
+//!
+//! Structs deriving `BinaryStream` with `#[binary(tlv_id = N)]` implement
+//! `TlvRecord` and can be read and written by id.
+//!
+//! **Notice:**
+//! A `Tlv` reads every remaining byte of the source when composed, so it
+//! must be the last field of a packet.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::tlv::Tlv;
+//! use binary_utils::{BinaryStream, Streamable};
+//!
+//! #[derive(Debug, PartialEq, BinaryStream)]
+//! #[binary(tlv_id = 2)]
+//! pub struct Timestamp {
+//!     pub millis: u32,
+//! }
+//!
+//! let mut section = Tlv::<u8, u16>::new();
+//! section.insert(&Timestamp { millis: 7 }).unwrap();
+//! let bytes = section.parse().unwrap();
+//! assert_eq!(bytes, vec![2, 0, 4, 0, 0, 0, 7]);
+//!
+//! let section = Tlv::<u8, u16>::compose(&bytes, &mut 0).unwrap();
+//! assert_eq!(section.get::<Timestamp>().unwrap(), Some(Timestamp { millis: 7 }));
+//! ```
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::profile::{DuplicateKeys, WireProfile};
+use crate::{u24, Streamable, VarInt};
+
+/// An integer type ids and lengths of a `Tlv` can be written as.
+pub trait TlvInt: Streamable + Sized {
+    fn to_u64(&self) -> u64;
+
+    /// Converts `value`, failing if it doesn't fit.
+    fn from_u64(value: u64) -> Result<Self, BinaryError>;
+}
+
+fn too_large(value: u64, ty: &str) -> BinaryError {
+    BinaryError::RecoverableKnown(format!("{} does not fit in a {}", value, ty))
+}
+
+macro_rules! impl_tlv_int {
+    ($($ty: ty),*) => {
+        $(
+            impl TlvInt for $ty {
+                fn to_u64(&self) -> u64 {
+                    *self as u64
+                }
+
+                fn from_u64(value: u64) -> Result<Self, BinaryError> {
+                    <$ty>::try_from(value).map_err(|_| too_large(value, stringify!($ty)))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_tlv_var_int {
+    ($($ty: ty),*) => {
+        $(
+            impl TlvInt for VarInt<$ty> {
+                fn to_u64(&self) -> u64 {
+                    self.0.to_u64()
+                }
+
+                fn from_u64(value: u64) -> Result<Self, BinaryError> {
+                    Ok(VarInt(<$ty>::from_u64(value)?))
+                }
+            }
+        )*
+    };
+}
+
+impl_tlv_int!(u8, u16, u32, u64);
+impl_tlv_var_int!(u32, u64);
+
+impl TlvInt for u24 {
+    fn to_u64(&self) -> u64 {
+        self.0 as u64
+    }
+
+    fn from_u64(value: u64) -> Result<Self, BinaryError> {
+        match value {
+            0..=0x00FF_FFFF => Ok(u24(value as u32)),
+            _ => Err(too_large(value, "u24")),
+        }
+    }
+}
+
+/// A type that can be carried in a `Tlv` under a fixed id.
+///
+/// Implemented by the derive for structs with `#[binary(tlv_id = N)]`.
+pub trait TlvRecord: Streamable {
+    /// The id the record is written with.
+    const ID: u64;
+}
+
+/// A list of type-length-value records, in the order they were written.
+///
+/// `I` is the type ids are written as and `L` the type lengths are written as.
+pub struct Tlv<I = VarInt<u32>, L = VarInt<u32>> {
+    records: Vec<(u64, Vec<u8>)>,
+    _types: PhantomData<fn() -> (I, L)>,
+}
+
+impl<I: TlvInt, L: TlvInt> Tlv<I, L> {
+    /// Creates an empty section.
+    pub fn new() -> Self {
+        Self {
+            records: Vec::new(),
+            _types: PhantomData,
+        }
+    }
+
+    /// Decodes the record `R`, if it is present.
+    pub fn get<R: TlvRecord>(&self) -> Result<Option<R>, BinaryError> {
+        match self.raw(R::ID) {
+            Some(bytes) => Ok(Some(R::compose(bytes, &mut 0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether a record with the id of `R` is present.
+    pub fn contains<R: TlvRecord>(&self) -> bool {
+        self.raw(R::ID).is_some()
+    }
+
+    /// Encodes `record`, replacing any record with the same id.
+    pub fn insert<R: TlvRecord>(&mut self, record: &R) -> Result<(), BinaryError> {
+        self.insert_raw(R::ID, record.parse()?);
+        Ok(())
+    }
+
+    /// Removes the record `R`, returning whether it was present.
+    pub fn remove<R: TlvRecord>(&mut self) -> bool {
+        self.remove_raw(R::ID)
+    }
+
+    /// The bytes of the record with `id`, the last one wins if it is repeated.
+    pub fn raw(&self, id: u64) -> Option<&[u8]> {
+        self.records
+            .iter()
+            .rfind(|(record, _)| *record == id)
+            .map(|(_, bytes)| &bytes[..])
+    }
+
+    /// Sets the bytes of the record with `id`, adding it to the end if it isn't present.
+    pub fn insert_raw(&mut self, id: u64, bytes: Vec<u8>) {
+        match self.records.iter_mut().rfind(|(record, _)| *record == id) {
+            Some((_, existing)) => *existing = bytes,
+            None => self.records.push((id, bytes)),
+        }
+    }
+
+    /// Removes every record with `id`, returning whether there were any.
+    pub fn remove_raw(&mut self, id: u64) -> bool {
+        let length = self.records.len();
+        self.records.retain(|(record, _)| *record != id);
+        self.records.len() != length
+    }
+
+    /// Every record as its id and bytes, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.records.iter().map(|(id, bytes)| (*id, &bytes[..]))
+    }
+
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl<I: TlvInt, L: TlvInt> Streamable for Tlv<I, L> {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut out = crate::alloc::allocate(0);
+        for (id, bytes) in &self.records {
+            out.extend_from_slice(&I::from_u64(*id)?.parse()?);
+            out.extend_from_slice(&L::from_u64(bytes.len() as u64)?.parse()?);
+            out.extend_from_slice(bytes);
+        }
+        Ok(out)
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let reject = WireProfile::current().duplicate_keys == DuplicateKeys::Error;
+        let mut records: Vec<(u64, Vec<u8>)> = Vec::new();
+        while *position < source.len() {
+            let start = *position;
+            let id = I::compose(source, position)?.to_u64();
+            if reject && records.iter().any(|(record, _)| *record == id) {
+                return Err(BinaryError::DuplicateKey {
+                    key_repr: id.to_string(),
+                    offset: start,
+                });
+            }
+            let length = L::compose(source, position)?.to_u64() as usize;
+            let bytes = source
+                .get(*position..position.saturating_add(length))
+                .ok_or(BinaryError::EOF(source.len()))?;
+            *position += length;
+            records.push((id, bytes.to_vec()));
+        }
+        Ok(Self {
+            records,
+            _types: PhantomData,
+        })
+    }
+}
+
+impl<I: TlvInt, L: TlvInt> Default for Tlv<I, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, L> Clone for Tlv<I, L> {
+    fn clone(&self) -> Self {
+        Self {
+            records: self.records.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<I, L> PartialEq for Tlv<I, L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.records == other.records
+    }
+}
+
+impl<I, L> Eq for Tlv<I, L> {}
+
+impl<I, L> fmt::Debug for Tlv<I, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.records.iter().map(|(id, bytes)| (id, bytes)))
+            .finish()
+    }
+}
@@ -0,0 +1,167 @@
+//! Re-encoding a decoded packet by patching only the fields that changed.
+//!
+//! Deriving `BinaryStream` on a struct also implements `FieldSpans`, which
+//! records the byte range every field was composed from. `Tracked` keeps those
+//! ranges next to the original buffer and marks fields dirty as they are
+//! modified, so `re_encode` only has to encode the modified fields and write
+//! them over their old bytes.
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use binary_utils::tracked::Tracked;
+//! use binary_utils::{BinaryStream, Streamable};
+//!
+//! #[derive(BinaryStream)]
+//! pub struct Move {
+//!     pub entity: u64,
+//!     pub x: f32,
+//!     pub y: f32,
+//!     pub name: String,
+//! }
+//!
+//! let bytes = Move { entity: 7, x: 1.0, y: 2.0, name: "steve".into() }.parse().unwrap();
+//! let mut packet = Tracked::<Move>::decode(bytes).unwrap();
+//! packet.modify("x", |m| m.x = 4.0).modify("y", |m| m.y = 8.0);
+//!
+//! let bytes = packet.re_encode().unwrap().to_vec();
+//! assert_eq!(Move::compose(&bytes, &mut 0).unwrap().x, 4.0);
+//! ```
+use std::ops::{Deref, Range};
+
+use crate::error::BinaryError;
+use crate::Streamable;
+
+/// A type that knows which bytes each of its fields was composed from.
+///
+/// This is implemented by `#[derive(BinaryStream)]` for structs.
+pub trait FieldSpans: Streamable + Sized {
+    /// The field names, in wire order.
+    const FIELDS: &'static [&'static str];
+
+    /// Composes the value like `compose`, pushing the range each field was read
+    /// from to `spans`.
+    fn compose_spanned(
+        source: &[u8],
+        position: &mut usize,
+        spans: &mut Vec<Range<usize>>,
+    ) -> Result<Self, BinaryError>;
+
+    /// Encodes the field at `index` of `FIELDS` on its own.
+    fn parse_field(&self, index: usize) -> Result<Vec<u8>, BinaryError>;
+}
+
+/// A decoded packet that remembers its original bytes and which fields were modified.
+///
+/// The value can be read through `Deref`. It can only be changed through
+/// `modify`, which marks the given field dirty, or `get_mut`, which marks
+/// every field dirty.
+pub struct Tracked<T> {
+    value: T,
+    buffer: Vec<u8>,
+    spans: Vec<Range<usize>>,
+    dirty: Vec<bool>,
+}
+
+impl<T: FieldSpans> Tracked<T> {
+    /// Decodes a packet from the front of `buffer`, keeping the bytes it was read from.
+    pub fn decode(mut buffer: Vec<u8>) -> Result<Self, BinaryError> {
+        let mut spans = Vec::with_capacity(T::FIELDS.len());
+        let mut position = 0;
+        let value = T::compose_spanned(&buffer, &mut position, &mut spans)?;
+        buffer.truncate(position);
+        Ok(Self {
+            value,
+            buffer,
+            dirty: vec![false; spans.len()],
+            spans,
+        })
+    }
+
+    /// Modifies the packet, marking `field` as changed.
+    ///
+    /// `f` must only change the named field, any other change is not written
+    /// by `re_encode`.
+    ///
+    /// **Panics** if `T` has no field called `field`.
+    pub fn modify(&mut self, field: &str, f: impl FnOnce(&mut T)) -> &mut Self {
+        let index = T::FIELDS
+            .iter()
+            .position(|name| *name == field)
+            .unwrap_or_else(|| panic!("{} has no field `{}`", std::any::type_name::<T>(), field));
+        f(&mut self.value);
+        self.dirty[index] = true;
+        self
+    }
+
+    /// Mutable access to the whole packet, marking every field as changed.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.dirty.iter_mut().for_each(|dirty| *dirty = true);
+        &mut self.value
+    }
+
+    /// Whether any field changed since the packet was decoded or last re-encoded.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.contains(&true)
+    }
+
+    /// The range of the buffer each field occupies, in the order of `T::FIELDS`.
+    pub fn spans(&self) -> &[Range<usize>] {
+        &self.spans[..]
+    }
+
+    /// The encoded packet as of the last `decode` or `re_encode`.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer[..]
+    }
+
+    /// Brings the buffer up to date with the packet and returns it.
+    ///
+    /// When every modified field still encodes to as many bytes as before,
+    /// only those fields are encoded and written over their old bytes.
+    /// Otherwise the whole packet is encoded again.
+    pub fn re_encode(&mut self) -> Result<&[u8], BinaryError> {
+        let mut changed = Vec::new();
+        for (index, dirty) in self.dirty.iter().enumerate() {
+            if *dirty {
+                changed.push((index, self.value.parse_field(index)?));
+            }
+        }
+
+        if changed
+            .iter()
+            .all(|(index, bytes)| bytes.len() == self.spans[*index].len())
+        {
+            for (index, bytes) in changed {
+                self.buffer[self.spans[index].clone()].copy_from_slice(&bytes);
+            }
+        } else {
+            self.encode_all()?;
+        }
+
+        self.dirty.iter_mut().for_each(|dirty| *dirty = false);
+        Ok(&self.buffer[..])
+    }
+
+    /// Takes the packet out, dropping the buffer.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    fn encode_all(&mut self) -> Result<(), BinaryError> {
+        let mut buffer = crate::alloc::allocate(self.buffer.len());
+        for (index, span) in self.spans.iter_mut().enumerate() {
+            let start = buffer.len();
+            buffer.extend_from_slice(&self.value.parse_field(index)?);
+            *span = start..buffer.len();
+        }
+        crate::alloc::release(std::mem::replace(&mut self.buffer, buffer));
+        Ok(())
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
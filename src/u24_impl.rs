@@ -1,29 +1,75 @@
 #![allow(non_camel_case_types)]
 
+#[cfg(feature = "std")]
 use byteorder::ReadBytesExt;
-use std::cmp::{Ordering, PartialEq, PartialOrd};
-use std::convert::{From, Into};
+use core::cmp::{Ordering, PartialEq, PartialOrd};
+use core::convert::From;
+use core::ops::{Add, BitOr, Div, Mul, Sub};
+#[cfg(feature = "std")]
 use std::io;
-use std::ops::{Add, BitOr, Div, Mul, Sub};
 
 use crate::error::BinaryError;
+use crate::prelude::*;
+use crate::profile::{Endian, WireProfile};
 use crate::Streamable;
 /// Base Implementation for a u24
 /// A u24 is 3 bytes (24 bits) wide number.
+///
+/// Like the other integers it is written in the profile's byte order, so
+/// `#[le]` or `LE<u24>` give the little endian triads RakNet uses.
+/// ```rust
+/// use binary_utils::{u24, Streamable, LE};
+///
+/// assert_eq!(u24(0x010203).parse().unwrap(), vec![1, 2, 3]);
+/// assert_eq!(LE(u24(0x010203)).parse().unwrap(), vec![3, 2, 1]);
+/// assert_eq!(LE::<u24>::compose(&[3, 2, 1, 0xff], &mut 0).unwrap().inner(), u24(0x010203));
+/// ```
 #[derive(Clone, Copy, Debug)]
 pub struct u24(pub u32); // inner is validated
 
+impl core::fmt::Display for u24 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Parses a decimal number, failing if it doesn't fit in 24 bits.
+/// ```rust
+/// use binary_utils::u24;
+///
+/// assert_eq!("70000".parse::<u24>().unwrap(), u24(70000));
+/// assert!("16777216".parse::<u24>().is_err());
+/// ```
+impl core::str::FromStr for u24 {
+    type Err = BinaryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s
+            .parse::<u32>()
+            .map_err(|error| BinaryError::RecoverableKnown(format!("{}: {}", s, error)))?;
+        if !u24::is_u24(value as usize) {
+            return Err(BinaryError::RecoverableKnown(format!(
+                "{} does not fit in a u24",
+                value
+            )));
+        }
+        Ok(u24(value))
+    }
+}
+
 impl u24 {
     pub fn is_u24(num: usize) -> bool {
-        num < 0x00FF_FFFF
+        num <= 0x00FF_FFFF
     }
 
+    /// Reads the first 3 bytes of `bytes`, most significant first.
     pub fn from_be_bytes(bytes: &[u8]) -> Self {
-        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], 0]).into()
+        u24(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
     }
 
+    /// Reads the first 3 bytes of `bytes`, least significant first.
     pub fn from_le_bytes(bytes: &[u8]) -> Self {
-        u32::from_be_bytes([0, bytes[1], bytes[2], bytes[3]]).into()
+        u24(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
     }
 
     pub fn to_le_bytes(self) -> [u8; 3] {
@@ -33,7 +79,7 @@ impl u24 {
 
     pub fn to_be_bytes(self) -> [u8; 3] {
         let bytes = self.0.to_be_bytes();
-        [bytes[0], bytes[1], bytes[2]]
+        [bytes[1], bytes[2], bytes[3]]
     }
 
     pub fn inner(self) -> u32 {
@@ -44,31 +90,72 @@ impl u24 {
 impl Streamable for u24 {
     /// Writes `self` to the given buffer.
     fn parse(&self) -> Result<Vec<u8>, BinaryError> {
-        Ok(self.to_be_bytes().to_vec().clone())
+        let mut out = crate::alloc::allocate(3);
+        self.parse_into(&mut out)?;
+        Ok(out)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        match WireProfile::current().default_endian {
+            Endian::Big => out.extend_from_slice(&self.to_be_bytes()),
+            Endian::Little => out.extend_from_slice(&self.to_le_bytes()),
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(3)
     }
     /// Reads `self` from the given buffer.
     fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
-        let buf = Self::from_be_bytes(&source[*position..]);
+        let bytes = crate::bytes_at(source, *position, 3)?;
+        let buf = match WireProfile::current().default_endian {
+            Endian::Big => Self::from_be_bytes(bytes),
+            Endian::Little => Self::from_le_bytes(bytes),
+        };
         *position += 3;
         Ok(buf)
     }
+
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        crate::skip(source, position, 3)
+    }
 }
 
+#[cfg(feature = "std")]
 pub trait u24Writer: io::Write {
     #[inline]
     fn write_u24(&mut self, num: u24) -> io::Result<usize> {
         self.write(&num.to_be_bytes())
     }
+
+    #[inline]
+    fn write_u24_le(&mut self, num: u24) -> io::Result<usize> {
+        self.write(&num.to_le_bytes())
+    }
 }
 
+#[cfg(feature = "std")]
 pub trait u24Reader: io::Read {
     #[inline]
     fn read_u24(&mut self) -> io::Result<u24> {
         let initial = [self.read_u8()?, self.read_u8()?, self.read_u8()?];
         Ok(u24::from_be_bytes(&initial))
     }
+
+    #[inline]
+    fn read_u24_le(&mut self) -> io::Result<u24> {
+        let initial = [self.read_u8()?, self.read_u8()?, self.read_u8()?];
+        Ok(u24::from_le_bytes(&initial))
+    }
 }
 
+#[cfg(feature = "std")]
+impl<W: io::Write + ?Sized> u24Writer for W {}
+
+#[cfg(feature = "std")]
+impl<R: io::Read + ?Sized> u24Reader for R {}
+
 impl Add<u24> for u24 {
     type Output = Self;
 
@@ -133,9 +220,9 @@ macro_rules! impl_primitive_u24 {
             }
         }
 
-        impl Into<$ty> for u24 {
-            fn into(self) -> $ty {
-                self.0 as $ty
+        impl From<u24> for $ty {
+            fn from(value: u24) -> Self {
+                value.0 as $ty
             }
         }
 
@@ -185,3 +272,160 @@ impl_primitive_u24!(i16);
 impl_primitive_u24!(i32);
 impl_primitive_u24!(i64);
 impl_primitive_u24!(i128);
+
+/// A signed 3 byte (24 bit) wide number, from `i24::MIN` to `i24::MAX`.
+///
+/// Decoding sign extends the top bit, and encoding a value outside of the
+/// range fails rather than silently dropping its high byte.
+/// ```rust
+/// use binary_utils::{i24, Streamable};
+///
+/// assert_eq!(i24(-2).parse().unwrap(), vec![0xff, 0xff, 0xfe]);
+/// assert_eq!(i24::compose(&[0x80, 0, 0], &mut 0).unwrap(), i24::MIN);
+/// assert!(i24(1 << 23).parse().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct i24(pub i32);
+
+impl core::fmt::Display for i24 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Parses a decimal number, failing if it doesn't fit in 24 bits.
+impl core::str::FromStr for i24 {
+    type Err = BinaryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s
+            .parse::<i32>()
+            .map_err(|error| BinaryError::RecoverableKnown(format!("{}: {}", s, error)))?;
+        i24::try_from(value)
+    }
+}
+
+impl i24 {
+    pub const MIN: i24 = i24(-0x0080_0000);
+    pub const MAX: i24 = i24(0x007F_FFFF);
+
+    pub fn is_i24(num: i64) -> bool {
+        (Self::MIN.0 as i64..=Self::MAX.0 as i64).contains(&num)
+    }
+
+    /// Reads the first 3 bytes of `bytes`, most significant first.
+    pub fn from_be_bytes(bytes: &[u8]) -> Self {
+        // shifting back down from the top byte extends the sign.
+        i24(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], 0]) >> 8)
+    }
+
+    /// Reads the first 3 bytes of `bytes`, least significant first.
+    pub fn from_le_bytes(bytes: &[u8]) -> Self {
+        i24(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8)
+    }
+
+    pub fn to_le_bytes(self) -> [u8; 3] {
+        let bytes = self.0.to_le_bytes();
+        [bytes[0], bytes[1], bytes[2]]
+    }
+
+    pub fn to_be_bytes(self) -> [u8; 3] {
+        let bytes = self.0.to_be_bytes();
+        [bytes[1], bytes[2], bytes[3]]
+    }
+
+    pub fn inner(self) -> i32 {
+        self.0
+    }
+}
+
+impl Streamable for i24 {
+    fn parse(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut out = crate::alloc::allocate(3);
+        self.parse_into(&mut out)?;
+        Ok(out)
+    }
+
+    fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), BinaryError> {
+        if !i24::is_i24(self.0 as i64) {
+            return Err(BinaryError::RecoverableKnown(format!(
+                "{} does not fit in an i24",
+                self.0
+            )));
+        }
+        match WireProfile::current().default_endian {
+            Endian::Big => out.extend_from_slice(&self.to_be_bytes()),
+            Endian::Little => out.extend_from_slice(&self.to_le_bytes()),
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(3)
+    }
+
+    fn compose(source: &[u8], position: &mut usize) -> Result<Self, BinaryError> {
+        let bytes = crate::bytes_at(source, *position, 3)?;
+        let value = match WireProfile::current().default_endian {
+            Endian::Big => Self::from_be_bytes(bytes),
+            Endian::Little => Self::from_le_bytes(bytes),
+        };
+        *position += 3;
+        Ok(value)
+    }
+
+    fn validate(source: &[u8], position: &mut usize) -> Result<(), BinaryError> {
+        crate::skip(source, position, 3)
+    }
+}
+
+impl From<i24> for i32 {
+    fn from(value: i24) -> Self {
+        value.0
+    }
+}
+
+impl From<i24> for i64 {
+    fn from(value: i24) -> Self {
+        value.0 as i64
+    }
+}
+
+macro_rules! impl_i24_try_from {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<$ty> for i24 {
+                type Error = BinaryError;
+
+                fn try_from(value: $ty) -> Result<Self, Self::Error> {
+                    match i64::try_from(value) {
+                        Ok(value) if i24::is_i24(value) => Ok(i24(value as i32)),
+                        _ => Err(BinaryError::RecoverableKnown(format!(
+                            "{} does not fit in an i24",
+                            value
+                        ))),
+                    }
+                }
+            }
+        )*
+    };
+}
+impl_i24_try_from!(i32, i64, u32, u64);
+
+impl From<i8> for i24 {
+    fn from(value: i8) -> Self {
+        i24(value.into())
+    }
+}
+
+impl From<i16> for i24 {
+    fn from(value: i16) -> Self {
+        i24(value.into())
+    }
+}
+
+impl From<u16> for i24 {
+    fn from(value: u16) -> Self {
+        i24(value.into())
+    }
+}
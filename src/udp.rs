@@ -0,0 +1,69 @@
+//! Sending and receiving single packets over UDP.
+//!
+//! Each packet is one datagram, without any framing. The tokio variants are
+//! available with the `tokio` feature.
+//! ```rust
+//! use std::net::UdpSocket;
+//! use binary_utils::udp;
+//!
+//! let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+//! let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+//! udp::send_packet(&client, server.local_addr().unwrap(), &"ping".to_string()).unwrap();
+//!
+//! let mut buffer = [0; 1500];
+//! let (packet, from) = udp::recv_packet::<String>(&server, &mut buffer).unwrap();
+//! assert_eq!(packet, "ping");
+//! assert_eq!(from, client.local_addr().unwrap());
+//! ```
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::scratch::with_scratch;
+use crate::Streamable;
+
+/// Encodes `packet` and sends it to `addr` as a single datagram.
+pub fn send_packet<T: Streamable>(
+    socket: &UdpSocket,
+    addr: impl ToSocketAddrs,
+    packet: &T,
+) -> io::Result<usize> {
+    with_scratch(|buf| {
+        packet.parse_into(buf)?;
+        socket.send_to(buf, addr)
+    })
+}
+
+/// Receives a datagram into `buffer` and decodes a `T` from it.
+///
+/// Datagrams larger than `buffer` are truncated, so it should be as large as
+/// the largest packet expected.
+pub fn recv_packet<T: Streamable>(
+    socket: &UdpSocket,
+    buffer: &mut [u8],
+) -> io::Result<(T, SocketAddr)> {
+    let (len, from) = socket.recv_from(buffer)?;
+    Ok((T::compose(&buffer[..len], &mut 0)?, from))
+}
+
+/// Encodes `packet` and sends it to `addr` as a single datagram.
+#[cfg(feature = "tokio")]
+pub async fn send_packet_async<T: Streamable>(
+    socket: &tokio::net::UdpSocket,
+    addr: impl tokio::net::ToSocketAddrs,
+    packet: &T,
+) -> io::Result<usize> {
+    socket.send_to(&packet.parse()?, addr).await
+}
+
+/// Receives a datagram into `buffer` and decodes a `T` from it.
+///
+/// Datagrams larger than `buffer` are truncated, so it should be as large as
+/// the largest packet expected.
+#[cfg(feature = "tokio")]
+pub async fn recv_packet_async<T: Streamable>(
+    socket: &tokio::net::UdpSocket,
+    buffer: &mut [u8],
+) -> io::Result<(T, SocketAddr)> {
+    let (len, from) = socket.recv_from(buffer).await?;
+    Ok((T::compose(&buffer[..len], &mut 0)?, from))
+}
@@ -1,24 +1,94 @@
+use crate::prelude::*;
+use crate::profile::WireProfile;
 use crate::Streamable;
-use byteorder::{ReadBytesExt, WriteBytesExt};
-use std::convert::{From, Into};
+#[cfg(feature = "std")]
+use byteorder::ReadBytesExt;
+use core::cmp::Ordering;
+use core::convert::{From, TryFrom};
+use core::num::TryFromIntError;
+use core::ops::{Add, AddAssign, BitOr, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+#[cfg(feature = "std")]
 use std::io::{self, Cursor};
-use std::ops::{Add, BitOr, Div, Mul, Sub};
 /// A minecraft specific unsized integer
 /// A varint can be one of `32` and `64` bits
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct VarInt<T>(pub T);
 
+impl<T: core::fmt::Display> core::fmt::Display for VarInt<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: core::str::FromStr> core::str::FromStr for VarInt<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(VarInt)
+    }
+}
+
+#[cfg(feature = "std")]
 pub trait VarIntWriter<T>: io::Write {
     fn write_var_int(&mut self, num: VarInt<T>) -> io::Result<usize>;
 }
 
+#[cfg(feature = "std")]
 pub trait VarIntReader<T>: io::Read {
     fn read_var_int(&mut self) -> io::Result<VarInt<T>>;
 }
 
+/// A 64 bit varint, as Minecraft calls them, taking up to `VAR_INT_64_BYTE_MAX` bytes.
+///
+/// `VarLong<i64>` writes negative numbers as their two's complement, so they
+/// always take the full 10 bytes; use `SVarInt<i64>` for ZigZag encoding.
+/// ```rust
+/// use binary_utils::{Streamable, VarInt, VarLong};
+///
+/// let timestamp: VarLong = VarInt(1_700_000_000_000);
+/// assert_eq!(timestamp.parse().unwrap().len(), 6);
+/// assert_eq!(VarLong::<i64>::from(-1).parse().unwrap().len(), 10);
+/// assert_eq!(VarLong::<u64>::try_from_be_bytes(&[0xac, 0x02]).unwrap(), VarInt(300));
+/// ```
+pub type VarLong<T = u64> = VarInt<T>;
+
 pub const VAR_INT_32_BYTE_MAX: usize = 5;
 pub const VAR_INT_64_BYTE_MAX: usize = 10;
 
+/// The number of bytes the varint at the start of `source` was written with,
+/// which can be more than its value needs if it was over-long.
+fn encoded_length(source: &[u8], max: usize) -> usize {
+    source
+        .iter()
+        .take(max)
+        .position(|byte| byte & 0x80 == 0)
+        .map_or(max.min(source.len()), |end| end + 1)
+}
+
+/// Advances `position` past a decoded varint, rejecting over-long encodings
+/// when the current profile is canonical.
+fn advance(
+    source: &[u8],
+    position: &mut usize,
+    minimal: usize,
+    max: usize,
+) -> Result<(), crate::error::BinaryError> {
+    let read = encoded_length(&source[*position..], max);
+    if read != minimal && WireProfile::current().canonical {
+        return Err(crate::error::BinaryError::RecoverableKnown(format!(
+            "VarInt at offset {} is not minimally encoded",
+            position
+        )));
+    }
+    *position += read;
+    Ok(())
+}
+
+/// The error for a varint still continuing after `max` bytes.
+fn too_long(max: usize) -> crate::error::BinaryError {
+    crate::error::BinaryError::RecoverableKnown(format!("VarInt is longer than {} bytes", max))
+}
+
 macro_rules! varint_impl_generic {
     ($ty:ty) => {
         impl VarInt<$ty> {
@@ -37,62 +107,90 @@ macro_rules! varint_impl_generic {
             }
 
             pub fn get_byte_length(self) -> u8 {
-                self.to_be_bytes().len() as u8
+                // seven bits per byte, and at least one byte for zero.
+                ((u32::BITS - (self.0 as u32 | 1).leading_zeros()).div_ceil(7)) as u8
             }
 
             fn to_bytes_be(self) -> Vec<u8> {
-                let mut to_write: $ty = self.0;
-                let mut buf: Vec<u8> = Vec::new();
+                let mut buf = crate::alloc::allocate(VAR_INT_32_BYTE_MAX);
+                self.write_be(&mut buf);
+                buf
+            }
+
+            fn write_be(self, buf: &mut Vec<u8>) {
+                // negative numbers are written as their two's complement bits.
+                let mut to_write = self.0 as u32;
 
                 // while there is more than a single byte to write
                 while to_write >= 0x80 {
                     // write at most a byte, to account for overflow
-                    buf.write_u8(to_write as u8 | 0x80).unwrap();
+                    buf.push(to_write as u8 | 0x80);
                     to_write >>= 7;
                 }
 
-                buf.write_u8(to_write as u8).unwrap();
-                buf
+                buf.push(to_write as u8);
             }
 
+            #[cfg(feature = "std")]
             pub fn from_be_bytes_cursor(stream: &mut Cursor<Vec<u8>>) -> Self {
-                 let mut value: $ty  = 0;
+                let mut value: u32 = 0;
 
-                 for x in (0..35).step_by(7) {
+                for x in (0..35).step_by(7) {
                     let byte = stream.read_u8().unwrap();
-                    value |= (byte & 0x7f) as $ty << x;
+                    value |= ((byte & 0x7f) as u32) << x;
 
                     // if the byte is a full length of a byte
                     // we can assume we are done
                     if byte & 0x80 == 0 {
-                         break;
+                        break;
                     }
-                 }
+                }
 
-                 VarInt::<$ty>(value)
+                VarInt::<$ty>(value as $ty)
             }
 
             pub fn from_be_bytes(bstream: &[u8]) -> Result<Self, crate::error::BinaryError> {
-                let mut stream = Cursor::new(bstream);
-                let mut value: $ty  = 0;
+                let mut stream = bstream.iter();
+                let mut value: u32 = 0;
 
                 for x in (0..35).step_by(7) {
-                   let byte = stream.read_u8()?;
-                   value |= (byte & 0x7f) as $ty << x;
+                    // running out of bytes means the varint may still arrive
+                    let byte = *stream
+                        .next()
+                        .ok_or(crate::error::BinaryError::EOF(bstream.len()))?;
+                    value |= ((byte & 0x7f) as u32) << x;
 
-                   // if the byte is a full length of a byte
-                   // we can assume we are done
-                   if byte & 0x80 == 0 {
-                        break;
-                   }
+                    // if the byte is a full length of a byte
+                    // we can assume we are done
+                    if byte & 0x80 == 0 {
+                        return Ok(VarInt::<$ty>(value as $ty));
+                    }
                 }
 
-                Ok(VarInt::<$ty>(value))
-           }
+                Err(too_long(VAR_INT_32_BYTE_MAX))
+            }
 
             //   pub fn from_le_bytes(bytes: &[u8]) -> Self {
             //       <$ty>::from_be_bytes([0, bytes[1], bytes[2], bytes[3]]).into()
             //   }
+            /// Reads a varint from `reader` a byte at a time, stopping at the
+            /// byte without the continuation bit, so nothing past it is consumed.
+            #[cfg(feature = "std")]
+            pub fn read_from(reader: &mut (impl io::Read + ?Sized)) -> io::Result<Self> {
+                let mut value: u64 = 0;
+                for x in (0..35).step_by(7) {
+                    let byte = reader.read_u8()?;
+                    value |= ((byte & 0x7f) as u64) << x;
+                    if byte & 0x80 == 0 {
+                        return Ok(VarInt::<$ty>(value as $ty));
+                    }
+                }
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "VarInt is too long",
+                ))
+            }
+
             pub fn is_var_int(_: $ty) -> bool {
                 true
             }
@@ -101,36 +199,42 @@ macro_rules! varint_impl_generic {
         impl Streamable for VarInt<$ty> {
             /// Writes `self` to the given buffer.
             fn parse(&self) -> Result<Vec<u8>, crate::error::BinaryError> {
-                Ok(self.to_be_bytes().to_vec().clone())
+                Ok(self.to_be_bytes())
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), crate::error::BinaryError> {
+                self.write_be(out);
+                Ok(())
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(self.get_byte_length() as usize)
             }
             /// Reads `self` from the given buffer.
-            fn compose(source: &[u8], position: &mut usize) -> Result<Self, crate::error::BinaryError> {
-               let v = Self::from_be_bytes(&source[*position..])?;
-               *position += v.get_byte_length() as usize;
-               Ok(v)
+            fn compose(
+                source: &[u8],
+                position: &mut usize,
+            ) -> Result<Self, crate::error::BinaryError> {
+                let v = Self::from_be_bytes(source.get(*position..).unwrap_or(&[]))?;
+                advance(
+                    source,
+                    position,
+                    v.get_byte_length() as usize,
+                    VAR_INT_32_BYTE_MAX,
+                )?;
+                Ok(v)
             }
         }
 
+        #[cfg(feature = "std")]
         impl VarIntReader<$ty> for dyn io::Read {
             #[inline]
             fn read_var_int(&mut self) -> io::Result<VarInt<$ty>> {
-                let mut value: $ty  = 0;
-
-                for x in (0..35).step_by(7) {
-                   let byte = self.read_u8().unwrap();
-                   value |= (byte & 0x7f) as $ty << x;
-
-                   // if the byte is a full length of a byte
-                   // we can assume we are done
-                   if byte & 0x80 == 0 {
-                        break;
-                   }
-                }
-
-                Ok(VarInt::<$ty>(value))
+                VarInt::<$ty>::read_from(self)
             }
         }
 
+        #[cfg(feature = "std")]
         impl VarIntWriter<$ty> for dyn io::Write {
             #[inline]
             fn write_var_int(&mut self, num: VarInt<$ty>) -> io::Result<usize> {
@@ -158,40 +262,67 @@ macro_rules! varint_impl_generic64 {
             }
 
             pub fn get_byte_length(self) -> u8 {
-                self.to_be_bytes().len() as u8
+                // seven bits per byte, and at least one byte for zero.
+                ((u64::BITS - (self.0 as u64 | 1).leading_zeros()).div_ceil(7)) as u8
             }
 
             fn to_bytes_be(self) -> Vec<u8> {
-                let mut to_write: $ty = self.0;
-                let mut buf: Vec<u8> = Vec::new();
+                let mut buf = crate::alloc::allocate(VAR_INT_64_BYTE_MAX);
+                self.write_be(&mut buf);
+                buf
+            }
+
+            fn write_be(self, buf: &mut Vec<u8>) {
+                // negative numbers are written as their two's complement bits.
+                let mut to_write = self.0 as u64;
 
                 // while there is more than a single byte to write
                 while to_write >= 0x80 {
                     // write at most a byte, to account for overflow
-                    buf.write_u8(to_write as u8 | 0x80).unwrap();
+                    buf.push(to_write as u8 | 0x80);
                     to_write >>= 7;
                 }
 
-                buf.write_u8(to_write as u8).unwrap();
-                buf
+                buf.push(to_write as u8);
             }
 
+            #[cfg(feature = "std")]
             pub fn from_be_bytes(stream: &mut Cursor<Vec<u8>>) -> Self {
-               let mut value: $ty  = 0;
+                let mut value: u64 = 0;
+
+                for x in (0..70).step_by(7) {
+                    let byte = stream.read_u8().unwrap();
+                    value |= ((byte & 0x7f) as u64) << x;
+
+                    // if the byte is a full length of a byte
+                    // we can assume we are done
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                }
+
+                VarInt::<$ty>(value as $ty)
+            }
 
-               for x in (0..70).step_by(7) {
-                  let byte = stream.read_u8().unwrap();
-                  value |= (byte & 0x7f) as $ty << x;
+            /// Decodes the varint at the start of `bytes`, which may take up to
+            /// `VAR_INT_64_BYTE_MAX` bytes.
+            pub fn try_from_be_bytes(bytes: &[u8]) -> Result<Self, crate::error::BinaryError> {
+                let mut value: u64 = 0;
 
-                  // if the byte is a full length of a byte
-                  // we can assume we are done
-                  if byte & 0x80 == 0 {
-                       break;
-                  }
-               }
+                for (x, byte) in (0..70).step_by(7).zip(bytes.iter().copied()) {
+                    value |= ((byte & 0x7f) as u64) << x;
 
-               VarInt::<$ty>(value)
-          }
+                    if byte & 0x80 == 0 {
+                        return Ok(VarInt::<$ty>(value as $ty));
+                    }
+                }
+
+                if bytes.len() < VAR_INT_64_BYTE_MAX {
+                    Err(crate::error::BinaryError::EOF(bytes.len()))
+                } else {
+                    Err(too_long(VAR_INT_64_BYTE_MAX))
+                }
+            }
 
             //   pub fn from_be_bytes(bytes: &[u8]) -> Self {
             //       <$ty>::from_be_bytes([bytes[0], bytes[1], bytes[2], 0]).into()
@@ -200,6 +331,24 @@ macro_rules! varint_impl_generic64 {
             //   pub fn from_le_bytes(bytes: &[u8]) -> Self {
             //       <$ty>::from_be_bytes([0, bytes[1], bytes[2], bytes[3]]).into()
             //   }
+            /// Reads a varint from `reader` a byte at a time, stopping at the
+            /// byte without the continuation bit, so nothing past it is consumed.
+            #[cfg(feature = "std")]
+            pub fn read_from(reader: &mut (impl io::Read + ?Sized)) -> io::Result<Self> {
+                let mut value: u64 = 0;
+                for x in (0..70).step_by(7) {
+                    let byte = reader.read_u8()?;
+                    value |= ((byte & 0x7f) as u64) << x;
+                    if byte & 0x80 == 0 {
+                        return Ok(VarInt::<$ty>(value as $ty));
+                    }
+                }
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "VarInt is too long",
+                ))
+            }
+
             pub fn is_var_int(_: $ty) -> bool {
                 true
             }
@@ -208,13 +357,37 @@ macro_rules! varint_impl_generic64 {
         impl Streamable for VarInt<$ty> {
             /// Writes `self` to the given buffer.
             fn parse(&self) -> Result<Vec<u8>, crate::error::BinaryError> {
-                Ok(self.to_be_bytes().to_vec().clone())
+                Ok(self.to_be_bytes())
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), crate::error::BinaryError> {
+                self.write_be(out);
+                Ok(())
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(self.get_byte_length() as usize)
             }
             /// Reads `self` from the given buffer.
-            fn compose(source: &[u8], position: &mut usize) -> Result<Self, crate::error::BinaryError> {
-               let v = Self::from_be_bytes(&mut Cursor::new(source[*position..].to_vec()));
-               *position += v.get_byte_length() as usize;
-               Ok(v)
+            fn compose(
+                source: &[u8],
+                position: &mut usize,
+            ) -> Result<Self, crate::error::BinaryError> {
+                let available = source.get(*position..).unwrap_or(&[]);
+                // the varint must end before the buffer does.
+                let v = Self::try_from_be_bytes(available).map_err(|e| match e {
+                    crate::error::BinaryError::EOF(_) => {
+                        crate::error::BinaryError::EOF(source.len())
+                    }
+                    e => e,
+                })?;
+                advance(
+                    source,
+                    position,
+                    v.get_byte_length() as usize,
+                    VAR_INT_64_BYTE_MAX,
+                )?;
+                Ok(v)
             }
         }
     };
@@ -226,18 +399,6 @@ varint_impl_generic64!(i64);
 
 macro_rules! impl_primitive_VarInt {
     ($ty:ty, $vk:ty) => {
-        impl From<$ty> for VarInt<$vk> {
-            fn from(value: $ty) -> Self {
-                if !VarInt::<$vk>::is_var_int(value as $vk) {
-                    panic!(
-                        "Can not convert a number larger than the bounds of a VarInt into a VarInt"
-                    )
-                } else {
-                    VarInt(value as $vk)
-                }
-            }
-        }
-
         impl BitOr<$ty> for VarInt<$vk> {
             type Output = Self;
 
@@ -246,12 +407,6 @@ macro_rules! impl_primitive_VarInt {
             }
         }
 
-        impl Into<$ty> for VarInt<$vk> {
-            fn into(self) -> $ty {
-                self.0 as $ty
-            }
-        }
-
         impl Add<$ty> for VarInt<$vk> {
             type Output = Self;
 
@@ -299,3 +454,289 @@ impl_primitive_VarInt!(i64, u64);
 impl_primitive_VarInt!(f32, u64);
 impl_primitive_VarInt!(f64, u64);
 impl_primitive_VarInt!(i128, u64);
+impl_primitive_VarInt!(i32, i32);
+impl_primitive_VarInt!(u64, u64);
+impl_primitive_VarInt!(i64, i64);
+
+/// Lossless conversions from a primitive into a `VarInt`.
+macro_rules! impl_var_int_from {
+    ($vk:ty: $($ty:ty),*) => {
+        $(
+            impl From<$ty> for VarInt<$vk> {
+                fn from(value: $ty) -> Self {
+                    VarInt(value.into())
+                }
+            }
+        )*
+    };
+}
+
+/// Lossless conversions from a `VarInt` into a primitive.
+macro_rules! impl_var_int_into {
+    ($vk:ty: $($ty:ty),*) => {
+        $(
+            impl From<VarInt<$vk>> for $ty {
+                fn from(value: VarInt<$vk>) -> Self {
+                    value.0.into()
+                }
+            }
+        )*
+    };
+}
+
+/// Checked conversions from a primitive into a `VarInt`, failing when the value doesn't fit.
+macro_rules! impl_var_int_try_from {
+    ($vk:ty: $($ty:ty),*) => {
+        $(
+            impl TryFrom<$ty> for VarInt<$vk> {
+                type Error = TryFromIntError;
+
+                fn try_from(value: $ty) -> Result<Self, Self::Error> {
+                    <$vk>::try_from(value).map(VarInt)
+                }
+            }
+        )*
+    };
+}
+
+/// Checked conversions from a `VarInt` into a narrower primitive.
+macro_rules! impl_var_int_try_into {
+    ($vk:ty: $($ty:ty),*) => {
+        $(
+            impl TryFrom<VarInt<$vk>> for $ty {
+                type Error = TryFromIntError;
+
+                fn try_from(value: VarInt<$vk>) -> Result<Self, Self::Error> {
+                    <$ty>::try_from(value.0)
+                }
+            }
+        )*
+    };
+}
+
+/// Checked conversions from floats, and into the floats that can't hold every
+/// value, failing unless the value is a whole number both types hold exactly.
+macro_rules! impl_var_int_float {
+    ($vk:ty: $($float:ty),*; $($lossy:ty),*) => {
+        $(
+            impl TryFrom<$float> for VarInt<$vk> {
+                type Error = crate::error::BinaryError;
+
+                fn try_from(value: $float) -> Result<Self, Self::Error> {
+                    let wide = value as f64;
+                    // `MAX + 1` is a power of two, so it is exact as an f64.
+                    if wide >= <$vk>::MIN as f64
+                        && wide < <$vk>::MAX as f64 + 1.0
+                        && (wide as $vk) as f64 == wide
+                    {
+                        Ok(VarInt(wide as $vk))
+                    } else {
+                        Err(crate::error::BinaryError::RecoverableKnown(format!(
+                            "{} does not fit in a VarInt<{}>",
+                            value,
+                            stringify!($vk)
+                        )))
+                    }
+                }
+            }
+        )*
+        $(
+            impl TryFrom<VarInt<$vk>> for $lossy {
+                type Error = crate::error::BinaryError;
+
+                fn try_from(value: VarInt<$vk>) -> Result<Self, Self::Error> {
+                    let float = value.0 as $lossy;
+                    if float as i128 == value.0 as i128 {
+                        Ok(float)
+                    } else {
+                        Err(crate::error::BinaryError::RecoverableKnown(format!(
+                            "{} can not be held exactly by an {}",
+                            value.0,
+                            stringify!($lossy)
+                        )))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_var_int_from!(u32: u8, u16, u32);
+impl_var_int_into!(u32: u32, u64, u128, i64, i128, f64);
+impl_var_int_try_from!(u32: u64, u128, i8, i16, i32, i64);
+impl_var_int_try_into!(u32: u8, u16, i8, i16, i32);
+impl_var_int_float!(u32: f32, f64; f32);
+
+impl_var_int_from!(i32: i8, i16, i32, u8, u16);
+impl_var_int_into!(i32: i32, i64, i128);
+impl_var_int_try_from!(i32: u32, u64);
+impl_var_int_try_into!(i32: i8, i16, u8, u16, u32, u64);
+
+impl_var_int_from!(u64: u8, u16, u32, u64);
+impl_var_int_into!(u64: u64, u128, i128);
+impl_var_int_try_from!(u64: u128, i8, i16, i32, i64, i128);
+impl_var_int_try_into!(u64: u8, u16, u32, i8, i16, i32, i64);
+impl_var_int_float!(u64: f32, f64; f32, f64);
+
+impl_var_int_from!(i64: i8, i16, i32, i64, u8, u16, u32);
+impl_var_int_into!(i64: i64, i128);
+impl_var_int_try_from!(i64: u64);
+impl_var_int_try_into!(i64: i8, i16, i32, u8, u16, u32);
+
+/// Compound assignment and comparisons against the inner type.
+macro_rules! impl_var_int_ops {
+    ($($ty:ty),*) => {
+        $(
+            impl AddAssign<$ty> for VarInt<$ty> {
+                fn add_assign(&mut self, other: $ty) {
+                    self.0 += other;
+                }
+            }
+
+            impl SubAssign<$ty> for VarInt<$ty> {
+                fn sub_assign(&mut self, other: $ty) {
+                    self.0 -= other;
+                }
+            }
+
+            impl MulAssign<$ty> for VarInt<$ty> {
+                fn mul_assign(&mut self, other: $ty) {
+                    self.0 *= other;
+                }
+            }
+
+            impl DivAssign<$ty> for VarInt<$ty> {
+                fn div_assign(&mut self, other: $ty) {
+                    self.0 /= other;
+                }
+            }
+
+            impl PartialEq<$ty> for VarInt<$ty> {
+                fn eq(&self, other: &$ty) -> bool {
+                    self.0 == *other
+                }
+            }
+
+            impl PartialOrd<$ty> for VarInt<$ty> {
+                fn partial_cmp(&self, other: &$ty) -> Option<Ordering> {
+                    self.0.partial_cmp(other)
+                }
+            }
+        )*
+    };
+}
+impl_var_int_ops!(u32, i32, u64, i64);
+
+impl<T: Add<Output = T>> Add for VarInt<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        VarInt(self.0 + other.0)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for VarInt<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        VarInt(self.0 - other.0)
+    }
+}
+
+impl<T: Mul<Output = T>> Mul for VarInt<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        VarInt(self.0 * other.0)
+    }
+}
+
+impl<T: Div<Output = T>> Div for VarInt<T> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self::Output {
+        VarInt(self.0 / other.0)
+    }
+}
+
+/// A signed varint, ZigZag encoded as protobuf's `sint32` and `sint64` and
+/// Bedrock's signed varints are.
+///
+/// `VarInt<i32>` writes the two's complement bits, so a negative number takes
+/// the full five bytes; ZigZag maps `0, -1, 1, -2, ...` to `0, 1, 2, 3, ...`
+/// first, so numbers close to zero stay short either way.
+/// ```rust
+/// use binary_utils::{SVarInt, Streamable, VarInt};
+///
+/// assert_eq!(VarInt(-1i32).parse().unwrap(), vec![0xff, 0xff, 0xff, 0xff, 0x0f]);
+/// assert_eq!(SVarInt(-1i32).parse().unwrap(), vec![0x01]);
+/// assert_eq!(SVarInt(64i32).parse().unwrap(), vec![0x80, 0x01]);
+/// assert_eq!(SVarInt::<i64>::compose(&[0x03], &mut 0).unwrap(), SVarInt(-2));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SVarInt<T>(pub T);
+
+impl<T: core::fmt::Display> core::fmt::Display for SVarInt<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: core::str::FromStr> core::str::FromStr for SVarInt<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(SVarInt)
+    }
+}
+
+macro_rules! svarint_impl {
+    ($ty:ty, $unsigned:ty) => {
+        impl SVarInt<$ty> {
+            /// The unsigned value `self` is written as.
+            pub fn zigzag(self) -> $unsigned {
+                ((self.0 << 1) ^ (self.0 >> (<$ty>::BITS - 1))) as $unsigned
+            }
+
+            /// The signed value written as `value`.
+            pub fn from_zigzag(value: $unsigned) -> Self {
+                SVarInt((value >> 1) as $ty ^ -((value & 1) as $ty))
+            }
+        }
+
+        impl Streamable for SVarInt<$ty> {
+            fn parse(&self) -> Result<Vec<u8>, crate::error::BinaryError> {
+                VarInt(self.zigzag()).parse()
+            }
+
+            fn parse_into(&self, out: &mut Vec<u8>) -> Result<(), crate::error::BinaryError> {
+                VarInt(self.zigzag()).parse_into(out)
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                VarInt(self.zigzag()).size_hint()
+            }
+
+            fn compose(
+                source: &[u8],
+                position: &mut usize,
+            ) -> Result<Self, crate::error::BinaryError> {
+                VarInt::<$unsigned>::compose(source, position).map(|v| Self::from_zigzag(v.0))
+            }
+        }
+
+        impl From<$ty> for SVarInt<$ty> {
+            fn from(value: $ty) -> Self {
+                SVarInt(value)
+            }
+        }
+
+        impl From<SVarInt<$ty>> for $ty {
+            fn from(value: SVarInt<$ty>) -> Self {
+                value.0
+            }
+        }
+    };
+}
+svarint_impl!(i32, u32);
+svarint_impl!(i64, u64);
@@ -0,0 +1,36 @@
+//! Converts registered packets to and from WebSocket binary messages.
+//!
+//! Every binary message carries exactly one frame of the registry, so a
+//! gateway can speak the same protocol over WebSockets as over raw sockets.
+//!
+//! This module is only available with the `ws` feature.
+use tungstenite::Message;
+
+use crate::error::BinaryError;
+use crate::registry::Registry;
+use crate::Streamable;
+
+/// Encodes `packet` into a `Message::Binary`.
+pub fn to_message<M, T>(registry: &Registry<M>, packet: &T) -> Result<Message, BinaryError>
+where
+    T: Streamable + 'static,
+{
+    Ok(Message::Binary(registry.encode(packet)?.into()))
+}
+
+/// Decodes the packet carried by a `Message::Binary`.
+///
+/// Control messages (ping, pong, close) carry no packet and return `None`,
+/// text messages are not part of the protocol and are an error.
+pub fn from_message<M>(
+    registry: &Registry<M>,
+    message: &Message,
+) -> Result<Option<M>, BinaryError> {
+    match message {
+        Message::Binary(data) => Ok(Some(registry.decode(&data[..])?)),
+        Message::Text(_) => Err(BinaryError::RecoverableKnown(
+            "Text messages can not be decoded as packets.".to_owned(),
+        )),
+        _ => Ok(None),
+    }
+}
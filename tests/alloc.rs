@@ -0,0 +1,72 @@
+#![cfg(feature = "derive")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use binary_utils::alloc::{self, BufferAlloc, Global};
+use binary_utils::scratch::with_scratch;
+use binary_utils::*;
+
+struct Counting {
+    allocated: AtomicUsize,
+    released: AtomicUsize,
+}
+
+impl BufferAlloc for Counting {
+    fn allocate(&self, capacity: usize) -> Vec<u8> {
+        self.allocated.fetch_add(1, Ordering::SeqCst);
+        Vec::with_capacity(capacity)
+    }
+
+    fn release(&self, buffer: Vec<u8>) {
+        self.released.fetch_add(1, Ordering::SeqCst);
+        drop(buffer);
+    }
+}
+
+static COUNTING: Counting = Counting {
+    allocated: AtomicUsize::new(0),
+    released: AtomicUsize::new(0),
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Chunk {
+    pub x: i32,
+    pub data: Vec<u8>,
+}
+
+#[test]
+fn codec_buffers_come_from_the_installed_allocator() {
+    alloc::set_buffer_alloc(&COUNTING);
+
+    let before = COUNTING.allocated.load(Ordering::SeqCst);
+    let chunk = Chunk {
+        x: 1,
+        data: vec![1, 2, 3],
+    };
+    let bytes = chunk.parse().unwrap();
+    assert_eq!(Chunk::compose(&bytes, &mut 0).unwrap(), chunk);
+    // just the encoded struct, its fields encode into the same buffer.
+    assert_eq!(COUNTING.allocated.load(Ordering::SeqCst), before + 1);
+
+    // built-in types encode into buffers from the allocator too.
+    let allocations = |parse: &dyn Fn() -> Vec<u8>| {
+        let before = COUNTING.allocated.load(Ordering::SeqCst);
+        alloc::release(parse());
+        COUNTING.allocated.load(Ordering::SeqCst) - before
+    };
+    assert_eq!(allocations(&|| 1u32.parse().unwrap()), 1);
+    assert_eq!(allocations(&|| "name".to_string().parse().unwrap()), 1);
+    assert_eq!(allocations(&|| VarInt(300u32).parse().unwrap()), 1);
+    assert_eq!(allocations(&|| (1u8, true).parse().unwrap()), 1);
+
+    // nesting deeper than the pool gives buffers back to the allocator.
+    let released = COUNTING.released.load(Ordering::SeqCst);
+    fn nest(depth: usize) {
+        if depth > 0 {
+            with_scratch(|_| nest(depth - 1));
+        }
+    }
+    nest(12);
+    assert!(COUNTING.released.load(Ordering::SeqCst) > released);
+
+    alloc::set_buffer_alloc(&Global);
+}
@@ -0,0 +1,70 @@
+#![cfg(all(feature = "derive", not(feature = "forbid-unsafe")))]
+use binary_utils::testing::{count_allocs, CountingAllocator};
+use binary_utils::*;
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+#[derive(BinaryStream)]
+pub struct Move {
+    pub entity: u64,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[test]
+fn counts_the_allocations_of_a_closure() {
+    let (_, count) = count_allocs(|| vec![1u8].len() + vec![2u8].len());
+    assert_eq!(count, 2);
+
+    let packet = Move {
+        entity: 1,
+        x: 0.0,
+        y: 1.0,
+    };
+    // the first encode on a thread allocates its scratch buffer
+    packet.parse().unwrap();
+    let bytes = assert_allocs!(<= 1, || packet.parse().unwrap());
+    assert_allocs!(== 0, || Move::compose(&bytes, &mut 0).unwrap());
+}
+
+#[test]
+fn frames_encode_through_the_scratch_pool() {
+    let packet = Move {
+        entity: 1,
+        x: 0.0,
+        y: 1.0,
+    };
+    let mut out = Vec::with_capacity(64);
+    // the first encode on a thread allocates its scratch buffer
+    framing::encode_frame(&packet, framing::LengthPrefix::VarInt, &mut out).unwrap();
+
+    // going through `parse` costs at least a buffer per frame...
+    out.clear();
+    assert_allocs!(>= 1, || {
+        let body = packet.parse().unwrap();
+        framing::LengthPrefix::VarInt.write(body.len(), &mut out).unwrap();
+        out.extend_from_slice(&body);
+    });
+    // ...while the scratch buffer is reused.
+    out.clear();
+    assert_allocs!(== 0, || {
+        framing::encode_frame(&packet, framing::LengthPrefix::VarInt, &mut out).unwrap()
+    });
+}
+
+#[test]
+fn nested_counts_add_up() {
+    let (inner, outer) = count_allocs(|| {
+        let _first = Vec::<u8>::with_capacity(4);
+        count_allocs(|| Vec::<u8>::with_capacity(4)).1
+    });
+    assert_eq!(inner, 1);
+    assert_eq!(outer, 2);
+}
+
+#[test]
+#[should_panic(expected = "`assert_allocs!`")]
+fn over_budget_panics() {
+    assert_allocs!(== 0, || vec![1u8]);
+}
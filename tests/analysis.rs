@@ -0,0 +1,118 @@
+#![cfg(feature = "registry")]
+use binary_utils::{
+    analysis::Analyzer,
+    framing::LengthPrefix,
+    registry::{Framing, PacketId, Registry},
+    *,
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Move {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Packet {
+    Move(Move),
+    Chat(String),
+}
+
+impl From<Move> for Packet {
+    fn from(v: Move) -> Self {
+        Packet::Move(v)
+    }
+}
+
+impl From<String> for Packet {
+    fn from(v: String) -> Self {
+        Packet::Chat(v)
+    }
+}
+
+fn registry(framing: Framing) -> Registry<Packet> {
+    let mut registry = Registry::new(PacketId::U8, framing);
+    registry.register::<Move>(0x01).register::<String>(0x02);
+    registry
+}
+
+#[test]
+fn analysis_counts_results_per_packet() {
+    let registry = registry(Framing::LengthPrefixed(LengthPrefix::U16));
+    let mut capture = Vec::new();
+    registry
+        .encode_into(&Move { x: 1, y: 2 }, &mut capture)
+        .unwrap();
+    registry
+        .encode_into(&"hi".to_string(), &mut capture)
+        .unwrap();
+    // a move missing its y, twice, then one with an extra field.
+    capture.extend_from_slice(&[0, 5, 0x01, 0, 0, 0, 1]);
+    capture.extend_from_slice(&[0, 5, 0x01, 0, 0, 0, 1]);
+    capture.extend_from_slice(&[0, 10, 0x01, 0, 0, 0, 1, 0, 0, 0, 2, 9]);
+    // an id nothing is registered under.
+    capture.extend_from_slice(&[0, 1, 0x07]);
+
+    let mut analyzer = Analyzer::new(&registry);
+    analyzer.stream(&capture);
+    let report = analyzer.report();
+
+    assert_eq!(report.frames, 6);
+    assert_eq!(report.skipped_bytes, 0);
+
+    let moves = report.packet(0x01).unwrap();
+    assert_eq!((moves.decoded, moves.failed), (1, 3));
+    assert_eq!(moves.success_rate(), 0.25);
+    assert_eq!(moves.failures[0].offset, 5);
+    assert_eq!(moves.failures[0].count, 2);
+    assert_eq!(moves.failures[1].offset, 9);
+    assert_eq!(moves.failures[1].error, "1 trailing bytes");
+    assert!(moves.name.unwrap().ends_with("Move"));
+
+    let chat = report.packet(0x02).unwrap();
+    assert_eq!((chat.decoded, chat.failed), (1, 0));
+
+    let unknown = report.packet(0x07).unwrap();
+    assert_eq!((unknown.name, unknown.failed), (None, 1));
+
+    let text = report.to_string();
+    assert!(text.starts_with("6 frames, 0 unreadable, 0 bytes skipped"));
+    assert!(text.contains("1/4 decoded (25.0%)"));
+    assert!(text.contains("2x at offset 5"));
+}
+
+#[test]
+fn analysis_skips_broken_framing() {
+    let registry = registry(Framing::LengthPrefixed(LengthPrefix::U16));
+    let mut capture = Vec::new();
+    registry
+        .encode_into(&Move { x: 1, y: 2 }, &mut capture)
+        .unwrap();
+    capture.extend_from_slice(&[0xFF, 0xFF, 0x13]);
+    registry
+        .encode_into(&"hi".to_string(), &mut capture)
+        .unwrap();
+
+    let mut analyzer = Analyzer::new(&registry);
+    analyzer.stream(&capture);
+    let report = analyzer.report();
+
+    assert_eq!(report.frames, 2);
+    assert_eq!(report.skipped_bytes, 3);
+    assert_eq!(report.packet(0x02).unwrap().decoded, 1);
+}
+
+#[test]
+fn analysis_of_datagrams() {
+    let registry = registry(Framing::Datagram);
+    let mut analyzer = Analyzer::new(&registry);
+    analyzer.datagram(&registry.encode(&Move { x: 1, y: 2 }).unwrap());
+    analyzer.datagram(&[0x02, 0, 5, b'h']);
+    analyzer.datagram(&[]);
+
+    let report = analyzer.report();
+    assert_eq!(report.frames, 3);
+    assert_eq!(report.unreadable, 1);
+    assert_eq!(report.packet(0x01).unwrap().decoded, 1);
+    assert_eq!(report.packet(0x02).unwrap().failed, 1);
+}
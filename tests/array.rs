@@ -0,0 +1,46 @@
+#![cfg(feature = "derive")]
+use binary_utils::layout::FixedSize;
+use binary_utils::profile::WireProfile;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Handshake {
+    pub magic: [u8; 4],
+    pub token: [u16; 2],
+    pub address: [u8; 16],
+}
+
+fn handshake() -> Handshake {
+    Handshake {
+        magic: [0x00, 0xFF, 0xFF, 0x00],
+        token: [1, 2],
+        address: [0xFE, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    }
+}
+
+#[test]
+fn arrays_have_no_length_prefix() {
+    let bytes = handshake().parse().unwrap();
+    assert_eq!(bytes.len(), 24);
+    assert_eq!(&bytes[..8], &[0x00, 0xFF, 0xFF, 0x00, 0, 1, 0, 2]);
+    assert_eq!(Handshake::compose(&bytes, &mut 0).unwrap(), handshake());
+    assert_eq!(<Handshake as FixedSize>::WIRE_SIZE, 24);
+    assert_eq!(handshake().size_hint(), Some(24));
+}
+
+#[test]
+fn array_items_follow_the_profile() {
+    let little = WireProfile::DEFAULT.with_endian(profile::Endian::Little);
+    assert_eq!(little.encode(&[1u16, 2]).unwrap(), vec![1, 0, 2, 0]);
+    assert_eq!(
+        [String::from("a"), String::from("bc")].parse().unwrap(),
+        vec![0, 1, b'a', 0, 2, b'b', b'c']
+    );
+}
+
+#[test]
+fn short_arrays_are_rejected() {
+    assert!(<[u32; 2]>::compose(&[0, 0, 0, 1, 0, 0], &mut 0).is_err());
+    assert!(<[u32; 2]>::validate(&[0, 0, 0, 1, 0, 0], &mut 0).is_err());
+    assert_eq!(<[u8; 0]>::compose(&[], &mut 0).unwrap(), []);
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "tokio")]
+
+use binary_utils::async_io::{read_streamable, write_streamable};
+use binary_utils::{BinaryStream, Streamable, VarInt};
+use tokio::io::{AsyncWriteExt, BufReader};
+
+#[derive(Debug, PartialEq, BinaryStream)]
+struct Login {
+    protocol: VarInt<u32>,
+    name: String,
+}
+
+#[tokio::test]
+async fn reads_values_back_to_back() {
+    let (mut writer, reader) = tokio::io::duplex(4);
+    let login = Login {
+        protocol: VarInt(589),
+        name: "Netrex".into(),
+    };
+
+    let sender = tokio::spawn(async move {
+        write_streamable(&mut writer, &login).await.unwrap();
+        write_streamable(&mut writer, &7u16).await.unwrap();
+    });
+
+    // a tiny buffer makes every value span several reads
+    let mut reader = BufReader::with_capacity(1, reader);
+    let login: Login = read_streamable(&mut reader, 64).await.unwrap();
+    assert_eq!(login.protocol, VarInt(589));
+    assert_eq!(login.name, "Netrex");
+    assert_eq!(read_streamable::<_, u16>(&mut reader, 64).await.unwrap(), 7);
+    sender.await.unwrap();
+}
+
+#[tokio::test]
+async fn leaves_the_rest_buffered() {
+    let mut bytes = 1u8.parse().unwrap();
+    bytes.extend(2u32.parse().unwrap());
+    let mut reader = BufReader::new(&bytes[..]);
+    assert_eq!(read_streamable::<_, u8>(&mut reader, 64).await.unwrap(), 1);
+    assert_eq!(read_streamable::<_, u32>(&mut reader, 64).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn errors_on_truncated_values() {
+    let (mut writer, reader) = tokio::io::duplex(16);
+    writer.write_all(&[0, 5, b'a']).await.unwrap();
+    drop(writer);
+    let error = read_streamable::<_, String>(&mut BufReader::new(reader), 64)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn errors_once_a_value_is_longer_than_max_len() {
+    // a length prefix promising far more than the limit
+    let (mut writer, reader) = tokio::io::duplex(16);
+    writer.write_all(&[0xff, 0xff, b'a', b'b']).await.unwrap();
+    let mut reader = BufReader::with_capacity(1, reader);
+    let error = read_streamable::<_, String>(&mut reader, 3)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    let bytes = "four".to_string().parse().unwrap();
+    let error = read_streamable::<_, String>(&mut BufReader::new(&bytes[..]), 5)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    let name: String = read_streamable(&mut BufReader::new(&bytes[..]), 6)
+        .await
+        .unwrap();
+    assert_eq!(name, "four");
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "derive")]
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use binary_utils::{
+    profile::{Endian, WireProfile},
+    *,
+};
+
+#[derive(Debug, BinaryStream)]
+pub struct ServerStats {
+    pub players: AtomicU32,
+    pub ticks: AtomicU64,
+    pub running: AtomicBool,
+}
+
+#[test]
+fn snapshot_shared_counters() {
+    let stats = ServerStats {
+        players: AtomicU32::new(0),
+        ticks: AtomicU64::new(0),
+        running: AtomicBool::new(false),
+    };
+    stats.players.fetch_add(3, Ordering::Relaxed);
+    stats.ticks.fetch_add(20, Ordering::Relaxed);
+    stats.running.store(true, Ordering::Relaxed);
+
+    let bytes = stats.parse().unwrap();
+    assert_eq!(bytes, vec![0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 20, 1]);
+
+    let copy = ServerStats::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(copy.players.load(Ordering::Relaxed), 3);
+    assert_eq!(copy.ticks.load(Ordering::Relaxed), 20);
+    assert!(copy.running.load(Ordering::Relaxed));
+}
+
+#[test]
+fn ordering_and_endian_come_from_the_profile() {
+    let counter = AtomicU32::new(1);
+    // a store ordering is loaded with its load counterpart.
+    let profile = WireProfile::DEFAULT
+        .with_endian(Endian::Little)
+        .with_atomic_ordering(Ordering::Release);
+    assert_eq!(profile.encode(&counter).unwrap(), vec![1, 0, 0, 0]);
+}
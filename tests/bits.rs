@@ -0,0 +1,91 @@
+#![cfg(feature = "derive")]
+use binary_utils::bits::{BitReader, BitWriter};
+use binary_utils::layout::FixedSize;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BitIo)]
+pub struct FrameFlags {
+    #[bits(3)]
+    pub reliability: u8,
+    #[bits(1)]
+    pub split: bool,
+    #[bits(4)]
+    pub reserved: u8,
+}
+
+#[derive(Debug, PartialEq, BitIo)]
+pub struct Wide {
+    #[bits(12)]
+    pub index: u16,
+    pub marker: bool,
+    pub full: u8,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Frame {
+    pub flags: FrameFlags,
+    pub length: u16,
+}
+
+#[test]
+fn bit_io_frame_flags() {
+    let flags = FrameFlags {
+        reliability: 6,
+        split: true,
+        reserved: 0,
+    };
+    assert_eq!(flags.parse().unwrap(), vec![0b1101_0000]);
+    assert_eq!(FrameFlags::WIRE_SIZE, 1);
+
+    let frame = Frame { flags, length: 9 };
+    let bytes = frame.parse().unwrap();
+    assert_eq!(bytes, vec![0b1101_0000, 0, 9]);
+    assert_eq!(Frame::compose(&bytes, &mut 0).unwrap(), frame);
+}
+
+#[test]
+fn bit_io_pads_last_byte() {
+    let wide = Wide {
+        index: 0xABC,
+        marker: true,
+        full: 0xFF,
+    };
+    let bytes = wide.parse().unwrap();
+    assert_eq!(bytes, vec![0xAB, 0xCF, 0xF8]);
+    assert_eq!(Wide::WIRE_SIZE, 3);
+
+    let mut position = 0;
+    assert_eq!(Wide::compose(&bytes, &mut position).unwrap(), wide);
+    assert_eq!(position, 3);
+}
+
+#[test]
+fn bit_io_rejects_values_wider_than_field() {
+    let flags = FrameFlags {
+        reliability: 8,
+        split: false,
+        reserved: 0,
+    };
+    assert!(flags.parse().is_err());
+    assert!(Wide::compose(&[0xAB], &mut 0).is_err());
+}
+
+#[test]
+fn bit_reader_and_writer() {
+    let mut writer = BitWriter::new();
+    writer.write_bits(1, 1).unwrap();
+    writer.write_bits(0x1FF, 9).unwrap();
+    writer.write(&true, 1).unwrap();
+    assert_eq!(writer.len(), 11);
+
+    let bytes = writer.into_bytes();
+    assert_eq!(bytes, vec![0xFF, 0xE0]);
+
+    let mut reader = BitReader::new(&bytes);
+    assert_eq!(reader.read_bits(1).unwrap(), 1);
+    assert_eq!(reader.read::<u16>(9).unwrap(), 0x1FF);
+    assert!(reader.read::<bool>(1).unwrap());
+    assert_eq!(reader.position(), 11);
+    assert_eq!(reader.bytes_read(), 2);
+    assert!(reader.read_bits(6).is_err());
+}
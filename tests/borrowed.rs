@@ -0,0 +1,75 @@
+#![cfg(feature = "derive")]
+use binary_utils::borrowed::StreamableRef;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Text {
+    pub id: VarInt<u32>,
+    pub message: String,
+    pub attachment: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, BinaryStreamRef)]
+pub struct TextRef<'a> {
+    pub id: VarInt<u32>,
+    pub message: &'a str,
+    pub attachment: &'a [u8],
+}
+
+fn text() -> Text {
+    Text {
+        id: VarInt(300),
+        message: "hello world".into(),
+        attachment: vec![1, 2, 3, 4],
+    }
+}
+
+#[test]
+fn reads_the_owned_layout() {
+    let bytes = text().parse().unwrap();
+    let mut position = 0;
+    let borrowed = TextRef::compose_ref(&bytes, &mut position).unwrap();
+    assert_eq!(position, bytes.len());
+    assert_eq!(borrowed.id, VarInt(300));
+    assert_eq!(borrowed.message, "hello world");
+    assert_eq!(borrowed.attachment, &[1, 2, 3, 4]);
+
+    let mut out = Vec::new();
+    borrowed.parse_ref(&mut out).unwrap();
+    assert_eq!(out, bytes);
+    assert_eq!(Text::compose(&out, &mut 0).unwrap(), text());
+}
+
+#[test]
+fn borrows_instead_of_copying() {
+    let bytes = text().parse().unwrap();
+    let borrowed = TextRef::compose_ref(&bytes, &mut 0).unwrap();
+    let range = bytes.as_ptr_range();
+    assert!(range.contains(&borrowed.message.as_ptr()));
+    assert!(range.contains(&borrowed.attachment.as_ptr()));
+}
+
+#[test]
+fn follows_the_profile() {
+    use binary_utils::framing::LengthPrefix;
+    use binary_utils::profile::WireProfile;
+
+    let profile = WireProfile {
+        string_prefix: LengthPrefix::VarInt,
+        vec_prefix: LengthPrefix::U32,
+        ..WireProfile::DEFAULT
+    };
+    let bytes = profile.encode(&text()).unwrap();
+    let borrowed = profile.scope(|| TextRef::compose_ref(&bytes, &mut 0).unwrap());
+    assert_eq!(borrowed.message, "hello world");
+    assert_eq!(borrowed.attachment, &[1, 2, 3, 4]);
+}
+
+#[test]
+fn rejects_bad_input() {
+    let bytes = text().parse().unwrap();
+    for end in 0..bytes.len() {
+        assert!(TextRef::compose_ref(&bytes[..end], &mut 0).is_err());
+    }
+    assert!(<&str>::compose_ref(&[0, 2, 0xff, 0xfe], &mut 0).is_err());
+}
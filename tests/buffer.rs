@@ -0,0 +1,173 @@
+#![cfg(feature = "std")]
+use std::io::{Cursor, ErrorKind};
+
+use binary_utils::buffer::{IBufferRead, IBufferWrite, IoBuffer};
+use binary_utils::{Streamable, VarInt};
+
+/// A plugin that only sees the trait objects.
+fn echo(source: &mut dyn IBufferRead, out: &mut dyn IBufferWrite) -> std::io::Result<()> {
+    let id = source.read_var_int()?;
+    let x = source.read_float_le()?;
+    let flag = source.read_bool()?;
+    out.write_var_int(id)?;
+    out.write_float_le(x)?;
+    out.write_bool(flag)
+}
+
+#[test]
+fn plugins_read_and_write_through_trait_objects() {
+    let mut input = IoBuffer(Vec::new());
+    input.write_var_int(300).unwrap();
+    input.write_float_le(1.5).unwrap();
+    input.write_bool(true).unwrap();
+    let input = input.into_inner();
+
+    let mut output = IoBuffer(Vec::new());
+    echo(&mut IoBuffer(Cursor::new(input.clone())), &mut output).unwrap();
+    assert_eq!(output.0, input);
+    assert_eq!(&input[..2], &VarInt(300u32).parse().unwrap()[..]);
+}
+
+#[test]
+fn values_match_their_streamable_encoding() {
+    let mut out = IoBuffer(Vec::new());
+    out.write_value(&"hi".to_string()).unwrap();
+    out.write_long(7).unwrap();
+    out.write_signed_short_le(-2).unwrap();
+
+    let source: &mut dyn IBufferRead = &mut IoBuffer(Cursor::new(out.0));
+    assert_eq!(source.read_string().unwrap(), "hi");
+    assert_eq!(source.read_long().unwrap(), 7);
+    assert_eq!(source.read_signed_short_le().unwrap(), -2);
+    assert_eq!(
+        source.read_byte().unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn triads_are_three_bytes_in_either_order() {
+    use binary_utils::u24;
+
+    let mut out = IoBuffer(Vec::new());
+    out.write_triad_le(u24(0x010203)).unwrap();
+    out.write_triad(u24(0x010203)).unwrap();
+    assert_eq!(out.0, vec![3, 2, 1, 1, 2, 3]);
+
+    let mut source = IoBuffer(Cursor::new(&out.0[..5]));
+    assert_eq!(source.read_triad_le().unwrap(), u24(0x010203));
+    assert_eq!(source.0.position(), 3);
+    // only two bytes are left.
+    assert_eq!(
+        source.read_triad().unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn binary_streams_encode_and_decode() {
+    use binary_utils::buffer::BinaryStream;
+    use binary_utils::u24;
+
+    let mut stream = BinaryStream::with_capacity(2);
+    stream.write_byte(0x84).unwrap();
+    stream.write_triad_le(u24(1)).unwrap();
+    stream.write_short_le(0xabcd).unwrap();
+    stream.write_var_int(300).unwrap();
+    stream.write_long(9).unwrap();
+    stream.write_value(&"netrex".to_string()).unwrap();
+
+    let plugin: &mut dyn IBufferRead = &mut stream;
+    assert_eq!(plugin.read_byte().unwrap(), 0x84);
+    assert_eq!(plugin.read_triad_le().unwrap(), u24(1));
+    assert_eq!(plugin.read_short_le().unwrap(), 0xabcd);
+    assert_eq!(plugin.read_var_int().unwrap(), 300);
+    assert_eq!(stream.read::<u64>().unwrap(), 9);
+    assert_eq!(stream.read::<String>().unwrap(), "netrex");
+    assert_eq!(stream.remaining(), 0);
+    assert!(stream.read_byte().is_err());
+
+    stream.clear();
+    assert_eq!((stream.offset(), stream.as_slice()), (0, &[][..]));
+}
+
+#[test]
+fn binary_streams_fail_instead_of_panicking() {
+    use binary_utils::buffer::BinaryStream;
+    use binary_utils::error::BinaryError;
+
+    let mut stream = BinaryStream::from(vec![1, 2, 3]);
+    assert_eq!(stream.try_read(2).unwrap(), &[1, 2]);
+    assert_eq!(
+        stream.try_read(2),
+        Err(BinaryError::NotEnoughBytes {
+            needed: 2,
+            available: 1
+        })
+    );
+    assert_eq!(stream.offset(), 2);
+
+    assert_eq!(stream.try_index(2), Ok(3));
+    assert!(stream.try_index(3).is_err());
+
+    assert!(stream.try_set_offset(4).is_err());
+    stream.try_set_offset(3).unwrap();
+    assert_eq!(stream.remaining(), 0);
+    stream.try_set_offset(0).unwrap();
+    assert_eq!(stream.read::<u8>(), Ok(1));
+}
+
+#[test]
+fn binary_streams_peek_without_moving() {
+    use binary_utils::buffer::BinaryStream;
+
+    let mut stream = BinaryStream::new();
+    stream.write_short(0x0102).unwrap();
+    stream.write_var_int(300).unwrap();
+
+    assert_eq!(stream.peek(), Ok(1));
+    assert_eq!(stream.peek_n(2).unwrap(), &[1, 2]);
+    assert_eq!(stream.peek_short(), Ok(0x0102));
+    assert_eq!(stream.offset(), 0);
+
+    stream.try_set_offset(2).unwrap();
+    assert_eq!(stream.peek_var_int(), Ok(300));
+    assert_eq!(stream.peek_value::<u8>(), Ok(0xac));
+    assert!(stream.peek_n(3).is_err());
+    assert_eq!(stream.read_var_int().unwrap(), 300);
+    assert!(stream.peek().is_err());
+}
+
+#[test]
+fn binary_streams_seek_and_reset_to_a_mark() {
+    use binary_utils::buffer::BinaryStream;
+
+    let mut stream = BinaryStream::from(vec![0, 1, 2, 3, 4]);
+    stream.seek(2).unwrap();
+    stream.mark();
+    assert_eq!(stream.read::<u16>(), Ok(0x0203));
+    // a speculative parse failed, back up to the mark
+    stream.reset();
+    assert_eq!(stream.offset(), 2);
+
+    stream.seek(-1).unwrap();
+    assert_eq!(stream.peek(), Ok(1));
+    assert!(stream.seek(-2).is_err());
+    assert!(stream.seek(5).is_err());
+    assert_eq!(stream.offset(), 1);
+    stream.seek(4).unwrap();
+    assert_eq!(stream.remaining(), 0);
+}
+
+#[test]
+fn buffer_methods_do_not_clash_with_byteorder() {
+    use binary_utils::buffer::BinaryStream;
+    use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+    let mut stream = BinaryStream::new();
+    stream.write_u16::<BigEndian>(7).unwrap();
+    stream.write_unsigned_int(9).unwrap();
+    assert_eq!(stream.as_slice(), &[0, 7, 0, 0, 0, 9]);
+    assert_eq!(stream.read_u16::<BigEndian>().unwrap(), 7);
+    assert_eq!(stream.read_unsigned_int().unwrap(), 9);
+}
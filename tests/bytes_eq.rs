@@ -0,0 +1,43 @@
+#![cfg(feature = "std")]
+use binary_utils::assert_bytes_eq;
+use binary_utils::testing::bytes_diff;
+
+#[test]
+fn equal_bytes_have_no_diff() {
+    assert_eq!(bytes_diff(&[1, 2, 3], &[1, 2, 3]), None);
+    assert_bytes_eq!(vec![1u8, 2, 3], [1, 2, 3]);
+    assert_bytes_eq!(&b"abc"[..], b"abc");
+}
+
+#[test]
+fn diff_marks_first_mismatch() {
+    let diff = bytes_diff(
+        &[0x0c, 0, 0, 0, 72, 105, 1, 2, 3],
+        &[0x0c, 0, 0, 0, 72, 111, 1, 2, 3],
+    )
+    .unwrap();
+    let lines: Vec<&str> = diff.lines().collect();
+    assert_eq!(
+        lines[0],
+        "bytes differ at offset 5 (expected 9 bytes, actual 9 bytes)"
+    );
+    assert_eq!(
+        lines[2],
+        "> 0000   | 0c 00 00 00 48[69]01 02   | 0c 00 00 00 48[6f]01 02"
+    );
+    assert_eq!(lines[3], "  0008   | 03                        | 03");
+}
+
+#[test]
+fn diff_marks_missing_bytes() {
+    let diff = bytes_diff(&[1, 2], &[1, 2, 3]).unwrap();
+    assert!(diff.starts_with("bytes differ at offset 2 (expected 2 bytes, actual 3 bytes)"));
+    assert!(diff.contains("01 02[--]"));
+    assert!(diff.contains("01 02[03]"));
+}
+
+#[test]
+#[should_panic(expected = "differ at offset 1")]
+fn assert_bytes_eq_panics() {
+    assert_bytes_eq!([1u8, 2], [1u8, 3]);
+}
@@ -0,0 +1,42 @@
+use binary_utils::{hash::WireHash, profile::WireProfile, *};
+
+#[test]
+fn nan_is_normalized() {
+    let quiet = f64::from_bits(0x7FF8_0000_0000_0001);
+    let signalling = f64::from_bits(0xFFF0_0000_0000_0002);
+    assert_ne!(quiet.parse().unwrap(), signalling.parse().unwrap());
+
+    let canonical = WireProfile::CANONICAL;
+    assert_eq!(
+        canonical.encode(&quiet).unwrap(),
+        canonical.encode(&signalling).unwrap()
+    );
+    assert_eq!(quiet.wire_hash().unwrap(), signalling.wire_hash().unwrap());
+    // non NaN values are untouched.
+    assert_eq!(canonical.encode(&1.5f32).unwrap(), 1.5f32.parse().unwrap());
+}
+
+#[test]
+fn overlong_varints_are_rejected() {
+    // `1` padded with a continuation byte.
+    let overlong = vec![0x81, 0x00, 7];
+
+    let mut position = 0;
+    let value = VarInt::<u32>::compose(&overlong, &mut position).unwrap();
+    assert_eq!(value.0, 1);
+    assert_eq!(position, 2);
+
+    assert!(WireProfile::CANONICAL
+        .decode::<VarInt<u32>>(&overlong, &mut 0)
+        .is_err());
+    assert!(WireProfile::CANONICAL
+        .decode::<VarInt<u64>>(&overlong, &mut 0)
+        .is_err());
+    assert_eq!(
+        WireProfile::CANONICAL
+            .decode::<VarInt<u32>>(&[0x81, 0x01], &mut 0)
+            .unwrap()
+            .0,
+        129
+    );
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "derive")]
+use binary_utils::layout::FixedSize;
+use binary_utils::*;
+
+// `all()` always holds and `any()` never does, standing in for features
+// that are on and off in this build.
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Spawn {
+    pub entity: u32,
+    #[binary(cfg(all()))]
+    pub pitch: u8,
+    #[binary(cfg(any()))]
+    pub skin: String,
+    #[binary(cfg(any()))]
+    pub cape: Option<String>,
+}
+
+#[derive(BinaryStream)]
+pub struct SpawnOn {
+    pub entity: u32,
+    pub pitch: u8,
+}
+
+#[test]
+fn fields_are_only_on_the_wire_when_their_cfg_holds() {
+    let spawn = Spawn {
+        entity: 1,
+        pitch: 90,
+        skin: "steve".to_string(),
+        cape: None,
+    };
+    assert_eq!(spawn.parse().unwrap(), vec![0, 0, 0, 1, 90]);
+    assert_eq!(<Spawn as FixedSize>::WIRE_SIZE, 5);
+
+    let spawn = Spawn::compose(&[0, 0, 0, 1, 90], &mut 0).unwrap();
+    assert_eq!(spawn.skin, "");
+    assert_eq!(spawn.pitch, 90);
+}
+
+#[test]
+fn fingerprints_follow_the_cfg() {
+    assert_eq!(schema::describe::<Spawn>(), "{u32,u8}");
+    assert_eq!(
+        schema::fingerprint::<Spawn>(),
+        schema::fingerprint::<SpawnOn>()
+    );
+}
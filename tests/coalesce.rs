@@ -0,0 +1,78 @@
+#![cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use binary_utils::coalesce::Coalescer;
+use binary_utils::framing::{encode_frame, split_frames, LengthPrefix};
+use binary_utils::*;
+
+#[test]
+fn frames_are_packed_up_to_the_mtu() {
+    let mut coalescer = Coalescer::new(12, LengthPrefix::VarInt, Duration::from_secs(1));
+    let mut datagrams = Vec::new();
+    for i in 0..10u32 {
+        datagrams.extend(coalescer.push(&i.parse().unwrap()).unwrap());
+    }
+    datagrams.extend(coalescer.flush());
+
+    // two frames of 5 bytes fit in each datagram.
+    assert_eq!(datagrams.len(), 5);
+    let mut values = Vec::new();
+    for datagram in &datagrams {
+        assert!(datagram.len() <= 12);
+        for frame in split_frames(datagram, LengthPrefix::VarInt).unwrap() {
+            values.push(u32::compose(frame, &mut 0).unwrap());
+        }
+    }
+    assert_eq!(values, (0..10).collect::<Vec<_>>());
+
+    let mut expected = Vec::new();
+    encode_frame(&0u32, LengthPrefix::VarInt, &mut expected).unwrap();
+    encode_frame(&1u32, LengthPrefix::VarInt, &mut expected).unwrap();
+    assert_eq!(datagrams[0], expected);
+}
+
+#[test]
+fn datagrams_are_due_after_the_delay() {
+    let mut coalescer = Coalescer::new(1200, LengthPrefix::U16, Duration::from_millis(20));
+    assert_eq!(coalescer.deadline(), None);
+    assert_eq!(coalescer.poll(Instant::now()), None);
+
+    coalescer.push(&[1]).unwrap();
+    let deadline = coalescer.deadline().unwrap();
+    assert_eq!(coalescer.poll(deadline - Duration::from_millis(1)), None);
+    coalescer.push(&[2]).unwrap();
+    // the deadline is set by the first frame of the datagram.
+    assert_eq!(coalescer.deadline(), Some(deadline));
+
+    assert_eq!(coalescer.poll(deadline), Some(vec![0, 1, 1, 0, 1, 2]));
+    assert!(coalescer.is_empty());
+    assert_eq!(coalescer.deadline(), None);
+    assert_eq!(coalescer.flush(), None);
+}
+
+#[test]
+fn oversized_frames_are_rejected() {
+    let mut coalescer = Coalescer::new(4, LengthPrefix::U16, Duration::ZERO);
+    assert!(coalescer.push(&[1, 2, 3]).is_err());
+    assert_eq!(coalescer.push(&[1, 2]).unwrap(), None);
+    assert_eq!(coalescer.len(), 4);
+}
+
+#[test]
+fn tracked_frames_name_their_datagram_and_bytes() {
+    let mut coalescer = Coalescer::new(8, LengthPrefix::U16, Duration::ZERO);
+    let (first, _) = coalescer.push_tracked(&[1, 2]).unwrap();
+    let (second, _) = coalescer.push_tracked(&[3]).unwrap();
+    let (third, finished) = coalescer.push_tracked(&[4, 5, 6]).unwrap();
+
+    assert_eq!((first.sequence, first.datagram, first.range), (0, 0, 0..4));
+    assert_eq!((second.sequence, second.datagram), (1, 0));
+    let datagram = finished.unwrap();
+    assert_eq!(&datagram[second.range], &[0, 1, 3]);
+
+    assert_eq!(
+        (third.sequence, third.datagram, third.range.clone()),
+        (2, 1, 0..5)
+    );
+    assert_eq!(&coalescer.flush().unwrap()[third.range], &[0, 3, 4, 5, 6]);
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "codec")]
+
+use binary_utils::{
+    codec::StreamableCodec,
+    framing::{encode_frame, LengthPrefix},
+};
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt};
+use tokio_util::codec::{Decoder, Framed};
+
+#[tokio::test]
+async fn framed_round_trip() {
+    let (client, server) = tokio::io::duplex(64);
+    let mut client = Framed::new(client, StreamableCodec::<String>::new(LengthPrefix::VarInt));
+    let mut server = Framed::new(server, StreamableCodec::<String>::new(LengthPrefix::VarInt));
+
+    let sender = tokio::spawn(async move {
+        for i in 0..20 {
+            client.send(format!("packet {}", i)).await.unwrap();
+        }
+    });
+    for i in 0..20 {
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            format!("packet {}", i)
+        );
+    }
+    sender.await.unwrap();
+}
+
+#[test]
+fn waits_for_whole_frames() {
+    let mut frame = Vec::new();
+    encode_frame(&"hello".to_string(), LengthPrefix::U16, &mut frame).unwrap();
+
+    let mut codec = StreamableCodec::<String>::new(LengthPrefix::U16);
+    let mut buffer = BytesMut::new();
+    for byte in &frame[..frame.len() - 1] {
+        buffer.extend_from_slice(&[*byte]);
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+    }
+    buffer.extend_from_slice(&frame[frame.len() - 1..]);
+    assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), "hello");
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn rejects_large_frames() {
+    let mut codec = StreamableCodec::<String>::with_max_frame(LengthPrefix::U32, 16);
+    let mut buffer = BytesMut::from(&[0, 0, 1, 0][..]);
+    let error = codec.decode(&mut buffer).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
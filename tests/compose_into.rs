@@ -0,0 +1,55 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct State {
+    pub tick: u32,
+    pub name: String,
+    pub ids: Vec<u16>,
+    #[skip]
+    pub cache: Option<u8>,
+    #[default]
+    pub extra: u8,
+}
+
+fn state(tick: u32, name: &str) -> State {
+    State {
+        tick,
+        name: name.to_string(),
+        ids: vec![1, 2, 3],
+        cache: None,
+        extra: 0,
+    }
+}
+
+#[test]
+fn composes_over_an_existing_value() {
+    let mut slot = state(0, &"x".repeat(64));
+    slot.cache = Some(1);
+    slot.extra = 9;
+    let (name, ids) = (slot.name.as_ptr(), slot.ids.as_ptr());
+
+    let bytes = state(7, "steve").parse().unwrap();
+    let mut position = 0;
+    slot.compose_into(&bytes, &mut position).unwrap();
+    assert_eq!(slot, state(7, "steve"));
+    assert_eq!(position, bytes.len());
+    // the buffers were reused
+    assert_eq!(slot.name.as_ptr(), name);
+    assert_eq!(slot.ids.as_ptr(), ids);
+}
+
+#[test]
+fn failed_composes_are_located() {
+    let mut slot = state(0, "");
+    let error = slot.compose_into(&[0, 0, 0, 1, 0, 9], &mut 0).unwrap_err();
+    assert_eq!(error.offset(), Some(4));
+    assert!(error.is_incomplete());
+}
+
+#[test]
+fn other_types_are_replaced() {
+    let mut value = (1u8, 2u16);
+    value.compose_into(&[3, 0, 4], &mut 0).unwrap();
+    assert_eq!(value, (3, 4));
+}
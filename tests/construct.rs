@@ -0,0 +1,64 @@
+#![cfg(feature = "derive")]
+mod wire {
+    use binary_utils::*;
+
+    #[derive(Debug, BinaryStream)]
+    #[binary(construct = "Self::from_wire")]
+    #[non_exhaustive]
+    pub struct Version {
+        major: u8,
+        minor: u8,
+        label: String,
+    }
+
+    impl Version {
+        pub fn new(major: u8, minor: u8, label: &str) -> Self {
+            Self {
+                major,
+                minor,
+                label: label.to_string(),
+            }
+        }
+
+        fn from_wire(major: u8, minor: u8, label: String) -> Self {
+            Self {
+                major,
+                minor,
+                label: label.to_lowercase(),
+            }
+        }
+
+        pub fn label(&self) -> &str {
+            &self.label
+        }
+
+        pub fn version(&self) -> (u8, u8) {
+            (self.major, self.minor)
+        }
+    }
+
+    // field names must not clash with the generated compose arguments.
+    #[derive(Debug, BinaryStream)]
+    pub struct Cursor {
+        pub source: u8,
+        pub position: u16,
+    }
+}
+
+use binary_utils::Streamable;
+use wire::{Cursor, Version};
+
+#[test]
+fn composes_through_constructor() {
+    let bytes = Version::new(1, 19, "STABLE").parse().unwrap();
+    let version = Version::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(version.version(), (1, 19));
+    assert_eq!(version.label(), "stable");
+}
+
+#[test]
+fn field_names_do_not_shadow_arguments() {
+    let cursor = Cursor::compose(&[1, 0, 2], &mut 0).unwrap();
+    assert_eq!(cursor.source, 1);
+    assert_eq!(cursor.position, 2);
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "io-streams")]
+use binary_utils::{
+    io::{copy_streamable, copy_streamable_with},
+    *,
+};
+
+fn log() -> Vec<u8> {
+    let mut log = Vec::new();
+    for i in 0..10u32 {
+        log.extend(i.parse().unwrap());
+    }
+    log
+}
+
+#[test]
+fn copies_up_to_the_limit() {
+    let source = log();
+    let mut sink = Vec::new();
+    assert_eq!(
+        copy_streamable::<u32, _, _>(&source[..], &mut sink, 4).unwrap(),
+        4
+    );
+    assert_eq!(sink, source[..16].to_vec());
+
+    let mut sink = Vec::new();
+    assert_eq!(
+        copy_streamable::<u32, _, _>(&source[..], &mut sink, 100).unwrap(),
+        10
+    );
+    assert_eq!(sink, source);
+}
+
+#[test]
+fn transcodes_items() {
+    let source = log();
+    let mut sink = Vec::new();
+    copy_streamable_with::<u32, u8, _, _, _>(&source[..], &mut sink, 10, |v| Ok(v as u8 * 2))
+        .unwrap();
+    assert_eq!(sink, vec![0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+}
+
+#[test]
+fn errors_report_the_item_offset() {
+    let mut source = Vec::new();
+    for _ in 0..3 {
+        source.extend(true.parse().unwrap());
+    }
+    source.push(9);
+
+    let mut sink = Vec::new();
+    let error = copy_streamable::<bool, _, _>(&source[..], &mut sink, 10).unwrap_err();
+    assert_eq!(error.item, 3);
+    assert_eq!(error.offset, 3);
+    assert_eq!(sink, vec![1, 1, 1]);
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Login {
+    pub protocol: u32,
+    pub name: String,
+    // added in later protocol versions.
+    #[default]
+    pub locale: String,
+    #[default = 20]
+    pub view_distance: u8,
+}
+
+#[test]
+fn missing_trailing_fields_are_defaulted() {
+    let old = [0, 0, 1, 0xB8, 0, 2, b'h', b'i'];
+    let login = Login::compose(&old, &mut 0).unwrap();
+    assert_eq!(
+        login,
+        Login {
+            protocol: 440,
+            name: "hi".to_string(),
+            locale: String::new(),
+            view_distance: 20,
+        }
+    );
+    assert!(Login::validate(&old, &mut 0).is_ok());
+
+    let mut newer = old.to_vec();
+    newer.extend_from_slice(&[0, 2, b'e', b'n']);
+    let login = Login::compose(&newer, &mut 0).unwrap();
+    assert_eq!((login.locale.as_str(), login.view_distance), ("en", 20));
+}
+
+#[test]
+fn present_fields_are_read() {
+    let login = Login {
+        protocol: 1,
+        name: "a".to_string(),
+        locale: "en".to_string(),
+        view_distance: 8,
+    };
+    let bytes = login.parse().unwrap();
+    assert_eq!(Login::compose(&bytes, &mut 0).unwrap(), login);
+}
+
+#[test]
+fn fields_cut_short_still_fail() {
+    // the locale's length prefix is there, but not the locale.
+    let bytes = [0, 0, 1, 0xB8, 0, 2, b'h', b'i', 0, 2, b'e'];
+    assert!(Login::compose(&bytes, &mut 0).is_err());
+    assert!(Login::validate(&bytes, &mut 0).is_err());
+    // required fields are never defaulted.
+    assert!(Login::compose(&[0, 0, 1, 0xB8], &mut 0).is_err());
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use binary_utils::delivery::{AckRanges, Delivery, DeliveryTracker, FrameHandle, ResendQueue};
+use binary_utils::*;
+
+fn handle(sequence: u64) -> FrameHandle {
+    FrameHandle {
+        sequence,
+        datagram: sequence / 2,
+        range: 0..4,
+    }
+}
+
+#[test]
+fn acknowledged_frames_are_forgotten() {
+    let mut tracker = DeliveryTracker::new();
+    for sequence in 0..4 {
+        tracker.track(handle(sequence));
+    }
+    assert!(tracker.ack(1));
+    assert!(!tracker.ack(1));
+    assert_eq!(tracker.state(1), None);
+    assert_eq!(tracker.state(2), Some(Delivery::Pending));
+    assert_eq!(
+        tracker.pending().map(|h| h.sequence).collect::<Vec<_>>(),
+        vec![0, 2, 3]
+    );
+}
+
+#[test]
+fn retransmits_are_taken_oldest_first() {
+    let mut tracker = DeliveryTracker::new();
+    for sequence in 0..4 {
+        tracker.track(handle(sequence));
+    }
+    assert!(tracker.retransmit(3));
+    assert!(tracker.retransmit(0));
+    assert!(!tracker.retransmit(9));
+    assert_eq!(tracker.state(3), Some(Delivery::Retransmit));
+
+    assert_eq!(tracker.take_retransmits(), vec![handle(0), handle(3)]);
+    assert_eq!(tracker.len(), 2);
+    assert!(tracker.take_retransmits().is_empty());
+}
+
+#[test]
+fn ack_ranges_round_trip() {
+    let acks = AckRanges::from_sequences([7, 0, 1, 2, 300, 301, 1, 5]);
+    assert_eq!(acks.ranges(), &[0..=2, 5..=5, 7..=7, 300..=301]);
+    assert_eq!(
+        acks.sequences().collect::<Vec<_>>(),
+        vec![0, 1, 2, 5, 7, 300, 301]
+    );
+    assert!(acks.contains(301) && !acks.contains(6));
+
+    let bytes = acks.parse().unwrap();
+    assert_eq!(AckRanges::compose(&bytes, &mut 0).unwrap(), acks);
+    assert!(AckRanges::compose(&[], &mut 0).is_err());
+    assert!(AckRanges::compose(&[0], &mut 0).unwrap().is_empty());
+}
+
+#[test]
+fn overlapping_ack_ranges_are_rejected() {
+    // 5..=2 and then 1..=1 after 3..=4.
+    assert!(AckRanges::compose(&[1, 5, 2], &mut 0).is_err());
+    assert!(AckRanges::compose(&[2, 3, 4, 1, 1], &mut 0).is_err());
+}
+
+#[test]
+fn resend_queue_returns_frames_past_their_timeout() {
+    let start = Instant::now();
+    let mut queue = ResendQueue::new(Duration::from_millis(50));
+    for sequence in 0..5u64 {
+        let sent = start + Duration::from_millis(sequence * 10);
+        queue.insert(sequence, vec![sequence as u8; 2], sent);
+    }
+    assert_eq!(queue.ack(2), Some(vec![2, 2]));
+    assert_eq!(queue.ack_ranges(&AckRanges::from_sequences([3, 9])), 1);
+    assert_eq!(queue.len(), 3);
+
+    // frames 0 and 1 have waited 50ms by now, 4 only 20ms.
+    let now = start + Duration::from_millis(60);
+    let due = queue
+        .due(now)
+        .map(|(sequence, bytes)| (sequence, bytes.to_vec()))
+        .collect::<Vec<_>>();
+    assert_eq!(due, vec![(0, vec![0, 0]), (1, vec![1, 1])]);
+    assert_eq!(queue.sends(0), Some(2));
+    assert_eq!(queue.sends(4), Some(1));
+
+    let later = now + Duration::from_millis(50);
+    let due = queue
+        .due(later)
+        .map(|(sequence, _)| sequence)
+        .collect::<Vec<_>>();
+    assert_eq!(due, vec![0, 1, 4]);
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "std")]
+use binary_utils::diff::{apply_patch, binary_diff, Op, Patch};
+use binary_utils::Streamable;
+
+fn roundtrip(old: &[u8], new: &[u8]) -> Patch {
+    let patch = binary_diff(old, new);
+    let bytes = patch.parse().unwrap();
+    let decoded = Patch::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(decoded, patch);
+    assert_eq!(apply_patch(old, &decoded).unwrap(), new);
+    patch
+}
+
+#[test]
+fn diff_identical_is_one_copy() {
+    let data: Vec<u8> = (0..200u8).collect();
+    let patch = roundtrip(&data, &data);
+    assert_eq!(
+        patch.ops,
+        vec![Op::Copy {
+            offset: 0,
+            length: 200
+        }]
+    );
+}
+
+#[test]
+fn diff_edits() {
+    let old: Vec<u8> = (0..1000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let mut new = old.clone();
+    new[500] ^= 0xFF;
+    new.splice(100..110, b"inserted bytes".iter().copied());
+    new.drain(800..850);
+    new.extend_from_slice(b"tail");
+
+    let patch = roundtrip(&old, &new);
+    assert!(patch.parse().unwrap().len() < new.len() / 10);
+}
+
+#[test]
+fn diff_empty_buffers() {
+    roundtrip(&[], &[]);
+    roundtrip(&[], b"new");
+    roundtrip(b"old bytes here", &[]);
+}
+
+#[test]
+fn apply_patch_wrong_source() {
+    let patch = binary_diff(b"some old buffer", b"some new buffer");
+    assert!(apply_patch(b"short", &patch).is_err());
+
+    let patch = Patch {
+        source_len: 4,
+        target_len: 8,
+        ops: vec![Op::Copy {
+            offset: 2,
+            length: 8,
+        }],
+    };
+    assert!(apply_patch(b"abcd", &patch).is_err());
+}
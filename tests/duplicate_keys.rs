@@ -0,0 +1,58 @@
+#![cfg(feature = "derive")]
+use binary_utils::error::BinaryError;
+use binary_utils::extensions::{Extension, Extensions};
+use binary_utils::profile::{DuplicateKeys, WireProfile};
+use binary_utils::tlv::Tlv;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Flag {
+    pub value: u8,
+}
+
+impl Extension for Flag {
+    const ID: u32 = 3;
+}
+
+// id 3 is written twice, the second record starts at offset 3.
+const REPEATED: [u8; 6] = [3, 1, 1, 3, 1, 2];
+
+#[test]
+fn last_wins_by_default() {
+    let section = Tlv::<u8, u8>::compose(&REPEATED, &mut 0).unwrap();
+    assert_eq!(section.len(), 2);
+    assert_eq!(section.raw(3), Some(&[2u8][..]));
+    assert_eq!(section.parse().unwrap(), REPEATED.to_vec());
+
+    let extensions = Extensions::compose(&[3, 1, 1, 3, 1, 2], &mut 0).unwrap();
+    assert_eq!(extensions.get::<Flag>().unwrap(), Some(Flag { value: 2 }));
+}
+
+#[test]
+fn insert_replaces_the_winning_record() {
+    let mut section = Tlv::<u8, u8>::compose(&REPEATED, &mut 0).unwrap();
+    section.insert_raw(3, vec![9]);
+    assert_eq!(section.parse().unwrap(), vec![3, 1, 1, 3, 1, 9]);
+}
+
+#[test]
+fn duplicate_keys_rejected() {
+    let profile = WireProfile::DEFAULT.with_duplicate_keys(DuplicateKeys::Error);
+    let result = profile.decode::<Tlv<u8, u8>>(&REPEATED, &mut 0);
+    assert_eq!(
+        result.err(),
+        Some(BinaryError::DuplicateKey {
+            key_repr: "3".to_string(),
+            offset: 3,
+        })
+    );
+
+    let result = profile.decode::<Extensions>(&[3, 1, 1, 3, 1, 2], &mut 0);
+    assert!(matches!(result, Err(BinaryError::DuplicateKey { .. })));
+
+    // distinct ids are unaffected.
+    let section = profile
+        .decode::<Tlv<u8, u8>>(&[1, 0, 2, 0], &mut 0)
+        .unwrap();
+    assert_eq!(section.len(), 2);
+}
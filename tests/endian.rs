@@ -0,0 +1,81 @@
+#![cfg(feature = "derive")]
+use binary_utils::tracked::FieldSpans;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Mixed {
+    pub id: u16,
+    #[le]
+    pub runtime: u32,
+    #[be]
+    pub sequence: u16,
+    #[le]
+    pub name: String,
+}
+
+#[derive(BinaryStream)]
+pub struct Wrapped {
+    pub id: u16,
+    pub runtime: LE<u32>,
+    pub sequence: BE<u16>,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+#[binary(little_endian)]
+pub struct Little {
+    pub id: u16,
+    pub runtime: u32,
+    #[be]
+    pub sequence: u16,
+}
+
+fn mixed() -> Mixed {
+    Mixed {
+        id: 1,
+        runtime: 2,
+        sequence: 3,
+        name: "a".to_string(),
+    }
+}
+
+#[test]
+fn fields_use_their_own_byte_order() {
+    let bytes = mixed().parse().unwrap();
+    assert_eq!(bytes, vec![0, 1, 2, 0, 0, 0, 0, 3, 1, 0, b'a']);
+    assert_eq!(Mixed::compose(&bytes, &mut 0).unwrap(), mixed());
+    assert!(Mixed::validate(&bytes, &mut 0).is_ok());
+    assert_eq!(mixed().size_hint(), Some(bytes.len()));
+    assert_eq!(mixed().parse_field(1).unwrap(), vec![2, 0, 0, 0]);
+}
+
+#[test]
+fn be_fields_ignore_a_little_endian_profile() {
+    let little = profile::WireProfile::DEFAULT.with_endian(profile::Endian::Little);
+    let bytes = little.encode(&mixed()).unwrap();
+    assert_eq!(&bytes[..8], &[1, 0, 2, 0, 0, 0, 0, 3]);
+}
+
+#[test]
+fn endian_fields_describe_like_wrappers() {
+    assert_eq!(
+        schema::describe::<Mixed>(),
+        "{u16,le<u32>,be<u16>,le<string>}"
+    );
+    assert_eq!(schema::describe::<Wrapped>(), "{u16,le<u32>,be<u16>}");
+}
+
+#[test]
+fn struct_byte_order_applies_to_fields_without_their_own() {
+    let little = Little {
+        id: 1,
+        runtime: 2,
+        sequence: 3,
+    };
+    let bytes = little.parse().unwrap();
+    assert_eq!(bytes, vec![1, 0, 2, 0, 0, 0, 0, 3]);
+    assert_eq!(Little::compose(&bytes, &mut 0).unwrap(), little);
+    // the profile's byte order doesn't matter either.
+    let big = profile::WireProfile::DEFAULT.with_endian(profile::Endian::Big);
+    assert_eq!(big.encode(&little).unwrap(), bytes);
+    assert_eq!(schema::describe::<Little>(), "{le<u16>,le<u32>,be<u16>}");
+}
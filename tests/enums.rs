@@ -1,3 +1,5 @@
+#![cfg(feature = "derive")]
+#![allow(clippy::match_like_matches_macro)]
 use bin_macro::*;
 use binary_utils::{error::BinaryError, Streamable};
 
@@ -35,3 +37,83 @@ fn write_read_buffer() -> Result<(), BinaryError> {
     );
     Ok(())
 }
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub enum Action {
+    Idle,
+    Move(i16, i16),
+    Chat { channel: u8, message: String },
+}
+
+#[derive(Debug, BinaryStream, PartialEq)]
+#[repr(u16)]
+pub enum Tagged {
+    Ping(u32) = 0x10,
+    Pong(u32),
+    Close = 0xFF,
+}
+
+#[test]
+fn variants_with_fields() -> Result<(), BinaryError> {
+    assert_eq!(Action::Idle.parse()?, vec![0]);
+    assert_eq!(Action::Move(1, -1).parse()?, vec![1, 0, 1, 0xFF, 0xFF]);
+
+    let chat = Action::Chat {
+        channel: 2,
+        message: "hi".to_string(),
+    };
+    let buffer = chat.parse()?;
+    assert_eq!(buffer, vec![2, 2, 0, 2, b'h', b'i']);
+
+    let mut position = 0;
+    assert_eq!(Action::compose(&buffer, &mut position)?, chat);
+    assert_eq!(position, buffer.len());
+    Ok(())
+}
+
+#[test]
+fn variants_with_fields_use_repr() -> Result<(), BinaryError> {
+    assert_eq!(Tagged::Pong(1).parse()?, vec![0, 0x11, 0, 0, 0, 1]);
+    assert_eq!(Tagged::Close.parse()?, vec![0, 0xFF]);
+    assert_eq!(
+        Tagged::compose(&[0, 0x10, 0, 0, 0, 7], &mut 0)?,
+        Tagged::Ping(7)
+    );
+    assert!(Tagged::compose(&[0, 0x12], &mut 0).is_err());
+    Ok(())
+}
+
+#[derive(Debug, BinaryStream, PartialEq)]
+#[repr(u8)]
+pub enum Frame {
+    // the low nibble of the id holds the flags of a datagram.
+    #[binary(id_expr = "0x80 | self.flags", id_match = "0x80..=0x8f")]
+    Datagram {
+        #[binary(from_id = "id & 0x0f")]
+        flags: u8,
+        sequence: u16,
+    },
+    Ack(u16) = 0xc0,
+}
+
+#[test]
+fn computed_ids_carry_fields() -> Result<(), BinaryError> {
+    let datagram = Frame::Datagram {
+        flags: 0x05,
+        sequence: 7,
+    };
+    let buffer = datagram.parse()?;
+    assert_eq!(buffer, vec![0x85, 0, 7]);
+    assert_eq!(datagram.size_hint(), Some(buffer.len()));
+    assert_eq!(Frame::compose(&buffer, &mut 0)?, datagram);
+    assert!(Frame::validate(&buffer, &mut 0).is_ok());
+
+    assert_eq!(Frame::Ack(1).parse()?, vec![0xc0, 0, 1]);
+    assert_eq!(Frame::compose(&[0xc0, 0, 1], &mut 0)?, Frame::Ack(1));
+    assert!(Frame::compose(&[0x90, 0, 1], &mut 0).is_err());
+    assert_eq!(
+        binary_utils::schema::describe::<Frame>(),
+        "enum<u8>{0x80 ..= 0x8f:{u16},0xc0:{u16}}"
+    );
+    Ok(())
+}
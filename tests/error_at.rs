@@ -0,0 +1,55 @@
+#![cfg(feature = "derive")]
+use binary_utils::error::BinaryError;
+use binary_utils::*;
+
+#[derive(Debug, BinaryStream)]
+pub struct Inner {
+    pub id: u8,
+    pub name: String,
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct Frame {
+    pub sequence: u32,
+    pub inner: Inner,
+}
+
+#[test]
+fn derived_errors_carry_the_failing_offset_and_type() {
+    // the name claims 5 bytes but only 1 follows
+    let error = Frame::compose(&[0, 0, 0, 1, 9, 0, 5, b'a'], &mut 0).unwrap_err();
+    assert_eq!(error.offset(), Some(5));
+    assert_eq!(error.type_name(), Some("alloc::string::String"));
+    assert_eq!(
+        error.root(),
+        &BinaryError::NotEnoughBytes {
+            needed: 5,
+            available: 1
+        }
+    );
+    assert!(error
+        .to_string()
+        .ends_with("(composing alloc::string::String at offset: 5)"));
+}
+
+#[test]
+fn errors_keep_their_innermost_location() {
+    let error = BinaryError::EOF(3).at(2, "u16").at(0, "Frame");
+    assert_eq!(error.offset(), Some(2));
+    assert_eq!(error.type_name(), Some("u16"));
+    assert_eq!(BinaryError::EOF(3).offset(), None);
+    assert_eq!(BinaryError::EOF(3).root(), &BinaryError::EOF(3));
+}
+
+#[test]
+fn derived_errors_name_the_field_path() {
+    let error = Frame::compose(&[0, 0, 0, 1, 9, 0, 5, b'a'], &mut 0).unwrap_err();
+    assert_eq!(error.field_path(), Some("Frame.inner.name"));
+    assert!(error
+        .to_string()
+        .starts_with("Frame.inner.name: Needed 5 bytes but only 1 are left"));
+
+    let error = Frame::compose(&[0, 0], &mut 0).unwrap_err();
+    assert_eq!(error.field_path(), Some("Frame.sequence"));
+    assert_eq!(error.offset(), Some(0));
+}
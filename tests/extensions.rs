@@ -0,0 +1,76 @@
+#![cfg(feature = "derive")]
+use binary_utils::extensions::{Extension, Extensions};
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct PingExtension {
+    pub latency: u16,
+}
+
+impl Extension for PingExtension {
+    const ID: u32 = 1;
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct NameExtension {
+    pub name: String,
+}
+
+impl Extension for NameExtension {
+    const ID: u32 = 200;
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct Hello {
+    pub protocol: u32,
+    pub extensions: Extensions,
+}
+
+#[test]
+fn extensions_typed_access() {
+    let mut extensions = Extensions::new();
+    assert!(extensions.is_empty());
+    extensions.insert(&PingExtension { latency: 5 }).unwrap();
+    extensions
+        .insert(&NameExtension { name: "abc".into() })
+        .unwrap();
+    extensions.insert(&PingExtension { latency: 6 }).unwrap();
+    assert_eq!(extensions.len(), 2);
+
+    let bytes = Hello {
+        protocol: 1,
+        extensions,
+    }
+    .parse()
+    .unwrap();
+    assert_eq!(
+        bytes,
+        vec![0, 0, 0, 1, 1, 2, 0, 6, 200, 1, 5, 0, 3, b'a', b'b', b'c']
+    );
+
+    let mut hello = Hello::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(
+        hello.extensions.get::<PingExtension>().unwrap(),
+        Some(PingExtension { latency: 6 })
+    );
+    assert!(hello.extensions.contains::<NameExtension>());
+    assert!(hello.extensions.remove::<NameExtension>());
+    assert!(!hello.extensions.remove::<NameExtension>());
+    assert_eq!(hello.extensions.get::<NameExtension>().unwrap(), None);
+}
+
+#[test]
+fn extensions_keep_unknown_records() {
+    let bytes = vec![0, 0, 0, 1, 9, 2, 0xAA, 0xBB, 1, 2, 0, 7];
+    let hello = Hello::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(
+        hello.extensions.iter().collect::<Vec<_>>(),
+        vec![(9, &[0xAA, 0xBB][..]), (1, &[0, 7][..])]
+    );
+    assert_eq!(hello.parse().unwrap(), bytes);
+}
+
+#[test]
+fn extensions_truncated_record() {
+    assert!(Extensions::compose(&[1, 4, 0, 0], &mut 0).is_err());
+}
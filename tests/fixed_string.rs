@@ -0,0 +1,53 @@
+#![cfg(feature = "derive")]
+use binary_utils::layout::FixedSize;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct SaveRecord {
+    pub slot: u8,
+    pub name: FixedString<16>,
+    pub level: u16,
+}
+
+assert_wire_size!(SaveRecord, 19);
+
+#[test]
+fn fixed_string_pads_to_size() {
+    let record = SaveRecord {
+        slot: 1,
+        name: FixedString::new("héllo").unwrap(),
+        level: 3,
+    };
+    let bytes = record.parse().unwrap();
+    assert_eq!(bytes.len(), SaveRecord::WIRE_SIZE);
+    assert_eq!(&bytes[1..7], "héllo".as_bytes());
+    assert!(bytes[7..17].iter().all(|b| *b == 0));
+
+    let decoded = SaveRecord::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(decoded, record);
+    assert_eq!(&*decoded.name, "héllo");
+    assert_eq!(decoded.name.to_string(), "héllo");
+}
+
+#[test]
+fn fixed_string_exact_and_empty() {
+    let exact = FixedString::<4>::try_from("abcd").unwrap();
+    assert_eq!(exact.parse().unwrap(), b"abcd".to_vec());
+    assert_eq!(FixedString::<4>::compose(b"abcd", &mut 0).unwrap(), exact);
+
+    let empty = FixedString::<3>::default();
+    assert_eq!(empty.parse().unwrap(), vec![0, 0, 0]);
+    assert_eq!(
+        FixedString::<3>::compose(&[0, 0, 0], &mut 0).unwrap(),
+        empty
+    );
+}
+
+#[test]
+fn fixed_string_errors() {
+    assert!(FixedString::<2>::new("abc").is_err());
+    // "é" is two bytes, so it doesn't fit in one.
+    assert!(FixedString::<1>::new("é").is_err());
+    assert!(FixedString::<4>::compose(b"ab", &mut 0).is_err());
+    assert!(FixedString::<2>::compose(&[0xC3, 0x28], &mut 0).is_err());
+}
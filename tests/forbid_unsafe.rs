@@ -0,0 +1,14 @@
+#![cfg(feature = "forbid-unsafe")]
+use binary_utils::io::BinaryReader;
+use binary_utils::*;
+use byteorder::BigEndian;
+
+#[test]
+fn invalid_utf8_is_an_error() {
+    let bytes = [0, 2, 0xC3, 0x28];
+    assert!(String::compose(&bytes, &mut 0).is_err());
+    assert!(std::io::Cursor::new(&bytes[..])
+        .read_string::<BigEndian>()
+        .is_err());
+    assert_eq!(String::compose(&[0, 1, b'a'], &mut 0).unwrap(), "a");
+}
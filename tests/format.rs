@@ -1,3 +1,4 @@
+#![cfg(feature = "derive")]
 use binary_utils::*;
 
 pub const EXPECTED_DEBUG: &[u8] = &[
@@ -0,0 +1,49 @@
+#![cfg(feature = "derive")]
+use binary_utils::{
+    hash::{xxh64, WireHash},
+    profile::{Endian, WireProfile},
+    *,
+};
+
+#[test]
+fn xxh64_reference_values() {
+    assert_eq!(xxh64(b"", 0), 0xEF46_DB37_51D8_E999);
+    assert_eq!(xxh64(b"abc", 0), 0x44BC_2CF5_AD77_0999);
+    assert_eq!(
+        xxh64(b"Nobody inspects the spammish repetition", 0),
+        0xFBCE_A83C_8A37_8BF1
+    );
+}
+
+#[derive(BinaryStream)]
+pub struct Chat {
+    pub sender: u64,
+    pub message: String,
+}
+
+#[test]
+fn equal_wire_bytes_hash_equal() {
+    let a = Chat {
+        sender: 1,
+        message: "hi".to_string(),
+    };
+    let b = Chat {
+        sender: 1,
+        message: "hi".to_string(),
+    };
+    let c = Chat {
+        sender: 2,
+        message: "hi".to_string(),
+    };
+    assert_eq!(a.wire_hash().unwrap(), b.wire_hash().unwrap());
+    assert_ne!(a.wire_hash().unwrap(), c.wire_hash().unwrap());
+    assert_eq!(a.wire_hash().unwrap(), xxh64(&a.parse().unwrap(), 0));
+}
+
+#[test]
+fn hash_ignores_the_thread_profile() {
+    let value = 7u32;
+    let expected = value.wire_hash().unwrap();
+    let little = WireProfile::DEFAULT.with_endian(Endian::Little);
+    assert_eq!(little.scope(|| value.wire_hash().unwrap()), expected);
+}
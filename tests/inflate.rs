@@ -0,0 +1,80 @@
+#![cfg(feature = "io-streams")]
+use std::io::Read;
+
+use binary_utils::{io::InflateSource, Streamable};
+
+/// A reader that hands out at most `chunk` bytes per read,
+/// similar to a decompressor producing output in small blocks.
+struct Trickle {
+    data: Vec<u8>,
+    position: usize,
+    chunk: usize,
+    reads: usize,
+}
+
+impl Read for Trickle {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amount = self
+            .chunk
+            .min(buf.len())
+            .min(self.data.len() - self.position);
+        buf[..amount].copy_from_slice(&self.data[self.position..self.position + amount]);
+        self.position += amount;
+        self.reads += 1;
+        Ok(amount)
+    }
+}
+
+#[test]
+fn compose_from_trickling_reader() {
+    let mut data: Vec<u8> = Vec::new();
+    for i in 0..100u32 {
+        data.extend(i.parse().unwrap());
+        data.extend(format!("item {}", i).parse().unwrap());
+    }
+
+    let mut source = InflateSource::with_window(
+        Trickle {
+            data,
+            position: 0,
+            chunk: 3,
+            reads: 0,
+        },
+        16,
+    );
+
+    for i in 0..100u32 {
+        assert_eq!(source.compose::<u32>().unwrap(), i);
+        assert_eq!(source.compose::<String>().unwrap(), format!("item {}", i));
+    }
+    assert!(source.is_empty().unwrap());
+}
+
+#[test]
+fn small_items_do_not_refill_the_window_every_time() {
+    let mut source = InflateSource::with_window(
+        Trickle {
+            data: (0..=255).collect(),
+            position: 0,
+            chunk: 64,
+            reads: 0,
+        },
+        64,
+    );
+
+    for i in 0..=255u8 {
+        assert_eq!(source.compose::<u8>().unwrap(), i);
+    }
+    assert!(source.is_empty().unwrap());
+    // roughly one read per half window, not one per item.
+    assert!(source.into_inner().reads <= 12);
+}
+
+#[test]
+fn read_passes_bytes_through() {
+    let data: Vec<u8> = (0..=255).collect();
+    let mut source = InflateSource::with_window(&data[..], 7);
+    let mut out = Vec::new();
+    source.read_to_end(&mut out).unwrap();
+    assert_eq!(out, data);
+}
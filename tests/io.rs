@@ -1,3 +1,5 @@
+#![cfg(feature = "io-streams")]
+#![allow(unused_imports)]
 use std::io::Cursor;
 
 use binary_utils::{
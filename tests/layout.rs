@@ -0,0 +1,47 @@
+#![cfg(feature = "derive")]
+use binary_utils::layout::FixedSize;
+use binary_utils::{assert_wire_size, u24, BinaryStream, Streamable, LE};
+
+#[derive(BinaryStream)]
+pub struct LoginHeader {
+    pub id: u8,
+    pub protocol: LE<u32>,
+    pub sequence: u24,
+    pub flags: u16,
+    pub encrypted: bool,
+}
+
+#[derive(Clone, Copy, BinaryStream)]
+#[repr(u16)]
+pub enum Kind {
+    A = 1,
+    B,
+}
+
+#[derive(BinaryStream)]
+pub struct Envelope {
+    pub header: LoginHeader,
+    pub kind: Kind,
+}
+
+assert_wire_size!(LoginHeader, 11);
+assert_wire_size!(Kind, 2);
+assert_wire_size!(Envelope, 13);
+
+#[test]
+fn wire_size_matches_encoding() {
+    let header = LoginHeader {
+        id: 1,
+        protocol: LE(589),
+        sequence: u24(5),
+        flags: 0,
+        encrypted: true,
+    };
+    assert_eq!(header.parse().unwrap().len(), LoginHeader::WIRE_SIZE);
+
+    let envelope = Envelope {
+        header,
+        kind: Kind::B,
+    };
+    assert_eq!(envelope.parse().unwrap().len(), Envelope::WIRE_SIZE);
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, BinaryStream)]
+pub struct ChunkData {
+    pub x: i32,
+    pub z: i32,
+    pub nbt: Lazy<Vec<u8>>,
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct Player {
+    pub name: Lazy<String>,
+    pub level: u8,
+}
+
+#[test]
+fn defers_decoding_until_accessed() {
+    let chunk = ChunkData {
+        x: 4,
+        z: -2,
+        nbt: Lazy::new(vec![1, 2, 3]).unwrap(),
+    };
+    let bytes = chunk.parse().unwrap();
+
+    let decoded = ChunkData::compose(&bytes, &mut 0).unwrap();
+    assert_eq!((decoded.x, decoded.z), (4, -2));
+    assert!(!decoded.nbt.is_decoded());
+    assert_eq!(decoded.nbt.raw(), &[3, 1, 2, 3]);
+    assert_eq!(decoded.nbt.get().unwrap(), &vec![1, 2, 3]);
+    assert!(decoded.nbt.is_decoded());
+}
+
+#[test]
+fn untouched_bytes_are_written_back_unchanged() {
+    // not valid UTF-8, but that only matters when it is decoded.
+    let bytes = vec![0, 2, 0xff, b'a', 7];
+    let decoded = Player::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(decoded.parse().unwrap(), bytes);
+    assert!(decoded.name.get().is_err());
+}
+
+#[test]
+fn fields_after_a_lazy_field_are_decoded() {
+    let bytes = vec![0, 2, b'h', b'i', 7];
+    let mut position = 0;
+    let decoded = Player::compose(&bytes, &mut position).unwrap();
+    assert_eq!(decoded.name.raw(), &[0, 2, b'h', b'i']);
+    assert_eq!(decoded.level, 7);
+    assert_eq!(position, bytes.len());
+    assert!(Player::compose(&bytes[..3], &mut 0)
+        .unwrap_err()
+        .is_incomplete());
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+#![allow(clippy::needless_borrow)]
 use std::io::Write;
 
 use binary_utils::*;
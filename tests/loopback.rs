@@ -0,0 +1,65 @@
+#![cfg(feature = "derive")]
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use binary_utils::buffer::{IBufferRead, IBufferWrite};
+use binary_utils::testing::LoopbackStream;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Request {
+    pub id: u32,
+    pub path: String,
+}
+
+#[test]
+fn requests_and_responses_cross_over() {
+    let (mut client, mut server) = LoopbackStream::pair();
+    let request = Request {
+        id: 9,
+        path: "/status".to_string(),
+    };
+    client.write_value(&request).unwrap();
+    assert_eq!(client.pending(), 0);
+
+    let mut bytes = vec![0; server.pending()];
+    server.read_exact(&mut bytes).unwrap();
+    assert_eq!(Request::compose(&bytes, &mut 0).unwrap(), request);
+
+    server.write_short(200).unwrap();
+    assert_eq!(client.read_short().unwrap(), 200);
+    assert_eq!(client.read(&mut [0; 4]).unwrap(), 0);
+}
+
+#[test]
+fn fragments_split_reads() {
+    let mut stream = LoopbackStream::new().with_fragment(3);
+    stream.write_all(&[1, 2, 3, 4, 5]).unwrap();
+    stream.write_all(&[6]).unwrap();
+
+    let mut out = [0; 8];
+    assert_eq!(stream.read(&mut out).unwrap(), 3);
+    assert_eq!(stream.read(&mut out).unwrap(), 2);
+    assert_eq!(stream.read(&mut out).unwrap(), 1);
+    assert_eq!(out[0], 6);
+}
+
+#[test]
+fn latency_delays_reads() {
+    let mut stream = LoopbackStream::new().with_latency(Duration::from_millis(20));
+    let start = Instant::now();
+    stream.write_byte(1).unwrap();
+    assert_eq!(stream.read_byte().unwrap(), 1);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
+
+#[test]
+fn corruption_flips_every_nth_byte() {
+    let mut stream = LoopbackStream::new().with_corruption(3);
+    stream.write_all(&[0; 4]).unwrap();
+    stream.write_all(&[0; 2]).unwrap();
+    assert_eq!(stream.read_vec(6).unwrap(), vec![0, 0, 0xff, 0, 0, 0xff]);
+
+    stream.write_value(&"abc".to_string()).unwrap();
+    assert!(String::compose(&stream.read_vec(5).unwrap(), &mut 0).is_err());
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+#![allow(clippy::redundant_slicing)]
 use binary_utils::*;
 use std::io::Write;
 
@@ -36,7 +38,7 @@ fn write_l32string() {
     let hello_world = "Hello World!".to_string();
     let data = LString32(hello_world).parse().unwrap();
 
-    assert_eq!(HW_TEST_DATA, &data[..]);
+    assert_bytes_eq!(HW_TEST_DATA, data);
 }
 
 #[test]
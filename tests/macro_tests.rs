@@ -1,3 +1,4 @@
+#![cfg(feature = "derive")]
 use bin_macro::*;
 use binary_utils::{reverse_vec, Streamable, LE};
 #[derive(Debug, BinaryStream)]
@@ -0,0 +1,59 @@
+#![cfg(feature = "derive")]
+use std::collections::{BTreeMap, HashMap};
+
+use binary_utils::error::BinaryError;
+use binary_utils::profile::{DuplicateKeys, WireProfile};
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Metadata {
+    pub entity: u32,
+    pub values: BTreeMap<u8, String>,
+}
+
+#[test]
+fn maps_are_a_count_followed_by_entries() {
+    let mut values = BTreeMap::new();
+    values.insert(2u8, "b".to_string());
+    values.insert(1u8, "a".to_string());
+    let metadata = Metadata { entity: 7, values };
+
+    let bytes = metadata.parse().unwrap();
+    assert_eq!(bytes, vec![0, 0, 0, 7, 2, 1, 0, 1, b'a', 2, 0, 1, b'b']);
+    assert_eq!(Metadata::compose(&bytes, &mut 0).unwrap(), metadata);
+    assert_eq!(metadata.size_hint(), Some(bytes.len()));
+}
+
+#[test]
+fn hash_maps_round_trip() {
+    let map: HashMap<u16, u32> = (0..50).map(|i| (i, i as u32 * 3)).collect();
+    let bytes = map.parse().unwrap();
+    assert_eq!(HashMap::<u16, u32>::compose(&bytes, &mut 0).unwrap(), map);
+}
+
+#[test]
+fn canonical_maps_are_sorted_by_key_bytes() {
+    let map: HashMap<u16, u8> = (0..50).map(|i| (i * 7, i as u8)).collect();
+    let sorted: BTreeMap<u16, u8> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(
+        WireProfile::CANONICAL.encode(&map).unwrap(),
+        sorted.parse().unwrap()
+    );
+}
+
+#[test]
+fn duplicate_map_keys_follow_the_profile() {
+    // two entries for key 1.
+    let bytes = [2, 0, 1, 0, 10, 0, 1, 0, 20];
+    let map = BTreeMap::<u16, u16>::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(map.get(&1), Some(&20));
+
+    let strict = WireProfile::DEFAULT.with_duplicate_keys(DuplicateKeys::Error);
+    assert_eq!(
+        strict.decode::<HashMap<u16, u16>>(&bytes, &mut 0),
+        Err(BinaryError::DuplicateKey {
+            key_repr: "0x0001".to_string(),
+            offset: 5,
+        })
+    );
+}
@@ -0,0 +1,196 @@
+#![cfg(feature = "migrate")]
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+use binary_utils::{migrate, *};
+use ::serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, BinaryStream)]
+#[repr(u8)]
+pub enum Mode {
+    Survival = 0,
+    Creative = 5,
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct Profile {
+    pub mode: Mode,
+    pub id: u64,
+    pub delta: i32,
+    pub xp: VarInt<u32>,
+    pub name: String,
+    pub items: Vec<u16>,
+    pub online: bool,
+    pub speed: f32,
+    pub server: SocketAddr,
+}
+
+// what the data used to be persisted with.
+#[derive(Serialize, Deserialize)]
+enum OldMode {
+    Survival,
+    Creative,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OldProfile {
+    mode: OldMode,
+    id: u64,
+    delta: i32,
+    xp: u32,
+    name: String,
+    items: Vec<u16>,
+    online: bool,
+    speed: f32,
+    server: SocketAddr,
+}
+
+fn old() -> OldProfile {
+    OldProfile {
+        mode: OldMode::Creative,
+        id: 1 << 40,
+        delta: -300,
+        xp: 70000,
+        name: "steve".to_string(),
+        items: vec![1, 1000],
+        online: true,
+        speed: 0.25,
+        server: "127.0.0.1:19132".parse().unwrap(),
+    }
+}
+
+fn check(profile: &Profile) {
+    assert_eq!(profile.mode, Mode::Creative);
+    assert_eq!(profile.id, 1 << 40);
+    assert_eq!(profile.delta, -300);
+    assert_eq!(profile.xp.0, 70000);
+    assert_eq!(profile.name, "steve");
+    assert_eq!(profile.items, vec![1, 1000]);
+    assert!(profile.online);
+    assert_eq!(profile.speed, 0.25);
+    assert_eq!(profile.server, "127.0.0.1:19132".parse().unwrap());
+}
+
+#[test]
+fn reads_and_writes_bincode() {
+    let bytes = bincode::serialize(&old()).unwrap();
+    let profile: Profile = migrate::from_bincode(&bytes).unwrap();
+    assert_eq!(migrate::to_bincode(&profile).unwrap(), bytes);
+    check(&profile);
+}
+
+#[test]
+fn reads_and_writes_postcard() {
+    let bytes = postcard::to_allocvec(&old()).unwrap();
+    let profile: Profile = migrate::from_postcard(&bytes).unwrap();
+    assert_eq!(migrate::to_postcard(&profile).unwrap(), bytes);
+    check(&profile);
+
+    // and onto the Streamable wire.
+    let wire = profile.parse().unwrap();
+    check(&Profile::compose(&wire, &mut 0).unwrap());
+}
+
+#[test]
+fn signed_and_large_values_match_postcard() {
+    for value in [0i64, -1, 1, i64::MIN, i64::MAX] {
+        let bytes = postcard::to_allocvec(&value).unwrap();
+        assert_eq!(migrate::to_postcard(&value).unwrap(), bytes);
+        assert_eq!(migrate::from_postcard::<i64>(&bytes).unwrap(), value);
+    }
+    let bytes = postcard::to_allocvec(&u128::MAX).unwrap();
+    assert_eq!(migrate::from_postcard::<u128>(&bytes).unwrap(), u128::MAX);
+    // 70000 does not fit a u16.
+    let bytes = postcard::to_allocvec(&70000u32).unwrap();
+    assert!(migrate::from_postcard::<u16>(&bytes).is_err());
+    assert!(migrate::from_bincode::<Mode>(&[2, 0, 0, 0]).is_err());
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub enum Shape {
+    Empty,
+    Circle(u32),
+    Rect { width: u16, height: u16 },
+}
+
+#[derive(Serialize, Deserialize)]
+enum OldShape {
+    Empty,
+    Circle(u32),
+    Rect { width: u16, height: u16 },
+}
+
+#[test]
+fn enums_with_fields_match_serde() {
+    let shapes = [
+        (OldShape::Empty, Shape::Empty),
+        (OldShape::Circle(300), Shape::Circle(300)),
+        (
+            OldShape::Rect {
+                width: 2,
+                height: 3,
+            },
+            Shape::Rect {
+                width: 2,
+                height: 3,
+            },
+        ),
+    ];
+    for (old, shape) in shapes {
+        let bytes = bincode::serialize(&old).unwrap();
+        assert_eq!(migrate::to_bincode(&shape).unwrap(), bytes);
+        assert_eq!(migrate::from_bincode::<Shape>(&bytes).unwrap(), shape);
+
+        let bytes = postcard::to_allocvec(&old).unwrap();
+        assert_eq!(migrate::to_postcard(&shape).unwrap(), bytes);
+        assert_eq!(migrate::from_postcard::<Shape>(&bytes).unwrap(), shape);
+    }
+}
+
+#[test]
+fn arrays_match_serde() {
+    let value = [7u16, 300, 2];
+    let bytes = bincode::serialize(&value).unwrap();
+    assert_eq!(migrate::to_bincode(&value).unwrap(), bytes);
+    assert_eq!(migrate::from_bincode::<[u16; 3]>(&bytes).unwrap(), value);
+
+    let bytes = postcard::to_allocvec(&value).unwrap();
+    assert_eq!(migrate::to_postcard(&value).unwrap(), bytes);
+    assert_eq!(migrate::from_postcard::<[u16; 3]>(&bytes).unwrap(), value);
+}
+
+#[test]
+fn maps_match_serde() {
+    let value: BTreeMap<u16, String> = [(1, "a".to_string()), (300, "b".to_string())].into();
+    let bytes = bincode::serialize(&value).unwrap();
+    assert_eq!(migrate::to_bincode(&value).unwrap(), bytes);
+    assert_eq!(
+        migrate::from_bincode::<BTreeMap<u16, String>>(&bytes).unwrap(),
+        value
+    );
+
+    let bytes = postcard::to_allocvec(&value).unwrap();
+    assert_eq!(migrate::to_postcard(&value).unwrap(), bytes);
+    assert_eq!(
+        migrate::from_postcard::<BTreeMap<u16, String>>(&bytes).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn tuples_match_serde() {
+    let value = (7u16, String::from("hi"), true);
+    let bytes = bincode::serialize(&value).unwrap();
+    assert_eq!(migrate::to_bincode(&value).unwrap(), bytes);
+    assert_eq!(
+        migrate::from_bincode::<(u16, String, bool)>(&bytes).unwrap(),
+        value
+    );
+
+    let bytes = postcard::to_allocvec(&value).unwrap();
+    assert_eq!(migrate::to_postcard(&value).unwrap(), bytes);
+    assert_eq!(
+        migrate::from_postcard::<(u16, String, bool)>(&bytes).unwrap(),
+        value
+    );
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "msgpack")]
+use std::collections::BTreeMap;
+
+use binary_utils::{msgpack, *};
+
+#[derive(Debug, Clone, Copy, PartialEq, BinaryStream)]
+#[repr(u8)]
+pub enum Level {
+    Info = 1,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct Event {
+    pub level: Level,
+    pub id: VarInt<u32>,
+    pub offset: i64,
+    pub tags: Vec<u16>,
+    pub ratio: f32,
+}
+
+fn event() -> Event {
+    Event {
+        level: Level::Warn,
+        id: VarInt(300),
+        offset: -5,
+        tags: vec![80, 443],
+        ratio: 0.5,
+    }
+}
+
+#[test]
+fn struct_round_trip() {
+    let bytes = msgpack::to_vec(&event()).unwrap();
+    // a map of five fields, the first keyed by its name.
+    assert_eq!(&bytes[..7], &[0x85, 0xA5, b'l', b'e', b'v', b'e', b'l']);
+    assert_eq!(bytes[7], 2);
+
+    let decoded: Event = msgpack::from_slice(&bytes).unwrap();
+    assert_eq!(decoded.level, Level::Warn);
+    assert_eq!(decoded.id.0, 300);
+    assert_eq!(decoded.offset, -5);
+    assert_eq!(decoded.tags, vec![80, 443]);
+    assert_eq!(decoded.ratio, 0.5);
+
+    // the binary wire is unchanged.
+    let wire = event().parse().unwrap();
+    assert_eq!(Event::compose(&wire, &mut 0).unwrap().id.0, 300);
+}
+
+#[test]
+fn fields_may_arrive_in_any_order() {
+    let mut bytes = vec![0x82];
+    bytes.extend_from_slice(&[0xA2, b'i', b'd', 7]);
+    bytes.extend_from_slice(&[0xA3, b'a', b'g', b'e', 30]);
+
+    #[derive(BinaryStream)]
+    pub struct Person {
+        pub age: u8,
+        pub id: u16,
+    }
+
+    let person: Person = msgpack::from_slice(&bytes).unwrap();
+    assert_eq!((person.age, person.id), (30, 7));
+}
+
+#[test]
+fn unknown_missing_and_invalid() {
+    // a map with a single unknown key.
+    let unknown = vec![0x81, 0xA1, b'x', 0];
+    assert!(msgpack::from_slice::<Event>(&unknown).is_err());
+    // an empty map is missing every field.
+    assert!(msgpack::from_slice::<Event>(&[0x80]).is_err());
+    // there is no variant for `9`.
+    assert!(msgpack::from_slice::<Level>(&[9]).is_err());
+    assert!(msgpack::to_vec(&u128::MAX).is_err());
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub enum Command {
+    Stop,
+    Say(String),
+    Teleport { x: i32, y: i32 },
+}
+
+#[test]
+fn enums_with_fields() {
+    let bytes = msgpack::to_vec(&Command::Say("a".to_string())).unwrap();
+    // an array of the discriminant and the field.
+    assert_eq!(bytes, vec![0x92, 1, 0xA1, b'a']);
+    assert_eq!(msgpack::to_vec(&Command::Stop).unwrap(), vec![0x91, 0]);
+
+    let teleport = Command::Teleport { x: -1, y: 2 };
+    let bytes = msgpack::to_vec(&teleport).unwrap();
+    assert_eq!(msgpack::from_slice::<Command>(&bytes).unwrap(), teleport);
+    // the wrong amount of fields for the variant.
+    assert!(msgpack::from_slice::<Command>(&[0x92, 0, 0]).is_err());
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub enum Flagged {
+    #[binary(id_expr = "0x80 | self.0", id_match = "0x80..=0x8f")]
+    Data(#[binary(from_id = "id & 0x0f")] u8, bool),
+}
+
+#[test]
+fn computed_ids_keep_every_field() {
+    let data = Flagged::Data(3, true);
+    let bytes = msgpack::to_vec(&data).unwrap();
+    assert_eq!(bytes, vec![0x93, 0xcc, 0x83, 3, 0xc3]);
+    assert_eq!(msgpack::from_slice::<Flagged>(&bytes).unwrap(), data);
+}
+
+#[test]
+fn arrays_are_fixed_length() {
+    let bytes = msgpack::to_vec(&[1u8, 2, 3]).unwrap();
+    assert_eq!(bytes, vec![0x93, 1, 2, 3]);
+    assert_eq!(msgpack::from_slice::<[u8; 3]>(&bytes).unwrap(), [1, 2, 3]);
+    assert!(msgpack::from_slice::<[u8; 2]>(&bytes).is_err());
+}
+
+#[test]
+fn maps_are_maps() {
+    let map: BTreeMap<u8, bool> = [(1, true), (2, false)].into();
+    let bytes = msgpack::to_vec(&map).unwrap();
+    assert_eq!(bytes, vec![0x82, 1, 0xC3, 2, 0xC2]);
+    assert_eq!(
+        msgpack::from_slice::<BTreeMap<u8, bool>>(&bytes).unwrap(),
+        map
+    );
+}
+
+#[test]
+fn ack_ranges_are_pairs() {
+    let acks = delivery::AckRanges::from_sequences([1, 2, 5]);
+    let bytes = msgpack::to_vec(&acks).unwrap();
+    assert_eq!(bytes, vec![0x92, 0x92, 1, 2, 0x92, 5, 5]);
+    assert_eq!(
+        msgpack::from_slice::<delivery::AckRanges>(&bytes).unwrap(),
+        acks
+    );
+}
+
+#[test]
+fn tuples_are_arrays() {
+    let bytes = msgpack::to_vec(&(1u8, String::from("a"))).unwrap();
+    assert_eq!(bytes, vec![0x92, 1, 0xA1, b'a']);
+    assert_eq!(
+        msgpack::from_slice::<(u8, String)>(&bytes).unwrap(),
+        (1, String::from("a"))
+    );
+    assert!(msgpack::from_slice::<(u8,)>(&bytes).is_err());
+}
@@ -0,0 +1,76 @@
+#![cfg(all(feature = "derive", feature = "net-addrs"))]
+use std::net::SocketAddr;
+
+use binary_utils::testing::{assert_robust, mutate, Mutation};
+use binary_utils::varint::VarInt;
+use binary_utils::*;
+
+#[derive(Debug, BinaryStream)]
+pub struct Login {
+    pub protocol: VarInt<u32>,
+    pub name: String,
+    pub address: SocketAddr,
+    pub sequence: u24,
+    pub chunks: Vec<u16>,
+    pub flags: [u8; 2],
+}
+
+fn login() -> Vec<u8> {
+    Login {
+        protocol: VarInt(589),
+        name: "Netrex".to_string(),
+        address: "127.0.0.1:19132".parse().unwrap(),
+        sequence: u24(7),
+        chunks: vec![1, 2, 3],
+        flags: [1, 0],
+    }
+    .parse()
+    .unwrap()
+}
+
+#[test]
+fn mutations_are_deterministic() {
+    let valid = [0x12u8, 0x34, 0x56];
+    assert_eq!(
+        mutate(&valid, Mutation::BitFlips).nth(9).unwrap(),
+        vec![0x12, 0x36, 0x56]
+    );
+    assert_eq!(
+        mutate(&valid, Mutation::LengthInflation).collect::<Vec<_>>(),
+        vec![
+            vec![0xff, 0x34, 0x56],
+            vec![0xff, 0xff, 0x56],
+            vec![0x12, 0xff, 0x56],
+            vec![0x12, 0xff, 0xff],
+            vec![0x12, 0x34, 0xff],
+        ]
+    );
+    assert_eq!(
+        mutate(&valid, Mutation::DuplicateFrames).collect::<Vec<_>>(),
+        vec![[valid, valid].concat()]
+    );
+}
+
+#[test]
+fn derived_packets_survive_every_mutation() {
+    assert_robust::<Login>(&login(), 1 << 20);
+}
+
+pub struct Fragile;
+
+impl Streamable for Fragile {
+    fn parse(&self) -> Result<Vec<u8>, error::BinaryError> {
+        Ok(vec![0])
+    }
+
+    fn compose(source: &[u8], _: &mut usize) -> Result<Self, error::BinaryError> {
+        assert_eq!(source[0], 0);
+        Ok(Fragile)
+    }
+}
+
+#[test]
+#[should_panic(expected = "panicked on BitFlips mutation 0")]
+fn panicking_decoders_are_caught() {
+    assert_robust::<Fragile>(&[0], usize::MAX);
+}
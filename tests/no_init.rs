@@ -1,3 +1,4 @@
+#![cfg(feature = "derive")]
 use binary_utils::*;
 
 #[derive(BinaryStream)]
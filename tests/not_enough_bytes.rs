@@ -0,0 +1,45 @@
+use binary_utils::error::BinaryError;
+use binary_utils::*;
+
+#[test]
+fn short_reads_say_how_many_bytes_were_missing() {
+    assert_eq!(
+        u32::compose(&[0, 0, 1, 2, 3], &mut 2),
+        Err(BinaryError::NotEnoughBytes {
+            needed: 4,
+            available: 3
+        })
+    );
+    assert_eq!(
+        String::compose(&[0, 4, b'a'], &mut 0),
+        Err(BinaryError::NotEnoughBytes {
+            needed: 4,
+            available: 1
+        })
+    );
+    assert_eq!(
+        bool::compose(&[], &mut 0),
+        Err(BinaryError::NotEnoughBytes {
+            needed: 1,
+            available: 0
+        })
+    );
+    // a position past the end has nothing left, rather than overflowing
+    assert_eq!(
+        u16::compose(&[1], &mut 3),
+        Err(BinaryError::NotEnoughBytes {
+            needed: 2,
+            available: 0
+        })
+    );
+}
+
+#[test]
+fn incomplete_frames_can_be_told_from_malformed_ones() {
+    let error = u64::compose(&[0; 6], &mut 0).unwrap_err();
+    assert!(error.is_incomplete());
+    assert!(error.at(0, "u64").is_incomplete());
+
+    let error = bool::compose(&[2], &mut 0).unwrap_err();
+    assert!(!error.is_incomplete());
+}
@@ -0,0 +1,55 @@
+#![cfg(all(feature = "derive", feature = "registry"))]
+use binary_utils::registry::Registry;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Login {
+    pub protocol: u32,
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct KeepAlive {
+    pub id: u64,
+}
+
+packet_enum! {
+    #[derive(Debug, PartialEq)]
+    pub ServerBound {
+        0x00 => Login(Login),
+        0x120 => KeepAlive(KeepAlive),
+    }
+}
+
+#[test]
+fn packet_enum_varint_ids() {
+    let packet = ServerBound::from(KeepAlive { id: 9 });
+    assert_eq!(packet.id(), 0x120);
+
+    let bytes = packet.parse().unwrap();
+    assert_eq!(&bytes[..2], &[0xA0, 0x02]);
+    assert_eq!(&bytes[2..], &KeepAlive { id: 9 }.parse().unwrap()[..]);
+
+    let mut position = 0;
+    assert_eq!(ServerBound::compose(&bytes, &mut position).unwrap(), packet);
+    assert_eq!(position, bytes.len());
+}
+
+#[test]
+fn packet_enum_unknown_id() {
+    let error = ServerBound::compose(&[0x05], &mut 0).unwrap_err();
+    assert_eq!(error.get_message(), "Unknown ServerBound packet id: 5");
+}
+
+#[test]
+fn packet_enum_in_registry() {
+    let mut registry = Registry::<ServerBound>::quic_datagram();
+    registry.register::<Login>(0x00);
+
+    let login = Login {
+        protocol: 1,
+        name: "a".into(),
+    };
+    let datagram = registry.encode(&login).unwrap();
+    assert_eq!(datagram, ServerBound::from(login).parse().unwrap());
+}
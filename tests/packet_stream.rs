@@ -0,0 +1,81 @@
+#![cfg(feature = "tokio")]
+
+use binary_utils::{
+    async_io::PacketStream,
+    framing::{encode_frame, LengthPrefix},
+};
+use futures::StreamExt;
+use tokio::io::AsyncWriteExt;
+
+fn frames(prefix: LengthPrefix) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for i in 0..20 {
+        encode_frame(&format!("packet {}", i), prefix, &mut buffer).unwrap();
+    }
+    buffer
+}
+
+#[tokio::test]
+async fn reads_all_frames() {
+    for prefix in [LengthPrefix::U16, LengthPrefix::U32, LengthPrefix::VarInt] {
+        let buffer = frames(prefix);
+        let stream = PacketStream::<_, String>::new(&buffer[..], prefix);
+        let packets: Vec<String> = stream.map(|p| p.unwrap()).collect().await;
+        assert_eq!(packets.len(), 20);
+        assert_eq!(packets[19], "packet 19");
+    }
+}
+
+#[tokio::test]
+async fn handles_partial_reads() {
+    let buffer = frames(LengthPrefix::VarInt);
+    let (mut writer, reader) = tokio::io::duplex(1);
+    let write = async move {
+        // every byte arrives on its own.
+        for byte in buffer {
+            writer.write_all(&[byte]).await.unwrap();
+        }
+    };
+    let read = async move {
+        PacketStream::<_, String>::new(reader, LengthPrefix::VarInt)
+            .map(|p| p.unwrap())
+            .collect::<Vec<String>>()
+            .await
+    };
+
+    let ((), packets) = tokio::join!(write, read);
+    assert_eq!(packets.len(), 20);
+    assert_eq!(packets[0], "packet 0");
+}
+
+#[tokio::test]
+async fn rejects_oversized_frames() {
+    let buffer = frames(LengthPrefix::U32);
+    let mut stream = PacketStream::<_, String>::with_max_frame(&buffer[..], LengthPrefix::U32, 4);
+    let error = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn truncated_frame_is_an_error() {
+    let buffer = frames(LengthPrefix::U16);
+    let mut stream = PacketStream::<_, String>::new(&buffer[..5], LengthPrefix::U16);
+    let error = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn undecodable_frames_do_not_end_the_stream() {
+    let mut buffer = Vec::new();
+    encode_frame(&"first".to_string(), LengthPrefix::U16, &mut buffer).unwrap();
+    // a well delimited frame whose string claims more bytes than it holds
+    buffer.extend_from_slice(&[0, 3, 0, 9, b'x']);
+    encode_frame(&"last".to_string(), LengthPrefix::U16, &mut buffer).unwrap();
+
+    let mut stream = PacketStream::<_, String>::new(&buffer[..], LengthPrefix::U16);
+    assert_eq!(stream.next().await.unwrap().unwrap(), "first");
+    assert!(stream.next().await.unwrap().is_err());
+    assert_eq!(stream.next().await.unwrap().unwrap(), "last");
+    assert!(stream.next().await.is_none());
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Entry {
+    pub id: VarInt<u32>,
+    pub name: String,
+    pub online: bool,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Batch {
+    pub count: u16,
+    pub first: Entry,
+    pub values: Vec<u32>,
+    pub kind: Kind,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub enum Kind {
+    Empty,
+    Sized(u8, u16),
+}
+
+fn batch() -> Batch {
+    Batch {
+        count: 2,
+        first: Entry {
+            id: VarInt(300),
+            name: "a".to_string(),
+            online: true,
+        },
+        values: vec![1, 2],
+        kind: Kind::Sized(1, 2),
+    }
+}
+
+#[test]
+fn parse_into_appends_to_the_buffer() {
+    let mut out = vec![0xAA];
+    batch().parse_into(&mut out).unwrap();
+    assert_eq!(out[0], 0xAA);
+    assert_eq!(&out[1..], &batch().parse().unwrap()[..]);
+
+    // several values written back to back into one buffer.
+    let mut out = Vec::new();
+    for value in [1u16, 2, 3] {
+        value.parse_into(&mut out).unwrap();
+    }
+    "hi".to_string().parse_into(&mut out).unwrap();
+    assert_eq!(out, vec![0, 1, 0, 2, 0, 3, 0, 2, b'h', b'i']);
+}
+
+#[test]
+fn parse_into_round_trips() {
+    let mut out = Vec::new();
+    batch().parse_into(&mut out).unwrap();
+    batch().parse_into(&mut out).unwrap();
+
+    let mut position = 0;
+    assert_eq!(Batch::compose(&out, &mut position).unwrap(), batch());
+    assert_eq!(Batch::compose(&out, &mut position).unwrap(), batch());
+    assert_eq!(position, out.len());
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "derive")]
+use binary_utils::{partial::PartialDecode, *};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct DataFrame {
+    #[binary(header)]
+    pub id: u8,
+    #[binary(header)]
+    pub sequence: u32,
+    pub channel: u8,
+    pub body: String,
+}
+
+fn frame() -> DataFrame {
+    DataFrame {
+        id: 0x84,
+        sequence: 12,
+        channel: 2,
+        body: "hello".to_string(),
+    }
+}
+
+fn route<T: PartialDecode>(bytes: &[u8]) -> (T::Header, usize) {
+    let mut position = 0;
+    let header = T::decode_header(bytes, &mut position).unwrap();
+    (header, position)
+}
+
+#[test]
+fn header_then_rest() {
+    let bytes = frame().parse().unwrap();
+    let (header, mut position): (DataFrameHeader, usize) = route::<DataFrame>(&bytes);
+    assert_eq!(header.id, 0x84);
+    assert_eq!(header.sequence, 12);
+    assert_eq!(position, 5);
+
+    let decoded = DataFrame::decode_rest(header, &bytes, &mut position).unwrap();
+    assert_eq!(decoded, frame());
+    assert_eq!(position, bytes.len());
+}
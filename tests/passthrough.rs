@@ -0,0 +1,42 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, BinaryStream)]
+pub struct Chat {
+    pub sender: VarInt<u32>,
+    pub message: PassthroughOr<String>,
+}
+
+#[test]
+fn passthrough_untouched_is_byte_identical() {
+    // the sender id is written over-long, which a re-encode would normalize.
+    let mut bytes = vec![0x85, 0x80, 0x00];
+    bytes.extend_from_slice(&"hello".to_string().parse().unwrap());
+
+    let packet = PassthroughOr::<Chat>::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(packet.sender.0, 5);
+    assert_eq!(*packet.message, "hello");
+    assert!(!packet.is_modified());
+    assert_eq!(packet.raw(), Some(&bytes[..]));
+    assert_eq!(packet.parse().unwrap(), bytes);
+}
+
+#[test]
+fn passthrough_mutated_is_encoded() {
+    let bytes = Chat {
+        sender: VarInt(5),
+        message: PassthroughOr::new("hello".to_string()),
+    }
+    .parse()
+    .unwrap();
+
+    let mut packet = PassthroughOr::<Chat>::compose(&bytes, &mut 0).unwrap();
+    *packet.get_mut().message.get_mut() = "bye".to_string();
+    assert!(packet.is_modified());
+    assert!(packet.message.is_modified());
+
+    let encoded = packet.parse().unwrap();
+    let chat = Chat::compose(&encoded, &mut 0).unwrap();
+    assert_eq!(*chat.message, "bye");
+    assert_eq!(chat.message.into_inner(), "bye");
+}
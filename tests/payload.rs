@@ -0,0 +1,77 @@
+#![cfg(feature = "derive")]
+use binary_utils::framing::LengthPrefix;
+use binary_utils::profile::WireProfile;
+use binary_utils::*;
+
+#[derive(Debug, Clone, PartialEq, BinaryStream)]
+pub struct Chunk {
+    pub x: i32,
+    pub z: i32,
+    pub data: Payload,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct OwnedChunk {
+    pub x: i32,
+    pub z: i32,
+    pub data: Vec<u8>,
+}
+
+#[test]
+fn same_layout_as_a_vec() {
+    let data: Vec<u8> = (0..=255).collect();
+    let chunk = Chunk {
+        x: 1,
+        z: -1,
+        data: Payload::from(data.clone()),
+    };
+    let owned = OwnedChunk { x: 1, z: -1, data };
+    let bytes = chunk.parse().unwrap();
+    assert_eq!(bytes, owned.parse().unwrap());
+    assert_eq!(chunk.size_hint(), Some(bytes.len()));
+    assert_eq!(Chunk::compose(&bytes, &mut 0).unwrap(), chunk);
+
+    let profile = WireProfile {
+        vec_prefix: LengthPrefix::U32,
+        ..WireProfile::DEFAULT
+    };
+    let bytes = profile.encode(&chunk).unwrap();
+    assert_eq!(bytes, profile.encode(&owned).unwrap());
+    assert_eq!(profile.decode::<Chunk>(&bytes, &mut 0).unwrap(), chunk);
+}
+
+#[test]
+fn clones_share_the_buffer() {
+    let chunk = Chunk {
+        x: 0,
+        z: 0,
+        data: Payload::from(vec![0; 1 << 20]),
+    };
+    let copies: Vec<Chunk> = (0..8).map(|_| chunk.clone()).collect();
+    let handles: Vec<_> = copies
+        .into_iter()
+        .map(|copy| std::thread::spawn(move || copy.data.len()))
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 1 << 20);
+    }
+
+    let mut copy = chunk.clone();
+    assert!(copy.data.ptr_eq(&chunk.data));
+    copy.data.make_mut()[0] = 1;
+    assert!(!copy.data.ptr_eq(&chunk.data));
+    assert_eq!(chunk.data[0], 0);
+}
+
+#[test]
+fn truncated_payloads_are_incomplete() {
+    let bytes = Payload::from(&[1, 2, 3][..]).parse().unwrap();
+    for end in 0..bytes.len() {
+        assert!(Payload::compose(&bytes[..end], &mut 0)
+            .unwrap_err()
+            .is_incomplete());
+    }
+    let mut position = 0;
+    Payload::validate(&bytes, &mut position).unwrap();
+    assert_eq!(position, bytes.len());
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "derive")]
+use binary_utils::position::Position;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Header {
+    pub id: u8,
+    pub length: u16,
+}
+
+#[test]
+fn reads_move_the_position_forward() {
+    let source = [1, 0, 3, 9, 9, 9];
+    let mut position = Position::new();
+    let start = position.checkpoint();
+    let header: Header = position.read(&source).unwrap();
+    assert_eq!(header, Header { id: 1, length: 3 });
+    assert_eq!(position.since(start), 3);
+    assert_eq!(position.rest(&source), &[9, 9, 9]);
+
+    position.advance(3);
+    assert_eq!(position.remaining(source.len()), 0);
+    assert!(position.read::<u8>(&source).is_err());
+    assert_eq!(position.rest(&source), &[] as &[u8]);
+}
+
+#[test]
+fn rewinding_retries_from_a_checkpoint() {
+    let source = [0, 1, 0, 2];
+    let mut position = Position::at(1);
+    let before = position.checkpoint();
+    assert_eq!(position.read::<u16>(&source).unwrap(), 256);
+    position.rewind(before);
+    assert_eq!(position.read::<u8>(&source).unwrap(), 1);
+    assert_eq!(before.offset(), 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "moved backwards")]
+fn moving_backwards_is_caught() {
+    let mut position = Position::at(4);
+    position.with_offset(|offset| *offset = 2);
+}
+
+#[test]
+fn composes_from_a_position() {
+    let source = [1, 0, 3, 7];
+    let mut position = Position::new();
+    assert_eq!(
+        Header::compose_from(&source, &mut position).unwrap(),
+        Header { id: 1, length: 3 }
+    );
+    assert_eq!(u8::compose_from(&source, &mut position).unwrap(), 7);
+    assert_eq!(position.remaining(source.len()), 0);
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "tokio")]
+
+use binary_utils::{
+    async_io::PacketSink,
+    framing::LengthPrefix,
+    registry::{Framing, PacketId, Priority, Registry},
+    *,
+};
+use futures::SinkExt;
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Movement {
+    pub x: u16,
+}
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Chunk {
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Packet {
+    Movement(Movement),
+    Chunk(Chunk),
+}
+
+impl From<Movement> for Packet {
+    fn from(v: Movement) -> Self {
+        Packet::Movement(v)
+    }
+}
+
+impl From<Chunk> for Packet {
+    fn from(v: Chunk) -> Self {
+        Packet::Chunk(v)
+    }
+}
+
+fn registry() -> Registry<Packet> {
+    let mut registry =
+        Registry::<Packet>::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::U16));
+    registry
+        .register_with_priority::<Movement>(1, Priority::High)
+        .register_with_priority::<Chunk>(2, Priority::Bulk);
+    registry
+}
+
+#[test]
+fn priorities_are_kept_per_type() {
+    let mut registry = registry();
+    assert_eq!(registry.priority_of::<Movement>(), Priority::High);
+    assert_eq!(registry.priority_of::<Chunk>(), Priority::Bulk);
+    assert_eq!(registry.priority_of::<u8>(), Priority::Normal);
+    assert_eq!(registry.priority_of_id(2), Priority::Bulk);
+
+    // registering over an id resets its priority.
+    registry.register::<Chunk>(2);
+    assert_eq!(registry.priority_of_id(2), Priority::Normal);
+}
+
+#[tokio::test]
+async fn urgent_packets_overtake_queued_bulk() {
+    let registry = registry();
+    let high = registry.priority_of::<Movement>();
+    // the sink sends single byte tags, 0 for chunks and 1 for movement.
+    let mut sink = PacketSink::<_, u8>::new(Vec::new(), LengthPrefix::U16)
+        .with_priority(move |tag| if *tag == 1 { high } else { Priority::Bulk });
+
+    for tag in [0, 0, 1, 0, 1] {
+        sink.feed(tag).await.unwrap();
+    }
+    assert_eq!(sink.queued(), 5);
+    sink.flush().await.unwrap();
+
+    let written = sink.into_inner();
+    let tags = written.chunks(3).map(|frame| frame[2]).collect::<Vec<_>>();
+    assert_eq!(tags, vec![1, 1, 0, 0, 0]);
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "derive")]
+use binary_utils::{
+    framing::LengthPrefix,
+    profile::{BoolMode, DuplicateKeys, Endian, Utf8Policy, WireProfile},
+    *,
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Handshake {
+    pub protocol: u32,
+    pub name: String,
+    pub online: bool,
+}
+
+fn dialect() -> WireProfile {
+    WireProfile {
+        default_endian: Endian::Little,
+        string_prefix: LengthPrefix::VarInt,
+        vec_prefix: LengthPrefix::U32,
+        bool_mode: BoolMode::Lenient,
+        canonical: false,
+        atomic_ordering: std::sync::atomic::Ordering::SeqCst,
+        duplicate_keys: DuplicateKeys::LastWins,
+        utf8_policy: Utf8Policy::Strict,
+    }
+}
+
+fn handshake() -> Handshake {
+    Handshake {
+        protocol: 1,
+        name: "ab".to_string(),
+        online: true,
+    }
+}
+
+#[test]
+fn same_struct_two_dialects() {
+    let default = WireProfile::DEFAULT.encode(&handshake()).unwrap();
+    assert_eq!(default, vec![0, 0, 0, 1, 0, 2, b'a', b'b', 1]);
+
+    let other = dialect().encode(&handshake()).unwrap();
+    assert_eq!(other, vec![1, 0, 0, 0, 2, b'a', b'b', 1]);
+
+    assert_eq!(
+        dialect().decode::<Handshake>(&other, &mut 0).unwrap(),
+        handshake()
+    );
+    // the thread default was left untouched.
+    assert_eq!(WireProfile::current(), WireProfile::DEFAULT);
+}
+
+#[test]
+fn explicit_endian_wrappers_ignore_the_profile() {
+    let bytes = dialect().encode(&BE(1u16)).unwrap();
+    assert_eq!(bytes, vec![0, 1]);
+    let bytes = dialect().encode(&LE(1u16)).unwrap();
+    assert_eq!(bytes, vec![1, 0]);
+}
+
+#[test]
+fn vector_prefix_and_bool_mode() {
+    let values: Vec<u16> = vec![1, 2];
+    assert_eq!(values.parse().unwrap(), vec![2, 0, 1, 0, 2]);
+    assert_eq!(
+        dialect().encode(&values).unwrap(),
+        vec![2, 0, 0, 0, 1, 0, 2, 0]
+    );
+    assert_eq!(
+        Vec::<u16>::compose(&[9, 2, 0, 1, 0, 2], &mut 1).unwrap(),
+        values
+    );
+
+    assert!(bool::compose(&[7], &mut 0).is_err());
+    assert!(dialect().decode::<bool>(&[7], &mut 0).unwrap());
+}
+
+#[test]
+fn thread_default_can_be_replaced() {
+    std::thread::spawn(|| {
+        WireProfile::set_thread_default(dialect());
+        assert_eq!(1u16.parse().unwrap(), vec![1, 0]);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(1u16.parse().unwrap(), vec![0, 1]);
+}
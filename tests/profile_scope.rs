@@ -0,0 +1,25 @@
+use binary_utils::framing::LengthPrefix;
+use binary_utils::profile::{BoolMode, DuplicateKeys, Endian, Utf8Policy, WireProfile};
+use std::sync::atomic::Ordering;
+
+#[test]
+fn scope_keeps_every_field_and_restores_the_previous_profile() {
+    let profile = WireProfile {
+        default_endian: Endian::Little,
+        string_prefix: LengthPrefix::VarInt,
+        vec_prefix: LengthPrefix::U32,
+        bool_mode: BoolMode::Lenient,
+        canonical: true,
+        atomic_ordering: Ordering::AcqRel,
+        duplicate_keys: DuplicateKeys::Error,
+        utf8_policy: Utf8Policy::Preserve,
+    };
+    let before = WireProfile::current();
+
+    profile.scope(|| {
+        assert_eq!(WireProfile::current(), profile);
+        WireProfile::DEFAULT.scope(|| assert_eq!(WireProfile::current(), WireProfile::DEFAULT));
+        assert_eq!(WireProfile::current(), profile);
+    });
+    assert_eq!(WireProfile::current(), before);
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "derive")]
+use binary_utils::proto::{ProtoField, ProtoMessage};
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Position {
+    #[binary(proto_tag = 1)]
+    pub x: f64,
+    #[binary(proto_tag = 2)]
+    pub y: i32,
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct Player {
+    #[binary(proto_tag = 1)]
+    pub id: VarInt<u32>,
+    #[binary(proto_tag = 3)]
+    pub name: String,
+    #[binary(proto_tag = 4)]
+    pub scores: Vec<u32>,
+    #[binary(proto_tag = 5)]
+    pub skin: Vec<u8>,
+    #[binary(proto_tag = 6)]
+    pub position: Position,
+}
+
+fn player() -> Player {
+    Player {
+        id: VarInt(7),
+        name: "ab".to_string(),
+        scores: vec![1, 300],
+        skin: vec![9],
+        position: Position { x: 0.0, y: -1 },
+    }
+}
+
+#[test]
+fn encodes_the_protobuf_wire_format() {
+    let bytes = player().encode_proto();
+    let mut expected = vec![0x08, 7];
+    expected.extend_from_slice(&[0x1A, 2, b'a', b'b']);
+    // packed scores: 1, 300.
+    expected.extend_from_slice(&[0x22, 3, 1, 0xAC, 0x02]);
+    expected.extend_from_slice(&[0x2A, 1, 9]);
+    // x is the default and omitted, y is sign extended to ten bytes.
+    expected.extend_from_slice(&[0x32, 11, 0x10]);
+    expected.extend_from_slice(&[0xFF; 9]);
+    expected.push(0x01);
+    assert_eq!(bytes, expected);
+
+    let decoded = Player::decode_proto(&bytes).unwrap();
+    assert_eq!(decoded.id.0, 7);
+    assert_eq!(decoded.name, "ab");
+    assert_eq!(decoded.scores, vec![1, 300]);
+    assert_eq!(decoded.skin, vec![9]);
+    assert_eq!(decoded.position, Position { x: 0.0, y: -1 });
+}
+
+#[test]
+fn unknown_fields_are_skipped_and_missing_are_defaults() {
+    // field 9 (a varint) is unknown, field 2 is unpacked.
+    let bytes = vec![0x48, 5, 0x10, 3];
+    let position = Position::decode_proto(&bytes).unwrap();
+    assert_eq!(position, Position { x: 0.0, y: 3 });
+
+    let mut scores = Vec::<u32>::proto_default();
+    scores
+        .merge_field(proto::WireType::Varint, &mut &[4][..])
+        .unwrap();
+    assert_eq!(scores, vec![4]);
+
+    // a string where a varint was expected.
+    assert!(Position::decode_proto(&[0x12, 0]).is_err());
+}
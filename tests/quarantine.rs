@@ -0,0 +1,47 @@
+#![cfg(feature = "quarantine")]
+use std::fs;
+
+use binary_utils::quarantine::Quarantine;
+use binary_utils::{BinaryStream, Streamable};
+
+#[derive(BinaryStream)]
+pub struct Move {
+    pub entity: u64,
+    pub x: f32,
+}
+
+#[test]
+fn keeps_only_the_last_frames() {
+    let dir = std::env::temp_dir().join(format!("binary_utils_quarantine_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let quarantine = Quarantine::open(&dir, 2).unwrap();
+
+    assert!(quarantine
+        .decode::<Move>(&[0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0])
+        .is_ok());
+    assert!(!dir.join("0.bin").exists());
+
+    for length in 1..=3u8 {
+        let frame: Vec<u8> = (0..length).collect();
+        assert!(quarantine.decode::<Move>(&frame).is_err());
+    }
+    // the first frame was overwritten by the third
+    assert_eq!(fs::read(dir.join("0.bin")).unwrap(), [0, 1, 2]);
+    assert_eq!(fs::read(dir.join("1.bin")).unwrap(), [0, 1]);
+    assert!(!dir.join("2.bin").exists());
+
+    let report = fs::read_to_string(dir.join("0.txt")).unwrap();
+    assert!(report.contains("failed at: entity (offset 0)"));
+    assert!(report.contains("0000  00 01 02"));
+
+    assert!(quarantine.decode_plain::<u32>(&[1]).is_err());
+    let report = fs::read_to_string(dir.join("1.txt")).unwrap();
+    assert!(report.starts_with("type: u32\n"));
+
+    // reopening continues after the most recent slot
+    let quarantine = Quarantine::open(&dir, 2).unwrap();
+    assert!(quarantine.decode::<Move>(&[9]).is_err());
+    assert_eq!(fs::read(dir.join("0.bin")).unwrap(), [9]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
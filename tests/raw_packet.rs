@@ -0,0 +1,83 @@
+#![cfg(all(feature = "derive", feature = "registry"))]
+use binary_utils::{
+    framing::LengthPrefix,
+    registry::{Framing, PacketId, RawPacket, Registry},
+    *,
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Ping {
+    pub time: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Packet {
+    Ping(Ping),
+    Raw(RawPacket),
+}
+
+impl From<Ping> for Packet {
+    fn from(v: Ping) -> Self {
+        Packet::Ping(v)
+    }
+}
+
+impl From<RawPacket> for Packet {
+    fn from(v: RawPacket) -> Self {
+        Packet::Raw(v)
+    }
+}
+
+#[test]
+fn raw_packet_for_unknown_id() {
+    let mut registry =
+        Registry::<Packet>::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::U16));
+    registry.register::<Ping>(0x01);
+
+    let mut stream = vec![0, 4, 0x7F, 1, 2, 3];
+    registry
+        .encode_into(&Ping { time: 5 }, &mut stream)
+        .unwrap();
+    assert!(registry.decode_from(&stream, &mut 0).is_err());
+
+    registry.passthrough_unknown();
+    let mut position = 0;
+    let raw = RawPacket {
+        id: 0x7F,
+        body: vec![1, 2, 3],
+    };
+    assert_eq!(
+        registry.decode_from(&stream, &mut position).unwrap(),
+        Packet::Raw(raw.clone())
+    );
+    assert_eq!(position, 6);
+    assert_eq!(
+        registry.decode_from(&stream, &mut position).unwrap(),
+        Packet::Ping(Ping { time: 5 })
+    );
+
+    let mut forwarded = Vec::new();
+    registry.encode_raw(&raw, &mut forwarded).unwrap();
+    assert_eq!(forwarded, &stream[..6]);
+}
+
+#[test]
+fn raw_packet_datagram() {
+    let mut registry = Registry::<Packet>::quic_datagram();
+    registry.register::<Ping>(0x01).passthrough_unknown();
+
+    let datagram = [0xAC, 0x02, 9, 9];
+    let packet = registry.decode(&datagram).unwrap();
+    assert_eq!(
+        packet,
+        Packet::Raw(RawPacket {
+            id: 300,
+            body: vec![9, 9]
+        })
+    );
+
+    // resync still skips ids that aren't registered.
+    let ping = registry.encode(&Ping { time: 1 }).unwrap();
+    assert_eq!(registry.resync(&ping, 0).unwrap().offset, 0);
+    assert!(registry.resync(&datagram, 0).is_none());
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "io-streams")]
+use std::net::UdpSocket;
+
+use binary_utils::recv::RecvBuffer;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Ping {
+    pub time: u64,
+    pub name: String,
+}
+
+#[test]
+fn recv_buffer_reused_for_datagrams() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+
+    let mut buffer = RecvBuffer::with_mtu(64);
+    for time in 0..3 {
+        let ping = Ping {
+            time,
+            name: "n".repeat(time as usize),
+        };
+        client.send_to(&ping.parse().unwrap(), address).unwrap();
+
+        let (datagram, _) = buffer.recv_from(&server).unwrap();
+        assert_eq!(datagram.len(), 10 + time as usize);
+        assert_eq!(buffer.decode::<Ping>().unwrap(), ping);
+    }
+}
+
+#[test]
+fn recv_buffer_fill() {
+    let mut buffer = RecvBuffer::new();
+    assert_eq!(buffer.capacity(), RecvBuffer::DEFAULT_MTU);
+    assert!(buffer.datagram().is_empty());
+
+    let datagram = buffer
+        .fill(|spare| -> Result<usize, ()> {
+            spare[..2].copy_from_slice(&[0, 9]);
+            Ok(2)
+        })
+        .unwrap();
+    assert_eq!(datagram, &[0, 9]);
+    assert_eq!(buffer.decode::<u16>().unwrap(), 9);
+
+    assert!(buffer.fill(|_| Err(())).is_err());
+    assert!(buffer.datagram().is_empty());
+}
+
+#[test]
+#[should_panic]
+fn recv_buffer_overfilled() {
+    RecvBuffer::with_mtu(4).set_filled(5);
+}
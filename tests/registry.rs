@@ -0,0 +1,206 @@
+#![cfg(feature = "registry")]
+use binary_utils::{
+    framing::LengthPrefix,
+    registry::{Framing, PacketId, Registry},
+    *,
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Login {
+    pub protocol: u32,
+    pub name: String,
+}
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Disconnect {
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Packet {
+    Login(Login),
+    Disconnect(Disconnect),
+}
+
+impl From<Login> for Packet {
+    fn from(v: Login) -> Self {
+        Packet::Login(v)
+    }
+}
+
+impl From<Disconnect> for Packet {
+    fn from(v: Disconnect) -> Self {
+        Packet::Disconnect(v)
+    }
+}
+
+fn login() -> Login {
+    Login {
+        protocol: 440,
+        name: "Netrex".to_string(),
+    }
+}
+
+#[test]
+fn quic_datagram_is_id_first_without_length() {
+    let mut registry = Registry::<Packet>::quic_datagram();
+    registry
+        .register::<Login>(0x01)
+        .register::<Disconnect>(0x05);
+
+    let datagram = registry.encode(&login()).unwrap();
+    assert_eq!(datagram[0], 0x01);
+    assert_eq!(&datagram[1..], &login().parse().unwrap()[..]);
+    assert_eq!(registry.decode(&datagram).unwrap(), Packet::Login(login()));
+}
+
+#[test]
+fn length_prefixed_frames_follow_each_other() {
+    let mut registry =
+        Registry::<Packet>::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::U16));
+    registry
+        .register::<Login>(0x01)
+        .register::<Disconnect>(0x05);
+
+    let mut buffer = Vec::new();
+    registry.encode_into(&login(), &mut buffer).unwrap();
+    registry
+        .encode_into(
+            &Disconnect {
+                reason: "bye".to_string(),
+            },
+            &mut buffer,
+        )
+        .unwrap();
+
+    let mut position = 0;
+    assert_eq!(
+        registry.decode_from(&buffer, &mut position).unwrap(),
+        Packet::Login(login())
+    );
+    assert!(matches!(
+        registry.decode_from(&buffer, &mut position).unwrap(),
+        Packet::Disconnect(d) if d.reason == "bye"
+    ));
+    assert_eq!(position, buffer.len());
+}
+
+#[test]
+fn unknown_ids_and_types_are_errors() {
+    let mut registry = Registry::<Packet>::quic_datagram();
+    registry.register::<Login>(0x01);
+
+    assert!(registry.decode(&[0x09, 0, 0]).is_err());
+    assert!(registry
+        .encode(&Disconnect {
+            reason: String::new()
+        })
+        .is_err());
+}
+
+#[test]
+fn observers_see_every_decoded_packet() {
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = Registry::<Packet>::quic_datagram();
+    let logins = seen.clone();
+    let raws = seen.clone();
+    registry
+        .register::<Login>(0x01)
+        .register::<Disconnect>(0x05)
+        .on_decode::<Login>(move |login, _| {
+            logins.lock().unwrap().push(login.name.clone().into_bytes())
+        })
+        .on_decode::<Login>(move |_, raw| raws.lock().unwrap().push(raw.to_vec()));
+
+    registry
+        .decode(&registry.encode(&login()).unwrap())
+        .unwrap();
+    let disconnect = Disconnect {
+        reason: "bye".to_string(),
+    };
+    registry
+        .decode(&registry.encode(&disconnect).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![b"Netrex".to_vec(), login().parse().unwrap()]
+    );
+}
+
+#[test]
+fn average_sizes_follow_encoded_and_decoded_packets() {
+    let mut registry = Registry::<Packet>::quic_datagram();
+    registry
+        .register::<Login>(0x01)
+        .register::<Disconnect>(0x05);
+    assert_eq!(registry.avg_size::<Disconnect>(), None);
+
+    let short = Disconnect {
+        reason: String::new(),
+    };
+    let long = Disconnect {
+        reason: "a".repeat(78),
+    };
+    // the first size is taken as is.
+    let frame = registry.encode(&short).unwrap();
+    assert_eq!(registry.avg_size::<Disconnect>(), Some(2));
+    // later ones move it an eighth of the way.
+    registry.encode(&long).unwrap();
+    assert_eq!(registry.avg_size::<Disconnect>(), Some(12));
+    registry.decode(&frame).unwrap();
+    assert_eq!(registry.avg_size_of_id(0x05), Some(11));
+
+    assert_eq!(registry.avg_size::<Login>(), None);
+    assert_eq!(registry.avg_size::<String>(), None);
+}
+
+#[test]
+fn re_registering_drops_stale_mappings() {
+    let disconnect = Disconnect {
+        reason: "bye".into(),
+    };
+
+    // another type takes over an id
+    let mut registry = Registry::<Packet>::quic_datagram();
+    registry
+        .register::<Login>(0x01)
+        .register::<Disconnect>(0x01);
+    assert_eq!(registry.id_of::<Login>(), None);
+    assert!(registry.encode(&login()).is_err());
+    let datagram = registry.encode(&disconnect).unwrap();
+    assert_eq!(
+        registry.decode(&datagram).unwrap(),
+        Packet::Disconnect(disconnect)
+    );
+
+    // a type moves to another id
+    let mut registry = Registry::<Packet>::quic_datagram();
+    registry.register::<Login>(0x01).register::<Login>(0x02);
+    assert_eq!(registry.id_of::<Login>(), Some(0x02));
+    let mut datagram = registry.encode(&login()).unwrap();
+    assert_eq!(datagram[0], 0x02);
+    assert_eq!(registry.decode(&datagram).unwrap(), Packet::Login(login()));
+    datagram[0] = 0x01;
+    assert!(registry.decode(&datagram).is_err());
+}
+
+#[test]
+fn bytes_left_over_after_a_packet_are_rejected() {
+    let mut registry = Registry::<Packet>::quic_datagram();
+    registry.register::<Login>(0x01);
+    let mut datagram = registry.encode(&login()).unwrap();
+    datagram.push(0xff);
+    assert!(registry.decode(&datagram).is_err());
+
+    let mut registry =
+        Registry::<Packet>::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::U16));
+    registry.register::<Login>(0x01);
+    let mut frame = registry.encode(&login()).unwrap();
+    frame.push(0xff);
+    frame[1] += 1;
+    let error = registry.decode(&frame).unwrap_err();
+    assert!(!error.is_incomplete());
+}
@@ -0,0 +1,104 @@
+#![cfg(all(feature = "derive", feature = "registry"))]
+use binary_utils::{
+    framing::{encode_frame, LengthPrefix},
+    registry::{Framing, PacketId, Registry},
+    resync::{resync, resync_framed, resync_magic},
+    *,
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Record {
+    pub id: u32,
+    pub name: String,
+}
+
+fn record(id: u32) -> Record {
+    Record {
+        id,
+        name: format!("r{}", id),
+    }
+}
+
+fn log_with_garbage() -> (Vec<u8>, usize) {
+    let mut log = Vec::new();
+    encode_frame(&record(1), LengthPrefix::U16, &mut log).unwrap();
+    let corrupt = log.len();
+    // a frame whose length runs past its body.
+    log.extend_from_slice(&[0x00, 0x40, 0xFF, 0xFF]);
+    encode_frame(&record(2), LengthPrefix::U16, &mut log).unwrap();
+    encode_frame(&record(3), LengthPrefix::U16, &mut log).unwrap();
+    (log, corrupt)
+}
+
+#[test]
+fn framed_scan_skips_the_corrupt_record() {
+    let (log, corrupt) = log_with_garbage();
+    let mut position = corrupt;
+    assert!(Record::compose(&log[2..], &mut 0).is_ok());
+
+    let mut found = Vec::new();
+    while let Some(next) = resync_framed::<Record>(&log, position, LengthPrefix::U16) {
+        position = next.end;
+        found.push((next.offset, next.value.id));
+    }
+    assert_eq!(found, vec![(corrupt + 4, 2), (corrupt + 4 + 10, 3)]);
+}
+
+#[test]
+fn magic_scan() {
+    let magic = [0xCA, 0xFE];
+    let mut log = vec![1, 2, 3];
+    log.extend_from_slice(&magic);
+    log.extend_from_slice(&7u16.parse().unwrap());
+
+    let found = resync_magic::<u16>(&log, 0, &magic).unwrap();
+    assert_eq!((found.offset, found.end, found.value), (3, 7, 7));
+    assert!(resync_magic::<u16>(&log, 4, &magic).is_none());
+}
+
+#[test]
+fn short_buffers_are_errors_not_panics() {
+    assert!(u64::compose(&[1, 2], &mut 0).is_err());
+    assert!(bool::compose(&[], &mut 0).is_err());
+    assert!(u24::compose(&[1], &mut 0).is_err());
+    assert!(LE::<u32>::compose(&[1], &mut 0).is_err());
+    assert!(VarInt::<u64>::compose(&[0x80], &mut 0).is_err());
+    assert!(VarInt::<u32>::compose(&[1], &mut 1).is_err());
+    // nothing decodes as a record in a single byte.
+    assert!(resync::<Record>(&[0xFF], 0).is_none());
+}
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Ping {
+    pub time: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Packet {
+    Ping(Ping),
+}
+
+impl From<Ping> for Packet {
+    fn from(v: Ping) -> Self {
+        Packet::Ping(v)
+    }
+}
+
+#[test]
+fn registry_resync_needs_a_known_id_and_exact_length() {
+    let mut registry =
+        Registry::<Packet>::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::VarInt));
+    registry.register::<Ping>(3);
+
+    let mut stream = vec![2, 9, 0xFF];
+    let start = stream.len();
+    registry
+        .encode_into(&Ping { time: 5 }, &mut stream)
+        .unwrap();
+
+    assert!(registry.decode_from(&stream, &mut 0).is_err());
+    let found = registry.resync(&stream, 0).unwrap();
+    assert_eq!(found.offset, start);
+    assert_eq!(found.end, stream.len());
+    assert_eq!(found.value, Packet::Ping(Ping { time: 5 }));
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, Default, PartialEq, BinaryStream)]
+#[binary(test_roundtrip)]
+pub struct Login {
+    pub protocol: u32,
+    pub username: String,
+    pub online: bool,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+#[binary(test_roundtrip = "sample_move")]
+pub struct Move {
+    pub x: f64,
+    pub y: f64,
+    pub ids: Vec<u16>,
+}
+
+fn sample_move() -> Move {
+    Move {
+        x: 1.5,
+        y: -2.0,
+        ids: vec![1, 256],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, BinaryStream)]
+#[binary(test_roundtrip = "sample_kind")]
+#[repr(u16)]
+pub enum Kind {
+    Chat = 1,
+    Whisper = 300,
+}
+
+fn sample_kind() -> Kind {
+    Kind::Whisper
+}
+
+#[test]
+fn generated_tests_are_callable() {
+    __binary_roundtrip_login();
+    __binary_roundtrip_move();
+    __binary_roundtrip_kind();
+}
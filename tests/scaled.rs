@@ -0,0 +1,35 @@
+#![cfg(feature = "derive")]
+use binary_utils::layout::FixedSize;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Move {
+    pub x: Scaled<i32, 32, 1>,
+    pub yaw: Scaled<u8, 256, 360>,
+}
+
+#[test]
+fn scaled_fields_are_written_as_integers() {
+    let movement = Move {
+        x: Scaled::new(-2.25),
+        yaw: Scaled::new(90.0),
+    };
+    let bytes = movement.parse().unwrap();
+    assert_eq!(bytes, vec![0xff, 0xff, 0xff, 0xb8, 64]);
+    assert_eq!(Move::compose(&bytes, &mut 0).unwrap(), movement);
+    assert_eq!(movement.size_hint(), Some(bytes.len()));
+    assert_eq!(Move::WIRE_SIZE, 5);
+}
+
+#[test]
+fn values_are_rounded_and_range_checked() {
+    // 1/64th of a block is half a step, rounded away from zero.
+    assert_eq!(Scaled::<i32, 32, 1>::new(1.0 / 64.0).to_wire().unwrap(), 1);
+    assert_eq!(Scaled::<i32, 32, 1>::from_wire(16).get(), 0.5);
+    assert!(Scaled::<i8, 1, 1>::new(-129.0).parse().is_err());
+    assert!(Scaled::<u16, 1, 1>::new(f64::NAN).parse().is_err());
+    assert_eq!(
+        schema::describe::<Move>(),
+        "{scaled<i32,32/1>,scaled<u8,256/360>}"
+    );
+}
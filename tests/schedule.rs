@@ -0,0 +1,60 @@
+#![cfg(all(feature = "derive", feature = "registry"))]
+use std::time::Duration;
+
+use binary_utils::{
+    framing::LengthPrefix,
+    registry::{Framing, PacketId, Registry},
+    schedule::DecodeScheduler,
+    *,
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Move {
+    pub x: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Packet {
+    Move(Move),
+}
+
+impl From<Move> for Packet {
+    fn from(v: Move) -> Self {
+        Packet::Move(v)
+    }
+}
+
+#[test]
+fn frames_are_spread_over_ticks() {
+    let mut registry =
+        Registry::<Packet>::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::U16));
+    registry.register::<Move>(1);
+
+    let mut scheduler = DecodeScheduler::new();
+    for x in 0..5 {
+        scheduler.push(registry.encode(&Move { x }).unwrap());
+    }
+    scheduler.push(vec![0, 1, 9]);
+
+    let first = scheduler.tick(Duration::ZERO, |frame| registry.decode(frame));
+    assert_eq!(first, vec![Ok(Packet::Move(Move { x: 0 }))]);
+    assert_eq!(scheduler.pending(), 5);
+
+    let rest = scheduler.tick(Duration::from_secs(60), |frame| registry.decode(frame));
+    assert_eq!(rest.len(), 5);
+    assert!(rest[4].is_err());
+    assert_eq!(scheduler.pending(), 0);
+
+    let stats = scheduler.reset_stats();
+    assert_eq!(stats.decoded, 5);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.ticks, 2);
+    assert_eq!(stats.deferred, 5);
+    assert!(stats.busy >= stats.slowest);
+    assert_eq!(scheduler.stats().ticks, 0);
+
+    // nothing to do still counts as a tick.
+    assert!(scheduler
+        .tick(Duration::ZERO, |frame| registry.decode(frame))
+        .is_empty());
+}
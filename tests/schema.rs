@@ -0,0 +1,104 @@
+#![cfg(feature = "derive")]
+use binary_utils::packet_enum;
+use binary_utils::schema::{self, assert_fingerprint, describe, fingerprint};
+use binary_utils::*;
+
+#[derive(BinaryStream)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(BinaryStream)]
+pub struct Move {
+    pub id: VarInt<u32>,
+    pub to: Position,
+    pub tags: Vec<u16>,
+}
+
+// the same layout under other names.
+#[derive(BinaryStream)]
+pub struct Teleport {
+    pub entity: VarInt<u32>,
+    pub target: Position,
+    pub labels: Vec<u16>,
+}
+
+#[derive(BinaryStream)]
+pub struct MoveV2 {
+    pub id: VarInt<u32>,
+    pub to: Position,
+    pub tags: Vec<u32>,
+}
+
+#[derive(BinaryStream)]
+#[repr(u8)]
+pub enum Action {
+    Stop = 1,
+    Walk(LE<u16>),
+}
+
+#[derive(BitIo)]
+pub struct Header {
+    #[bits(3)]
+    pub kind: u8,
+    pub urgent: bool,
+}
+
+packet_enum! {
+    pub Inbound: u8 {
+        0x01 => Move(Move),
+        0x02 => Teleport(Teleport),
+    }
+}
+
+#[test]
+fn describes_the_wire_layout() {
+    assert_eq!(describe::<Move>(), "{varint<u32>,{f32,f32},vec<u16>}");
+    assert_eq!(describe::<Action>(), "enum<u8>{1:{},2:{le<u16>}}");
+    assert_eq!(describe::<Header>(), "bits<3,1>");
+    assert_eq!(
+        describe::<Inbound>(),
+        "packets<u8>{0x01:{varint<u32>,{f32,f32},vec<u16>},0x02:{varint<u32>,{f32,f32},vec<u16>}}"
+    );
+}
+
+#[test]
+fn fingerprints_follow_the_layout_only() {
+    assert_eq!(fingerprint::<Move>(), fingerprint::<Teleport>());
+    assert_ne!(fingerprint::<Move>(), fingerprint::<MoveV2>());
+    // stable across compiles and platforms.
+    assert_eq!(
+        fingerprint::<Position>(),
+        binary_utils::hash::xxh64(b"{f32,f32}", 0)
+    );
+}
+
+#[test]
+fn fingerprint_file_detects_changes() {
+    let path = std::env::temp_dir()
+        .join(format!("binary_utils_schema_{}", std::process::id()))
+        .join("move.schema");
+    let _ = std::fs::remove_file(&path);
+
+    // the first run records the layout.
+    assert_fingerprint::<Move>(&path);
+    let stored = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        stored,
+        format!(
+            "{:016x}\n{}\n",
+            fingerprint::<Move>(),
+            schema::describe::<Move>()
+        )
+    );
+    assert_fingerprint::<Teleport>(&path);
+
+    let changed = std::panic::catch_unwind(|| assert_fingerprint::<MoveV2>(&path));
+    let message = changed.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains("wire layout of"), "{}", message);
+    assert!(message.contains("vec<u32>"), "{}", message);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "derive")]
+use binary_utils::{scratch::with_scratch, *};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Inner {
+    pub a: u16,
+    pub name: String,
+}
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Outer {
+    pub inner: Inner,
+    pub b: u32,
+}
+
+#[test]
+fn nested_scratch_buffers_are_independent() {
+    with_scratch(|outer| {
+        outer.push(1);
+        with_scratch(|inner| {
+            assert!(inner.is_empty());
+            inner.push(2);
+        });
+        assert_eq!(outer, &vec![1]);
+    });
+}
+
+#[test]
+fn derived_parse_into_reuses_the_scratch_buffer() {
+    let value = Outer {
+        inner: Inner {
+            a: 1,
+            name: "x".to_string(),
+        },
+        b: 2,
+    };
+    let bytes = value.parse().unwrap();
+    assert_eq!(bytes, vec![0, 1, 0, 1, b'x', 0, 0, 0, 2]);
+    assert_eq!(Outer::compose(&bytes, &mut 0).unwrap(), value);
+
+    let first = with_scratch(|buf| {
+        value.parse_into(buf).unwrap();
+        assert_eq!(buf, &bytes);
+        buf.as_ptr()
+    });
+    let second = with_scratch(|buf| {
+        value.parse_into(buf).unwrap();
+        buf.as_ptr()
+    });
+    assert_eq!(first, second);
+}
+
+#[test]
+fn shrink_to_watermark_releases_capacity() {
+    use binary_utils::scratch::{high_water, pooled_capacity, shrink_to_watermark};
+
+    with_scratch(|buf| buf.resize(64 * 1024, 0));
+    assert_eq!(high_water(), 64 * 1024);
+    assert!(pooled_capacity() >= 64 * 1024);
+
+    // the large buffer is still within the mark, so nothing is released yet.
+    shrink_to_watermark();
+    assert_eq!(high_water(), 0);
+    assert!(pooled_capacity() >= 64 * 1024);
+
+    with_scratch(|buf| buf.extend_from_slice(&[0; 100]));
+    assert_eq!(high_water(), 100);
+    shrink_to_watermark();
+    assert!(pooled_capacity() < 64 * 1024);
+
+    // the buffer is still pooled.
+    assert!(with_scratch(|buf| buf.capacity() >= 100));
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "serde")]
+use std::collections::BTreeMap;
+
+use binary_utils::serde::{from_bytes, to_bytes};
+use binary_utils::{BinaryStream, Streamable, VarInt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Player {
+    id: u64,
+    name: String,
+    position: (f32, f32, f32),
+    inventory: Vec<u16>,
+    guild: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Event {
+    Ping,
+    Chat(String),
+    Move { x: i32, z: i32 },
+}
+
+#[derive(BinaryStream)]
+struct Wire {
+    id: u64,
+    name: String,
+    inventory: Vec<u16>,
+}
+
+#[derive(Serialize)]
+struct Same {
+    id: u64,
+    name: String,
+    inventory: Vec<u16>,
+}
+
+#[test]
+fn matches_streamable_layout() {
+    let wire = Wire {
+        id: 9,
+        name: "Netrex".into(),
+        inventory: vec![1, 2, 3],
+    };
+    let same = Same {
+        id: 9,
+        name: "Netrex".into(),
+        inventory: vec![1, 2, 3],
+    };
+    assert_eq!(to_bytes(&same).unwrap(), wire.parse().unwrap());
+}
+
+#[test]
+fn round_trip() {
+    let player = Player {
+        id: 42,
+        name: "Steve".into(),
+        position: (1.5, 64.0, -3.25),
+        inventory: vec![276, 1, 0],
+        guild: Some("Netrex".into()),
+    };
+    let bytes = to_bytes(&player).unwrap();
+    assert_eq!(from_bytes::<Player>(&bytes).unwrap(), player);
+
+    let player = Player {
+        guild: None,
+        ..player
+    };
+    let bytes = to_bytes(&player).unwrap();
+    assert_eq!(from_bytes::<Player>(&bytes).unwrap(), player);
+}
+
+#[test]
+fn enums_and_maps() {
+    let events = vec![
+        Event::Ping,
+        Event::Chat("hi".into()),
+        Event::Move { x: -1, z: 7 },
+    ];
+    let bytes = to_bytes(&events).unwrap();
+    assert_eq!(from_bytes::<Vec<Event>>(&bytes).unwrap(), events);
+
+    // variants are a varint index
+    let bytes = to_bytes(&Event::Move { x: 0, z: 0 }).unwrap();
+    assert_eq!(bytes[0], VarInt(2u32).parse().unwrap()[0]);
+
+    let scores: BTreeMap<String, u32> = [("a".to_string(), 1), ("b".to_string(), 2)].into();
+    let bytes = to_bytes(&scores).unwrap();
+    assert_eq!(from_bytes::<BTreeMap<String, u32>>(&bytes).unwrap(), scores);
+}
+
+#[test]
+fn borrows_strings() {
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        name: &'a str,
+    }
+    let bytes = to_bytes("Netrex").unwrap();
+    assert_eq!(from_bytes::<Borrowed>(&bytes).unwrap().name, "Netrex");
+}
+
+#[test]
+fn truncated_input_errors() {
+    let bytes = to_bytes(&Event::Chat("hello".into())).unwrap();
+    let error = from_bytes::<Event>(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert!(error.is_incomplete());
+    assert!(from_bytes::<Option<u8>>(&[2, 0]).is_err());
+}
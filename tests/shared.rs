@@ -0,0 +1,74 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Entry {
+    pub id: u16,
+    pub name: String,
+}
+
+fn batch(entries: &[Entry]) -> Vec<u8> {
+    let mut out = vec![entries.len() as u8];
+    for entry in entries {
+        let body = entry.parse().unwrap();
+        out.extend_from_slice(&(body.len() as u16).parse().unwrap());
+        out.extend_from_slice(&body);
+    }
+    out
+}
+
+#[test]
+fn entries_outlive_the_datagram() {
+    let entries = [
+        Entry {
+            id: 1,
+            name: "a".to_string(),
+        },
+        Entry {
+            id: 2,
+            name: "bc".to_string(),
+        },
+    ];
+    let datagram = SharedSource::from(batch(&entries));
+    let start = datagram.as_ptr() as usize;
+
+    let mut position = 0;
+    let count: u8 = datagram.compose_at(&mut position).unwrap();
+    let mut pending = Vec::new();
+    for _ in 0..count {
+        let length: u16 = datagram.compose_at(&mut position).unwrap();
+        pending.push(datagram.take(&mut position, length as usize).unwrap());
+    }
+    assert_eq!(position, datagram.len());
+    drop(datagram);
+
+    // every entry points into the original buffer.
+    assert!(pending.iter().all(|view| view.as_ptr() as usize > start));
+    let decoded = pending
+        .iter()
+        .map(|view| view.decode::<Entry>().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, entries);
+}
+
+#[test]
+fn slices_and_bounds() {
+    let source = SharedSource::from(&[1u8, 2, 3, 4, 5][..]);
+    let middle = source.slice(1..4);
+    assert_eq!(&middle[..], &[2, 3, 4]);
+    assert_eq!(middle.slice(1..), SharedSource::from(vec![3, 4]));
+    assert!(middle.slice(..0).is_empty());
+
+    let mut position = 2;
+    assert_eq!(
+        middle.take(&mut position, 2),
+        Err(error::BinaryError::EOF(3))
+    );
+    assert_eq!(position, 2);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn slice_out_of_bounds_panics() {
+    SharedSource::from(vec![1, 2]).slice(1..3);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "tokio")]
+
+use binary_utils::{async_io::PacketSink, framing::LengthPrefix};
+use futures::SinkExt;
+use tokio::io::AsyncReadExt;
+
+#[tokio::test]
+async fn send_frames_packets() {
+    let mut sink = PacketSink::<_, u16>::new(Vec::new(), LengthPrefix::U16);
+    sink.send(1).await.unwrap();
+    sink.send(2).await.unwrap();
+    assert_eq!(sink.queued(), 0);
+    assert_eq!(sink.into_inner(), vec![0, 2, 0, 1, 0, 2, 0, 2]);
+}
+
+#[tokio::test]
+async fn applies_backpressure_on_slow_writer() {
+    // a tiny pipe forces the sink to wait on the reader.
+    let (writer, mut reader) = tokio::io::duplex(8);
+    let mut sink = PacketSink::<_, String>::with_limits(writer, LengthPrefix::VarInt, 2, 32);
+
+    let send = async move {
+        for i in 0..50 {
+            sink.feed(format!("packet {}", i)).await.unwrap();
+        }
+        sink.close().await.unwrap();
+    };
+    let receive = async move {
+        let mut received = Vec::new();
+        reader.read_to_end(&mut received).await.unwrap();
+        received
+    };
+
+    let ((), received) = tokio::join!(send, receive);
+    let mut expected = Vec::new();
+    for i in 0..50 {
+        binary_utils::framing::encode_frame(
+            &format!("packet {}", i),
+            LengthPrefix::VarInt,
+            &mut expected,
+        )
+        .unwrap();
+    }
+    assert_eq!(received, expected);
+}
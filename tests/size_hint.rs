@@ -0,0 +1,72 @@
+#![cfg(feature = "derive")]
+use binary_utils::framing::LengthPrefix;
+use binary_utils::profile::WireProfile;
+use binary_utils::tlv::Tlv;
+use binary_utils::*;
+
+#[derive(Debug, BinaryStream)]
+pub struct Chunk {
+    pub x: i32,
+    pub z: i32,
+    pub id: VarInt<u32>,
+    pub flag: bool,
+    pub name: String,
+    pub data: Vec<u16>,
+    pub tag: FixedString<4>,
+    pub kind: Kind,
+}
+
+#[derive(Debug, BinaryStream)]
+#[repr(u16)]
+pub enum Kind {
+    Empty = 0,
+    Filled(u32, LE<u64>),
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct WithTrailer {
+    pub id: u8,
+    pub trailer: Tlv,
+}
+
+fn chunk() -> Chunk {
+    Chunk {
+        x: -1,
+        z: 2,
+        id: VarInt(300),
+        flag: true,
+        name: "overworld".to_string(),
+        data: vec![0; 200],
+        tag: FixedString::new("abc").unwrap(),
+        kind: Kind::Filled(1, LE(2)),
+    }
+}
+
+#[test]
+fn hints_match_encoded_length() {
+    let chunk = chunk();
+    assert_eq!(chunk.size_hint(), Some(chunk.parse().unwrap().len()));
+    assert_eq!(Kind::Empty.size_hint(), Some(2));
+
+    // hints follow the prefixes of the current profile.
+    let profile = WireProfile {
+        string_prefix: LengthPrefix::U32,
+        ..WireProfile::DEFAULT
+    };
+    let hint = profile.scope(|| chunk.size_hint());
+    assert_eq!(hint, Some(profile.encode(&chunk).unwrap().len()));
+    assert_ne!(hint, chunk.size_hint());
+
+    let long = "a".repeat(20_000);
+    assert_eq!(long.size_hint(), Some(long.parse().unwrap().len()));
+}
+
+#[test]
+fn unknown_fields_give_no_hint() {
+    let value = WithTrailer {
+        id: 1,
+        trailer: Tlv::new(),
+    };
+    assert_eq!(value.size_hint(), None);
+    assert_eq!(value.parse().unwrap(), vec![1]);
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "derive")]
+use binary_utils::layout::FixedSize;
+use binary_utils::tracked::FieldSpans;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Player {
+    pub id: u32,
+    #[skip]
+    pub last_seen: Option<std::time::Instant>,
+    pub health: u16,
+    #[skip]
+    pub cache: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+#[binary(construct = "Counter::new")]
+pub struct Counter {
+    pub value: u8,
+    #[skip]
+    hits: u32,
+}
+
+impl Counter {
+    fn new(value: u8, hits: u32) -> Self {
+        Self { value, hits }
+    }
+}
+
+#[test]
+fn skipped_fields_are_not_written() {
+    let player = Player {
+        id: 1,
+        last_seen: Some(std::time::Instant::now()),
+        health: 20,
+        cache: vec!["a".to_string()],
+    };
+    let bytes = player.parse().unwrap();
+    assert_eq!(bytes, vec![0, 0, 0, 1, 0, 20]);
+    assert_eq!(player.size_hint(), Some(6));
+    assert_eq!(<Player as FixedSize>::WIRE_SIZE, 6);
+    assert_eq!(schema::describe::<Player>(), "{u32,u16}");
+    assert_eq!(Player::FIELDS, &["id", "health"]);
+}
+
+#[test]
+fn skipped_fields_are_defaulted() {
+    let player = Player::compose(&[0, 0, 0, 1, 0, 20], &mut 0).unwrap();
+    assert_eq!(
+        player,
+        Player {
+            id: 1,
+            last_seen: None,
+            health: 20,
+            cache: Vec::new(),
+        }
+    );
+
+    let counter = Counter::compose(&[7], &mut 0).unwrap();
+    assert_eq!(counter, Counter::new(7, 0));
+    assert_eq!(Counter::new(7, 3).parse().unwrap(), vec![7]);
+}
@@ -1,10 +1,91 @@
-
-use std::net::SocketAddr;
+#![cfg(feature = "net-addrs")]
 use binary_utils::*;
+use std::net::SocketAddr;
 
 #[test]
 fn test_socket() {
     let socket: SocketAddr = "127.0.0.1:19132".parse().unwrap();
     dbg!(&socket.ip());
     assert_eq!(socket.fparse(), vec![4, 127, 0, 0, 1, 74, 188]);
-}
\ No newline at end of file
+}
+#[test]
+fn truncated_addresses_are_errors() {
+    let v4: SocketAddr = "127.0.0.1:19132".parse().unwrap();
+    let v6: SocketAddr = "[::1]:19133".parse().unwrap();
+    for address in [v4, v6] {
+        let bytes = address.parse().unwrap();
+        let mut position = 0;
+        assert_eq!(SocketAddr::compose(&bytes, &mut position), Ok(address));
+        assert_eq!(position, bytes.len());
+        for end in 0..bytes.len() {
+            let error = SocketAddr::compose(&bytes[..end], &mut 0).unwrap_err();
+            assert!(error.is_incomplete());
+        }
+    }
+    assert!(SocketAddr::compose(&[5, 0, 0], &mut 0).is_err());
+}
+
+#[test]
+fn little_endian_vecs_read_from_the_position() {
+    let bytes = [0xff, 0, 2, 1, 0, 2, 0];
+    let mut position = 1;
+    let values = Vec::<LE<u16>>::compose(&bytes, &mut position).unwrap();
+    assert_eq!(values.iter().map(|v| v.0).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(position, bytes.len());
+    assert!(Vec::<LE<u16>>::compose(&bytes[..6], &mut 1).is_err());
+}
+
+#[test]
+fn raknet_addresses_complement_ipv4() {
+    let v4: SocketAddr = "127.0.0.1:19132".parse().unwrap();
+    let bytes = RakNetAddr(v4).parse().unwrap();
+    assert_eq!(bytes, vec![4, 128, 255, 255, 254, 74, 188]);
+    assert_eq!(RakNetAddr::compose(&bytes, &mut 0).unwrap().inner(), v4);
+
+    let v6: SocketAddr = "[fe80::1]:19133".parse().unwrap();
+    let bytes = RakNetAddr(v6).parse().unwrap();
+    assert_eq!(&bytes[..5], &[6, 23, 0, 74, 189]);
+    assert_eq!(bytes.len(), 29);
+    assert_eq!(RakNetAddr::compose(&bytes, &mut 0).unwrap().inner(), v6);
+    assert!(RakNetAddr::compose(&bytes[..28], &mut 0)
+        .unwrap_err()
+        .is_incomplete());
+}
+
+#[test]
+fn plain_addresses_follow_the_profile() {
+    use binary_utils::profile::{Endian, WireProfile};
+
+    let v4: SocketAddr = "127.0.0.1:19132".parse().unwrap();
+    assert_eq!(PlainAddr(v4).parse().unwrap(), v4.parse().unwrap());
+
+    let le = WireProfile::DEFAULT.with_endian(Endian::Little);
+    for address in [v4, "[::1]:19133".parse().unwrap()] {
+        let bytes = le.encode(&PlainAddr(address)).unwrap();
+        assert_eq!(bytes.len(), PlainAddr(address).size_hint().unwrap());
+        let decoded: PlainAddr = le.decode(&bytes, &mut 0).unwrap();
+        assert_eq!(decoded.inner(), address);
+    }
+    assert_eq!(
+        le.encode(&PlainAddr(v4)).unwrap(),
+        vec![4, 127, 0, 0, 1, 188, 74]
+    );
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Ping {
+    pub from: RakNetAddr,
+    pub to: PlainAddr,
+}
+
+#[test]
+fn addresses_in_packets() {
+    let address: SocketAddr = "10.0.0.1:25565".parse().unwrap();
+    let ping = Ping {
+        from: RakNetAddr(address),
+        to: PlainAddr(address),
+    };
+    let bytes = ping.parse().unwrap();
+    assert_eq!(&bytes[..5], &[4, 245, 255, 255, 254]);
+    assert_eq!(Ping::compose(&bytes, &mut 0).unwrap(), ping);
+}
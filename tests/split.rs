@@ -0,0 +1,62 @@
+use binary_utils::framing::{encode_frame, split_frames, LengthPrefix};
+
+fn frames() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for i in 0..100 {
+        encode_frame(&format!("frame {}", i), LengthPrefix::VarInt, &mut buffer).unwrap();
+    }
+    buffer
+}
+
+#[test]
+fn splits_without_decoding() {
+    let buffer = frames();
+    let split = split_frames(&buffer, LengthPrefix::VarInt).unwrap();
+    assert_eq!(split.len(), 100);
+    assert_eq!(
+        split[3],
+        &[0, 7, b'f', b'r', b'a', b'm', b'e', b' ', b'3'][..]
+    );
+}
+
+#[test]
+fn truncated_frame_is_an_error() {
+    let buffer = frames();
+    assert!(split_frames(&buffer[..buffer.len() - 1], LengthPrefix::VarInt).is_err());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn decodes_in_parallel_in_order() {
+    let buffer = frames();
+    let decoded =
+        binary_utils::framing::decode_parallel::<String>(&buffer, LengthPrefix::VarInt).unwrap();
+    assert_eq!(decoded.len(), 100);
+    for (i, frame) in decoded.iter().enumerate() {
+        assert_eq!(frame, &format!("frame {}", i));
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_decoding_uses_the_callers_profile() {
+    use binary_utils::profile::{Endian, WireProfile};
+
+    let mut buffer = Vec::new();
+    for value in [1u8, 2, 3] {
+        encode_frame(&[value, 0], LengthPrefix::U16, &mut buffer).unwrap();
+    }
+    let little = WireProfile::DEFAULT.with_endian(Endian::Little);
+    let decoded = little.scope(|| {
+        binary_utils::framing::decode_parallel::<u16>(&buffer, LengthPrefix::U16).unwrap()
+    });
+    assert_eq!(decoded, vec![1, 2, 3]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_decoding_rejects_bytes_left_over_in_a_frame() {
+    let mut buffer = Vec::new();
+    encode_frame(&[0u8, 1, 2], LengthPrefix::U16, &mut buffer).unwrap();
+    assert!(binary_utils::framing::decode_parallel::<u16>(&buffer, LengthPrefix::U16).is_err());
+}
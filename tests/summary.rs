@@ -0,0 +1,34 @@
+#![cfg(feature = "derive")]
+use binary_utils::{BinaryStream, Streamable, VarInt};
+
+#[derive(BinaryStream)]
+#[binary(summary("Chunk {id} seq={sequence} x={x:.1}"))]
+pub struct Chunk {
+    pub id: u8,
+    pub sequence: VarInt<u32>,
+    pub x: f32,
+    pub payload: Vec<u8>,
+}
+
+#[test]
+fn summary_display() {
+    let chunk = Chunk {
+        id: 4,
+        sequence: VarInt(300),
+        x: 1.25,
+        payload: vec![0; 1 << 20],
+    };
+    assert_eq!(chunk.to_string(), "Chunk 4 seq=300 x=1.2");
+}
+
+#[derive(BinaryStream)]
+#[binary(summary("{{{name}}} {name}"))]
+pub struct Named {
+    pub name: String,
+}
+
+#[test]
+fn summary_escapes_and_repeats() {
+    let named = Named { name: "a".into() };
+    assert_eq!(format!("{}", named), "{a} a");
+}
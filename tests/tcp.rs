@@ -0,0 +1,74 @@
+#![cfg(feature = "io-streams")]
+use std::io::{Cursor, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+
+use binary_utils::framing::{encode_frame, LengthPrefix};
+use binary_utils::tcp::FramedSync;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Command {
+    pub id: u16,
+    pub args: Vec<u8>,
+}
+
+#[test]
+fn framed_sync_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    let mut client = FramedSync::<_, Command>::new(client, LengthPrefix::U32);
+    let mut server = FramedSync::<_, Command>::new(server, LengthPrefix::U32);
+
+    for id in 0..3 {
+        client
+            .send(&Command {
+                id,
+                args: vec![id as u8; id as usize * 100],
+            })
+            .unwrap();
+    }
+    for id in 0..3 {
+        let command = server.recv().unwrap().unwrap();
+        assert_eq!(command.id, id);
+        assert_eq!(command.args.len(), id as usize * 100);
+    }
+
+    drop(client);
+    assert!(server.recv().unwrap().is_none());
+}
+
+#[test]
+fn framed_sync_varint_in_memory() {
+    let mut stream = Vec::new();
+    encode_frame(&300u16, LengthPrefix::VarInt, &mut stream).unwrap();
+    encode_frame(&7u16, LengthPrefix::VarInt, &mut stream).unwrap();
+
+    let mut framed = FramedSync::<_, u16>::new(Cursor::new(stream), LengthPrefix::VarInt);
+    assert_eq!(framed.recv().unwrap(), Some(300));
+    assert_eq!(framed.recv().unwrap(), Some(7));
+    assert_eq!(framed.recv().unwrap(), None);
+}
+
+#[test]
+fn framed_sync_enforces_max_frame() {
+    let mut framed =
+        FramedSync::<_, String>::with_max_frame(Cursor::new(Vec::new()), LengthPrefix::U16, 4);
+    let error = framed.send(&"too long".to_string()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(framed.get_ref().get_ref().is_empty());
+
+    let mut framed =
+        FramedSync::<_, String>::with_max_frame(Cursor::new(vec![0, 10]), LengthPrefix::U16, 4);
+    assert_eq!(framed.recv().unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn framed_sync_truncated_frame() {
+    let mut framed = FramedSync::<_, u32>::new(Cursor::new(vec![0, 4, 1, 2]), LengthPrefix::U16);
+    assert_eq!(framed.recv().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+    let mut framed = FramedSync::<_, u32>::new(Cursor::new(vec![0x80]), LengthPrefix::VarInt);
+    assert_eq!(framed.recv().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
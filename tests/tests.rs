@@ -1,9 +1,84 @@
+mod alloc;
+mod allocs;
+mod analysis;
+mod array;
+mod async_streamable;
+mod atomic;
+mod bits;
+mod borrowed;
+mod buffer;
+mod bytes_eq;
+mod canonical;
+mod cfg_fields;
+mod coalesce;
+mod codec;
+mod compose_into;
+mod construct;
+mod copy;
+mod default_fields;
+mod delivery;
+mod diff;
+mod duplicate_keys;
+mod endian;
 mod enums;
+mod error_at;
+mod extensions;
+mod fixed_string;
+mod forbid_unsafe;
 mod format;
+mod hash;
+mod inflate;
 mod io;
+mod layout;
+mod lazy;
 mod le_test;
+mod loopback;
 mod lstring;
 mod macro_tests;
+mod map;
+mod migrate;
+mod msgpack;
+mod mutate;
 mod no_init;
+mod not_enough_bytes;
+mod packet_enum;
+mod packet_stream;
+mod parse_into;
+mod partial;
+mod passthrough;
+mod payload;
+mod position;
+mod priority;
+mod profile;
+mod profile_scope;
+mod proto;
+mod quarantine;
+mod raw_packet;
+mod recv;
+mod registry;
+mod resync;
+mod roundtrip;
+mod scaled;
+mod schedule;
+mod schema;
+mod scratch;
+mod serde_backend;
+mod shared;
+mod sink;
+mod size_hint;
+mod skip;
+mod split;
+mod summary;
+mod tcp;
+mod tlv;
+mod tracked;
+mod tuple;
+mod u24;
+mod udp;
+mod utf8;
+mod validate;
 mod var_int;
 mod vec;
+mod versioned;
+mod wire_eq;
+mod ws;
@@ -0,0 +1,88 @@
+#![cfg(feature = "derive")]
+use binary_utils::tlv::{Tlv, TlvRecord};
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+#[binary(tlv_id = 1)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+#[binary(tlv_id = 300)]
+pub struct Label {
+    pub text: String,
+}
+
+#[derive(Debug, BinaryStream)]
+pub struct Certificate {
+    pub serial: u32,
+    pub fields: Tlv<u16, u16>,
+}
+
+#[test]
+fn tlv_record_ids_from_derive() {
+    assert_eq!(Version::ID, 1);
+    assert_eq!(Label::ID, 300);
+}
+
+#[test]
+fn tlv_lookup_and_iteration() {
+    let mut fields = Tlv::<u16, u16>::new();
+    fields.insert(&Version { major: 1, minor: 2 }).unwrap();
+    fields
+        .insert(&Label {
+            text: "cert".into(),
+        })
+        .unwrap();
+    fields.insert_raw(9, vec![0xFF]);
+
+    let bytes = Certificate { serial: 5, fields }.parse().unwrap();
+    let certificate = Certificate::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(certificate.fields.len(), 3);
+    assert_eq!(
+        certificate.fields.get::<Version>().unwrap(),
+        Some(Version { major: 1, minor: 2 })
+    );
+    assert_eq!(
+        certificate.fields.get::<Label>().unwrap().unwrap().text,
+        "cert"
+    );
+    assert_eq!(
+        certificate
+            .fields
+            .iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>(),
+        vec![1, 300, 9]
+    );
+    assert_eq!(certificate.fields.raw(9), Some(&[0xFF][..]));
+}
+
+#[test]
+fn tlv_unknown_records_round_trip() {
+    let bytes = vec![7, 3, 1, 2, 3, 1, 2, 4, 5];
+    let tlv = Tlv::<u8, u8>::compose(&bytes, &mut 0).unwrap();
+    assert!(tlv.contains::<Version>());
+    assert_eq!(tlv.parse().unwrap(), bytes);
+
+    let mut tlv = tlv;
+    assert!(tlv.remove::<Version>());
+    assert_eq!(tlv.parse().unwrap(), vec![7, 3, 1, 2, 3]);
+}
+
+#[test]
+fn tlv_id_too_large_for_wire() {
+    let mut tlv = Tlv::<u8, u8>::new();
+    tlv.insert(&Label { text: "a".into() }).unwrap();
+    assert!(tlv.parse().is_err());
+}
+
+#[test]
+fn tlv_varint_default() {
+    let mut tlv: Tlv = Tlv::new();
+    tlv.insert(&Label { text: "a".into() }).unwrap();
+    assert_eq!(tlv.parse().unwrap(), vec![0xAC, 0x02, 3, 0, 1, b'a']);
+    assert!(Tlv::<u8, u8>::compose(&[1, 5, 0], &mut 0).is_err());
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "derive")]
+use binary_utils::tracked::{FieldSpans, Tracked};
+use binary_utils::{BinaryStream, Streamable, VarInt};
+
+#[derive(Debug, Clone, PartialEq, BinaryStream)]
+pub struct Position {
+    pub entity: u64,
+    pub x: f32,
+    pub y: f32,
+    pub name: String,
+}
+
+fn sample() -> Position {
+    Position {
+        entity: 7,
+        x: 1.5,
+        y: -2.0,
+        name: "steve".into(),
+    }
+}
+
+#[test]
+fn field_spans_cover_buffer() {
+    let bytes = sample().parse().unwrap();
+    let mut spans = Vec::new();
+    let value = Position::compose_spanned(&bytes, &mut 0, &mut spans).unwrap();
+    assert_eq!(value, sample());
+    assert_eq!(Position::FIELDS, &["entity", "x", "y", "name"]);
+    assert_eq!(spans, vec![0..8, 8..12, 12..16, 16..bytes.len()]);
+}
+
+#[test]
+fn re_encode_in_place() {
+    let bytes = sample().parse().unwrap();
+    let mut tracked = Tracked::<Position>::decode(bytes.clone()).unwrap();
+    assert!(!tracked.is_dirty());
+    assert_eq!(tracked.re_encode().unwrap(), &bytes[..]);
+
+    tracked
+        .modify("x", |p| p.x = 10.0)
+        .modify("y", |p| p.y = 20.0);
+    assert!(tracked.is_dirty());
+
+    let expected = Position {
+        x: 10.0,
+        y: 20.0,
+        ..sample()
+    };
+    let spans = tracked.spans().to_vec();
+    assert_eq!(tracked.re_encode().unwrap(), &expected.parse().unwrap()[..]);
+    assert_eq!(tracked.spans(), &spans[..]);
+    assert!(!tracked.is_dirty());
+}
+
+#[test]
+fn re_encode_resized_field() {
+    let mut tracked = Tracked::<Position>::decode(sample().parse().unwrap()).unwrap();
+    tracked.modify("name", |p| p.name = "alexander".into());
+
+    let expected = Position {
+        name: "alexander".into(),
+        ..sample()
+    };
+    let bytes = tracked.re_encode().unwrap().to_vec();
+    assert_eq!(bytes, expected.parse().unwrap());
+    assert_eq!(tracked.spans()[3], 16..bytes.len());
+    assert_eq!(tracked.into_inner(), expected);
+}
+
+#[test]
+fn decode_keeps_only_packet_bytes() {
+    let mut bytes = sample().parse().unwrap();
+    let length = bytes.len();
+    bytes.extend_from_slice(&[1, 2, 3]);
+    let tracked = Tracked::<Position>::decode(bytes).unwrap();
+    assert_eq!(tracked.buffer().len(), length);
+    assert_eq!(tracked.name, "steve");
+}
+
+#[derive(BinaryStream)]
+pub struct Counter {
+    pub count: VarInt<u32>,
+    pub flag: bool,
+}
+
+#[test]
+fn re_encode_get_mut() {
+    let bytes = Counter {
+        count: VarInt(1),
+        flag: false,
+    }
+    .parse()
+    .unwrap();
+    let mut tracked = Tracked::<Counter>::decode(bytes).unwrap();
+    tracked.get_mut().count = VarInt(300);
+    tracked.get_mut().flag = true;
+
+    let bytes = tracked.re_encode().unwrap().to_vec();
+    let counter = Counter::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(counter.count.0, 300);
+    assert!(counter.flag);
+}
+
+#[test]
+#[should_panic(expected = "no field `z`")]
+fn modify_unknown_field() {
+    let mut tracked = Tracked::<Position>::decode(sample().parse().unwrap()).unwrap();
+    tracked.modify("z", |_| {});
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "derive")]
+use binary_utils::layout::FixedSize;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Entry {
+    pub id: u8,
+    pub flag: bool,
+}
+
+#[test]
+fn tuples_are_written_in_order() {
+    let value = (1u8, String::from("hi"), 2u16);
+    let bytes = value.parse().unwrap();
+    assert_eq!(bytes, vec![1, 0, 2, b'h', b'i', 0, 2]);
+    assert_eq!(<(u8, String, u16)>::compose(&bytes, &mut 0).unwrap(), value);
+    assert_eq!(value.size_hint(), Some(7));
+    assert!(<(u8, String, u16)>::validate(&bytes[..6], &mut 0).is_err());
+}
+
+#[test]
+fn tuples_match_a_struct_of_the_same_fields() {
+    let entry = Entry { id: 3, flag: true };
+    assert_eq!((3u8, true).parse().unwrap(), entry.parse().unwrap());
+    assert_eq!(<(u8, bool) as FixedSize>::WIRE_SIZE, 2);
+    assert_eq!(
+        schema::fingerprint::<(u8, bool)>(),
+        schema::fingerprint::<Entry>()
+    );
+}
+
+#[test]
+fn twelve_item_tuples() {
+    let value = (
+        1u8,
+        2u8,
+        3u8,
+        4u8,
+        5u8,
+        6u8,
+        7u8,
+        8u8,
+        9u8,
+        10u8,
+        11u8,
+        (12u16,),
+    );
+    let bytes = value.parse().unwrap();
+    assert_eq!(bytes.len(), 13);
+    assert_eq!(Streamable::compose(&bytes, &mut 0).ok(), Some(value));
+}
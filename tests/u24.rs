@@ -0,0 +1,95 @@
+#![cfg(feature = "derive")]
+use binary_utils::*;
+
+#[derive(Debug, BinaryStream)]
+pub struct Frame {
+    #[le]
+    pub sequence: u24,
+    pub index: LE<u24>,
+    pub order: u24,
+}
+
+#[test]
+fn u24s_round_trip_in_both_orders() {
+    let value = u24(0x00AB_CDEF);
+    assert_eq!(value.to_be_bytes(), [0xAB, 0xCD, 0xEF]);
+    assert_eq!(value.to_le_bytes(), [0xEF, 0xCD, 0xAB]);
+    assert_eq!(u24::from_be_bytes(&value.to_be_bytes()), value);
+    assert_eq!(u24::from_le_bytes(&value.to_le_bytes()), value);
+
+    let max = u24::from(0x00FF_FFFFu32);
+    assert_eq!(u24::compose(&max.parse().unwrap(), &mut 0).unwrap(), max);
+}
+
+#[test]
+fn little_endian_triads_in_a_struct() {
+    let bytes = [1, 0, 0, 2, 0, 0, 0, 0, 3];
+    let frame = Frame::compose(&bytes, &mut 0).unwrap();
+    assert_eq!(
+        (frame.sequence, frame.index.0, frame.order),
+        (u24(1), u24(2), u24(3))
+    );
+    assert_eq!(frame.parse().unwrap(), bytes);
+}
+
+#[test]
+fn readers_and_writers_take_either_order() {
+    use std::io::Cursor;
+
+    let mut out = Cursor::new(Vec::new());
+    out.write_u24_le(u24(0x010203)).unwrap();
+    out.write_u24(u24(0x010203)).unwrap();
+    assert_eq!(out.get_ref(), &vec![3, 2, 1, 1, 2, 3]);
+
+    let mut source = Cursor::new(out.into_inner());
+    assert_eq!(source.read_u24_le().unwrap(), u24(0x010203));
+    assert_eq!(source.read_u24().unwrap(), u24(0x010203));
+}
+
+#[test]
+fn i24s_sign_extend_and_check_their_range() {
+    for value in [0, 1, -1, 0x7F_FFFF, -0x80_0000] {
+        let be = i24(value).parse().unwrap();
+        assert_eq!(i24::compose(&be, &mut 0).unwrap().inner(), value);
+        let le = LE(i24(value)).parse().unwrap();
+        assert_eq!(LE::<i24>::compose(&le, &mut 0).unwrap().inner(), i24(value));
+    }
+    assert_eq!(i24::from_le_bytes(&[0xfe, 0xff, 0xff]), i24(-2));
+    assert_eq!(i24::from_be_bytes(&[0x7f, 0xff, 0xff]), i24::MAX);
+
+    assert!(i24(0x80_0000).parse().is_err());
+    assert!(i24(-0x80_0001).parse().is_err());
+    assert!(i24::try_from(0x80_0000i32).is_err());
+    assert_eq!(i24::try_from(-5i64).unwrap(), i24(-5));
+    assert_eq!(schema::describe::<LE<i24>>(), "le<i24>");
+}
+
+#[test]
+fn triads_display_and_parse_like_numbers() {
+    use binary_utils::varint::{SVarInt, VarInt};
+
+    assert_eq!(u24(70000).to_string(), "70000");
+    assert_eq!("70000".parse::<u24>().unwrap(), u24(70000));
+    assert!("16777216".parse::<u24>().is_err());
+    assert!("-1".parse::<u24>().is_err());
+    assert_eq!("-8388608".parse::<i24>().unwrap(), i24::MIN);
+    assert!("8388608".parse::<i24>().is_err());
+
+    assert_eq!("25565".parse::<VarInt<u32>>().unwrap(), VarInt(25565));
+    assert_eq!(VarInt(25565u32).to_string(), "25565");
+    assert_eq!("-3".parse::<SVarInt<i64>>().unwrap(), SVarInt(-3));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn triads_serialize_as_numbers() {
+    use binary_utils::varint::VarInt;
+
+    let bytes = bincode::serialize(&(u24(70000), i24(-2), VarInt(7u64))).unwrap();
+    assert_eq!(bytes, bincode::serialize(&(70000u32, -2i32, 7u64)).unwrap());
+    let value: (u24, i24, VarInt<u64>) = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(value, (u24(70000), i24(-2), VarInt(7)));
+
+    let too_big = bincode::serialize(&0x0100_0000u32).unwrap();
+    assert!(bincode::deserialize::<u24>(&too_big).is_err());
+}
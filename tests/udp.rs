@@ -0,0 +1,62 @@
+#![cfg(feature = "io-streams")]
+use std::net::UdpSocket;
+
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Pong {
+    pub time: u64,
+    pub motd: String,
+}
+
+fn pong() -> Pong {
+    Pong {
+        time: 42,
+        motd: "A server".into(),
+    }
+}
+
+#[test]
+fn udp_send_and_recv_packet() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let sent = udp::send_packet(&client, server.local_addr().unwrap(), &pong()).unwrap();
+    assert_eq!(sent, pong().parse().unwrap().len());
+
+    let mut buffer = [0; 1500];
+    let (packet, from) = udp::recv_packet::<Pong>(&server, &mut buffer).unwrap();
+    assert_eq!(packet, pong());
+    assert_eq!(from, client.local_addr().unwrap());
+}
+
+#[test]
+fn udp_recv_packet_invalid() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    client
+        .send_to(&[1, 2], server.local_addr().unwrap())
+        .unwrap();
+
+    let mut buffer = [0; 64];
+    let error = udp::recv_packet::<Pong>(&server, &mut buffer).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn udp_send_and_recv_packet_async() {
+    let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    udp::send_packet_async(&client, server.local_addr().unwrap(), &pong())
+        .await
+        .unwrap();
+
+    let mut buffer = [0; 1500];
+    let (packet, from) = udp::recv_packet_async::<Pong>(&server, &mut buffer)
+        .await
+        .unwrap();
+    assert_eq!(packet, pong());
+    assert_eq!(from, client.local_addr().unwrap());
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "derive")]
+use binary_utils::profile::{Utf8Policy, WireProfile};
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Chat {
+    pub sender: String,
+    pub message: String,
+}
+
+// "h", an invalid byte, "i", then a truncated two byte sequence.
+const INVALID: [u8; 6] = [0, 4, b'h', 0xFF, b'i', 0xC3];
+
+#[test]
+fn strict_rejects_invalid_utf8() {
+    assert!(String::compose(&INVALID, &mut 0).is_err());
+    assert!(FixedString::<4>::compose(&INVALID[2..], &mut 0).is_err());
+}
+
+#[test]
+fn lossy_replaces_invalid_sequences() {
+    let lossy = WireProfile::DEFAULT.with_utf8_policy(Utf8Policy::Lossy);
+    let value: String = lossy.decode(&INVALID, &mut 0).unwrap();
+    assert_eq!(value, "h\u{FFFD}i\u{FFFD}");
+
+    let mut bytes = vec![0, 2, b'o', b'k'];
+    bytes.extend_from_slice(&INVALID);
+    let chat: Chat = lossy.decode(&bytes, &mut 0).unwrap();
+    assert_eq!(chat.sender, "ok");
+    assert_eq!(chat.message, "h\u{FFFD}i\u{FFFD}");
+}
+
+#[test]
+fn preserve_writes_back_the_same_bytes() {
+    let preserve = WireProfile::DEFAULT.with_utf8_policy(Utf8Policy::Preserve);
+    let value: String = preserve.decode(&INVALID, &mut 0).unwrap();
+    assert_eq!(value, "h\u{10FFFF}i\u{10FFC3}");
+    assert_eq!(preserve.encode(&value).unwrap(), INVALID.to_vec());
+    assert_eq!(preserve.scope(|| value.size_hint()), Some(INVALID.len()));
+
+    let fixed: FixedString<4> = preserve.decode(&INVALID[2..], &mut 0).unwrap();
+    assert_eq!(preserve.encode(&fixed).unwrap(), INVALID[2..].to_vec());
+
+    // valid strings are unchanged.
+    assert_eq!(
+        preserve.encode(&"hé".to_string()).unwrap(),
+        vec![0, 3, b'h', 0xC3, 0xA9]
+    );
+}
+
+#[test]
+fn preserve_writes_its_private_use_range_as_raw_bytes() {
+    let private = "\u{10FF80}".to_string();
+    let preserve = WireProfile::DEFAULT.with_utf8_policy(Utf8Policy::Preserve);
+    assert_eq!(preserve.encode(&private).unwrap(), vec![0, 1, 0x80]);
+    assert_eq!(
+        WireProfile::DEFAULT.encode(&private).unwrap(),
+        vec![0, 4, 0xF4, 0x8F, 0xBE, 0x80]
+    );
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "derive")]
+use binary_utils::error::BinaryError;
+use binary_utils::*;
+
+#[derive(Debug, PartialEq, BinaryStream)]
+#[repr(u8)]
+pub enum Kind {
+    Text = 1,
+    Binary = 2,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub struct Message {
+    pub kind: Kind,
+    pub channel: u16,
+    pub body: String,
+    pub tags: Vec<u32>,
+}
+
+#[derive(Debug, PartialEq, BinaryStream)]
+pub enum Frame {
+    Ping(u64),
+    Data(Message),
+}
+
+fn message() -> Message {
+    Message {
+        kind: Kind::Text,
+        channel: 3,
+        body: "hello".to_string(),
+        tags: vec![1, 2],
+    }
+}
+
+#[test]
+fn validate_walks_the_layout() {
+    let bytes = message().parse().unwrap();
+    let mut position = 0;
+    Message::validate(&bytes, &mut position).unwrap();
+    assert_eq!(position, bytes.len());
+
+    // validating agrees with composing.
+    let mut composed = 0;
+    Message::compose(&bytes, &mut composed).unwrap();
+    assert_eq!(composed, position);
+}
+
+#[test]
+fn validate_rejects_truncated_frames() {
+    let bytes = message().parse().unwrap();
+    for end in 0..bytes.len() {
+        assert!(
+            Message::validate(&bytes[..end], &mut 0).is_err(),
+            "{} bytes passed",
+            end
+        );
+    }
+}
+
+#[test]
+fn validate_rejects_unknown_discriminants() {
+    let mut bytes = message().parse().unwrap();
+    bytes[0] = 9;
+    assert_eq!(
+        Message::validate(&bytes, &mut 0),
+        Err(BinaryError::RecoverableKnown(
+            "9 is not a variant of Kind".to_string()
+        ))
+    );
+
+    let frame = Frame::Data(message()).parse().unwrap();
+    Frame::validate(&frame, &mut 0).unwrap();
+    assert!(Frame::validate(&[2], &mut 0).is_err());
+}
+
+#[test]
+fn validate_rejects_oversized_lengths() {
+    // a vec claiming u32::MAX items of 4 bytes.
+    let bytes = [0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0, 0, 0, 0];
+    assert_eq!(
+        Vec::<u32>::validate(&bytes, &mut 0),
+        Err(BinaryError::EOF(bytes.len()))
+    );
+}
@@ -1,3 +1,4 @@
+#![allow(clippy::useless_vec)]
 use binary_utils::Streamable;
 use binary_utils::*;
 
@@ -48,3 +49,161 @@ fn var_int_test_middle() {
         255
     );
 }
+
+#[test]
+fn var_int_conversions() {
+    let id: VarInt<i32> = (-5i16).into();
+    assert_eq!(i64::from(id), -5);
+    assert!(u32::try_from(id).is_err());
+    assert_eq!(i8::try_from(id), Ok(-5));
+
+    let length = VarInt::<u64>::from(300u32);
+    assert!(u8::try_from(length).is_err());
+    assert_eq!(u16::try_from(length), Ok(300));
+
+    assert!(VarInt::<u32>::try_from(-1i32).is_err());
+    assert_eq!(VarInt::<u32>::try_from(7i64), Ok(VarInt(7)));
+    assert!(VarInt::<i32>::try_from(u64::MAX).is_err());
+    assert_eq!(VarInt::<i64>::from(u32::MAX).0, u32::MAX as i64);
+}
+
+#[test]
+fn narrowing_conversions_are_checked() {
+    assert!(VarInt::<u32>::try_from(u64::from(u32::MAX) + 1).is_err());
+    assert_eq!(VarInt::<u32>::try_from(300u64), Ok(VarInt(300)));
+    assert!(VarInt::<u32>::try_from(u128::MAX).is_err());
+    assert!(u8::try_from(VarInt::<u32>(256)).is_err());
+    assert_eq!(u16::try_from(VarInt::<u32>(256)), Ok(256));
+    assert!(VarInt::<u64>::try_from(-1i8).is_err());
+    assert!(i64::try_from(VarInt::<u64>(u64::MAX)).is_err());
+    assert_eq!(i128::from(VarInt::<u64>(u64::MAX)), u64::MAX as i128);
+
+    assert_eq!(VarInt::<u32>::try_from(3.0f64).unwrap(), VarInt(3));
+    assert!(VarInt::<u32>::try_from(3.5f64).is_err());
+    assert!(VarInt::<u32>::try_from(-1.0f32).is_err());
+    assert!(VarInt::<u32>::try_from(4294967296.0f64).is_err());
+    assert!(VarInt::<u32>::try_from(f32::NAN).is_err());
+    assert_eq!(f64::from(VarInt::<u32>(u32::MAX)), u32::MAX as f64);
+    assert!(f32::try_from(VarInt::<u32>(u32::MAX)).is_err());
+    assert_eq!(
+        f32::try_from(VarInt::<u32>(1 << 20)).unwrap(),
+        (1 << 20) as f32
+    );
+    assert!(f64::try_from(VarInt::<u64>(u64::MAX)).is_err());
+}
+
+#[test]
+fn var_int_arithmetic_and_ordering() {
+    let mut count = VarInt::<i64>::default();
+    assert_eq!(count, 0);
+
+    count += 10;
+    count -= 3;
+    count *= 2;
+    count /= 7;
+    assert_eq!(count, 2);
+    assert_eq!(count + 1, VarInt(3));
+    assert_eq!(count * VarInt(4), VarInt(8));
+
+    assert!(VarInt(5u32) > 4);
+    assert!(VarInt(5u32) <= 5);
+    assert!(VarInt(1u64) < VarInt(2u64));
+    assert_eq!(VarInt(300u32).to_string(), "300");
+}
+
+#[test]
+fn signed_var_ints_are_zigzag_encoded() {
+    assert_eq!(SVarInt(0i32).parse().unwrap(), vec![0x00]);
+    assert_eq!(SVarInt(-1i32).parse().unwrap(), vec![0x01]);
+    assert_eq!(SVarInt(1i32).parse().unwrap(), vec![0x02]);
+    assert_eq!(SVarInt(-64i32).parse().unwrap(), vec![0x7f]);
+    assert_eq!(SVarInt(i32::MIN).zigzag(), u32::MAX);
+    assert_eq!(SVarInt(i64::MAX).zigzag(), u64::MAX - 1);
+
+    for value in [0, 1, -1, 300, -300, i32::MAX, i32::MIN] {
+        let bytes = SVarInt(value).parse().unwrap();
+        assert_eq!(SVarInt(value).size_hint(), Some(bytes.len()));
+        assert_eq!(SVarInt::<i32>::compose(&bytes, &mut 0).unwrap().0, value);
+    }
+    for value in [0, -1, i64::MAX, i64::MIN] {
+        let bytes = SVarInt(value).parse().unwrap();
+        assert_eq!(SVarInt::<i64>::compose(&bytes, &mut 0).unwrap().0, value);
+    }
+    assert_eq!(SVarInt::<i64>::from_zigzag(3), SVarInt(-2));
+
+    // a plain VarInt<i32> still round trips negatives, at the full width.
+    let raw = VarInt(-2i32).parse().unwrap();
+    assert_eq!(raw.len(), VAR_INT_32_BYTE_MAX);
+    assert_eq!(VarInt::<i32>::compose(&raw, &mut 0).unwrap(), -2);
+    assert_eq!(SVarInt(-2i32).parse().unwrap(), vec![0x03]);
+}
+
+#[test]
+fn var_longs_hold_every_64_bit_value() {
+    for value in [0, 1, u32::MAX as u64 + 1, u64::MAX] {
+        let bytes = VarLong::<u64>::from(value).parse().unwrap();
+        assert_eq!(VarLong::<u64>::compose(&bytes, &mut 0).unwrap(), value);
+        assert_eq!(VarLong::<u64>::try_from_be_bytes(&bytes).unwrap(), value);
+    }
+    assert_eq!(
+        VarLong::<u64>::from(u64::MAX).parse().unwrap().len(),
+        VAR_INT_64_BYTE_MAX
+    );
+
+    for value in [-1, i64::MIN, i64::MAX] {
+        let bytes = VarLong::<i64>::from(value).parse().unwrap();
+        assert_eq!(
+            bytes.len(),
+            VarLong::<i64>::from(value).size_hint().unwrap()
+        );
+        assert_eq!(VarLong::<i64>::compose(&bytes, &mut 0).unwrap(), value);
+    }
+    assert!(VarLong::<u64>::try_from_be_bytes(&[0x80, 0x80]).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn var_ints_read_from_a_reader_stop_after_their_last_byte() {
+    use std::io::{Cursor, ErrorKind, Read};
+
+    let mut reader = Cursor::new(vec![0xac, 0x02, 0xff]);
+    assert_eq!(VarInt::<u32>::read_from(&mut reader).unwrap(), 300);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, vec![0xff]);
+
+    let long = VarLong::<u64>::from(u64::MAX).parse().unwrap();
+    assert_eq!(VarLong::<u64>::read_from(&mut &long[..]).unwrap(), u64::MAX);
+
+    let truncated = VarInt::<u32>::read_from(&mut &[0x80][..]).unwrap_err();
+    assert_eq!(truncated.kind(), ErrorKind::UnexpectedEof);
+    let too_long = VarInt::<u32>::read_from(&mut &[0x80; 6][..]).unwrap_err();
+    assert_eq!(too_long.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn var_ints_longer_than_their_maximum_width_are_rejected() {
+    let too_long = VarLong::<u64>::try_from_be_bytes(&[0xff; 11]).unwrap_err();
+    assert!(!too_long.is_incomplete());
+    assert!(VarLong::<u64>::compose(&[0xff; 11], &mut 0).is_err());
+    assert!(VarLong::<u64>::try_from_be_bytes(&[0xff; 9])
+        .unwrap_err()
+        .is_incomplete());
+
+    let too_long = VarInt::<u32>::compose(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00], &mut 0);
+    assert!(!too_long.unwrap_err().is_incomplete());
+    assert!(VarInt::<u32>::from_be_bytes(&[0x80; 6]).is_err());
+}
+
+#[test]
+fn negative_var_ints_round_trip() {
+    for value in [-1i32, i32::MIN, -300] {
+        let bytes = VarInt(value).parse().unwrap();
+        assert_eq!(bytes.len(), VAR_INT_32_BYTE_MAX);
+        assert_eq!(VarInt::<i32>::compose(&bytes, &mut 0).unwrap(), value);
+    }
+    assert_eq!(
+        VarInt(-1i32).parse().unwrap(),
+        [0xff, 0xff, 0xff, 0xff, 0x0f]
+    );
+}
@@ -1,3 +1,4 @@
+#![allow(unused_must_use, clippy::vec_init_then_push)]
 use binary_utils::{varint::VarInt, Streamable, LE};
 
 #[test]
@@ -28,3 +29,38 @@ fn test_le_vec() {
     let restored = Vec::<LE<String>>::fcompose(&vector[..], &mut 0);
     assert_eq!(restored[0].clone().inner(), str_bytes.inner())
 }
+
+#[test]
+fn composing_into_a_vec_reuses_it() {
+    use binary_utils::compose_into_vec;
+
+    let mut names = vec!["old".to_string(); 4];
+    // two strings, after the default VarInt length.
+    let source = [2, 0, 1, b'a', 0, 2, b'b', b'c'];
+    let mut position = 0;
+    compose_into_vec(&mut names, &source, &mut position).unwrap();
+    assert_eq!(names, vec!["a".to_string(), "bc".to_string()]);
+    assert_eq!(position, source.len());
+    assert!(names.capacity() >= 4);
+
+    // a failed item leaves the ones before it.
+    let truncated = &source[..5];
+    assert!(compose_into_vec(&mut names, truncated, &mut 0).is_err());
+    assert_eq!(names, vec!["a".to_string()]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn encodes_in_parallel_in_order_under_the_callers_profile() {
+    use binary_utils::profile::{Endian, WireProfile};
+
+    let values: Vec<u32> = (0..1000).collect();
+    let encoded = WireProfile::DEFAULT
+        .with_endian(Endian::Little)
+        .scope(|| binary_utils::encode_all_parallel(&values))
+        .unwrap();
+    assert_eq!(encoded.len(), values.len());
+    for (value, bytes) in values.iter().zip(&encoded) {
+        assert_eq!(bytes, &value.to_le_bytes());
+    }
+}
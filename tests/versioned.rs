@@ -0,0 +1,139 @@
+#![cfg(all(feature = "derive", feature = "registry"))]
+use binary_utils::{
+    framing::LengthPrefix,
+    registry::{Framing, PacketId, Priority, VersionedRegistry},
+    *,
+};
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Login {
+    pub protocol: u32,
+}
+
+#[derive(Debug, BinaryStream, PartialEq)]
+pub struct Chat {
+    pub message: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Packet {
+    Login(Login),
+    Chat(Chat),
+}
+
+impl From<Login> for Packet {
+    fn from(v: Login) -> Self {
+        Packet::Login(v)
+    }
+}
+
+impl From<Chat> for Packet {
+    fn from(v: Chat) -> Self {
+        Packet::Chat(v)
+    }
+}
+
+fn registry() -> VersionedRegistry<Packet> {
+    let mut registry =
+        VersionedRegistry::new(PacketId::U8, Framing::LengthPrefixed(LengthPrefix::U16));
+    registry
+        .register::<Login>(1, 0x01)
+        .register::<Chat>(1, 0x02)
+        .register::<Login>(2, 0x01)
+        .register_with_priority::<Chat>(2, 0x09, Priority::High);
+    registry
+}
+
+#[test]
+fn versioned_ids_per_version() {
+    let registry = registry();
+    let chat = Chat {
+        message: "hi".into(),
+    };
+
+    let old = registry.encode(1, &chat).unwrap();
+    let new = registry.encode(2, &chat).unwrap();
+    assert_eq!(old[2], 0x02);
+    assert_eq!(new[2], 0x09);
+    assert_eq!(old[3..], new[3..]);
+
+    assert!(matches!(registry.decode(1, &old).unwrap(), Packet::Chat(c) if c == chat));
+    assert!(matches!(registry.decode(2, &new).unwrap(), Packet::Chat(c) if c == chat));
+    assert!(registry.decode(2, &old).is_err());
+    assert_eq!(
+        registry.version(2).unwrap().priority_of::<Chat>(),
+        Priority::High
+    );
+}
+
+#[test]
+fn versioned_decode_from_stream() {
+    let registry = registry();
+    let mut stream = Vec::new();
+    registry
+        .encode_into(2, &Login { protocol: 2 }, &mut stream)
+        .unwrap();
+    registry
+        .encode_into(
+            2,
+            &Chat {
+                message: "a".into(),
+            },
+            &mut stream,
+        )
+        .unwrap();
+
+    let mut position = 0;
+    assert_eq!(
+        registry.decode_from(2, &stream, &mut position).unwrap(),
+        Packet::Login(Login { protocol: 2 })
+    );
+    assert!(matches!(
+        registry.decode_from(2, &stream, &mut position).unwrap(),
+        Packet::Chat(_)
+    ));
+    assert_eq!(position, stream.len());
+}
+
+#[test]
+fn versioned_unknown_version() {
+    let registry = registry();
+    assert_eq!(registry.versions().collect::<Vec<_>>(), vec![1, 2]);
+    assert!(registry.version(3).is_none());
+    assert!(registry.encode(3, &Login { protocol: 3 }).is_err());
+    assert!(registry.decode(3, &[0, 1, 1]).is_err());
+}
+
+#[test]
+fn versioned_re_registering_drops_stale_mappings() {
+    let mut registry = registry();
+    registry
+        .register::<Login>(1, 0x02)
+        .register::<Chat>(2, 0x0a);
+
+    let login = Login { protocol: 1 };
+    let frame = registry.encode(1, &login).unwrap();
+    assert_eq!(frame[2], 0x02);
+    assert_eq!(registry.version(1).unwrap().id_of::<Chat>(), None);
+    assert_eq!(registry.version(1).unwrap().id_of::<Login>(), Some(0x02));
+
+    let chat = Chat {
+        message: "hi".into(),
+    };
+    let mut frame = registry.encode(2, &chat).unwrap();
+    assert_eq!(frame[2], 0x0a);
+    frame[2] = 0x09;
+    assert!(registry.decode(2, &frame).is_err());
+}
+
+#[test]
+fn versioned_bytes_left_over_after_a_packet_are_rejected() {
+    let registry = registry();
+    let chat = Chat {
+        message: "hi".into(),
+    };
+    let mut frame = registry.encode(1, &chat).unwrap();
+    frame.push(0xff);
+    frame[1] += 1;
+    assert!(registry.decode_from(1, &frame, &mut 0).is_err());
+}
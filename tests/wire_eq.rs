@@ -0,0 +1,61 @@
+#![cfg(feature = "derive")]
+use std::collections::HashSet;
+
+use binary_utils::*;
+
+#[derive(Debug, BinaryStream)]
+#[binary(wire_eq)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, BinaryStream)]
+#[binary(wire_eq)]
+#[repr(u8)]
+pub enum Mode {
+    Survival = 0,
+    Creative = 1,
+}
+
+#[test]
+fn nan_fields_compare_equal() {
+    let a = Position {
+        x: f32::NAN,
+        y: 1.0,
+    };
+    let b = Position {
+        x: f32::from_bits(0x7F80_0001),
+        y: 1.0,
+    };
+    assert_eq!(a, a);
+    assert_eq!(a, b);
+    assert_ne!(
+        a,
+        Position {
+            x: f32::NAN,
+            y: 2.0
+        }
+    );
+}
+
+#[test]
+fn dedupe_by_wire_identity() {
+    let mut seen = HashSet::new();
+    assert!(seen.insert(Position {
+        x: f32::NAN,
+        y: 0.0
+    }));
+    assert!(!seen.insert(Position {
+        x: -f32::NAN,
+        y: 0.0
+    }));
+    assert!(seen.insert(Position { x: 0.0, y: 0.0 }));
+    assert_eq!(seen.len(), 2);
+}
+
+#[test]
+fn enums_compare_by_discriminant() {
+    assert_eq!(Mode::Creative, Mode::Creative);
+    assert_ne!(Mode::Survival, Mode::Creative);
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "ws")]
+
+use binary_utils::{registry::Registry, ws};
+use tungstenite::Message;
+
+#[test]
+fn round_trips_binary_messages() {
+    let mut registry = Registry::<String>::quic_datagram();
+    registry.register::<String>(0x07);
+
+    let message = ws::to_message(&registry, &"hello".to_string()).unwrap();
+    assert!(message.is_binary());
+    assert_eq!(
+        ws::from_message(&registry, &message).unwrap(),
+        Some("hello".to_string())
+    );
+}
+
+#[test]
+fn control_and_text_messages() {
+    let registry = Registry::<String>::quic_datagram();
+    assert_eq!(
+        ws::from_message(&registry, &Message::Ping(Vec::new().into())).unwrap(),
+        None
+    );
+    assert!(ws::from_message(&registry, &Message::Text("hi".into())).is_err());
+}